@@ -11,6 +11,7 @@ mod binancefuturesum;
 mod bybit;
 mod error;
 mod file;
+mod okx;
 mod throttler;
 
 #[derive(Parser, Debug)]
@@ -90,6 +91,31 @@ async fn main() -> Result<(), anyhow::Error> {
 
             tokio::spawn(bybit::run_collection(topics, args.symbols, writer_tx))
         }
+        "okx" => {
+            // "books-l2-tbt" requires a login with a VIP tier, so it is collected instead of
+            // "books" only if the API credentials are given.
+            let credentials = okx::Credentials::from_env();
+            let books = if credentials.is_some() {
+                "books-l2-tbt"
+            } else {
+                info!(
+                    "collecting books instead of books-l2-tbt; set OKX_API_KEY, OKX_SECRET, and \
+                    OKX_PASSPHRASE to log in."
+                );
+                "books"
+            };
+            let channels = [books, "trades", "bbo-tbt"]
+                .iter()
+                .map(|channel| channel.to_string())
+                .collect();
+
+            tokio::spawn(okx::run_collection(
+                channels,
+                args.symbols,
+                credentials,
+                writer_tx,
+            ))
+        }
         exchange => {
             return Err(anyhow!("{exchange} is not supported."));
         }
@@ -0,0 +1,224 @@
+use std::{
+    io,
+    io::ErrorKind,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Error, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::{
+    select,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Bytes, Message, Utf8Bytes, client::IntoClientRequest},
+};
+use tracing::{error, warn};
+
+/// The API credentials to log in with, which channels such as `books-l2-tbt` require.
+#[derive(Clone)]
+pub struct Credentials {
+    api_key: String,
+    secret: String,
+    passphrase: String,
+}
+
+impl Credentials {
+    /// Reads the credentials from the `OKX_API_KEY`, `OKX_SECRET`, and `OKX_PASSPHRASE`
+    /// environment variables. Returns `None` if any of them is not set.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            api_key: std::env::var("OKX_API_KEY").ok()?,
+            secret: std::env::var("OKX_SECRET").ok()?,
+            passphrase: std::env::var("OKX_PASSPHRASE").ok()?,
+        })
+    }
+
+    /// Builds the login request, which signs `timestamp + "GET" + "/users/self/verify"` with the
+    /// timestamp in seconds, using the Base64-encoded HMAC SHA256.
+    fn login_msg(&self) -> String {
+        let timestamp = Utc::now().timestamp().to_string();
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).unwrap();
+        mac.update(format!("{timestamp}GET/users/self/verify").as_bytes());
+        let sign = STANDARD.encode(mac.finalize().into_bytes());
+        serde_json::json!({
+            "op": "login",
+            "args": [{
+                "apiKey": self.api_key,
+                "passphrase": self.passphrase,
+                "timestamp": timestamp,
+                "sign": sign,
+            }],
+        })
+        .to_string()
+    }
+}
+
+pub async fn connect(
+    url: &str,
+    args: Vec<String>,
+    credentials: Option<&Credentials>,
+    ws_tx: UnboundedSender<(DateTime<Utc>, Utf8Bytes)>,
+) -> Result<(), anyhow::Error> {
+    let request = url.into_client_request()?;
+    let (ws_stream, _) = connect_async(request).await?;
+    let (mut write, mut read) = ws_stream.split();
+    let (tx, mut rx) = unbounded_channel::<()>();
+
+    if let Some(credentials) = credentials {
+        write
+            .send(Message::Text(credentials.login_msg().into()))
+            .await?;
+        // The channels that require a login can be subscribed to only after the login succeeds.
+        loop {
+            match read.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    let j: serde_json::Value = serde_json::from_str(text.as_str())?;
+                    match j.get("event").and_then(|event| event.as_str()) {
+                        Some("login") => break,
+                        Some("error") => return Err(anyhow!("couldn't log in: {text}")),
+                        _ => {}
+                    }
+                }
+                Some(Ok(Message::Close(close_frame))) => {
+                    warn!(?close_frame, "closed");
+                    return Err(Error::from(io::Error::new(
+                        ErrorKind::ConnectionAborted,
+                        "closed",
+                    )));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    return Err(Error::from(e));
+                }
+                None => {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    write
+        .send(Message::Text(
+            format!(r#"{{"op": "subscribe", "args": [{}]}}"#, args.join(",")).into(),
+        ))
+        .await?;
+
+    tokio::spawn(async move {
+        // OKX closes the connection if there is no message for 30 seconds.
+        let mut ping_interval = tokio::time::interval(Duration::from_secs(20));
+        loop {
+            select! {
+                result = rx.recv() => {
+                    match result {
+                        Some(_) => {
+                            if write.send(Message::Pong(Bytes::default())).await.is_err() {
+                                return;
+                            }
+                        }
+                        None => {
+                            break;
+                        }
+                    }
+                }
+                _ = ping_interval.tick() => {
+                    if write.send(Message::Text("ping".into())).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+
+    loop {
+        match read.next().await {
+            Some(Ok(Message::Text(text))) => {
+                if text.as_str() == "pong" {
+                    continue;
+                }
+                let recv_time = Utc::now();
+                if ws_tx.send((recv_time, text)).is_err() {
+                    break;
+                }
+            }
+            Some(Ok(Message::Binary(_))) => {}
+            Some(Ok(Message::Ping(_))) => {
+                tx.send(()).unwrap();
+            }
+            Some(Ok(Message::Pong(_))) => {}
+            Some(Ok(Message::Close(close_frame))) => {
+                warn!(?close_frame, "closed");
+                return Err(Error::from(io::Error::new(
+                    ErrorKind::ConnectionAborted,
+                    "closed",
+                )));
+            }
+            Some(Ok(Message::Frame(_))) => {}
+            Some(Err(e)) => {
+                return Err(Error::from(e));
+            }
+            None => {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn keep_connection(
+    channels: Vec<String>,
+    symbol_list: Vec<String>,
+    credentials: Option<Credentials>,
+    ws_tx: UnboundedSender<(DateTime<Utc>, Utf8Bytes)>,
+) {
+    let mut error_count = 0;
+    loop {
+        let connect_time = Instant::now();
+        let args = symbol_list
+            .iter()
+            .flat_map(|inst_id| {
+                channels
+                    .iter()
+                    .map(|channel| {
+                        format!(
+                            r#"{{"channel": "{channel}", "instId": "{}"}}"#,
+                            inst_id.to_uppercase()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if let Err(error) = connect(
+            "wss://ws.okx.com:8443/ws/v5/public",
+            args,
+            credentials.as_ref(),
+            ws_tx.clone(),
+        )
+        .await
+        {
+            error!(?error, "websocket error");
+            error_count += 1;
+            if connect_time.elapsed() > Duration::from_secs(30) {
+                error_count = 0;
+            }
+            if error_count > 20 {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            } else if error_count > 10 {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            } else if error_count > 3 {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        } else if ws_tx.is_closed() {
+            break;
+        } else {
+            // OKX disconnects the session periodically; the subscription is restored on
+            // reconnection, and the server resends the book snapshot.
+            warn!("disconnected; reconnecting.");
+        }
+    }
+}
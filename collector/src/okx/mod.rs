@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tokio_tungstenite::tungstenite::Utf8Bytes;
+use tracing::{error, warn};
+
+pub use self::http::Credentials;
+use self::http::keep_connection;
+use crate::error::ConnectorError;
+
+mod http;
+
+fn handle(
+    prev_seq_id_map: &mut HashMap<(String, String), i64>,
+    writer_tx: &UnboundedSender<(DateTime<Utc>, String, String)>,
+    recv_time: DateTime<Utc>,
+    data: Utf8Bytes,
+) -> Result<(), ConnectorError> {
+    let j: serde_json::Value = serde_json::from_str(data.as_str())?;
+    if let Some(j_event) = j.get("event") {
+        let event = j_event.as_str().ok_or(ConnectorError::FormatError)?;
+        if event == "error" {
+            error!(%data, "couldn't subscribe the channels.");
+            return Err(ConnectorError::ConnectionAbort);
+        }
+    } else if let Some(j_arg) = j.get("arg") {
+        let channel = j_arg
+            .get("channel")
+            .ok_or(ConnectorError::FormatError)?
+            .as_str()
+            .ok_or(ConnectorError::FormatError)?;
+        let symbol = j_arg
+            .get("instId")
+            .ok_or(ConnectorError::FormatError)?
+            .as_str()
+            .ok_or(ConnectorError::FormatError)?;
+        // `books5` pushes the full 5 levels every time, without the sequence IDs to check.
+        if channel.starts_with("books") && channel != "books5" {
+            // Order book channels carry `seqId` and `prevSeqId`. A snapshot has a `prevSeqId`
+            // of -1, and each update's `prevSeqId` must match the previous message's `seqId`.
+            let j_data = j
+                .get("data")
+                .ok_or(ConnectorError::FormatError)?
+                .as_array()
+                .ok_or(ConnectorError::FormatError)?;
+            for j_book in j_data {
+                let seq_id = j_book
+                    .get("seqId")
+                    .ok_or(ConnectorError::FormatError)?
+                    .as_i64()
+                    .ok_or(ConnectorError::FormatError)?;
+                let prev_seq_id = j_book
+                    .get("prevSeqId")
+                    .ok_or(ConnectorError::FormatError)?
+                    .as_i64()
+                    .ok_or(ConnectorError::FormatError)?;
+                let key = (channel.to_string(), symbol.to_string());
+                if prev_seq_id != -1 {
+                    match prev_seq_id_map.get(&key) {
+                        Some(last_seq_id) if *last_seq_id == prev_seq_id => {}
+                        _ => {
+                            warn!(%channel, %symbol, "missing depth feed has been detected.");
+                        }
+                    }
+                }
+                prev_seq_id_map.insert(key, seq_id);
+            }
+        }
+        let _ = writer_tx.send((recv_time, symbol.to_string(), data.to_string()));
+    }
+    Ok(())
+}
+
+pub async fn run_collection(
+    channels: Vec<String>,
+    symbols: Vec<String>,
+    credentials: Option<Credentials>,
+    writer_tx: UnboundedSender<(DateTime<Utc>, String, String)>,
+) -> Result<(), anyhow::Error> {
+    let mut prev_seq_id_map = HashMap::new();
+    let (ws_tx, mut ws_rx) = unbounded_channel();
+    let h = tokio::spawn(keep_connection(
+        channels,
+        symbols,
+        credentials,
+        ws_tx.clone(),
+    ));
+    while let Some((recv_time, data)) = ws_rx.recv().await {
+        if let Err(error) = handle(&mut prev_seq_id_map, &writer_tx, recv_time, data) {
+            error!(?error, "couldn't handle the received data.");
+        }
+    }
+    let _ = h.await;
+    Ok(())
+}
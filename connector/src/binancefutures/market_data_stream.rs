@@ -14,7 +14,7 @@ use tokio_tungstenite::{
     connect_async,
     tungstenite::{Message, client::IntoClientRequest},
 };
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use crate::{
     binancefutures::{
@@ -30,14 +30,27 @@ use crate::{
     utils::{generate_rand_string, parse_depth, parse_px_qty_tup},
 };
 
+/// The local order book state of a symbol in the managed depth mode.
+enum ManagedDepth {
+    /// Buffers the diffs until they can be aligned with the snapshot by the update ID.
+    Syncing {
+        pending: Vec<stream::Depth>,
+        snapshot: Option<rest::Depth>,
+    },
+    /// The diffs are applied while their update IDs are continuous.
+    Synced { prev_u: i64 },
+}
+
 pub struct MarketDataStream {
     client: BinanceFuturesClient,
     ev_tx: UnboundedSender<PublishEvent>,
     symbol_rx: Receiver<String>,
-    pending_depth_messages: HashMap<String, Vec<stream::Depth>>,
     prev_u: HashMap<String, i64>,
     rest_tx: UnboundedSender<(String, rest::Depth)>,
     rest_rx: UnboundedReceiver<(String, rest::Depth)>,
+    managed: bool,
+    managed_depth: HashMap<String, ManagedDepth>,
+    gap_count: HashMap<String, u64>,
 }
 
 impl MarketDataStream {
@@ -45,54 +58,228 @@ impl MarketDataStream {
         client: BinanceFuturesClient,
         ev_tx: UnboundedSender<PublishEvent>,
         symbol_rx: Receiver<String>,
+        managed: bool,
     ) -> Self {
         let (rest_tx, rest_rx) = unbounded_channel::<(String, rest::Depth)>();
         Self {
             client,
             ev_tx,
             symbol_rx,
-            pending_depth_messages: Default::default(),
             prev_u: Default::default(),
             rest_tx,
             rest_rx,
+            managed,
+            managed_depth: Default::default(),
+            gap_count: Default::default(),
+        }
+    }
+
+    fn request_snapshot(&self, symbol: String) {
+        let client_ = self.client.clone();
+        let rest_tx = self.rest_tx.clone();
+        tokio::spawn(async move {
+            let resp = client_.get_depth(&symbol).await;
+            match resp {
+                Ok(depth) => {
+                    rest_tx.send((symbol, depth)).unwrap();
+                }
+                Err(error) => {
+                    error!(
+                        ?error,
+                        %symbol,
+                        "Couldn't get the market depth via REST."
+                    );
+                }
+            }
+        });
+    }
+
+    fn send_depth(&self, symbol: &str, ev: u64, exch_ts: i64, levels: Vec<(f64, f64)>) {
+        for (px, qty) in levels {
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                    symbol: symbol.to_string(),
+                    event: Event {
+                        ev,
+                        exch_ts,
+                        local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                        order_id: 0,
+                        px,
+                        qty,
+                        ival: 0,
+                        fval: 0.0,
+                    },
+                }))
+                .unwrap();
+        }
+    }
+
+    fn send_depth_diff(&self, data: stream::Depth) {
+        match parse_depth(data.bids, data.asks) {
+            Ok((bids, asks)) => {
+                let exch_ts = data.transaction_time * 1_000_000;
+                self.send_depth(&data.symbol, LOCAL_BID_DEPTH_EVENT, exch_ts, bids);
+                self.send_depth(&data.symbol, LOCAL_ASK_DEPTH_EVENT, exch_ts, asks);
+            }
+            Err(error) => {
+                error!(?error, "Couldn't parse DepthUpdate stream.");
+            }
+        }
+    }
+
+    /// Handles a gap in the update ID sequence by reporting it and resynchronizing the order book
+    /// from the given diff.
+    fn resync(&mut self, symbol: String, pending: Vec<stream::Depth>, reason: &str) {
+        let count = self.gap_count.entry(symbol.clone()).or_insert(0);
+        *count += 1;
+        warn!(%symbol, gap_count = *count, reason, "Resynchronizing the market depth.");
+        self.ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                ErrorKind::DepthResync,
+                BinanceFuturesError::DepthGap {
+                    symbol: symbol.clone(),
+                    gap_count: *count,
+                }
+                .into(),
+            ))))
+            .unwrap();
+        self.managed_depth.insert(
+            symbol.clone(),
+            ManagedDepth::Syncing {
+                pending,
+                snapshot: None,
+            },
+        );
+        self.request_snapshot(symbol);
+    }
+
+    /// Processes the diff in the managed depth mode, which validates the update ID sequence.
+    /// Please see: `<https://developers.binance.com/docs/derivatives/usds-margined-futures/websocket-market-streams/How-to-manage-a-local-order-book-correctly>`
+    fn process_managed_depth(&mut self, data: stream::Depth) {
+        match self.managed_depth.get_mut(&data.symbol) {
+            Some(ManagedDepth::Synced { prev_u }) => {
+                if data.prev_update_id == *prev_u {
+                    *prev_u = data.last_update_id;
+                    self.ev_tx.send(PublishEvent::BatchStart(TO_ALL)).unwrap();
+                    self.send_depth_diff(data);
+                    self.ev_tx.send(PublishEvent::BatchEnd(TO_ALL)).unwrap();
+                } else {
+                    let symbol = data.symbol.clone();
+                    self.resync(symbol, vec![data], "pu doesn't match the previous u.");
+                }
+            }
+            Some(ManagedDepth::Syncing { pending, snapshot }) => {
+                pending.push(data);
+                if snapshot.is_some() {
+                    let symbol = pending.last().unwrap().symbol.clone();
+                    self.align(symbol);
+                }
+            }
+            None => {
+                // Starts buffering the diffs and fetches the snapshot.
+                let symbol = data.symbol.clone();
+                self.managed_depth.insert(
+                    symbol.clone(),
+                    ManagedDepth::Syncing {
+                        pending: vec![data],
+                        snapshot: None,
+                    },
+                );
+                self.request_snapshot(symbol);
+            }
+        }
+    }
+
+    /// Aligns the buffered diffs with the snapshot. If they are aligned, publishes the
+    /// [`DEPTH_CLEAR_EVENT`], the snapshot, and the buffered diffs in a batch.
+    fn align(&mut self, symbol: String) {
+        let Some(ManagedDepth::Syncing {
+            mut pending,
+            snapshot: Some(snapshot),
+        }) = self.managed_depth.remove(&symbol)
+        else {
+            return;
+        };
+
+        // Drops any diff where u is < lastUpdateId in the snapshot.
+        pending.retain(|diff| diff.last_update_id >= snapshot.last_update_id);
+        let Some(first) = pending.first() else {
+            // Waits for the diffs following the snapshot.
+            self.managed_depth.insert(
+                symbol,
+                ManagedDepth::Syncing {
+                    pending,
+                    snapshot: Some(snapshot),
+                },
+            );
+            return;
+        };
+        if first.first_update_id > snapshot.last_update_id {
+            // The snapshot is older than the buffered diffs, so fetches a new one.
+            self.managed_depth.insert(
+                symbol.clone(),
+                ManagedDepth::Syncing {
+                    pending,
+                    snapshot: None,
+                },
+            );
+            self.request_snapshot(symbol);
+            return;
+        }
+        if let Some(i) =
+            (1..pending.len()).find(|&i| pending[i].prev_update_id != pending[i - 1].last_update_id)
+        {
+            let pending = pending.split_off(i);
+            self.resync(symbol, pending, "The buffered diffs are not continuous.");
+            return;
+        }
+
+        let prev_u = pending.last().unwrap().last_update_id;
+        self.ev_tx.send(PublishEvent::BatchStart(TO_ALL)).unwrap();
+        let exch_ts = snapshot.transaction_time * 1_000_000;
+        self.ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                symbol: symbol.clone(),
+                event: Event {
+                    ev: LOCAL_DEPTH_CLEAR_EVENT,
+                    exch_ts,
+                    local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                    order_id: 0,
+                    px: 0.0,
+                    qty: 0.0,
+                    ival: 0,
+                    fval: 0.0,
+                },
+            }))
+            .unwrap();
+        match parse_depth(snapshot.bids, snapshot.asks) {
+            Ok((bids, asks)) => {
+                self.send_depth(&symbol, LOCAL_BID_DEPTH_EVENT, exch_ts, bids);
+                self.send_depth(&symbol, LOCAL_ASK_DEPTH_EVENT, exch_ts, asks);
+            }
+            Err(error) => {
+                error!(?error, "Couldn't parse Depth response.");
+            }
+        }
+        for diff in pending {
+            self.send_depth_diff(diff);
         }
+        self.ev_tx.send(PublishEvent::BatchEnd(TO_ALL)).unwrap();
+        self.managed_depth
+            .insert(symbol, ManagedDepth::Synced { prev_u });
     }
 
     fn process_message(&mut self, stream: EventStream) {
         match stream {
+            EventStream::DepthUpdate(data) if self.managed => {
+                self.process_managed_depth(data);
+            }
             EventStream::DepthUpdate(data) => {
-                let mut prev_u_val = self.prev_u.get_mut(&data.symbol);
-                if prev_u_val.is_none()
-                /* fixme: || data.prev_update_id != **prev_u_val.as_ref().unwrap()*/
-                {
-                    // if !pending_depth_messages.contains_key(&data.symbol) {
-                    let client_ = self.client.clone();
-                    let symbol = data.symbol.clone();
-                    let rest_tx = self.rest_tx.clone();
-                    tokio::spawn(async move {
-                        let resp = client_.get_depth(&symbol).await;
-                        match resp {
-                            Ok(depth) => {
-                                rest_tx.send((symbol, depth)).unwrap();
-                            }
-                            Err(error) => {
-                                error!(
-                                    ?error,
-                                    %symbol,
-                                    "Couldn't get the market depth via REST."
-                                );
-                            }
-                        }
-                    });
-                    // }
-                    // pending_depth_messages
-                    //     .entry(data.symbol.clone())
-                    //     .or_insert(Vec::new())
-                    //     .push(data);
-                    // continue;
+                // In the natural refresh mode, the snapshot is applied once and the market depth
+                // is refreshed by the diffs without validating the update ID sequence.
+                if !self.prev_u.contains_key(&data.symbol) {
+                    self.request_snapshot(data.symbol.clone());
                 }
-                // *prev_u_val.unwrap() = data.last_update_id;
-                // fixme: currently supports natural refresh only.
                 *self
                     .prev_u
                     .entry(data.symbol.clone())
@@ -177,7 +364,16 @@ impl MarketDataStream {
         }
     }
 
-    fn process_snapshot(&self, symbol: String, data: rest::Depth) {
+    fn process_snapshot(&mut self, symbol: String, data: rest::Depth) {
+        if self.managed {
+            if let Some(ManagedDepth::Syncing { snapshot, .. }) =
+                self.managed_depth.get_mut(&symbol)
+            {
+                *snapshot = Some(data);
+                self.align(symbol);
+            }
+            return;
+        }
         match parse_depth(data.bids, data.asks) {
             Ok((bids, asks)) => {
                 self.ev_tx.send(PublishEvent::BatchStart(TO_ALL)).unwrap();
@@ -224,35 +420,6 @@ impl MarketDataStream {
                 error!(?error, "Couldn't parse Depth response.");
             }
         }
-        // fixme: waits for pending messages without blocking.
-        // prev_u.remove(&symbol);
-        // let mut new_prev_u: Option<i64> = None;
-        // while new_prev_u.is_none() {
-        //     if let Some(msg) = pending_depth_messages.get_mut(&symbol) {
-        //         for pending_depth in msg.into_iter() {
-        //             // https://binance-docs.github.io/apidocs/futures/en/#how-to-manage-a-local-order-book-correctly
-        //             // The first processed event should have U <= lastUpdateId AND u >= lastUpdateId
-        //             if (
-        //                 pending_depth.last_update_id < resp.last_update_id
-        //                 || pending_depth.first_update_id > resp.last_update_id
-        //             ) && new_prev_u.is_none() {
-        //                 continue;
-        //             }
-        //             if new_prev_u.is_some() && pending_depth.prev_update_id != *new_prev_u.as_ref().unwrap() {
-        //                 warn!(%symbol, ?pending_depth, "UpdateId does not match.");
-        //             }
-        //
-        //             // Processes a pending depth message
-        //             new_prev_u = Some(pending_depth.last_update_id);
-        //             *prev_u.entry(symbol.clone())
-        //                 .or_insert(pending_depth.last_update_id) = pending_depth.last_update_id;
-        //         }
-        //     }
-        //     if new_prev_u.is_none() {
-        //         // Waits for depth messages.
-        //         todo!()
-        //     }
-        // }
     }
 
     pub async fn connect(&mut self, url: &str) -> Result<(), BinanceFuturesError> {
@@ -268,11 +435,12 @@ impl MarketDataStream {
                 msg = self.symbol_rx.recv() => match msg {
                     Ok(symbol) => {
                         let id = generate_rand_string(16);
+                        let depth = self.client.market().depth_stream();
                         write.send(Message::Text(format!(r#"{{
                             "method": "SUBSCRIBE",
                             "params": [
                                 "{symbol}@trade",
-                                "{symbol}@depth@0ms"
+                                "{symbol}@{depth}"
                             ],
                             "id": "{id}"
                         }}"#).into())).await?;
@@ -320,3 +488,145 @@ impl MarketDataStream {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::prelude::*;
+    use tokio::sync::{
+        broadcast,
+        mpsc::{UnboundedReceiver, unbounded_channel},
+    };
+
+    use crate::{
+        binancefutures::{
+            Market,
+            market_data_stream::MarketDataStream,
+            msg::{rest, stream},
+            rest::BinanceFuturesClient,
+        },
+        connector::PublishEvent,
+    };
+
+    fn managed_stream() -> (MarketDataStream, UnboundedReceiver<PublishEvent>) {
+        let (ev_tx, ev_rx) = unbounded_channel();
+        let (_, symbol_rx) = broadcast::channel(1);
+        // The snapshot requests are never sent since the spawned tasks are not polled.
+        let client = BinanceFuturesClient::new("http://127.0.0.1:1", "", "", Market::UsdM);
+        (MarketDataStream::new(client, ev_tx, symbol_rx, true), ev_rx)
+    }
+
+    fn diff(
+        first_update_id: i64,
+        last_update_id: i64,
+        prev_update_id: i64,
+        px: f64,
+    ) -> stream::Depth {
+        stream::Depth {
+            transaction_time: 1,
+            event_time: 1,
+            symbol: "btcusdt".to_string(),
+            first_update_id,
+            last_update_id,
+            prev_update_id,
+            bids: vec![(px.to_string(), "1".to_string())],
+            asks: vec![],
+        }
+    }
+
+    fn snapshot(last_update_id: i64, px: f64) -> rest::Depth {
+        rest::Depth {
+            last_update_id,
+            event_time: 1,
+            transaction_time: 1,
+            bids: vec![(px.to_string(), "1".to_string())],
+            asks: vec![],
+        }
+    }
+
+    fn published(ev_rx: &mut UnboundedReceiver<PublishEvent>) -> Vec<String> {
+        let mut events = Vec::new();
+        while let Ok(ev) = ev_rx.try_recv() {
+            events.push(match ev {
+                PublishEvent::BatchStart(_) => "start".to_string(),
+                PublishEvent::BatchEnd(_) => "end".to_string(),
+                PublishEvent::LiveEvent(LiveEvent::Feed { event, .. }) => {
+                    if event.ev == LOCAL_DEPTH_CLEAR_EVENT {
+                        "clear".to_string()
+                    } else {
+                        format!("bid {}", event.px)
+                    }
+                }
+                PublishEvent::LiveEvent(LiveEvent::Error(error)) => format!("{:?}", error.kind),
+                _ => "other".to_string(),
+            });
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn drops_stale_diffs() {
+        let (mut stream, mut ev_rx) = managed_stream();
+        stream.process_managed_depth(diff(1, 5, 0, 1.0));
+        stream.process_managed_depth(diff(6, 10, 5, 2.0));
+        assert!(published(&mut ev_rx).is_empty());
+
+        // The snapshot is older than the buffered diffs, so it waits for a new one.
+        stream.process_snapshot("btcusdt".to_string(), snapshot(0, 100.0));
+        assert!(published(&mut ev_rx).is_empty());
+
+        // The first diff ends before the snapshot, so only the second one is applied.
+        stream.process_snapshot("btcusdt".to_string(), snapshot(7, 100.0));
+        assert_eq!(
+            published(&mut ev_rx),
+            vec!["start", "clear", "bid 100", "bid 2", "end"]
+        );
+    }
+
+    #[tokio::test]
+    async fn applies_aligned_diffs() {
+        let (mut stream, mut ev_rx) = managed_stream();
+        stream.process_managed_depth(diff(6, 10, 5, 2.0));
+        stream.process_snapshot("btcusdt".to_string(), snapshot(8, 100.0));
+        stream.process_managed_depth(diff(11, 12, 10, 3.0));
+        stream.process_managed_depth(diff(13, 15, 12, 4.0));
+        assert_eq!(
+            published(&mut ev_rx),
+            vec![
+                "start", "clear", "bid 100", "bid 2", "end", "start", "bid 3", "end", "start",
+                "bid 4", "end",
+            ]
+        );
+        assert!(stream.gap_count.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resyncs_on_gap() {
+        let (mut stream, mut ev_rx) = managed_stream();
+        stream.process_managed_depth(diff(6, 10, 5, 2.0));
+        stream.process_snapshot("btcusdt".to_string(), snapshot(8, 100.0));
+        published(&mut ev_rx);
+
+        // The diff from 11 to 12 is missed.
+        stream.process_managed_depth(diff(13, 15, 12, 4.0));
+        assert_eq!(published(&mut ev_rx), vec!["DepthResync"]);
+        assert_eq!(stream.gap_count.get("btcusdt"), Some(&1));
+
+        // The diffs are buffered until the new snapshot arrives.
+        stream.process_managed_depth(diff(16, 17, 15, 5.0));
+        assert!(published(&mut ev_rx).is_empty());
+        stream.process_snapshot("btcusdt".to_string(), snapshot(15, 101.0));
+        assert_eq!(
+            published(&mut ev_rx),
+            vec!["start", "clear", "bid 101", "bid 4", "bid 5", "end"]
+        );
+
+        // Another gap is followed by a gap within the buffered diffs, which also triggers a
+        // resync.
+        stream.process_managed_depth(diff(20, 21, 19, 6.0));
+        stream.process_managed_depth(diff(22, 23, 21, 7.0));
+        stream.process_managed_depth(diff(25, 26, 24, 8.0));
+        stream.process_snapshot("btcusdt".to_string(), snapshot(20, 102.0));
+        assert_eq!(published(&mut ev_rx), vec!["DepthResync", "DepthResync"]);
+        assert_eq!(stream.gap_count.get("btcusdt"), Some(&3));
+    }
+}
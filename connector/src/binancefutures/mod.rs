@@ -1,21 +1,31 @@
 mod market_data_stream;
-mod msg;
-mod ordermanager;
+pub(crate) mod msg;
+pub(crate) mod ordermanager;
 mod rest;
+pub(crate) mod trade_stream;
 mod user_data_stream;
 
 use std::{
     collections::{HashMap, HashSet},
-    sync::{Arc, Mutex},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
 };
 
 use hftbacktest::{
     prelude::get_precision,
-    types::{ErrorKind, LiveError, LiveEvent, Order, Status, Value},
+    types::{ErrorKind, ErrorReason, LiveError, LiveEvent, Order, Status, Value, VenueError},
 };
 use serde::Deserialize;
 use thiserror::Error;
-use tokio::sync::{broadcast, broadcast::Sender, mpsc::UnboundedSender};
+use tokio::sync::{
+    broadcast,
+    broadcast::Sender,
+    mpsc::{UnboundedSender, unbounded_channel},
+};
 use tokio_tungstenite::tungstenite;
 use tracing::{error, warn};
 
@@ -23,6 +33,7 @@ use crate::{
     binancefutures::{
         ordermanager::{OrderManager, SharedOrderManager},
         rest::BinanceFuturesClient,
+        trade_stream::{OrderOp, SharedOrderSender, TradeStream},
     },
     connector::{Connector, ConnectorBuilder, GetOrders, PublishEvent},
     utils::{ExponentialBackoff, Retry},
@@ -48,6 +59,8 @@ pub enum BinanceFuturesError {
     PrefixUnmatched,
     #[error("OrderNotFound")]
     OrderNotFound,
+    #[error("DepthGap: {symbol} - {gap_count}")]
+    DepthGap { symbol: String, gap_count: u64 },
     #[error("Tunstenite: {0:?}")]
     Tunstenite(#[from] tungstenite::Error),
     #[error("Config: {0:?}")]
@@ -73,6 +86,12 @@ impl From<BinanceFuturesError> for Value {
                 map.insert("msg".to_string(), Value::String(msg));
                 map
             }),
+            BinanceFuturesError::DepthGap { symbol, gap_count } => Value::Map({
+                let mut map = HashMap::new();
+                map.insert("symbol".to_string(), Value::String(symbol));
+                map.insert("gap_count".to_string(), Value::Int(gap_count as i64));
+                map
+            }),
             BinanceFuturesError::Tunstenite(error) => Value::String(format!("{error}")),
             BinanceFuturesError::ListenKeyExpired => Value::String(value.to_string()),
             BinanceFuturesError::ConnectionInterrupted => Value::String(value.to_string()),
@@ -84,34 +103,242 @@ impl From<BinanceFuturesError> for Value {
     }
 }
 
+impl BinanceFuturesError {
+    /// Returns the normalized reason for the error. Binance Spot shares this error type, so its
+    /// error codes are also mapped.
+    pub fn reason(&self) -> ErrorReason {
+        match self {
+            BinanceFuturesError::OrderError { code, msg } => match *code {
+                // TOO_MANY_REQUESTS, TOO_MANY_ORDERS
+                -1003 | -1015 => ErrorReason::RateLimited,
+                // BALANCE_NOT_SUFFICIENT, MARGIN_NOT_SUFFICIEN
+                -2018 | -2019 => ErrorReason::InsufficientMargin,
+                // GTX_ORDER_REJECT
+                -5022 => ErrorReason::PostOnlyWouldTake,
+                // PRICE_HIGHER_THAN_MULTIPLIER_UP, PRICE_LOWER_THAN_MULTIPLIER_DOWN
+                -4016 | -4024 => ErrorReason::PriceOutOfBand,
+                // CANCEL_REJECTED, NO_SUCH_ORDER
+                -2011 | -2013 => ErrorReason::UnknownOrder,
+                // DUPLICATED_CLIENT_ORDER_ID
+                -4116 => ErrorReason::DuplicateOrderId,
+                // UNAUTHORIZED, INVALID_SIGNATURE, BAD_API_KEY_FMT, REJECTED_MBX_KEY
+                -1002 | -1022 | -2014 | -2015 => ErrorReason::AuthFailure,
+                // Binance Spot rejects orders with NEW_ORDER_REJECTED or FILTER_FAILURE, which are
+                // only distinguished by the message.
+                -2010 | -1013 => {
+                    if msg.contains("insufficient balance") {
+                        ErrorReason::InsufficientMargin
+                    } else if msg.contains("immediately match and take") {
+                        ErrorReason::PostOnlyWouldTake
+                    } else if msg.contains("PERCENT_PRICE") {
+                        ErrorReason::PriceOutOfBand
+                    } else if msg.contains("Duplicate order sent") {
+                        ErrorReason::DuplicateOrderId
+                    } else {
+                        ErrorReason::Other
+                    }
+                }
+                _ => ErrorReason::Other,
+            },
+            BinanceFuturesError::ReqError(error) => match error.status().map(|s| s.as_u16()) {
+                Some(418) | Some(429) => ErrorReason::RateLimited,
+                Some(401) => ErrorReason::AuthFailure,
+                _ => ErrorReason::Other,
+            },
+            BinanceFuturesError::OrderNotFound => ErrorReason::UnknownOrder,
+            _ => ErrorReason::Other,
+        }
+    }
+
+    /// Returns the normalized error with the exchange's error code and message.
+    pub fn to_venue_error(&self) -> VenueError {
+        match self {
+            BinanceFuturesError::OrderError { code, msg } => {
+                VenueError::new(self.reason(), msg.clone()).code(*code)
+            }
+            _ => VenueError::new(self.reason(), self.to_string()),
+        }
+    }
+}
+
+/// The Binance futures market the connector trades on.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Market {
+    /// USD-M Futures, whose quantity is in the base asset.
+    #[default]
+    #[serde(rename = "usdm")]
+    UsdM,
+    /// COIN-M Futures, whose quantity and position are in contracts. This corresponds to
+    /// `InverseAsset` with the contract's face value, such as 100 for `BTCUSD_PERP`, as the
+    /// contract size. If the contract size is configured for the symbol, the quantities are
+    /// converted into the face value instead, which corresponds to `InverseAsset` with a contract
+    /// size of 1.
+    #[serde(rename = "coinm")]
+    CoinM,
+}
+
+impl Market {
+    fn path_prefix(&self) -> &'static str {
+        match self {
+            Market::UsdM => "/fapi",
+            Market::CoinM => "/dapi",
+        }
+    }
+
+    fn position_risk_path(&self) -> &'static str {
+        match self {
+            Market::UsdM => "/fapi/v2/positionRisk",
+            Market::CoinM => "/dapi/v1/positionRisk",
+        }
+    }
+
+    fn depth_stream(&self) -> &'static str {
+        match self {
+            Market::UsdM => "depth@0ms",
+            Market::CoinM => "depth@100ms",
+        }
+    }
+
+    fn qty_prec(&self) -> usize {
+        match self {
+            Market::UsdM => 5,
+            // COIN-M Futures orders are placed in the number of contracts.
+            Market::CoinM => 0,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct Config {
+    /// `usdm` for USD-M Futures or `coinm` for COIN-M Futures. The default is `usdm`.
+    #[serde(default)]
+    market: Market,
     stream_url: String,
     api_url: String,
+    /// WebSocket API URL for the order requests. If it is empty, orders are sent through the REST
+    /// API only.
+    #[serde(default)]
+    order_url: String,
+    /// If true, the market depth is managed by validating the update ID sequence of the diffs and
+    /// resynchronized with the snapshot when a gap is detected. Otherwise, the market depth is
+    /// naturally refreshed by the diffs.
+    #[serde(default)]
+    managed_depth: bool,
     #[serde(default)]
     order_prefix: String,
     #[serde(default)]
     api_key: String,
     #[serde(default)]
     secret: String,
+    /// The interval in seconds at which the order states are reconciled with the exchange through
+    /// the REST API, in addition to after every reconnection of the user data stream. `0` disables
+    /// the periodic reconciliation. The default is `60`.
+    #[serde(default = "default_reconciliation_interval")]
+    reconciliation_interval: u64,
+    /// COIN-M Futures only. The contract sizes by symbol, such as `btcusd_perp = 100`. The
+    /// quantities of a symbol with a contract size, including the market depth, trades, orders,
+    /// and position, are converted from contracts into the contract's face value in USD.
+    #[serde(default)]
+    contract_sizes: HashMap<String, f64>,
 }
 
-type SharedSymbolSet = Arc<Mutex<HashSet<String>>>;
+fn default_reconciliation_interval() -> u64 {
+    60
+}
 
-/// A connector for Binance USD-m Futures.
+pub(crate) type SharedSymbolSet = Arc<Mutex<HashSet<String>>>;
+
+/// A connector for Binance USD-M and COIN-M Futures.
 pub struct BinanceFutures {
     config: Config,
     symbols: SharedSymbolSet,
     order_manager: SharedOrderManager,
     client: BinanceFuturesClient,
     symbol_tx: Sender<String>,
+    order_tx: SharedOrderSender,
+    face_value_tx: Option<UnboundedSender<PublishEvent>>,
+}
+
+/// Converts the order's quantities by multiplying them by the factor.
+fn scale_order(order: &mut Order, factor: f64) {
+    order.qty *= factor;
+    order.leaves_qty *= factor;
+    order.exec_qty *= factor;
+}
+
+/// Converts the quantities of the event from contracts into the contract's face value by the
+/// symbol's contract size. The events of the symbols without a contract size are left unchanged.
+fn to_face_value(mut ev: PublishEvent, contract_sizes: &HashMap<String, f64>) -> PublishEvent {
+    match &mut ev {
+        PublishEvent::LiveEvent(LiveEvent::Feed { symbol, event }) => {
+            if let Some(contract_size) = contract_sizes.get(symbol) {
+                event.qty *= contract_size;
+            }
+        }
+        PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }) => {
+            if let Some(contract_size) = contract_sizes.get(symbol) {
+                scale_order(order, *contract_size);
+            }
+        }
+        PublishEvent::LiveEvent(LiveEvent::Position { symbol, qty, .. }) => {
+            if let Some(contract_size) = contract_sizes.get(symbol) {
+                *qty *= contract_size;
+            }
+        }
+        _ => {}
+    }
+    ev
 }
 
 impl BinanceFutures {
+    /// Returns the contract size by which the symbol's quantities in contracts are converted into
+    /// the face value. It is `1` unless it is configured for a COIN-M Futures symbol.
+    fn contract_size(&self, symbol: &str) -> f64 {
+        match self.config.market {
+            Market::CoinM => self
+                .config
+                .contract_sizes
+                .get(symbol)
+                .copied()
+                .unwrap_or(1.0),
+            Market::UsdM => 1.0,
+        }
+    }
+
+    /// Routes the events through a task that converts the quantities into the face value if the
+    /// contract sizes are configured for COIN-M Futures, and returns the sender to use for
+    /// publishing. Since all events are published through it, their order is preserved.
+    fn route_face_value(
+        &mut self,
+        ev_tx: UnboundedSender<PublishEvent>,
+    ) -> UnboundedSender<PublishEvent> {
+        if self.config.market != Market::CoinM || self.config.contract_sizes.is_empty() {
+            return ev_tx;
+        }
+        let contract_sizes = self.config.contract_sizes.clone();
+        let (face_value_tx, mut face_value_rx) = unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(ev) = face_value_rx.recv().await {
+                if ev_tx.send(to_face_value(ev, &contract_sizes)).is_err() {
+                    break;
+                }
+            }
+        });
+        self.face_value_tx = Some(face_value_tx.clone());
+        face_value_tx
+    }
+
+    /// Returns the sender to publish the order events through, which converts the quantities into
+    /// the face value if required.
+    fn publish_tx(&self, tx: UnboundedSender<PublishEvent>) -> UnboundedSender<PublishEvent> {
+        self.face_value_tx.clone().unwrap_or(tx)
+    }
+
     pub fn connect_market_data_stream(&mut self, ev_tx: UnboundedSender<PublishEvent>) {
         let base_url = self.config.stream_url.clone();
         let client = self.client.clone();
         let symbol_tx = self.symbol_tx.clone();
+        let managed_depth = self.config.managed_depth;
 
         tokio::spawn(async move {
             let _ = Retry::new(ExponentialBackoff::default())
@@ -133,6 +360,7 @@ impl BinanceFutures {
                         client.clone(),
                         ev_tx.clone(),
                         symbol_tx.subscribe(),
+                        managed_depth,
                     );
                     stream.connect(&base_url).await?;
                     Ok(())
@@ -147,6 +375,8 @@ impl BinanceFutures {
         let order_manager = self.order_manager.clone();
         let instruments = self.symbols.clone();
         let symbol_tx = self.symbol_tx.clone();
+        let reconciliation_interval = Duration::from_secs(self.config.reconciliation_interval);
+        let connected = AtomicBool::new(false);
 
         tokio::spawn(async move {
             let _ = Retry::new(ExponentialBackoff::default())
@@ -170,6 +400,8 @@ impl BinanceFutures {
                         order_manager.clone(),
                         instruments.clone(),
                         symbol_tx.subscribe(),
+                        connected.swap(true, Ordering::Relaxed),
+                        reconciliation_interval,
                     );
 
                     let listen_key = stream.get_listen_key().await?;
@@ -180,6 +412,61 @@ impl BinanceFutures {
                 .await;
         });
     }
+
+    pub fn connect_trade_stream(&self, ev_tx: UnboundedSender<PublishEvent>) {
+        let order_url = self.config.order_url.clone();
+        let api_key = self.config.api_key.clone();
+        let secret = self.config.secret.clone();
+        let client = self.client.clone();
+        let order_manager = self.order_manager.clone();
+        let order_tx = self.order_tx.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: BinanceFuturesError| {
+                    // Order requests fall back to the REST API until the connection is restored.
+                    error!(?error, "An error occurred in the trade stream connection.");
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.into(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream = TradeStream::new(
+                        api_key.clone(),
+                        secret.clone(),
+                        ev_tx.clone(),
+                        client.clone(),
+                        order_manager.clone(),
+                        order_tx.clone(),
+                    );
+                    stream.connect(&order_url).await?;
+                    Ok(())
+                })
+                .await;
+        });
+    }
+
+    /// Sends the order request through the WebSocket API if the session is established; otherwise,
+    /// falls back to the REST API.
+    fn send_order_op(&self, op: OrderOp, tx: UnboundedSender<PublishEvent>) {
+        let op = match self.order_tx.lock().unwrap().as_ref() {
+            Some(order_tx) => match order_tx.send(op) {
+                Ok(()) => return,
+                Err(error) => error.0,
+            },
+            None => op,
+        };
+        tokio::spawn(trade_stream::send_rest(
+            self.client.clone(),
+            self.order_manager.clone(),
+            op,
+            tx,
+        ));
+    }
 }
 
 impl ConnectorBuilder for BinanceFutures {
@@ -189,7 +476,12 @@ impl ConnectorBuilder for BinanceFutures {
         let config: Config = toml::from_str(config)?;
 
         let order_manager = Arc::new(Mutex::new(OrderManager::new(&config.order_prefix)));
-        let client = BinanceFuturesClient::new(&config.api_url, &config.api_key, &config.secret);
+        let client = BinanceFuturesClient::new(
+            &config.api_url,
+            &config.api_key,
+            &config.secret,
+            config.market,
+        );
         let (symbol_tx, _) = broadcast::channel(500);
 
         Ok(BinanceFutures {
@@ -198,6 +490,8 @@ impl ConnectorBuilder for BinanceFutures {
             order_manager,
             client,
             symbol_tx,
+            order_tx: Default::default(),
+            face_value_tx: None,
         })
     }
 }
@@ -222,143 +516,249 @@ impl Connector for BinanceFutures {
     }
 
     fn run(&mut self, ev_tx: UnboundedSender<PublishEvent>) {
+        let ev_tx = self.route_face_value(ev_tx);
         self.connect_market_data_stream(ev_tx.clone());
         // Connects to the user stream only if the API key and secret are provided.
         if !self.config.api_key.is_empty() && !self.config.secret.is_empty() {
             self.connect_user_data_stream(ev_tx.clone());
+            if !self.config.order_url.is_empty() {
+                self.connect_trade_stream(ev_tx.clone());
+            }
         }
     }
 
     fn submit(&self, symbol: String, mut order: Order, tx: UnboundedSender<PublishEvent>) {
-        let client = self.client.clone();
-        let order_manager = self.order_manager.clone();
+        let tx = self.publish_tx(tx);
+        // The order manager keeps the orders in contracts.
+        scale_order(&mut order, 1.0 / self.contract_size(&symbol));
+        let client_order_id = self
+            .order_manager
+            .lock()
+            .unwrap()
+            .prepare_client_order_id(symbol.clone(), order.clone());
 
-        tokio::spawn(async move {
-            let client_order_id = order_manager
-                .lock()
-                .unwrap()
-                .prepare_client_order_id(symbol.clone(), order.clone());
-
-            match client_order_id {
-                Some(client_order_id) => {
-                    let result = client
-                        .submit_order(
-                            &client_order_id,
-                            &symbol,
-                            order.side,
-                            order.price_tick as f64 * order.tick_size,
-                            get_precision(order.tick_size),
-                            order.qty,
-                            order.order_type,
-                            order.time_in_force,
-                        )
-                        .await;
-                    match result {
-                        Ok(resp) => {
-                            if let Some(order) = order_manager
-                                .lock()
-                                .unwrap()
-                                .update_from_rest(&client_order_id, &resp)
-                            {
-                                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
-                                    symbol,
-                                    order,
-                                }))
-                                .unwrap();
-                            }
-                        }
-                        Err(error) => {
-                            if let Some(order) = order_manager
-                                .lock()
-                                .unwrap()
-                                .update_submit_fail(&client_order_id, &error)
-                            {
-                                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
-                                    symbol,
-                                    order,
-                                }))
-                                .unwrap();
-                            }
-
-                            tx.send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                                ErrorKind::OrderError,
-                                error.into(),
-                            ))))
-                            .unwrap();
-                        }
-                    }
-                }
-                None => {
-                    warn!(
-                        ?order,
-                        "Coincidentally, creates a duplicated client order id. \
-                        This order request will be expired."
-                    );
-                    order.req = Status::None;
-                    order.status = Status::Expired;
-                    tx.send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
-                        .unwrap();
-                }
+        match client_order_id {
+            Some(client_order_id) => {
+                let op = OrderOp::Submit {
+                    symbol,
+                    client_order_id,
+                    side: order.side,
+                    price: order.price_tick as f64 * order.tick_size,
+                    price_prec: get_precision(order.tick_size),
+                    qty: order.qty,
+                    order_type: order.order_type,
+                    time_in_force: order.time_in_force,
+                };
+                self.send_order_op(op, tx);
             }
-        });
+            None => {
+                warn!(
+                    ?order,
+                    "Coincidentally, creates a duplicated client order id. \
+                    This order request will be expired."
+                );
+                order.req = Status::None;
+                order.status = Status::Expired;
+                tx.send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn modify(&self, symbol: String, order: Order, tx: UnboundedSender<PublishEvent>) {
+        let tx = self.publish_tx(tx);
+        let contract_size = self.contract_size(&symbol);
+        let client_order_id = self
+            .order_manager
+            .lock()
+            .unwrap()
+            .get_client_order_id(&symbol, order.order_id);
+
+        match client_order_id {
+            Some(client_order_id) => {
+                let op = OrderOp::Modify {
+                    symbol,
+                    client_order_id,
+                    side: order.side,
+                    price: order.price_tick as f64 * order.tick_size,
+                    price_prec: get_precision(order.tick_size),
+                    qty: order.qty / contract_size,
+                };
+                self.send_order_op(op, tx);
+            }
+            None => {
+                warn!(
+                    order_id = order.order_id,
+                    "client_order_id corresponding to order_id is not found; \
+                    this may be due to the order already being canceled or filled."
+                );
+            }
+        }
     }
 
     fn cancel(&self, symbol: String, order: Order, tx: UnboundedSender<PublishEvent>) {
-        let client = self.client.clone();
-        let order_manager = self.order_manager.clone();
+        let tx = self.publish_tx(tx);
+        let client_order_id = self
+            .order_manager
+            .lock()
+            .unwrap()
+            .get_client_order_id(&symbol, order.order_id);
 
+        match client_order_id {
+            Some(client_order_id) => {
+                let op = OrderOp::Cancel {
+                    symbol,
+                    client_order_id,
+                };
+                self.send_order_op(op, tx);
+            }
+            None => {
+                warn!(
+                    order_id = order.order_id,
+                    "client_order_id corresponding to order_id is not found; \
+                    this may be due to the order already being canceled or filled."
+                );
+            }
+        }
+    }
+
+    fn refresh_dead_man_switch(&self, symbol: String, timeout: Duration) {
+        let client = self.client.clone();
         tokio::spawn(async move {
-            let client_order_id = order_manager
-                .lock()
-                .unwrap()
-                .get_client_order_id(&symbol, order.order_id);
-
-            match client_order_id {
-                Some(client_order_id) => {
-                    let result = client.cancel_order(&client_order_id, &symbol).await;
-                    match result {
-                        Ok(resp) => {
-                            if let Some(order) = order_manager
-                                .lock()
-                                .unwrap()
-                                .update_from_rest(&client_order_id, &resp)
-                            {
-                                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
-                                    symbol,
-                                    order,
-                                }))
-                                .unwrap();
-                            }
-                        }
-                        Err(error) => {
-                            if let Some(order) = order_manager
-                                .lock()
-                                .unwrap()
-                                .update_cancel_fail(&client_order_id, &error)
-                            {
-                                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
-                                    symbol,
-                                    order,
-                                }))
-                                .unwrap();
-                            }
-
-                            tx.send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                                ErrorKind::OrderError,
-                                error.into(),
-                            ))))
-                            .unwrap();
-                        }
-                    }
-                }
-                None => {
-                    warn!(
-                        order_id = order.order_id,
-                        "client_order_id corresponding to order_id is not found; \
-                        this may be due to the order already being canceled or filled."
-                    );
+            if let Err(error) = client
+                .countdown_cancel_all(&symbol, timeout.as_millis() as i64)
+                .await
+            {
+                error!(?error, %symbol, "Couldn't refresh the countdown cancel all.");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use hftbacktest::{
+        prelude::{LOCAL_BID_DEPTH_EVENT, OrdType, Side, TimeInForce},
+        types::{ErrorReason, Event, LiveEvent, Order},
+    };
+
+    use crate::{
+        binancefutures::{BinanceFuturesError, scale_order, to_face_value},
+        connector::PublishEvent,
+    };
+
+    fn order_error(code: i64, msg: &str) -> BinanceFuturesError {
+        BinanceFuturesError::OrderError {
+            code,
+            msg: msg.to_string(),
+        }
+    }
+
+    #[test]
+    fn maps_error_reasons() {
+        let cases = [
+            (order_error(-1003, "Too many requests."), ErrorReason::RateLimited),
+            (order_error(-1015, "Too many new orders."), ErrorReason::RateLimited),
+            (order_error(-2019, "Margin is insufficient."), ErrorReason::InsufficientMargin),
+            (
+                order_error(-5022, "Post Only order will be rejected."),
+                ErrorReason::PostOnlyWouldTake,
+            ),
+            (order_error(-4016, "Limit price can't be higher."), ErrorReason::PriceOutOfBand),
+            (order_error(-2011, "Unknown order sent."), ErrorReason::UnknownOrder),
+            (order_error(-4116, "ClientOrderId is duplicated."), ErrorReason::DuplicateOrderId),
+            (order_error(-2015, "Invalid API-key."), ErrorReason::AuthFailure),
+            // Binance Spot
+            (
+                order_error(-2010, "Account has insufficient balance for requested action."),
+                ErrorReason::InsufficientMargin,
+            ),
+            (
+                order_error(-2010, "Order would immediately match and take."),
+                ErrorReason::PostOnlyWouldTake,
+            ),
+            (
+                order_error(-1013, "Filter failure: PERCENT_PRICE_BY_SIDE"),
+                ErrorReason::PriceOutOfBand,
+            ),
+            (order_error(-2010, "Duplicate order sent."), ErrorReason::DuplicateOrderId),
+            (order_error(-1013, "Filter failure: LOT_SIZE"), ErrorReason::Other),
+            (order_error(-1100, "Illegal characters found."), ErrorReason::Other),
+            (BinanceFuturesError::OrderNotFound, ErrorReason::UnknownOrder),
+            (BinanceFuturesError::ConnectionInterrupted, ErrorReason::Other),
+        ];
+        for (error, reason) in cases {
+            assert_eq!(error.reason(), reason, "{error}");
+        }
+    }
+
+    #[test]
+    fn converts_contracts_to_face_value() {
+        let contract_sizes = HashMap::from([("btcusd_perp".to_string(), 100.0)]);
+
+        let feed = |symbol: &str| {
+            PublishEvent::LiveEvent(LiveEvent::Feed {
+                symbol: symbol.to_string(),
+                event: Event {
+                    ev: LOCAL_BID_DEPTH_EVENT,
+                    exch_ts: 1,
+                    local_ts: 2,
+                    px: 60000.0,
+                    qty: 3.0,
+                    order_id: 0,
+                    ival: 0,
+                    fval: 0.0,
+                },
+            })
+        };
+        for (symbol, qty) in [("btcusd_perp", 300.0), ("ethusd_perp", 3.0)] {
+            match to_face_value(feed(symbol), &contract_sizes) {
+                PublishEvent::LiveEvent(LiveEvent::Feed { event, .. }) => {
+                    assert_eq!(event.qty, qty, "{symbol}");
                 }
+                _ => panic!("unexpected event"),
             }
+        }
+
+        let position = PublishEvent::LiveEvent(LiveEvent::Position {
+            symbol: "btcusd_perp".to_string(),
+            qty: -2.0,
+            exch_ts: 1,
+        });
+        match to_face_value(position, &contract_sizes) {
+            PublishEvent::LiveEvent(LiveEvent::Position { qty, .. }) => assert_eq!(qty, -200.0),
+            _ => panic!("unexpected event"),
+        }
+
+        // The order submitted in the face value is kept in contracts and converted back when it is
+        // published.
+        let mut order = Order::new(
+            1,
+            600000,
+            0.1,
+            500.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        scale_order(&mut order, 1.0 / 100.0);
+        assert_eq!(order.qty, 5.0);
+        assert_eq!(order.leaves_qty, 5.0);
+        order.leaves_qty = 3.0;
+        order.exec_qty = 2.0;
+        let ev = PublishEvent::LiveEvent(LiveEvent::Order {
+            symbol: "btcusd_perp".to_string(),
+            order,
         });
+        match to_face_value(ev, &contract_sizes) {
+            PublishEvent::LiveEvent(LiveEvent::Order { order, .. }) => {
+                assert_eq!(order.qty, 500.0);
+                assert_eq!(order.leaves_qty, 300.0);
+                assert_eq!(order.exec_qty, 200.0);
+            }
+            _ => panic!("unexpected event"),
+        }
     }
 }
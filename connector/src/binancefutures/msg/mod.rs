@@ -9,8 +9,9 @@ use serde::{
 pub mod rest;
 #[allow(dead_code)]
 pub mod stream;
+pub mod trade;
 
-fn from_str_to_side<'de, D>(deserializer: D) -> Result<Side, D::Error>
+pub(crate) fn from_str_to_side<'de, D>(deserializer: D) -> Result<Side, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -22,7 +23,7 @@ where
     }
 }
 
-fn from_str_to_status<'de, D>(deserializer: D) -> Result<Status, D::Error>
+pub(crate) fn from_str_to_status<'de, D>(deserializer: D) -> Result<Status, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -42,7 +43,7 @@ where
     }
 }
 
-fn from_str_to_type<'de, D>(deserializer: D) -> Result<OrdType, D::Error>
+pub(crate) fn from_str_to_type<'de, D>(deserializer: D) -> Result<OrdType, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -59,7 +60,7 @@ where
     }
 }
 
-fn from_str_to_tif<'de, D>(deserializer: D) -> Result<TimeInForce, D::Error>
+pub(crate) fn from_str_to_tif<'de, D>(deserializer: D) -> Result<TimeInForce, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -92,10 +92,49 @@ pub struct OrderResponse {
     pub price_match: String,
     #[serde(rename = "selfTradePreventionMode")]
     pub self_trade_prevention_mode: String,
+    /// USD-M Futures only field
     #[serde(rename = "goodTillDate")]
+    #[serde(default)]
     pub good_till_date: i64,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum QueryOrderResponseResult {
+    Ok(QueryOrderResponse),
+    Err(ErrorResponse),
+}
+
+/// The order returned by the Query Order and Current All Open Orders endpoints, which, unlike the
+/// order request responses, doesn't provide `cumQty`.
+#[derive(Deserialize, Debug)]
+pub struct QueryOrderResponse {
+    #[serde(rename = "clientOrderId")]
+    pub client_order_id: String,
+    #[serde(rename = "executedQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub executed_qty: f64,
+    #[serde(rename = "origQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub orig_qty: f64,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub status: Status,
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "timeInForce")]
+    #[serde(deserialize_with = "from_str_to_tif")]
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    #[serde(deserialize_with = "from_str_to_type")]
+    pub ty: OrdType,
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ErrorResponse {
     pub code: i64,
@@ -122,13 +161,14 @@ pub struct PositionInformationV2 {
     #[serde(rename = "markPrice")]
     #[serde(deserialize_with = "from_str_to_f64")]
     pub mark_price: f64,
+    // COIN-M Futures doesn't provide `maxNotionalValue` and `notional`.
     #[serde(rename = "maxNotionalValue")]
-    #[serde(deserialize_with = "from_str_to_f64")]
+    #[serde(default, deserialize_with = "from_str_to_f64")]
     pub max_notional_value: f64,
     #[serde(rename = "positionAmt")]
     #[serde(deserialize_with = "from_str_to_f64")]
     pub position_amount: f64,
-    #[serde(deserialize_with = "from_str_to_f64")]
+    #[serde(default, deserialize_with = "from_str_to_f64")]
     pub notional: f64,
     #[serde(rename = "isolatedWallet")]
     pub isolated_wallet: String,
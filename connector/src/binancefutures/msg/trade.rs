@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use super::rest::ErrorResponse;
+
+#[derive(Serialize, Debug)]
+pub struct Request<'a> {
+    pub id: String,
+    pub method: &'a str,
+    pub params: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Response {
+    pub id: Option<String>,
+    pub status: i64,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<ErrorResponse>,
+}
@@ -2,13 +2,16 @@ use std::sync::{Arc, Mutex};
 
 use chrono::Utc;
 use hashbrown::HashMap;
-use hftbacktest::types::{Order, OrderId, Status};
+use hftbacktest::types::{OrdType, Order, OrderId, Side, Status, TimeInForce};
 use tracing::error;
 
 use crate::{
     binancefutures::{
         BinanceFuturesError,
-        msg::{rest::OrderResponse, stream::OrderTradeUpdate},
+        msg::{
+            rest::{OrderResponse, QueryOrderResponse},
+            stream::OrderTradeUpdate,
+        },
     },
     connector::GetOrders,
     utils::{RefSymbolOrderId, SymbolOrderId, generate_rand_string},
@@ -26,6 +29,74 @@ pub type SharedOrderManager = Arc<Mutex<OrderManager>>;
 
 pub type ClientOrderId = String;
 
+/// An order state update received from either the REST API or the WebSocket stream, normalized so
+/// that the [`OrderManager`] can be shared across the Binance markets.
+#[derive(Debug)]
+pub struct OrderUpdate {
+    pub client_order_id: ClientOrderId,
+    /// The exchange timestamp in milliseconds.
+    pub exch_ts: i64,
+    pub qty: f64,
+    pub cum_qty: f64,
+    pub exec_qty: f64,
+    pub price: f64,
+    pub side: Side,
+    pub time_in_force: TimeInForce,
+    pub order_type: OrdType,
+    pub status: Status,
+}
+
+impl From<&OrderTradeUpdate> for OrderUpdate {
+    fn from(data: &OrderTradeUpdate) -> Self {
+        Self {
+            client_order_id: data.order.client_order_id.clone(),
+            exch_ts: data.transaction_time,
+            qty: data.order.original_qty,
+            cum_qty: data.order.order_filled_accumulated_qty,
+            exec_qty: data.order.order_last_filled_qty,
+            price: data.order.original_price,
+            side: data.order.side,
+            time_in_force: data.order.time_in_force,
+            order_type: data.order.order_type,
+            status: data.order.order_status,
+        }
+    }
+}
+
+impl From<&OrderResponse> for OrderUpdate {
+    fn from(resp: &OrderResponse) -> Self {
+        Self {
+            client_order_id: resp.client_order_id.clone(),
+            exch_ts: resp.update_time,
+            qty: resp.orig_qty,
+            cum_qty: resp.cum_qty,
+            exec_qty: resp.executed_qty,
+            price: resp.price,
+            side: resp.side,
+            time_in_force: resp.time_in_force,
+            order_type: resp.ty,
+            status: resp.status,
+        }
+    }
+}
+
+impl From<&QueryOrderResponse> for OrderUpdate {
+    fn from(resp: &QueryOrderResponse) -> Self {
+        Self {
+            client_order_id: resp.client_order_id.clone(),
+            exch_ts: resp.update_time,
+            qty: resp.orig_qty,
+            cum_qty: resp.executed_qty,
+            exec_qty: resp.executed_qty,
+            price: resp.price,
+            side: resp.side,
+            time_in_force: resp.time_in_force,
+            order_type: resp.ty,
+            status: resp.status,
+        }
+    }
+}
+
 /// Binance has separated channels for REST APIs and Websocket. Order responses are delivered
 /// through these channels, with no guaranteed order of transmission. To prevent duplicate handling
 /// of order responses, such as order deletion due to cancellation or fill, OrderManager manages the
@@ -58,27 +129,31 @@ impl OrderManager {
 
     pub fn update_from_ws(
         &mut self,
-        resp: &OrderTradeUpdate,
+        resp: &OrderUpdate,
     ) -> Result<Option<Order>, BinanceFuturesError> {
-        if !resp.order.client_order_id.starts_with(&self.prefix) {
+        if !resp.client_order_id.starts_with(&self.prefix) {
             return Err(BinanceFuturesError::PrefixUnmatched);
         }
         let order_ext = self
             .orders
-            .get_mut(&resp.order.client_order_id)
+            .get_mut(&resp.client_order_id)
             .ok_or(BinanceFuturesError::OrderNotFound)?;
 
         let already_removed = order_ext.removed_by_ws || order_ext.removed_by_rest;
-        if resp.transaction_time * 1_000_000 >= order_ext.order.exch_timestamp {
-            order_ext.order.qty = resp.order.original_qty;
-            order_ext.order.leaves_qty =
-                resp.order.original_qty - resp.order.order_filled_accumulated_qty;
-            order_ext.order.side = resp.order.side;
-            order_ext.order.time_in_force = resp.order.time_in_force;
-            order_ext.order.exch_timestamp = resp.transaction_time * 1_000_000;
-            order_ext.order.status = resp.order.order_status;
-            order_ext.order.exec_qty = resp.order.order_last_filled_qty;
-            order_ext.order.order_type = resp.order.order_type;
+        if resp.exch_ts * 1_000_000 >= order_ext.order.exch_timestamp {
+            order_ext.order.qty = resp.qty;
+            order_ext.order.leaves_qty = resp.qty - resp.cum_qty;
+            order_ext.order.side = resp.side;
+            order_ext.order.time_in_force = resp.time_in_force;
+            order_ext.order.exch_timestamp = resp.exch_ts * 1_000_000;
+            order_ext.order.status = resp.status;
+            order_ext.order.exec_qty = resp.exec_qty;
+            order_ext.order.order_type = resp.order_type;
+            if resp.order_type == OrdType::Limit {
+                // The price can be changed by modification.
+                order_ext.order.price_tick =
+                    (resp.price / order_ext.order.tick_size).round() as i64;
+            }
         }
 
         let result = if already_removed {
@@ -99,7 +174,7 @@ impl OrderManager {
             }
 
             if order_ext.removed_by_ws && order_ext.removed_by_rest {
-                self.orders.remove(&resp.order.client_order_id).unwrap();
+                self.orders.remove(&resp.client_order_id).unwrap();
             }
         }
 
@@ -155,6 +230,27 @@ impl OrderManager {
         }
     }
 
+    pub fn update_modify_fail(
+        &mut self,
+        client_order_id: &ClientOrderId,
+        error: &BinanceFuturesError,
+    ) -> Option<Order> {
+        match error {
+            BinanceFuturesError::OrderError { code: -5027, .. } => {
+                // No need to modify the order.
+            }
+            BinanceFuturesError::OrderError { code: -2013, .. } => {
+                // The order does not exist. It could have already been filled or canceled, which
+                // will be updated by the user data stream.
+            }
+            error => {
+                error!(?error, "modify error");
+            }
+        }
+        // The order keeps the current price and quantity.
+        self.update_from_rest_fail(client_order_id, None)
+    }
+
     pub fn update_from_rest_fail(
         &mut self,
         client_order_id: &ClientOrderId,
@@ -197,21 +293,26 @@ impl OrderManager {
     pub fn update_from_rest(
         &mut self,
         client_order_id: &ClientOrderId,
-        resp: &OrderResponse,
+        resp: &OrderUpdate,
     ) -> Option<Order> {
         let order_ext = self.orders.get_mut(client_order_id)?;
         // .ok_or(BinanceFuturesError::OrderNotFound)?;
 
         let already_removed = order_ext.removed_by_ws || order_ext.removed_by_rest;
-        if resp.update_time * 1_000_000 >= order_ext.order.exch_timestamp {
-            order_ext.order.qty = resp.orig_qty;
-            order_ext.order.leaves_qty = resp.orig_qty - resp.cum_qty;
+        if resp.exch_ts * 1_000_000 >= order_ext.order.exch_timestamp {
+            order_ext.order.qty = resp.qty;
+            order_ext.order.leaves_qty = resp.qty - resp.cum_qty;
             order_ext.order.side = resp.side;
             order_ext.order.time_in_force = resp.time_in_force;
-            order_ext.order.exch_timestamp = resp.update_time * 1_000_000;
+            order_ext.order.exch_timestamp = resp.exch_ts * 1_000_000;
             order_ext.order.status = resp.status;
-            order_ext.order.exec_qty = resp.executed_qty;
-            order_ext.order.order_type = resp.ty;
+            order_ext.order.exec_qty = resp.exec_qty;
+            order_ext.order.order_type = resp.order_type;
+            if resp.order_type == OrdType::Limit {
+                // The price can be changed by modification.
+                order_ext.order.price_tick =
+                    (resp.price / order_ext.order.tick_size).round() as i64;
+            }
             order_ext.order.req = Status::None;
         }
 
@@ -265,6 +366,84 @@ impl OrderManager {
         Some(client_order_id)
     }
 
+    /// Returns the client order IDs of the symbol's active orders that have no request in flight,
+    /// which are expected to be open on the exchange.
+    pub fn reconcilable_orders(&self, symbol: &str) -> Vec<ClientOrderId> {
+        self.orders
+            .iter()
+            .filter(|(_, order_ext)| {
+                order_ext.symbol == symbol
+                    && !order_ext.removed_by_ws
+                    && !order_ext.removed_by_rest
+                    && order_ext.order.active()
+                    && order_ext.order.req == Status::None
+            })
+            .map(|(client_order_id, _)| client_order_id.clone())
+            .collect()
+    }
+
+    /// Applies the order state queried through the REST API during the reconciliation, which may
+    /// have diverged from the managed state if the user data stream missed updates. Returns the
+    /// repaired order only if its state differs from the managed one.
+    pub fn reconcile(&mut self, resp: &OrderUpdate) -> Result<Option<Order>, BinanceFuturesError> {
+        if !resp.client_order_id.starts_with(&self.prefix) {
+            return Err(BinanceFuturesError::PrefixUnmatched);
+        }
+        let order_ext = self
+            .orders
+            .get(&resp.client_order_id)
+            .ok_or(BinanceFuturesError::OrderNotFound)?;
+        if order_ext.removed_by_ws
+            || order_ext.removed_by_rest
+            || order_ext.order.req != Status::None
+        {
+            // The order is already closed, or the response of the request in flight will update it.
+            return Ok(None);
+        }
+        let order = &order_ext.order;
+        let cum_qty = order.qty - order.leaves_qty;
+        if resp.exch_ts * 1_000_000 < order.exch_timestamp
+            || (order.status == resp.status && (resp.cum_qty - cum_qty).abs() < order.qty * 1e-9)
+        {
+            // The managed state is newer or already up to date.
+            return Ok(None);
+        }
+        // The executed quantity is what was missed since the last known state.
+        let resp = OrderUpdate {
+            client_order_id: resp.client_order_id.clone(),
+            exec_qty: resp.cum_qty - cum_qty,
+            ..*resp
+        };
+        Ok(self.update_from_rest(&resp.client_order_id, &resp))
+    }
+
+    /// Applies the order state queried through the REST API to the order whose request is lost,
+    /// such as by a disconnection of the WebSocket API, so that the request is no longer in flight.
+    pub fn resolve_lost_request(&mut self, resp: &OrderUpdate) -> Option<Order> {
+        let order_ext = self.orders.get_mut(&resp.client_order_id)?;
+        // The request is resolved even if the queried state is older than the managed one.
+        order_ext.order.req = Status::None;
+        let cum_qty = order_ext.order.qty - order_ext.order.leaves_qty;
+        let resp = OrderUpdate {
+            client_order_id: resp.client_order_id.clone(),
+            exec_qty: resp.cum_qty - cum_qty,
+            ..*resp
+        };
+        self.update_from_rest(&resp.client_order_id, &resp)
+    }
+
+    pub fn get_order_id(&self, client_order_id: &str) -> Option<OrderId> {
+        self.orders
+            .get(client_order_id)
+            .map(|order_ext| order_ext.order.order_id)
+    }
+
+    pub fn get_order(&self, client_order_id: &str) -> Option<&Order> {
+        self.orders
+            .get(client_order_id)
+            .map(|order_ext| &order_ext.order)
+    }
+
     pub fn get_client_order_id(&self, symbol: &str, order_id: OrderId) -> Option<String> {
         self.order_id_map
             .get(&RefSymbolOrderId::new(symbol, order_id))
@@ -348,3 +527,142 @@ impl GetOrders for OrderManager {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::types::{OrdType, Order, Side, Status, TimeInForce};
+
+    use crate::binancefutures::{
+        BinanceFuturesError,
+        ordermanager::{OrderManager, OrderUpdate},
+    };
+
+    fn order_update(
+        client_order_id: &str,
+        status: Status,
+        cum_qty: f64,
+        exch_ts: i64,
+    ) -> OrderUpdate {
+        OrderUpdate {
+            client_order_id: client_order_id.to_string(),
+            exch_ts,
+            qty: 2.0,
+            cum_qty,
+            exec_qty: 0.0,
+            price: 100.0,
+            side: Side::Buy,
+            time_in_force: TimeInForce::GTC,
+            order_type: OrdType::Limit,
+            status,
+        }
+    }
+
+    #[test]
+    fn reconcile_missed_fill() {
+        let mut order_manager = OrderManager::new("prefix");
+        let mut order = Order::new(
+            1,
+            1000,
+            0.1,
+            2.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        order.req = Status::New;
+        let client_order_id = order_manager
+            .prepare_client_order_id("btcusdt".to_string(), order)
+            .unwrap();
+
+        // The order with the request in flight isn't reconciled.
+        assert!(order_manager.reconcilable_orders("btcusdt").is_empty());
+        assert!(
+            order_manager
+                .reconcile(&order_update(&client_order_id, Status::New, 0.0, 1))
+                .unwrap()
+                .is_none()
+        );
+
+        order_manager.update_from_rest(
+            &client_order_id,
+            &order_update(&client_order_id, Status::New, 0.0, 1),
+        );
+        assert_eq!(
+            order_manager.reconcilable_orders("btcusdt"),
+            vec![client_order_id.clone()]
+        );
+        assert!(
+            order_manager
+                .reconcile(&order_update(&client_order_id, Status::New, 0.0, 2))
+                .unwrap()
+                .is_none()
+        );
+
+        let order = order_manager
+            .reconcile(&order_update(&client_order_id, Status::Filled, 2.0, 3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, Status::Filled);
+        assert_eq!(order.exec_qty, 2.0);
+        assert_eq!(order.leaves_qty, 0.0);
+        assert!(order_manager.reconcilable_orders("btcusdt").is_empty());
+
+        assert!(matches!(
+            order_manager.reconcile(&order_update("prefixunknown", Status::New, 0.0, 4)),
+            Err(BinanceFuturesError::OrderNotFound)
+        ));
+    }
+
+    #[test]
+    fn resolve_lost_request() {
+        let mut order_manager = OrderManager::new("prefix");
+        let mut order = Order::new(
+            1,
+            1000,
+            0.1,
+            2.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        order.req = Status::New;
+        let client_order_id = order_manager
+            .prepare_client_order_id("btcusdt".to_string(), order)
+            .unwrap();
+        order_manager.update_from_rest(
+            &client_order_id,
+            &order_update(&client_order_id, Status::New, 0.0, 2),
+        );
+
+        // The cancel request is lost while the order is partially filled.
+        let order_ext = order_manager.orders.get_mut(&client_order_id).unwrap();
+        order_ext.order.req = Status::Canceled;
+        let order = order_manager
+            .resolve_lost_request(&order_update(
+                &client_order_id,
+                Status::PartiallyFilled,
+                0.5,
+                3,
+            ))
+            .unwrap();
+        assert_eq!(order.req, Status::None);
+        assert_eq!(order.status, Status::PartiallyFilled);
+        assert_eq!(order.exec_qty, 0.5);
+        assert_eq!(order.leaves_qty, 1.5);
+
+        // The queried state older than the managed one only resolves the request.
+        let order_ext = order_manager.orders.get_mut(&client_order_id).unwrap();
+        order_ext.order.req = Status::Canceled;
+        let order = order_manager
+            .resolve_lost_request(&order_update(&client_order_id, Status::New, 0.0, 1))
+            .unwrap();
+        assert_eq!(order.req, Status::None);
+        assert_eq!(order.status, Status::PartiallyFilled);
+
+        assert!(
+            order_manager
+                .resolve_lost_request(&order_update("prefixunknown", Status::New, 0.0, 4))
+                .is_none()
+        );
+    }
+}
@@ -6,8 +6,14 @@ use super::msg::{rest, rest::PositionInformationV2};
 use crate::{
     binancefutures::{
         BinanceFuturesError,
+        Market,
         msg::{
-            rest::{OrderResponse, OrderResponseResult},
+            rest::{
+                OrderResponse,
+                OrderResponseResult,
+                QueryOrderResponse,
+                QueryOrderResponseResult,
+            },
             stream::ListenKey,
         },
     },
@@ -20,18 +26,28 @@ pub struct BinanceFuturesClient {
     url: String,
     api_key: String,
     secret: String,
+    market: Market,
 }
 
 impl BinanceFuturesClient {
-    pub fn new(url: &str, api_key: &str, secret: &str) -> Self {
+    pub fn new(url: &str, api_key: &str, secret: &str, market: Market) -> Self {
         Self {
             client: reqwest::Client::new(),
             url: url.to_string(),
             api_key: api_key.to_string(),
             secret: secret.to_string(),
+            market,
         }
     }
 
+    pub fn market(&self) -> Market {
+        self.market
+    }
+
+    fn path(&self, path: &str) -> String {
+        format!("{}{}", self.market.path_prefix(), path)
+    }
+
     async fn get_noauth<T: for<'a> Deserialize<'a>>(
         &self,
         path: &str,
@@ -148,12 +164,13 @@ impl BinanceFuturesClient {
     }
 
     pub async fn start_user_data_stream(&self) -> Result<String, reqwest::Error> {
-        let resp: Result<ListenKey, _> = self.post("/fapi/v1/listenKey", String::new()).await;
+        let resp: Result<ListenKey, _> =
+            self.post(&self.path("/v1/listenKey"), String::new()).await;
         resp.map(|v| v.listen_key)
     }
 
     pub async fn keepalive_user_data_stream(&self) -> Result<(), reqwest::Error> {
-        let _: serde_json::Value = self.put("/fapi/v1/listenKey", String::new()).await?;
+        let _: serde_json::Value = self.put(&self.path("/v1/listenKey"), String::new()).await?;
         Ok(())
     }
 
@@ -177,7 +194,7 @@ impl BinanceFuturesClient {
         body.push_str("&side=");
         body.push_str(side.as_ref());        
         body.push_str("&quantity=");
-        body.push_str(&format!("{:.5}", qty));
+        body.push_str(&format!("{:.prec$}", qty, prec = self.market.qty_prec()));
         body.push_str("&type=");
         body.push_str(order_type.as_ref());
         
@@ -189,7 +206,7 @@ impl BinanceFuturesClient {
             body.push_str(time_in_force.as_ref());
         } 
 
-        let resp: OrderResponseResult = self.post("/fapi/v1/order", body).await?;
+        let resp: OrderResponseResult = self.post(&self.path("/v1/order"), body).await?;
         match resp {
             OrderResponseResult::Ok(resp) => Ok(resp),
             OrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
@@ -220,7 +237,11 @@ impl BinanceFuturesClient {
             body.push_str(order.2.as_ref());
            
             body.push_str("\",\"quantity\":\"");
-            body.push_str(&format!("{:.5}", order.5));
+            body.push_str(&format!(
+                "{:.prec$}",
+                order.5,
+                prec = self.market.qty_prec()
+            ));
             body.push_str("\",\"type\":\"");
             body.push_str(order.6.as_ref());
             if order.6 == OrdType::Limit {
@@ -236,7 +257,7 @@ impl BinanceFuturesClient {
         }
         body.push_str("]}");
 
-        let resp: Vec<OrderResponseResult> = self.post("/fapi/v1/batchOrders", body).await?;
+        let resp: Vec<OrderResponseResult> = self.post(&self.path("/v1/batchOrders"), body).await?;
         Ok(resp
             .into_iter()
             .map(|resp| match resp {
@@ -268,9 +289,9 @@ impl BinanceFuturesClient {
         body.push_str("&price=");
         body.push_str(&format!("{:.prec$}", price, prec = price_prec));
         body.push_str("&quantity=");
-        body.push_str(&format!("{:.5}", qty));
+        body.push_str(&format!("{:.prec$}", qty, prec = self.market.qty_prec()));
 
-        let resp: OrderResponseResult = self.put("/fapi/v1/order", body).await?;
+        let resp: OrderResponseResult = self.put(&self.path("/v1/order"), body).await?;
         match resp {
             OrderResponseResult::Ok(resp) => Ok(resp),
             OrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
@@ -291,7 +312,7 @@ impl BinanceFuturesClient {
         body.push_str("&origClientOrderId=");
         body.push_str(client_order_id);
 
-        let resp: OrderResponseResult = self.delete("/fapi/v1/order", body).await?;
+        let resp: OrderResponseResult = self.delete(&self.path("/v1/order"), body).await?;
         match resp {
             OrderResponseResult::Ok(resp) => Ok(resp),
             OrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
@@ -322,7 +343,7 @@ impl BinanceFuturesClient {
             body.push('\"');
         }
         body.push_str("]}");
-        let resp: Vec<OrderResponseResult> = self.post("/fapi/v1/batchOrders", body).await?;
+        let resp: Vec<OrderResponseResult> = self.post(&self.path("/v1/batchOrders"), body).await?;
         Ok(resp
             .into_iter()
             .map(|resp| match resp {
@@ -337,22 +358,98 @@ impl BinanceFuturesClient {
 
     pub async fn cancel_all_orders(&self, symbol: &str) -> Result<(), reqwest::Error> {
         let _: serde_json::Value = self
-            .delete("/fapi/v1/allOpenOrders", format!("symbol={}", symbol))
+            .delete(
+                &self.path("/v1/allOpenOrders"),
+                format!("symbol={}", symbol),
+            )
             .await?;
         Ok(())
     }
 
+    pub async fn countdown_cancel_all(
+        &self,
+        symbol: &str,
+        countdown_time: i64,
+    ) -> Result<(), BinanceFuturesError> {
+        let resp: serde_json::Value = self
+            .post(
+                &self.path("/v1/countdownCancelAll"),
+                format!("symbol={symbol}&countdownTime={countdown_time}"),
+            )
+            .await?;
+        match resp.get("code").and_then(|code| code.as_i64()) {
+            Some(code) if code < 0 => Err(BinanceFuturesError::OrderError {
+                code,
+                msg: resp
+                    .get("msg")
+                    .and_then(|msg| msg.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn get_open_orders(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<QueryOrderResponse>, reqwest::Error> {
+        let resp: Vec<QueryOrderResponse> = self
+            .get(&self.path("/v1/openOrders"), format!("symbol={symbol}"))
+            .await?;
+        Ok(resp)
+    }
+
+    /// Returns the most recent orders of the symbol, including the ones that are no longer open,
+    /// up to the maximum limit.
+    pub async fn get_all_orders(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<QueryOrderResponse>, reqwest::Error> {
+        let resp: Vec<QueryOrderResponse> = self
+            .get(
+                &self.path("/v1/allOrders"),
+                format!("symbol={symbol}&limit=1000"),
+            )
+            .await?;
+        Ok(resp)
+    }
+
+    pub async fn query_order(
+        &self,
+        client_order_id: &str,
+        symbol: &str,
+    ) -> Result<QueryOrderResponse, BinanceFuturesError> {
+        let resp: QueryOrderResponseResult = self
+            .get(
+                &self.path("/v1/order"),
+                format!("symbol={symbol}&origClientOrderId={client_order_id}"),
+            )
+            .await?;
+        match resp {
+            QueryOrderResponseResult::Ok(resp) => Ok(resp),
+            QueryOrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
+                code: resp.code,
+                msg: resp.msg,
+            }),
+        }
+    }
+
     pub async fn get_position_information(
         &self,
     ) -> Result<Vec<PositionInformationV2>, reqwest::Error> {
-        let resp: Vec<PositionInformationV2> =
-            self.get("/fapi/v2/positionRisk", String::new()).await?;
+        let resp: Vec<PositionInformationV2> = self
+            .get(self.market.position_risk_path(), String::new())
+            .await?;
         Ok(resp)
     }
 
     pub async fn get_depth(&self, symbol: &str) -> Result<rest::Depth, reqwest::Error> {
         let resp: rest::Depth = self
-            .get_noauth("/fapi/v1/depth", format!("symbol={}&limit=1000", symbol))
+            .get_noauth(
+                &self.path("/v1/depth"),
+                format!("symbol={}&limit=1000", symbol),
+            )
             .await?;
         Ok(resp)
     }
@@ -0,0 +1,522 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use hftbacktest::types::{ErrorKind, LiveError, LiveEvent, OrdType, Side, TimeInForce};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::{
+    select,
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    time,
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Message, client::IntoClientRequest},
+};
+use tracing::{debug, error, warn};
+
+use crate::{
+    binancefutures::{
+        BinanceFuturesError,
+        msg::{
+            rest::OrderResponse,
+            trade::{Request, Response},
+        },
+        ordermanager::{OrderUpdate, SharedOrderManager},
+        rest::BinanceFuturesClient,
+    },
+    connector::PublishEvent,
+    utils::sign_hmac_sha256,
+};
+
+/// The time window within which the exchange accepts a request after its timestamp.
+const RECV_WINDOW: Duration = Duration::from_secs(5);
+
+/// Holds the sender to the trade stream while its WebSocket API session is established. If it is
+/// `None`, order requests should be sent through the REST API instead.
+pub type SharedOrderSender = Arc<Mutex<Option<UnboundedSender<OrderOp>>>>;
+
+#[derive(Clone, Debug)]
+pub enum OrderOp {
+    Submit {
+        symbol: String,
+        client_order_id: String,
+        side: Side,
+        price: f64,
+        price_prec: usize,
+        qty: f64,
+        order_type: OrdType,
+        time_in_force: TimeInForce,
+    },
+    Modify {
+        symbol: String,
+        client_order_id: String,
+        side: Side,
+        price: f64,
+        price_prec: usize,
+        qty: f64,
+    },
+    Cancel {
+        symbol: String,
+        client_order_id: String,
+    },
+}
+
+impl OrderOp {
+    pub fn symbol(&self) -> &str {
+        match self {
+            OrderOp::Submit { symbol, .. }
+            | OrderOp::Modify { symbol, .. }
+            | OrderOp::Cancel { symbol, .. } => symbol,
+        }
+    }
+
+    pub fn client_order_id(&self) -> &str {
+        match self {
+            OrderOp::Submit {
+                client_order_id, ..
+            }
+            | OrderOp::Modify {
+                client_order_id, ..
+            }
+            | OrderOp::Cancel {
+                client_order_id, ..
+            } => client_order_id,
+        }
+    }
+
+    fn params(&self, qty_prec: usize) -> Vec<(&'static str, String)> {
+        // The WebSocket API requires the symbol in uppercase, unlike the streams.
+        let mut params = vec![("symbol", self.symbol().to_uppercase())];
+        match self {
+            OrderOp::Submit {
+                client_order_id,
+                side,
+                price,
+                price_prec,
+                qty,
+                order_type,
+                time_in_force,
+                ..
+            } => {
+                params.push(("newClientOrderId", client_order_id.clone()));
+                params.push(("side", AsRef::<str>::as_ref(side).to_string()));
+                params.push(("quantity", format!("{:.prec$}", qty, prec = qty_prec)));
+                params.push(("type", AsRef::<str>::as_ref(order_type).to_string()));
+                if *order_type == OrdType::Limit {
+                    params.push(("price", format!("{:.prec$}", price, prec = price_prec)));
+                    params.push((
+                        "timeInForce",
+                        AsRef::<str>::as_ref(time_in_force).to_string(),
+                    ));
+                }
+            }
+            OrderOp::Modify {
+                client_order_id,
+                side,
+                price,
+                price_prec,
+                qty,
+                ..
+            } => {
+                params.push(("origClientOrderId", client_order_id.clone()));
+                params.push(("side", AsRef::<str>::as_ref(side).to_string()));
+                params.push(("price", format!("{:.prec$}", price, prec = price_prec)));
+                params.push(("quantity", format!("{:.prec$}", qty, prec = qty_prec)));
+            }
+            OrderOp::Cancel {
+                client_order_id, ..
+            } => {
+                params.push(("origClientOrderId", client_order_id.clone()));
+            }
+        }
+        params
+    }
+}
+
+/// The order requests of a Binance market, which can be sent through either the REST API or the
+/// WebSocket API, so that the [`TradeStream`] can be shared across the Binance markets.
+pub trait OrderApi: Clone + Send + Sync + 'static {
+    /// Returns the WebSocket API method and the parameters of the order request, excluding the
+    /// authentication parameters.
+    fn ws_request(&self, op: &OrderOp) -> (&'static str, Vec<(&'static str, String)>);
+
+    /// Parses the result of the WebSocket API response to the order request.
+    fn parse_ws_result(
+        &self,
+        op: &OrderOp,
+        result: Value,
+    ) -> Result<OrderUpdate, serde_json::Error>;
+
+    /// Sends the order request through the REST API.
+    fn send_rest_order(
+        &self,
+        op: &OrderOp,
+    ) -> impl Future<Output = Result<OrderUpdate, BinanceFuturesError>> + Send;
+
+    /// Queries the order through the REST API.
+    fn query_order_update(
+        &self,
+        client_order_id: &str,
+        symbol: &str,
+    ) -> impl Future<Output = Result<OrderUpdate, BinanceFuturesError>> + Send;
+}
+
+impl OrderApi for BinanceFuturesClient {
+    fn ws_request(&self, op: &OrderOp) -> (&'static str, Vec<(&'static str, String)>) {
+        let method = match op {
+            OrderOp::Submit { .. } => "order.place",
+            OrderOp::Modify { .. } => "order.modify",
+            OrderOp::Cancel { .. } => "order.cancel",
+        };
+        (method, op.params(self.market().qty_prec()))
+    }
+
+    fn parse_ws_result(
+        &self,
+        _op: &OrderOp,
+        result: Value,
+    ) -> Result<OrderUpdate, serde_json::Error> {
+        // Deserializes from the reference so that the fields can borrow the strings.
+        OrderResponse::deserialize(&result).map(|resp| (&resp).into())
+    }
+
+    async fn send_rest_order(&self, op: &OrderOp) -> Result<OrderUpdate, BinanceFuturesError> {
+        let resp = match op {
+            OrderOp::Submit {
+                symbol,
+                client_order_id,
+                side,
+                price,
+                price_prec,
+                qty,
+                order_type,
+                time_in_force,
+            } => {
+                self.submit_order(
+                    client_order_id,
+                    symbol,
+                    *side,
+                    *price,
+                    *price_prec,
+                    *qty,
+                    *order_type,
+                    *time_in_force,
+                )
+                .await?
+            }
+            OrderOp::Modify {
+                symbol,
+                client_order_id,
+                side,
+                price,
+                price_prec,
+                qty,
+            } => {
+                self.modify_order(client_order_id, symbol, *side, *price, *price_prec, *qty)
+                    .await?
+            }
+            OrderOp::Cancel {
+                symbol,
+                client_order_id,
+            } => self.cancel_order(client_order_id, symbol).await?,
+        };
+        Ok((&resp).into())
+    }
+
+    async fn query_order_update(
+        &self,
+        client_order_id: &str,
+        symbol: &str,
+    ) -> Result<OrderUpdate, BinanceFuturesError> {
+        let resp = self.query_order(client_order_id, symbol).await?;
+        Ok((&resp).into())
+    }
+}
+
+/// Sends the order request through the REST API. This is used when the WebSocket API session is
+/// unavailable.
+pub async fn send_rest<C: OrderApi>(
+    client: C,
+    order_manager: SharedOrderManager,
+    op: OrderOp,
+    tx: UnboundedSender<PublishEvent>,
+) {
+    let result = client.send_rest_order(&op).await;
+    handle_order_response(&order_manager, op, result, &tx);
+}
+
+/// Resolves the request whose WebSocket API response is lost by querying the order through the
+/// REST API once the request can no longer be accepted by the exchange. If the order doesn't exist,
+/// the request fails as if the exchange rejected it.
+pub async fn resolve_lost_request<C: OrderApi>(
+    client: C,
+    order_manager: SharedOrderManager,
+    op: OrderOp,
+    tx: UnboundedSender<PublishEvent>,
+) {
+    time::sleep(RECV_WINDOW).await;
+    match client
+        .query_order_update(op.client_order_id(), op.symbol())
+        .await
+    {
+        Ok(update) => {
+            if let Some(order) = order_manager.lock().unwrap().resolve_lost_request(&update) {
+                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
+                    symbol: op.symbol().to_string(),
+                    order,
+                }))
+                .unwrap();
+            }
+        }
+        Err(error) => {
+            handle_order_response(&order_manager, op, Err(error), &tx);
+        }
+    }
+}
+
+/// Applies the order response, from either the REST API or the WebSocket API, to the
+/// [`OrderManager`](crate::binancefutures::ordermanager::OrderManager) and publishes the result.
+pub fn handle_order_response(
+    order_manager: &SharedOrderManager,
+    op: OrderOp,
+    result: Result<OrderUpdate, BinanceFuturesError>,
+    tx: &UnboundedSender<PublishEvent>,
+) {
+    let client_order_id = op.client_order_id().to_string();
+    match result {
+        Ok(update) => {
+            if let Some(order) = order_manager
+                .lock()
+                .unwrap()
+                .update_from_rest(&client_order_id, &update)
+            {
+                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
+                    symbol: op.symbol().to_string(),
+                    order,
+                }))
+                .unwrap();
+            }
+        }
+        Err(error) => {
+            let (order_id, order) = {
+                let mut order_manager_ = order_manager.lock().unwrap();
+                let order_id = order_manager_.get_order_id(&client_order_id);
+                let order = match &op {
+                    OrderOp::Submit { .. } => {
+                        order_manager_.update_submit_fail(&client_order_id, &error)
+                    }
+                    OrderOp::Modify { .. } => {
+                        order_manager_.update_modify_fail(&client_order_id, &error)
+                    }
+                    OrderOp::Cancel { .. } => {
+                        order_manager_.update_cancel_fail(&client_order_id, &error)
+                    }
+                };
+                (order_id, order)
+            };
+            if let Some(order) = order {
+                tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
+                    symbol: op.symbol().to_string(),
+                    order,
+                }))
+                .unwrap();
+            }
+
+            let mut venue = error.to_venue_error().symbol(op.symbol());
+            if let Some(order_id) = order_id {
+                venue = venue.order_id(order_id);
+            }
+            tx.send(PublishEvent::LiveEvent(LiveEvent::Error(
+                LiveError::with(ErrorKind::OrderError, error.into()).with_venue(venue),
+            )))
+            .unwrap();
+        }
+    }
+}
+
+/// Places, modifies, and cancels orders through the WebSocket API, which has lower latency than
+/// the REST API and doesn't consume the REST API request weight.
+///
+/// Since the session logon only supports Ed25519 keys, each request is signed individually so that
+/// the same HMAC key as the REST API can be used.
+pub struct TradeStream<C> {
+    api_key: String,
+    secret: String,
+    ev_tx: UnboundedSender<PublishEvent>,
+    client: C,
+    order_manager: SharedOrderManager,
+    order_tx: SharedOrderSender,
+    pending: HashMap<String, OrderOp>,
+    next_id: u64,
+}
+
+impl<C: OrderApi> TradeStream<C> {
+    pub fn new(
+        api_key: String,
+        secret: String,
+        ev_tx: UnboundedSender<PublishEvent>,
+        client: C,
+        order_manager: SharedOrderManager,
+        order_tx: SharedOrderSender,
+    ) -> Self {
+        Self {
+            api_key,
+            secret,
+            ev_tx,
+            client,
+            order_manager,
+            order_tx,
+            pending: Default::default(),
+            next_id: 0,
+        }
+    }
+
+    fn request(&mut self, op: OrderOp) -> String {
+        self.next_id += 1;
+        let id = self.next_id.to_string();
+
+        let (method, mut params) = self.client.ws_request(&op);
+        params.push(("apiKey", self.api_key.clone()));
+        params.push((
+            "timestamp",
+            (Utc::now().timestamp_millis() - 1000).to_string(),
+        ));
+        params.push(("recvWindow", RECV_WINDOW.as_millis().to_string()));
+        // The signature payload is the parameters sorted by name.
+        params.sort_by(|a, b| a.0.cmp(b.0));
+        let payload = params
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signature = sign_hmac_sha256(&self.secret, &payload);
+
+        let mut params_ = serde_json::Map::new();
+        for (k, v) in params {
+            params_.insert(k.to_string(), Value::String(v));
+        }
+        params_.insert("signature".to_string(), Value::String(signature));
+
+        let req = Request {
+            id: id.clone(),
+            method,
+            params: params_,
+        };
+        self.pending.insert(id, op);
+        serde_json::to_string(&req).unwrap()
+    }
+
+    fn handle_response(&mut self, text: &str) -> Result<(), BinanceFuturesError> {
+        let resp: Response = serde_json::from_str(text).map_err(|error| {
+            error!(?error, %text, "Couldn't parse the WebSocket API response.");
+            BinanceFuturesError::InvalidRequest
+        })?;
+        let Some(op) = resp.id.and_then(|id| self.pending.remove(&id)) else {
+            debug!(%text, "Received a response that doesn't match any request.");
+            return Ok(());
+        };
+        let result = match (resp.result, resp.error) {
+            (Some(result), _) if resp.status == 200 => {
+                self.client.parse_ws_result(&op, result).map_err(|error| {
+                    error!(?error, %text, "Couldn't parse the order response.");
+                    BinanceFuturesError::InvalidRequest
+                })
+            }
+            (_, Some(error)) => Err(BinanceFuturesError::OrderError {
+                code: error.code,
+                msg: error.msg,
+            }),
+            _ => Err(BinanceFuturesError::InvalidRequest),
+        };
+        handle_order_response(&self.order_manager, op, result, &self.ev_tx);
+        Ok(())
+    }
+
+    pub async fn connect(&mut self, url: &str) -> Result<(), BinanceFuturesError> {
+        let request = url.into_client_request()?;
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        // Order requests are routed to this stream only after the connection is established.
+        let (order_tx, mut order_rx) = unbounded_channel();
+        *self.order_tx.lock().unwrap() = Some(order_tx);
+
+        let result = loop {
+            select! {
+                op = order_rx.recv() => {
+                    // The sender is only dropped by this stream.
+                    let op = op.unwrap();
+                    let req = self.request(op);
+                    if let Err(error) = write.send(Message::Text(req.into())).await {
+                        break Err(BinanceFuturesError::from(error));
+                    }
+                }
+                message = read.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Err(error) = self.handle_response(&text) {
+                            error!(?error, %text, "Couldn't handle the WebSocket API response.");
+                        }
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        if let Err(error) = write.send(Message::Pong(data)).await {
+                            break Err(BinanceFuturesError::from(error));
+                        }
+                    }
+                    Some(Ok(Message::Close(close_frame))) => {
+                        break Err(BinanceFuturesError::ConnectionAbort(
+                            close_frame.map(|f| f.to_string()).unwrap_or(String::new())
+                        ));
+                    }
+                    Some(Ok(Message::Binary(_)))
+                    | Some(Ok(Message::Frame(_)))
+                    | Some(Ok(Message::Pong(_))) => {}
+                    Some(Err(error)) => {
+                        break Err(BinanceFuturesError::from(error));
+                    }
+                    None => {
+                        break Err(BinanceFuturesError::ConnectionInterrupted);
+                    }
+                }
+            }
+        };
+
+        self.fall_back(order_rx);
+        result
+    }
+
+    /// Stops routing order requests to this stream and sends the requests that have not been sent
+    /// yet through the REST API.
+    fn fall_back(&mut self, mut order_rx: UnboundedReceiver<OrderOp>) {
+        self.order_tx.lock().unwrap().take();
+        order_rx.close();
+        while let Ok(op) = order_rx.try_recv() {
+            tokio::spawn(send_rest(
+                self.client.clone(),
+                self.order_manager.clone(),
+                op,
+                self.ev_tx.clone(),
+            ));
+        }
+        // The results of the in-flight requests are unknown, so they are resolved through the REST
+        // API.
+        for (_, op) in self.pending.drain() {
+            warn!(
+                ?op,
+                "The connection was lost before receiving the WebSocket API response."
+            );
+            tokio::spawn(resolve_lost_request(
+                self.client.clone(),
+                self.order_manager.clone(),
+                op,
+                self.ev_tx.clone(),
+            ));
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use futures_util::{SinkExt, StreamExt};
 use hftbacktest::prelude::*;
@@ -9,19 +12,20 @@ use tokio::{
         mpsc::UnboundedSender,
     },
     time,
+    time::Instant,
 };
 use tokio_tungstenite::{
     connect_async,
     tungstenite::{Message, client::IntoClientRequest},
 };
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 
 use crate::{
     binancefutures::{
         BinanceFuturesError,
         SharedSymbolSet,
         msg::stream::{EventStream, Stream},
-        ordermanager::SharedOrderManager,
+        ordermanager::{OrderUpdate, SharedOrderManager},
         rest::BinanceFuturesClient,
     },
     connector::PublishEvent,
@@ -33,6 +37,8 @@ pub struct UserDataStream {
     ev_tx: UnboundedSender<PublishEvent>,
     order_manager: SharedOrderManager,
     symbol_rx: Receiver<String>,
+    reconnected: bool,
+    reconciliation_interval: Duration,
 }
 
 impl UserDataStream {
@@ -42,6 +48,8 @@ impl UserDataStream {
         order_manager: SharedOrderManager,
         symbols: SharedSymbolSet,
         symbol_rx: Receiver<String>,
+        reconnected: bool,
+        reconciliation_interval: Duration,
     ) -> Self {
         Self {
             symbols,
@@ -49,6 +57,8 @@ impl UserDataStream {
             ev_tx,
             order_manager,
             symbol_rx,
+            reconnected,
+            reconciliation_interval,
         }
     }
 
@@ -75,7 +85,12 @@ impl UserDataStream {
                 }
             }
             EventStream::OrderTradeUpdate(data) => {
-                match self.order_manager.lock().unwrap().update_from_ws(&data) {
+                match self
+                    .order_manager
+                    .lock()
+                    .unwrap()
+                    .update_from_ws(&(&data).into())
+                {
                     Ok(Some(order)) => {
                         self.ev_tx
                             .send(PublishEvent::LiveEvent(LiveEvent::Order {
@@ -115,25 +130,43 @@ impl UserDataStream {
         let (ws_stream, _) = connect_async(request).await?;
         let (mut write, mut read) = ws_stream.split();
         let mut interval = time::interval(Duration::from_secs(60 * 30));
+        // The periodic reconciliation is disabled if the interval is zero.
+        let periodic = !self.reconciliation_interval.is_zero();
+        let period = self.reconciliation_interval.max(Duration::from_secs(1));
+        let mut reconciliation_interval = time::interval_at(Instant::now() + period, period);
 
         let symbols: HashSet<_> = self.symbols.lock().unwrap().iter().cloned().collect();
         let client = self.client.clone();
         let order_manager = self.order_manager.clone();
         let ev_tx = self.ev_tx.clone();
 
+        let reconnected = self.reconnected;
+
         tokio::spawn(async move {
-            // Cancel all orders before connecting to the stream in order to start with the
-            // clean state.
-            for symbol in &symbols {
-                if let Err(error) = cancel_all(
+            if reconnected {
+                // Updates may have been missed while disconnected, so the order states are
+                // reconciled with the exchange instead of starting over.
+                reconcile(
                     client.clone(),
-                    symbol.clone(),
+                    &symbols,
                     order_manager.clone(),
                     ev_tx.clone(),
                 )
-                .await
-                {
-                    error!(?error, %symbol, "Couldn't cancel all orders.");
+                .await;
+            } else {
+                // Cancel all orders before connecting to the stream in order to start with the
+                // clean state.
+                for symbol in &symbols {
+                    if let Err(error) = cancel_all(
+                        client.clone(),
+                        symbol.clone(),
+                        order_manager.clone(),
+                        ev_tx.clone(),
+                    )
+                    .await
+                    {
+                        error!(?error, %symbol, "Couldn't cancel all orders.");
+                    }
                 }
             }
 
@@ -159,6 +192,19 @@ impl UserDataStream {
                         }
                     });
                 }
+                _ = reconciliation_interval.tick(), if periodic => {
+                    let symbols: HashSet<_> =
+                        self.symbols.lock().unwrap().iter().cloned().collect();
+                    let client = self.client.clone();
+                    let order_manager = self.order_manager.clone();
+                    let ev_tx = self.ev_tx.clone();
+                    tokio::spawn(async move {
+                        reconcile(client.clone(), &symbols, order_manager, ev_tx.clone()).await;
+                        if let Err(error) = get_position_information(client, symbols, ev_tx).await {
+                            error!(?error, "Couldn't get position information.");
+                        }
+                    });
+                }
                 msg = self.symbol_rx.recv() => {
                     match msg {
                         Ok(symbol) => {
@@ -228,7 +274,6 @@ pub async fn cancel_all(
     order_manager: SharedOrderManager,
     ev_tx: UnboundedSender<PublishEvent>,
 ) -> Result<(), BinanceFuturesError> {
-    // todo: rate-limit throttling.
     client.cancel_all_orders(&symbol).await?;
     let orders = order_manager.lock().unwrap().cancel_all_from_rest(&symbol);
     for order in orders {
@@ -247,7 +292,6 @@ pub async fn get_position_information(
     mut symbols: HashSet<String>,
     ev_tx: UnboundedSender<PublishEvent>,
 ) -> Result<(), BinanceFuturesError> {
-    // todo: rate-limit throttling.
     let position_information = client.get_position_information().await?;
     position_information.into_iter().for_each(|position| {
         symbols.remove(&position.symbol);
@@ -270,3 +314,138 @@ pub async fn get_position_information(
     }
     Ok(())
 }
+
+/// Reconciles the order states of the symbols with the exchange through the REST API, and
+/// publishes the repaired orders, followed by a [`ErrorKind::StateRepaired`] error summarizing
+/// the repair if any.
+pub async fn reconcile(
+    client: BinanceFuturesClient,
+    symbols: &HashSet<String>,
+    order_manager: SharedOrderManager,
+    ev_tx: UnboundedSender<PublishEvent>,
+) {
+    for symbol in symbols {
+        match reconcile_orders(client.clone(), symbol, order_manager.clone(), ev_tx.clone()).await {
+            Ok((0, 0)) => {}
+            Ok((repaired, orphaned)) => {
+                info!(%symbol, repaired, orphaned, "The order states are reconciled.");
+                let mut value = HashMap::new();
+                value.insert("symbol".to_string(), Value::String(symbol.clone()));
+                value.insert("repaired".to_string(), Value::Int(repaired as i64));
+                value.insert("orphaned".to_string(), Value::Int(orphaned as i64));
+                ev_tx
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                        ErrorKind::StateRepaired,
+                        Value::Map(value),
+                    ))))
+                    .unwrap();
+            }
+            Err(error) => {
+                error!(?error, %symbol, "Couldn't reconcile the order states.");
+            }
+        }
+    }
+}
+
+/// Returns the number of the repaired orders and the number of the canceled orphan orders, which
+/// are open on the exchange with the connector's prefix but unknown to the [`OrderManager`].
+///
+/// [`OrderManager`]: crate::binancefutures::ordermanager::OrderManager
+async fn reconcile_orders(
+    client: BinanceFuturesClient,
+    symbol: &str,
+    order_manager: SharedOrderManager,
+    ev_tx: UnboundedSender<PublishEvent>,
+) -> Result<(usize, usize), BinanceFuturesError> {
+    // Takes the orders expected to be open before querying, since orders submitted afterward may
+    // not be included in the response.
+    let mut missing: HashSet<_> = order_manager
+        .lock()
+        .unwrap()
+        .reconcilable_orders(symbol)
+        .into_iter()
+        .collect();
+    let open_orders = client.get_open_orders(symbol).await?;
+
+    let mut repaired = 0;
+    let mut orphans = Vec::new();
+    for open_order in &open_orders {
+        missing.remove(&open_order.client_order_id);
+        match order_manager.lock().unwrap().reconcile(&open_order.into()) {
+            Ok(Some(order)) => {
+                repaired += 1;
+                ev_tx
+                    .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                        symbol: symbol.to_string(),
+                        order,
+                    }))
+                    .unwrap();
+            }
+            Ok(None) | Err(BinanceFuturesError::PrefixUnmatched) => {}
+            Err(BinanceFuturesError::OrderNotFound) => {
+                orphans.push(open_order.client_order_id.clone());
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    // The orders that are no longer open have been filled, canceled, or expired without the
+    // updates being received. Their states are looked up in the recent orders with a single
+    // request instead of querying each order, which could exceed the request weight limit when
+    // many updates are missed. Only the orders too old to be included are queried individually.
+    let mut closed_orders: HashMap<_, _> = if missing.is_empty() {
+        HashMap::new()
+    } else {
+        client
+            .get_all_orders(symbol)
+            .await?
+            .into_iter()
+            .map(|order| (order.client_order_id.clone(), order))
+            .collect()
+    };
+    for client_order_id in missing {
+        let resp = match closed_orders.remove(&client_order_id) {
+            Some(resp) => Ok(resp),
+            None => client.query_order(&client_order_id, symbol).await,
+        };
+        let update: OrderUpdate = match resp {
+            Ok(resp) => (&resp).into(),
+            Err(BinanceFuturesError::OrderError { code: -2013, .. }) => {
+                // The order doesn't exist, so its status cannot be determined.
+                if let Some(order) = order_manager
+                    .lock()
+                    .unwrap()
+                    .update_from_rest_fail(&client_order_id, Some(Status::None))
+                {
+                    repaired += 1;
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                            symbol: symbol.to_string(),
+                            order,
+                        }))
+                        .unwrap();
+                }
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        if let Some(order) = order_manager.lock().unwrap().reconcile(&update)? {
+            repaired += 1;
+            ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                    symbol: symbol.to_string(),
+                    order,
+                }))
+                .unwrap();
+        }
+    }
+
+    // The orphan orders cannot be mapped to the bots' orders, such as when the submission
+    // response was lost, so they are canceled.
+    for client_order_id in &orphans {
+        if let Err(error) = client.cancel_order(client_order_id, symbol).await {
+            error!(?error, %symbol, %client_order_id, "Couldn't cancel the orphan order.");
+        }
+    }
+    Ok((repaired, orphans.len()))
+}
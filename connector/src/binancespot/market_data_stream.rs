@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use hftbacktest::{live::ipc::TO_ALL, prelude::*};
+use tokio::{
+    select,
+    sync::{
+        broadcast::{Receiver, error::RecvError},
+        mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    },
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Message, client::IntoClientRequest},
+};
+use tracing::{debug, error};
+
+use crate::{
+    binancefutures::BinanceFuturesError,
+    binancespot::{
+        msg::{
+            rest,
+            stream::{EventStream, Stream},
+        },
+        rest::BinanceSpotClient,
+    },
+    connector::PublishEvent,
+    utils::{generate_rand_string, parse_depth, parse_px_qty_tup},
+};
+
+pub struct MarketDataStream {
+    client: BinanceSpotClient,
+    ev_tx: UnboundedSender<PublishEvent>,
+    symbol_rx: Receiver<String>,
+    /// The event time of the latest depth diff for each symbol.
+    last_ts: HashMap<String, i64>,
+    rest_tx: UnboundedSender<(String, rest::Depth)>,
+    rest_rx: UnboundedReceiver<(String, rest::Depth)>,
+}
+
+impl MarketDataStream {
+    pub fn new(
+        client: BinanceSpotClient,
+        ev_tx: UnboundedSender<PublishEvent>,
+        symbol_rx: Receiver<String>,
+    ) -> Self {
+        let (rest_tx, rest_rx) = unbounded_channel::<(String, rest::Depth)>();
+        Self {
+            client,
+            ev_tx,
+            symbol_rx,
+            last_ts: Default::default(),
+            rest_tx,
+            rest_rx,
+        }
+    }
+
+    fn request_snapshot(&self, symbol: String) {
+        let client_ = self.client.clone();
+        let rest_tx = self.rest_tx.clone();
+        tokio::spawn(async move {
+            let resp = client_.get_depth(&symbol).await;
+            match resp {
+                Ok(depth) => {
+                    rest_tx.send((symbol, depth)).unwrap();
+                }
+                Err(error) => {
+                    error!(
+                        ?error,
+                        %symbol,
+                        "Couldn't get the market depth via REST."
+                    );
+                }
+            }
+        });
+    }
+
+    fn send_depth(&self, symbol: &str, ev: u64, exch_ts: i64, levels: Vec<(f64, f64)>) {
+        for (px, qty) in levels {
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                    symbol: symbol.to_string(),
+                    event: Event {
+                        ev,
+                        exch_ts,
+                        local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                        order_id: 0,
+                        px,
+                        qty,
+                        ival: 0,
+                        fval: 0.0,
+                    },
+                }))
+                .unwrap();
+        }
+    }
+
+    fn process_message(&mut self, stream: EventStream) {
+        match stream {
+            EventStream::DepthUpdate(data) => {
+                // The snapshot is applied once and the market depth is naturally refreshed by the
+                // diffs.
+                if !self.last_ts.contains_key(&data.symbol) {
+                    self.request_snapshot(data.symbol.clone());
+                }
+                let exch_ts = data.event_time * 1_000_000;
+                self.last_ts.insert(data.symbol.clone(), exch_ts);
+
+                match parse_depth(data.bids, data.asks) {
+                    Ok((bids, asks)) => {
+                        self.ev_tx.send(PublishEvent::BatchStart(TO_ALL)).unwrap();
+                        self.send_depth(&data.symbol, LOCAL_BID_DEPTH_EVENT, exch_ts, bids);
+                        self.send_depth(&data.symbol, LOCAL_ASK_DEPTH_EVENT, exch_ts, asks);
+                        self.ev_tx.send(PublishEvent::BatchEnd(TO_ALL)).unwrap();
+                    }
+                    Err(error) => {
+                        error!(?error, "Couldn't parse DepthUpdate stream.");
+                    }
+                }
+            }
+            EventStream::Trade(data) => match parse_px_qty_tup(data.price, data.qty) {
+                Ok((px, qty)) => {
+                    self.ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                            symbol: data.symbol,
+                            event: Event {
+                                ev: {
+                                    if data.is_the_buyer_the_market_maker {
+                                        LOCAL_SELL_TRADE_EVENT
+                                    } else {
+                                        LOCAL_BUY_TRADE_EVENT
+                                    }
+                                },
+                                exch_ts: data.transaction_time * 1_000_000,
+                                local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                                order_id: 0,
+                                px,
+                                qty,
+                                ival: 0,
+                                fval: 0.0,
+                            },
+                        }))
+                        .unwrap();
+                }
+                Err(e) => {
+                    error!(error = ?e, "Couldn't parse trade stream.");
+                }
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    fn process_snapshot(&mut self, symbol: String, data: rest::Depth) {
+        // The spot depth snapshot doesn't have a timestamp. Since the snapshot is requested after
+        // receiving a diff, it is at least as recent as the latest diff.
+        let exch_ts = self.last_ts.get(&symbol).copied().unwrap_or(0);
+        match parse_depth(data.bids, data.asks) {
+            Ok((bids, asks)) => {
+                self.ev_tx.send(PublishEvent::BatchStart(TO_ALL)).unwrap();
+                self.send_depth(&symbol, LOCAL_BID_DEPTH_EVENT, exch_ts, bids);
+                self.send_depth(&symbol, LOCAL_ASK_DEPTH_EVENT, exch_ts, asks);
+                self.ev_tx.send(PublishEvent::BatchEnd(TO_ALL)).unwrap();
+            }
+            Err(error) => {
+                error!(?error, "Couldn't parse Depth response.");
+            }
+        }
+    }
+
+    pub async fn connect(&mut self, url: &str) -> Result<(), BinanceFuturesError> {
+        let request = url.into_client_request()?;
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        loop {
+            select! {
+                Some((symbol, data)) = self.rest_rx.recv() => {
+                    self.process_snapshot(symbol, data);
+                }
+                msg = self.symbol_rx.recv() => match msg {
+                    Ok(symbol) => {
+                        let id = generate_rand_string(16);
+                        write.send(Message::Text(format!(r#"{{
+                            "method": "SUBSCRIBE",
+                            "params": [
+                                "{symbol}@trade",
+                                "{symbol}@depth@100ms"
+                            ],
+                            "id": "{id}"
+                        }}"#).into())).await?;
+                    }
+                    Err(RecvError::Closed) => {
+                        return Ok(());
+                    }
+                    Err(RecvError::Lagged(num)) => {
+                        error!("{num} subscription requests were missed.");
+                    }
+                },
+                message = read.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<Stream>(&text) {
+                            Ok(Stream::EventStream(stream)) => {
+                                self.process_message(stream);
+                            }
+                            Ok(Stream::Result(result)) => {
+                                debug!(?result, "Subscription request response is received.");
+                            }
+                            Err(error) => {
+                                error!(?error, %text, "Couldn't parse Stream.");
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        write.send(Message::Pong(data)).await?;
+                    }
+                    Some(Ok(Message::Close(close_frame))) => {
+                        return Err(BinanceFuturesError::ConnectionAbort(
+                            close_frame.map(|f| f.to_string()).unwrap_or(String::new())
+                        ));
+                    }
+                    Some(Ok(Message::Binary(_)))
+                    | Some(Ok(Message::Frame(_)))
+                    | Some(Ok(Message::Pong(_))) => {}
+                    Some(Err(error)) => {
+                        return Err(BinanceFuturesError::from(error));
+                    }
+                    None => {
+                        return Err(BinanceFuturesError::ConnectionInterrupted);
+                    }
+                }
+            }
+        }
+    }
+}
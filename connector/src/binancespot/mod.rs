@@ -0,0 +1,352 @@
+mod market_data_stream;
+mod msg;
+mod rest;
+mod trade_stream;
+mod user_data_stream;
+
+use std::sync::{Arc, Mutex};
+
+use hftbacktest::{
+    prelude::get_precision,
+    types::{ErrorKind, LiveError, LiveEvent, Order, Status},
+};
+use serde::Deserialize;
+use tokio::sync::{broadcast, broadcast::Sender, mpsc::UnboundedSender};
+use tracing::{error, warn};
+
+use crate::{
+    binancefutures::{
+        BinanceFuturesError,
+        SharedSymbolSet,
+        ordermanager::{OrderManager, SharedOrderManager},
+        trade_stream::{self as futures_trade_stream, OrderOp, SharedOrderSender, TradeStream},
+    },
+    binancespot::{rest::BinanceSpotClient, user_data_stream::SharedBaseAssetMap},
+    connector::{Connector, ConnectorBuilder, GetOrders, PublishEvent},
+    utils::{ExponentialBackoff, Retry},
+};
+
+#[derive(Deserialize)]
+pub struct Config {
+    stream_url: String,
+    api_url: String,
+    /// WebSocket API URL for the order requests. If it is empty, orders are sent through the REST
+    /// API only.
+    #[serde(default)]
+    order_url: String,
+    #[serde(default)]
+    order_prefix: String,
+    #[serde(default)]
+    api_key: String,
+    #[serde(default)]
+    secret: String,
+}
+
+/// A connector for Binance Spot.
+///
+/// It shares the [`OrderManager`], the [`TradeStream`], and the error type with the Binance
+/// Futures connector, since both have the same order handling over the REST API, the WebSocket
+/// API, and the user data stream. Spot has no position, so the balance of the symbol's base asset
+/// is published as its position.
+///
+/// Spot cannot change the price of an open order, so a modification can only reduce the quantity,
+/// which keeps the order's priority.
+pub struct BinanceSpot {
+    config: Config,
+    symbols: SharedSymbolSet,
+    base_assets: SharedBaseAssetMap,
+    order_manager: SharedOrderManager,
+    client: BinanceSpotClient,
+    symbol_tx: Sender<String>,
+    order_tx: SharedOrderSender,
+}
+
+impl BinanceSpot {
+    pub fn connect_market_data_stream(&mut self, ev_tx: UnboundedSender<PublishEvent>) {
+        let base_url = self.config.stream_url.clone();
+        let client = self.client.clone();
+        let symbol_tx = self.symbol_tx.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: BinanceFuturesError| {
+                    error!(
+                        ?error,
+                        "An error occurred in the market data stream connection."
+                    );
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.into(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream = market_data_stream::MarketDataStream::new(
+                        client.clone(),
+                        ev_tx.clone(),
+                        symbol_tx.subscribe(),
+                    );
+                    stream.connect(&base_url).await?;
+                    Ok(())
+                })
+                .await;
+        });
+    }
+
+    pub fn connect_user_data_stream(&self, ev_tx: UnboundedSender<PublishEvent>) {
+        let base_url = self.config.stream_url.clone();
+        let client = self.client.clone();
+        let order_manager = self.order_manager.clone();
+        let instruments = self.symbols.clone();
+        let base_assets = self.base_assets.clone();
+        let symbol_tx = self.symbol_tx.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: BinanceFuturesError| {
+                    error!(
+                        ?error,
+                        "An error occurred in the user data stream connection."
+                    );
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.into(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream = user_data_stream::UserDataStream::new(
+                        client.clone(),
+                        ev_tx.clone(),
+                        order_manager.clone(),
+                        instruments.clone(),
+                        base_assets.clone(),
+                        symbol_tx.subscribe(),
+                    );
+
+                    let listen_key = stream.get_listen_key().await?;
+
+                    stream.connect(&format!("{base_url}/{listen_key}")).await?;
+                    Ok(())
+                })
+                .await;
+        });
+    }
+
+    pub fn connect_trade_stream(&self, ev_tx: UnboundedSender<PublishEvent>) {
+        let order_url = self.config.order_url.clone();
+        let api_key = self.config.api_key.clone();
+        let secret = self.config.secret.clone();
+        let client = self.client.clone();
+        let order_manager = self.order_manager.clone();
+        let order_tx = self.order_tx.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: BinanceFuturesError| {
+                    // Order requests fall back to the REST API until the connection is restored.
+                    error!(?error, "An error occurred in the trade stream connection.");
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.into(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream = TradeStream::new(
+                        api_key.clone(),
+                        secret.clone(),
+                        ev_tx.clone(),
+                        client.clone(),
+                        order_manager.clone(),
+                        order_tx.clone(),
+                    );
+                    stream.connect(&order_url).await?;
+                    Ok(())
+                })
+                .await;
+        });
+    }
+
+    /// Sends the order request through the WebSocket API if the session is established; otherwise,
+    /// falls back to the REST API.
+    fn send_order_op(&self, op: OrderOp, tx: UnboundedSender<PublishEvent>) {
+        let op = match self.order_tx.lock().unwrap().as_ref() {
+            Some(order_tx) => match order_tx.send(op) {
+                Ok(()) => return,
+                Err(error) => error.0,
+            },
+            None => op,
+        };
+        tokio::spawn(futures_trade_stream::send_rest(
+            self.client.clone(),
+            self.order_manager.clone(),
+            op,
+            tx,
+        ));
+    }
+}
+
+impl ConnectorBuilder for BinanceSpot {
+    type Error = BinanceFuturesError;
+
+    fn build_from(config: &str) -> Result<Self, Self::Error> {
+        let config: Config = toml::from_str(config)?;
+
+        let order_manager = Arc::new(Mutex::new(OrderManager::new(&config.order_prefix)));
+        let client = BinanceSpotClient::new(&config.api_url, &config.api_key, &config.secret);
+        let (symbol_tx, _) = broadcast::channel(500);
+
+        Ok(BinanceSpot {
+            config,
+            symbols: Default::default(),
+            base_assets: Default::default(),
+            order_manager,
+            client,
+            symbol_tx,
+            order_tx: Default::default(),
+        })
+    }
+}
+
+impl Connector for BinanceSpot {
+    fn register(&mut self, symbol: String) {
+        // Binance spot symbols must be lowercase to subscribe to the WebSocket stream.
+        if symbol.to_lowercase() != symbol {
+            error!("Binance Spot symbol must be lowercase.");
+        }
+        let symbol = symbol.to_lowercase();
+        let mut symbols = self.symbols.lock().unwrap();
+        if !symbols.contains(&symbol) {
+            symbols.insert(symbol.clone());
+            self.symbol_tx.send(symbol).unwrap();
+        }
+    }
+
+    fn order_manager(&self) -> Arc<Mutex<dyn GetOrders + Send + 'static>> {
+        self.order_manager.clone()
+    }
+
+    fn run(&mut self, ev_tx: UnboundedSender<PublishEvent>) {
+        self.connect_market_data_stream(ev_tx.clone());
+        // Connects to the user stream only if the API key and secret are provided.
+        if !self.config.api_key.is_empty() && !self.config.secret.is_empty() {
+            self.connect_user_data_stream(ev_tx.clone());
+            if !self.config.order_url.is_empty() {
+                self.connect_trade_stream(ev_tx.clone());
+            }
+        }
+    }
+
+    fn submit(&self, symbol: String, mut order: Order, tx: UnboundedSender<PublishEvent>) {
+        let client_order_id = self
+            .order_manager
+            .lock()
+            .unwrap()
+            .prepare_client_order_id(symbol.clone(), order.clone());
+
+        match client_order_id {
+            Some(client_order_id) => {
+                let op = OrderOp::Submit {
+                    symbol,
+                    client_order_id,
+                    side: order.side,
+                    price: order.price_tick as f64 * order.tick_size,
+                    price_prec: get_precision(order.tick_size),
+                    qty: order.qty,
+                    order_type: order.order_type,
+                    time_in_force: order.time_in_force,
+                };
+                self.send_order_op(op, tx);
+            }
+            None => {
+                warn!(
+                    ?order,
+                    "Coincidentally, creates a duplicated client order id. \
+                    This order request will be expired."
+                );
+                order.req = Status::None;
+                order.status = Status::Expired;
+                tx.send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn modify(&self, symbol: String, order: Order, tx: UnboundedSender<PublishEvent>) {
+        let (client_order_id, price_tick) = {
+            let order_manager = self.order_manager.lock().unwrap();
+            let client_order_id = order_manager.get_client_order_id(&symbol, order.order_id);
+            let price_tick = client_order_id
+                .as_ref()
+                .and_then(|client_order_id| order_manager.get_order(client_order_id))
+                .map(|order| order.price_tick);
+            (client_order_id, price_tick)
+        };
+
+        match client_order_id {
+            Some(client_order_id) => {
+                let op = OrderOp::Modify {
+                    symbol,
+                    client_order_id,
+                    side: order.side,
+                    price: order.price_tick as f64 * order.tick_size,
+                    price_prec: get_precision(order.tick_size),
+                    qty: order.qty,
+                };
+                if price_tick != Some(order.price_tick) {
+                    warn!(
+                        order_id = order.order_id,
+                        "Binance Spot cannot change the price of an open order."
+                    );
+                    futures_trade_stream::handle_order_response(
+                        &self.order_manager,
+                        op,
+                        Err(BinanceFuturesError::InvalidRequest),
+                        &tx,
+                    );
+                    return;
+                }
+                self.send_order_op(op, tx);
+            }
+            None => {
+                warn!(
+                    order_id = order.order_id,
+                    "client_order_id corresponding to order_id is not found; \
+                    this may be due to the order already being canceled or filled."
+                );
+            }
+        }
+    }
+
+    fn cancel(&self, symbol: String, order: Order, tx: UnboundedSender<PublishEvent>) {
+        let client_order_id = self
+            .order_manager
+            .lock()
+            .unwrap()
+            .get_client_order_id(&symbol, order.order_id);
+
+        match client_order_id {
+            Some(client_order_id) => {
+                let op = OrderOp::Cancel {
+                    symbol,
+                    client_order_id,
+                };
+                self.send_order_op(op, tx);
+            }
+            None => {
+                warn!(
+                    order_id = order.order_id,
+                    "client_order_id corresponding to order_id is not found; \
+                    this may be due to the order already being canceled or filled."
+                );
+            }
+        }
+    }
+}
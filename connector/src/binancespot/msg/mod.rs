@@ -0,0 +1,43 @@
+use hftbacktest::types::{OrdType, Status, TimeInForce};
+use serde::{
+    Deserialize,
+    Deserializer,
+    de::{Error, Unexpected},
+};
+
+#[allow(dead_code)]
+pub mod rest;
+#[allow(dead_code)]
+pub mod stream;
+
+fn from_str_to_status<'de, D>(deserializer: D) -> Result<Status, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: &str = Deserialize::deserialize(deserializer)?;
+    match s {
+        "NEW" => Ok(Status::New),
+        "PARTIALLY_FILLED" => Ok(Status::PartiallyFilled),
+        "FILLED" => Ok(Status::Filled),
+        "CANCELED" => Ok(Status::Canceled),
+        // The order is still working until the cancellation is confirmed.
+        "PENDING_CANCEL" => Ok(Status::New),
+        "REJECTED" => Ok(Status::Rejected),
+        "EXPIRED" | "EXPIRED_IN_MATCH" => Ok(Status::Expired),
+        s => Err(Error::invalid_value(
+            Unexpected::Other(s),
+            &"NEW,PARTIALLY_FILLED,FILLED,CANCELED,PENDING_CANCEL,REJECTED,EXPIRED,EXPIRED_IN_MATCH",
+        )),
+    }
+}
+
+/// Converts the spot order type into [`OrdType`] and [`TimeInForce`]. Spot expresses a post-only
+/// order as the `LIMIT_MAKER` order type instead of the `GTX` time-in-force.
+fn to_order_type(ty: &str, time_in_force: TimeInForce) -> (OrdType, TimeInForce) {
+    match ty {
+        "LIMIT" => (OrdType::Limit, time_in_force),
+        "LIMIT_MAKER" => (OrdType::Limit, TimeInForce::GTX),
+        "MARKET" => (OrdType::Market, time_in_force),
+        _ => (OrdType::Unsupported, time_in_force),
+    }
+}
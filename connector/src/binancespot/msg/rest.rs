@@ -0,0 +1,234 @@
+use hftbacktest::types::{Side, Status, TimeInForce};
+use serde::Deserialize;
+
+use super::{from_str_to_status, to_order_type};
+use crate::{
+    binancefutures::{
+        msg::{from_str_to_side, from_str_to_tif, rest::ErrorResponse},
+        ordermanager::OrderUpdate,
+    },
+    utils::{from_str_to_f64, to_lowercase},
+};
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum OrderResponseResult {
+    Ok(OrderResponse),
+    Err(ErrorResponse),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderResponse {
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: i64,
+    /// For the Cancel Order response, this is the client order ID of the cancel request.
+    #[serde(rename = "clientOrderId")]
+    pub client_order_id: String,
+    /// Cancel Order response only field
+    #[serde(rename = "origClientOrderId")]
+    #[serde(default)]
+    pub orig_client_order_id: Option<String>,
+    #[serde(rename = "transactTime")]
+    pub transact_time: i64,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub price: f64,
+    #[serde(rename = "origQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub orig_qty: f64,
+    #[serde(rename = "executedQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub executed_qty: f64,
+    #[serde(rename = "cummulativeQuoteQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub cum_quote_qty: f64,
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub status: Status,
+    #[serde(rename = "timeInForce")]
+    #[serde(deserialize_with = "from_str_to_tif")]
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+}
+
+impl From<&OrderResponse> for OrderUpdate {
+    fn from(resp: &OrderResponse) -> Self {
+        let (order_type, time_in_force) = to_order_type(&resp.ty, resp.time_in_force);
+        Self {
+            client_order_id: resp
+                .orig_client_order_id
+                .clone()
+                .unwrap_or_else(|| resp.client_order_id.clone()),
+            exch_ts: resp.transact_time,
+            qty: resp.orig_qty,
+            cum_qty: resp.executed_qty,
+            exec_qty: resp.executed_qty,
+            price: resp.price,
+            side: resp.side,
+            time_in_force,
+            order_type,
+            status: resp.status,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum AmendOrderResponseResult {
+    Ok(AmendOrderResponse),
+    Err(ErrorResponse),
+}
+
+/// The response of the Order Amend Keep Priority endpoint, which reduces the quantity of an open
+/// order without losing its priority.
+#[derive(Deserialize, Debug)]
+pub struct AmendOrderResponse {
+    #[serde(rename = "transactTime")]
+    pub transact_time: i64,
+    #[serde(rename = "amendedOrder")]
+    pub amended_order: AmendedOrder,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AmendedOrder {
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: i64,
+    #[serde(rename = "clientOrderId")]
+    pub client_order_id: String,
+    #[serde(rename = "origClientOrderId")]
+    pub orig_client_order_id: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub qty: f64,
+    #[serde(rename = "executedQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub executed_qty: f64,
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub status: Status,
+    #[serde(rename = "timeInForce")]
+    #[serde(deserialize_with = "from_str_to_tif")]
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+}
+
+impl From<&AmendOrderResponse> for OrderUpdate {
+    fn from(resp: &AmendOrderResponse) -> Self {
+        let order = &resp.amended_order;
+        let (order_type, time_in_force) = to_order_type(&order.ty, order.time_in_force);
+        Self {
+            client_order_id: order.client_order_id.clone(),
+            exch_ts: resp.transact_time,
+            qty: order.qty,
+            cum_qty: order.executed_qty,
+            // The amendment doesn't execute the order.
+            exec_qty: 0.0,
+            price: order.price,
+            side: order.side,
+            time_in_force,
+            order_type,
+            status: order.status,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum QueryOrderResponseResult {
+    Ok(QueryOrderResponse),
+    Err(ErrorResponse),
+}
+
+/// The order returned by the Query Order endpoint, which, unlike the order request responses,
+/// provides `updateTime` instead of `transactTime`.
+#[derive(Deserialize, Debug)]
+pub struct QueryOrderResponse {
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "clientOrderId")]
+    pub client_order_id: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub price: f64,
+    #[serde(rename = "origQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub orig_qty: f64,
+    #[serde(rename = "executedQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub executed_qty: f64,
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub status: Status,
+    #[serde(rename = "timeInForce")]
+    #[serde(deserialize_with = "from_str_to_tif")]
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+}
+
+impl From<&QueryOrderResponse> for OrderUpdate {
+    fn from(resp: &QueryOrderResponse) -> Self {
+        let (order_type, time_in_force) = to_order_type(&resp.ty, resp.time_in_force);
+        Self {
+            client_order_id: resp.client_order_id.clone(),
+            exch_ts: resp.update_time,
+            qty: resp.orig_qty,
+            cum_qty: resp.executed_qty,
+            exec_qty: resp.executed_qty,
+            price: resp.price,
+            side: resp.side,
+            time_in_force,
+            order_type,
+            status: resp.status,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Depth {
+    #[serde(rename = "lastUpdateId")]
+    pub last_update_id: i64,
+    pub bids: Vec<(String, String)>,
+    pub asks: Vec<(String, String)>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Account {
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+    pub balances: Vec<Balance>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Balance {
+    pub asset: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub free: f64,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub locked: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExchangeInfo {
+    pub symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SymbolInfo {
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "baseAsset")]
+    pub base_asset: String,
+    #[serde(rename = "quoteAsset")]
+    pub quote_asset: String,
+}
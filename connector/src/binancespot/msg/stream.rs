@@ -0,0 +1,192 @@
+use hftbacktest::types::{Side, Status, TimeInForce};
+use serde::Deserialize;
+
+use super::{from_str_to_status, to_order_type};
+use crate::{
+    binancefutures::{
+        msg::{from_str_to_side, from_str_to_tif},
+        ordermanager::OrderUpdate,
+    },
+    utils::{from_str_to_f64, to_lowercase},
+};
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Stream {
+    EventStream(EventStream),
+    Result(Result),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Result {
+    pub result: Option<String>,
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "e")]
+pub enum EventStream {
+    #[serde(rename = "depthUpdate")]
+    DepthUpdate(Depth),
+    #[serde(rename = "trade")]
+    Trade(Trade),
+    #[serde(rename = "executionReport")]
+    ExecutionReport(ExecutionReport),
+    #[serde(rename = "outboundAccountPosition")]
+    OutboundAccountPosition(OutboundAccountPosition),
+    #[serde(rename = "balanceUpdate")]
+    BalanceUpdate(BalanceUpdate),
+    #[serde(rename = "listenKeyExpired")]
+    ListenKeyExpired(ListenKeyStream),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Depth {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "s")]
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "U")]
+    pub first_update_id: i64,
+    #[serde(rename = "u")]
+    pub last_update_id: i64,
+    #[serde(rename = "b")]
+    pub bids: Vec<(String, String)>,
+    #[serde(rename = "a")]
+    pub asks: Vec<(String, String)>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Trade {
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "s")]
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    #[serde(rename = "t")]
+    pub id: i64,
+    #[serde(rename = "p")]
+    pub price: String,
+    #[serde(rename = "q")]
+    pub qty: String,
+    #[serde(rename = "m")]
+    pub is_the_buyer_the_market_maker: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExecutionReport {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "s")]
+    #[serde(deserialize_with = "to_lowercase")]
+    pub symbol: String,
+    /// For a canceled order, this is the client order ID of the cancel request.
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    #[serde(rename = "S")]
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+    #[serde(rename = "o")]
+    pub order_type: String,
+    #[serde(rename = "f")]
+    #[serde(deserialize_with = "from_str_to_tif")]
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "q")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub original_qty: f64,
+    #[serde(rename = "p")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub original_price: f64,
+    /// The client order ID of the order being canceled. It is empty unless the order is canceled.
+    #[serde(rename = "C")]
+    pub orig_client_order_id: String,
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    #[serde(rename = "X")]
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub order_status: Status,
+    #[serde(rename = "r")]
+    pub reject_reason: String,
+    #[serde(rename = "i")]
+    pub order_id: i64,
+    #[serde(rename = "l")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub order_last_filled_qty: f64,
+    #[serde(rename = "z")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub order_filled_accumulated_qty: f64,
+    #[serde(rename = "L")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub last_filled_price: f64,
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+    #[serde(rename = "t")]
+    pub trade_id: i64,
+}
+
+impl From<&ExecutionReport> for OrderUpdate {
+    fn from(data: &ExecutionReport) -> Self {
+        let (order_type, time_in_force) = to_order_type(&data.order_type, data.time_in_force);
+        Self {
+            client_order_id: if data.orig_client_order_id.is_empty() {
+                data.client_order_id.clone()
+            } else {
+                data.orig_client_order_id.clone()
+            },
+            exch_ts: data.transaction_time,
+            qty: data.original_qty,
+            cum_qty: data.order_filled_accumulated_qty,
+            exec_qty: data.order_last_filled_qty,
+            price: data.original_price,
+            side: data.side,
+            time_in_force,
+            order_type,
+            status: data.order_status,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OutboundAccountPosition {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "u")]
+    pub last_update_time: i64,
+    #[serde(rename = "B")]
+    pub balances: Vec<Balance>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Balance {
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "f")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub free: f64,
+    #[serde(rename = "l")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub locked: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BalanceUpdate {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "d")]
+    pub delta: String,
+    #[serde(rename = "T")]
+    pub clear_time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ListenKeyStream {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "listenKey")]
+    pub listen_key: String,
+}
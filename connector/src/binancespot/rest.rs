@@ -0,0 +1,288 @@
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::{
+    binancefutures::{
+        BinanceFuturesError,
+        msg::stream::ListenKey,
+        ordermanager::OrderUpdate,
+        trade_stream::OrderOp,
+    },
+    binancespot::{
+        msg::rest::{
+            Account,
+            AmendOrderResponseResult,
+            Depth,
+            ExchangeInfo,
+            OrderResponseResult,
+            QueryOrderResponse,
+            QueryOrderResponseResult,
+        },
+        trade_stream::order_params,
+    },
+    utils::sign_hmac_sha256,
+};
+
+#[derive(Clone)]
+pub struct BinanceSpotClient {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    secret: String,
+}
+
+impl BinanceSpotClient {
+    pub fn new(url: &str, api_key: &str, secret: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            api_key: api_key.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+
+    async fn get_noauth<T: for<'a> Deserialize<'a>>(
+        &self,
+        path: &str,
+        query: String,
+    ) -> Result<T, reqwest::Error> {
+        let resp = self
+            .client
+            .get(format!("{}{}?{}", self.url, path, query))
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+
+    async fn get<T: for<'a> Deserialize<'a>>(
+        &self,
+        path: &str,
+        mut query: String,
+    ) -> Result<T, reqwest::Error> {
+        let time = Utc::now().timestamp_millis() - 1000;
+        if !query.is_empty() {
+            query.push('&');
+        }
+        query.push_str("recvWindow=5000&timestamp=");
+        query.push_str(&time.to_string());
+        let signature = sign_hmac_sha256(&self.secret, &query);
+        let resp = self
+            .client
+            .get(format!(
+                "{}{}?{}&signature={}",
+                self.url, path, query, signature
+            ))
+            .header("Accept", "application/json")
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+
+    async fn post<T: for<'a> Deserialize<'a>>(
+        &self,
+        path: &str,
+        body: String,
+    ) -> Result<T, reqwest::Error> {
+        let time = Utc::now().timestamp_millis() - 1000;
+        let sign_body = format!("recvWindow=5000&timestamp={}{}", time, body);
+        let signature = sign_hmac_sha256(&self.secret, &sign_body);
+        let resp = self
+            .client
+            .post(format!(
+                "{}{}?recvWindow=5000&timestamp={}&signature={}",
+                self.url, path, time, signature
+            ))
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("X-MBX-APIKEY", &self.api_key)
+            .body(body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+
+    async fn put<T: for<'a> Deserialize<'a>>(
+        &self,
+        path: &str,
+        body: String,
+    ) -> Result<T, reqwest::Error> {
+        let time = Utc::now().timestamp_millis() - 1000;
+        let sign_body = format!("recvWindow=5000&timestamp={}{}", time, body);
+        let signature = sign_hmac_sha256(&self.secret, &sign_body);
+        let resp = self
+            .client
+            .put(format!(
+                "{}{}?recvWindow=5000&timestamp={}&signature={}",
+                self.url, path, time, signature
+            ))
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("X-MBX-APIKEY", &self.api_key)
+            .body(body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+
+    async fn delete<T: for<'a> Deserialize<'a>>(
+        &self,
+        path: &str,
+        body: String,
+    ) -> Result<T, reqwest::Error> {
+        let time = Utc::now().timestamp_millis() - 1000;
+        let sign_body = format!("recvWindow=5000&timestamp={}{}", time, body);
+        let signature = sign_hmac_sha256(&self.secret, &sign_body);
+        let resp = self
+            .client
+            .delete(format!(
+                "{}{}?recvWindow=5000&timestamp={}&signature={}",
+                self.url, path, time, signature
+            ))
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("X-MBX-APIKEY", &self.api_key)
+            .body(body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+
+    /// The user data stream endpoints require only the API key, without the signature.
+    async fn send_api_key_only<T: for<'a> Deserialize<'a>>(
+        &self,
+        method: reqwest::Method,
+        query: String,
+    ) -> Result<T, reqwest::Error> {
+        let resp = self
+            .client
+            .request(
+                method,
+                format!("{}/api/v3/userDataStream?{}", self.url, query),
+            )
+            .header("Accept", "application/json")
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+
+    pub async fn start_user_data_stream(&self) -> Result<String, reqwest::Error> {
+        let resp: ListenKey = self
+            .send_api_key_only(reqwest::Method::POST, String::new())
+            .await?;
+        Ok(resp.listen_key)
+    }
+
+    pub async fn keepalive_user_data_stream(&self, listen_key: &str) -> Result<(), reqwest::Error> {
+        let _: serde_json::Value = self
+            .send_api_key_only(reqwest::Method::PUT, format!("listenKey={listen_key}"))
+            .await?;
+        Ok(())
+    }
+
+    /// Sends the order request through the REST API with the parameters shared with the WebSocket
+    /// API.
+    pub async fn send_order(&self, op: &OrderOp) -> Result<OrderUpdate, BinanceFuturesError> {
+        let body = order_params(op)
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let resp: OrderResponseResult = match op {
+            OrderOp::Submit { .. } => self.post("/api/v3/order", body).await?,
+            OrderOp::Cancel { .. } => self.delete("/api/v3/order", body).await?,
+            OrderOp::Modify { .. } => {
+                let resp: AmendOrderResponseResult =
+                    self.put("/api/v3/order/amend/keepPriority", body).await?;
+                return match resp {
+                    AmendOrderResponseResult::Ok(resp) => Ok((&resp).into()),
+                    AmendOrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
+                        code: resp.code,
+                        msg: resp.msg,
+                    }),
+                };
+            }
+        };
+        match resp {
+            OrderResponseResult::Ok(resp) => Ok((&resp).into()),
+            OrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
+                code: resp.code,
+                msg: resp.msg,
+            }),
+        }
+    }
+
+    pub async fn query_order(
+        &self,
+        client_order_id: &str,
+        symbol: &str,
+    ) -> Result<QueryOrderResponse, BinanceFuturesError> {
+        let resp: QueryOrderResponseResult = self
+            .get(
+                "/api/v3/order",
+                format!(
+                    "symbol={}&origClientOrderId={client_order_id}",
+                    symbol.to_uppercase()
+                ),
+            )
+            .await?;
+        match resp {
+            QueryOrderResponseResult::Ok(resp) => Ok(resp),
+            QueryOrderResponseResult::Err(resp) => Err(BinanceFuturesError::OrderError {
+                code: resp.code,
+                msg: resp.msg,
+            }),
+        }
+    }
+
+    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<(), reqwest::Error> {
+        let _: serde_json::Value = self
+            .delete(
+                "/api/v3/openOrders",
+                format!("symbol={}", symbol.to_uppercase()),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_account(&self) -> Result<Account, reqwest::Error> {
+        let resp: Account = self
+            .get("/api/v3/account", "omitZeroBalances=true".to_string())
+            .await?;
+        Ok(resp)
+    }
+
+    pub async fn get_exchange_info(&self, symbol: &str) -> Result<ExchangeInfo, reqwest::Error> {
+        let resp: ExchangeInfo = self
+            .get_noauth(
+                "/api/v3/exchangeInfo",
+                format!("symbol={}", symbol.to_uppercase()),
+            )
+            .await?;
+        Ok(resp)
+    }
+
+    pub async fn get_depth(&self, symbol: &str) -> Result<Depth, reqwest::Error> {
+        let resp: Depth = self
+            .get_noauth(
+                "/api/v3/depth",
+                format!("symbol={}&limit=1000", symbol.to_uppercase()),
+            )
+            .await?;
+        Ok(resp)
+    }
+}
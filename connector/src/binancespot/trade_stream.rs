@@ -0,0 +1,228 @@
+use hftbacktest::types::{OrdType, TimeInForce};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    binancefutures::{
+        BinanceFuturesError,
+        ordermanager::OrderUpdate,
+        trade_stream::{OrderApi, OrderOp},
+    },
+    binancespot::{
+        msg::rest::{AmendOrderResponse, OrderResponse},
+        rest::BinanceSpotClient,
+    },
+};
+
+/// Returns the parameters of the order request, which are shared by the REST API and the
+/// WebSocket API.
+pub fn order_params(op: &OrderOp) -> Vec<(&'static str, String)> {
+    // Unlike the streams, the APIs require the symbol in uppercase.
+    let mut params = vec![("symbol", op.symbol().to_uppercase())];
+    match op {
+        OrderOp::Submit {
+            client_order_id,
+            side,
+            price,
+            price_prec,
+            qty,
+            order_type,
+            time_in_force,
+            ..
+        } => {
+            params.push(("newClientOrderId", client_order_id.clone()));
+            params.push(("side", AsRef::<str>::as_ref(side).to_string()));
+            // Spot allows up to 8 decimal places for the quantity.
+            params.push(("quantity", format!("{:.8}", qty)));
+            params.push(("newOrderRespType", "RESULT".to_string()));
+            match (order_type, time_in_force) {
+                (OrdType::Limit, TimeInForce::GTX) => {
+                    // Spot expresses a post-only order as the LIMIT_MAKER order type.
+                    params.push(("type", "LIMIT_MAKER".to_string()));
+                    params.push(("price", format!("{:.prec$}", price, prec = price_prec)));
+                }
+                (OrdType::Limit, time_in_force) => {
+                    params.push(("type", "LIMIT".to_string()));
+                    params.push(("price", format!("{:.prec$}", price, prec = price_prec)));
+                    params.push((
+                        "timeInForce",
+                        AsRef::<str>::as_ref(time_in_force).to_string(),
+                    ));
+                }
+                (order_type, _) => {
+                    params.push(("type", AsRef::<str>::as_ref(order_type).to_string()));
+                }
+            }
+        }
+        OrderOp::Modify {
+            client_order_id,
+            qty,
+            ..
+        } => {
+            // Spot can only reduce the quantity of an open order, which keeps its priority. The
+            // client order ID is also kept so that the order can still be tracked by it.
+            params.push(("origClientOrderId", client_order_id.clone()));
+            params.push(("newClientOrderId", client_order_id.clone()));
+            params.push(("newQty", format!("{:.8}", qty)));
+        }
+        OrderOp::Cancel {
+            client_order_id, ..
+        } => {
+            params.push(("origClientOrderId", client_order_id.clone()));
+        }
+    }
+    params
+}
+
+impl OrderApi for BinanceSpotClient {
+    fn ws_request(&self, op: &OrderOp) -> (&'static str, Vec<(&'static str, String)>) {
+        let method = match op {
+            OrderOp::Submit { .. } => "order.place",
+            OrderOp::Modify { .. } => "order.amend.keepPriority",
+            OrderOp::Cancel { .. } => "order.cancel",
+        };
+        (method, order_params(op))
+    }
+
+    fn parse_ws_result(
+        &self,
+        op: &OrderOp,
+        result: Value,
+    ) -> Result<OrderUpdate, serde_json::Error> {
+        match op {
+            OrderOp::Modify { .. } => {
+                AmendOrderResponse::deserialize(&result).map(|resp| (&resp).into())
+            }
+            OrderOp::Submit { .. } | OrderOp::Cancel { .. } => {
+                OrderResponse::deserialize(&result).map(|resp| (&resp).into())
+            }
+        }
+    }
+
+    async fn send_rest_order(&self, op: &OrderOp) -> Result<OrderUpdate, BinanceFuturesError> {
+        self.send_order(op).await
+    }
+
+    async fn query_order_update(
+        &self,
+        client_order_id: &str,
+        symbol: &str,
+    ) -> Result<OrderUpdate, BinanceFuturesError> {
+        let resp = self.query_order(client_order_id, symbol).await?;
+        Ok((&resp).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::types::{OrdType, Side, Status, TimeInForce};
+
+    use crate::{
+        binancefutures::{
+            ordermanager::OrderUpdate,
+            trade_stream::{OrderApi, OrderOp},
+        },
+        binancespot::{rest::BinanceSpotClient, trade_stream::order_params},
+    };
+
+    fn submit(order_type: OrdType, time_in_force: TimeInForce) -> OrderOp {
+        OrderOp::Submit {
+            symbol: "btcusdt".to_string(),
+            client_order_id: "prefixabc".to_string(),
+            side: Side::Buy,
+            price: 60000.0,
+            price_prec: 2,
+            qty: 0.001,
+            order_type,
+            time_in_force,
+        }
+    }
+
+    fn find<'a>(params: &'a [(&'static str, String)], key: &str) -> Option<&'a str> {
+        params
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn builds_order_params() {
+        let params = order_params(&submit(OrdType::Limit, TimeInForce::GTX));
+        assert_eq!(find(&params, "symbol"), Some("BTCUSDT"));
+        assert_eq!(find(&params, "type"), Some("LIMIT_MAKER"));
+        assert_eq!(find(&params, "price"), Some("60000.00"));
+        assert_eq!(find(&params, "quantity"), Some("0.00100000"));
+        assert_eq!(find(&params, "timeInForce"), None);
+
+        let params = order_params(&submit(OrdType::Limit, TimeInForce::GTC));
+        assert_eq!(find(&params, "type"), Some("LIMIT"));
+        assert_eq!(find(&params, "timeInForce"), Some("GTC"));
+
+        let params = order_params(&submit(OrdType::Market, TimeInForce::GTC));
+        assert_eq!(find(&params, "type"), Some("MARKET"));
+        assert_eq!(find(&params, "price"), None);
+
+        // The amendment keeps the client order ID.
+        let params = order_params(&OrderOp::Modify {
+            symbol: "btcusdt".to_string(),
+            client_order_id: "prefixabc".to_string(),
+            side: Side::Buy,
+            price: 60000.0,
+            price_prec: 2,
+            qty: 0.0005,
+        });
+        assert_eq!(find(&params, "origClientOrderId"), Some("prefixabc"));
+        assert_eq!(find(&params, "newClientOrderId"), Some("prefixabc"));
+        assert_eq!(find(&params, "newQty"), Some("0.00050000"));
+        assert_eq!(find(&params, "price"), None);
+    }
+
+    #[test]
+    fn parses_amend_result() {
+        let client = BinanceSpotClient::new("http://127.0.0.1:1", "", "");
+        let op = OrderOp::Modify {
+            symbol: "btcusdt".to_string(),
+            client_order_id: "prefixabc".to_string(),
+            side: Side::Sell,
+            price: 60000.0,
+            price_prec: 2,
+            qty: 0.0005,
+        };
+        let result = serde_json::from_str(
+            r#"{
+                "transactTime": 1741926410242,
+                "executionId": 75,
+                "amendedOrder": {
+                    "symbol": "BTCUSDT",
+                    "orderId": 33,
+                    "orderListId": -1,
+                    "origClientOrderId": "prefixabc",
+                    "clientOrderId": "prefixabc",
+                    "transactTime": 1741926410242,
+                    "price": "60000.00000000",
+                    "qty": "0.00050000",
+                    "executedQty": "0.00020000",
+                    "preventedQty": "0.00000000",
+                    "quoteOrderQty": "0.00000000",
+                    "cumulativeQuoteQty": "12.00000000",
+                    "status": "PARTIALLY_FILLED",
+                    "timeInForce": "GTC",
+                    "type": "LIMIT_MAKER",
+                    "side": "SELL",
+                    "workingTime": 1741926410242,
+                    "selfTradePreventionMode": "NONE"
+                }
+            }"#,
+        )
+        .unwrap();
+        let update: OrderUpdate = client.parse_ws_result(&op, result).unwrap();
+        assert_eq!(update.client_order_id, "prefixabc");
+        assert_eq!(update.exch_ts, 1741926410242);
+        assert_eq!(update.qty, 0.0005);
+        assert_eq!(update.cum_qty, 0.0002);
+        assert_eq!(update.exec_qty, 0.0);
+        assert_eq!(update.order_type, OrdType::Limit);
+        assert_eq!(update.time_in_force, TimeInForce::GTX);
+        assert_eq!(update.status, Status::PartiallyFilled);
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::{SinkExt, StreamExt};
+use hftbacktest::prelude::*;
+use tokio::{
+    select,
+    sync::{
+        broadcast::{Receiver, error::RecvError},
+        mpsc::UnboundedSender,
+    },
+    time,
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Message, client::IntoClientRequest},
+};
+use tracing::{debug, error};
+
+use crate::{
+    binancefutures::{BinanceFuturesError, SharedSymbolSet, ordermanager::SharedOrderManager},
+    binancespot::{
+        msg::stream::{EventStream, Stream},
+        rest::BinanceSpotClient,
+    },
+    connector::PublishEvent,
+};
+
+/// Maps a symbol to its base asset. Spot has no position, so the balance of the base asset is
+/// published as the position of the symbol.
+pub type SharedBaseAssetMap = Arc<Mutex<HashMap<String, String>>>;
+
+pub struct UserDataStream {
+    symbols: SharedSymbolSet,
+    client: BinanceSpotClient,
+    ev_tx: UnboundedSender<PublishEvent>,
+    order_manager: SharedOrderManager,
+    base_assets: SharedBaseAssetMap,
+    symbol_rx: Receiver<String>,
+    listen_key: String,
+}
+
+impl UserDataStream {
+    pub fn new(
+        client: BinanceSpotClient,
+        ev_tx: UnboundedSender<PublishEvent>,
+        order_manager: SharedOrderManager,
+        symbols: SharedSymbolSet,
+        base_assets: SharedBaseAssetMap,
+        symbol_rx: Receiver<String>,
+    ) -> Self {
+        Self {
+            symbols,
+            client,
+            ev_tx,
+            order_manager,
+            base_assets,
+            symbol_rx,
+            listen_key: String::new(),
+        }
+    }
+
+    pub async fn get_listen_key(&mut self) -> Result<String, BinanceFuturesError> {
+        self.listen_key = self.client.start_user_data_stream().await?;
+        Ok(self.listen_key.clone())
+    }
+
+    fn process_message(&self, stream: EventStream) -> Result<(), BinanceFuturesError> {
+        match stream {
+            EventStream::DepthUpdate(_) | EventStream::Trade(_) => unreachable!(),
+            EventStream::ListenKeyExpired(_) => {
+                return Err(BinanceFuturesError::ListenKeyExpired);
+            }
+            EventStream::OutboundAccountPosition(data) => {
+                let base_assets = self.base_assets.lock().unwrap();
+                for balance in data.balances {
+                    for (symbol, _) in base_assets
+                        .iter()
+                        .filter(|(_, asset)| **asset == balance.asset)
+                    {
+                        self.ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Position {
+                                symbol: symbol.clone(),
+                                qty: balance.free + balance.locked,
+                                exch_ts: data.last_update_time * 1_000_000,
+                            }))
+                            .unwrap();
+                    }
+                }
+            }
+            EventStream::BalanceUpdate(_) => {
+                // The resulting balance is delivered by the outboundAccountPosition message.
+            }
+            EventStream::ExecutionReport(data) => {
+                match self
+                    .order_manager
+                    .lock()
+                    .unwrap()
+                    .update_from_ws(&(&data).into())
+                {
+                    Ok(Some(order)) => {
+                        self.ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                                symbol: data.symbol,
+                                order,
+                            }))
+                            .unwrap();
+                    }
+                    Ok(None) => {
+                        // This order is already deleted.
+                    }
+                    Err(BinanceFuturesError::PrefixUnmatched) => {
+                        // This order is not created by this connector.
+                    }
+                    Err(error) => {
+                        error!(
+                            ?error,
+                            ?data,
+                            "Couldn't update the order from ExecutionReport message."
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn connect(&mut self, url: &str) -> Result<(), BinanceFuturesError> {
+        let request = url.into_client_request()?;
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+        let mut interval = time::interval(Duration::from_secs(60 * 30));
+
+        let symbols: HashSet<_> = self.symbols.lock().unwrap().iter().cloned().collect();
+        let client = self.client.clone();
+        let order_manager = self.order_manager.clone();
+        let base_assets = self.base_assets.clone();
+        let ev_tx = self.ev_tx.clone();
+
+        tokio::spawn(async move {
+            // Initializes the registered symbols to start with the clean state.
+            for symbol in symbols {
+                if let Err(error) = init_symbol(
+                    client.clone(),
+                    symbol.clone(),
+                    order_manager.clone(),
+                    base_assets.clone(),
+                    ev_tx.clone(),
+                )
+                .await
+                {
+                    error!(?error, %symbol, "Couldn't initialize the symbol.");
+                }
+            }
+        });
+
+        loop {
+            select! {
+                _ = interval.tick() => {
+                    self.order_manager
+                        .lock()
+                        .unwrap()
+                        .gc();
+                    let client_ = self.client.clone();
+                    let listen_key = self.listen_key.clone();
+                    tokio::spawn(async move {
+                        if let Err(error) = client_.keepalive_user_data_stream(&listen_key).await {
+                            error!(?error, "Failed keepalive user data stream.");
+                        }
+                    });
+                }
+                msg = self.symbol_rx.recv() => {
+                    match msg {
+                        Ok(symbol) => {
+                            let client = self.client.clone();
+                            let order_manager = self.order_manager.clone();
+                            let base_assets = self.base_assets.clone();
+                            let ev_tx = self.ev_tx.clone();
+
+                            tokio::spawn(async move {
+                                if let Err(error) = init_symbol(
+                                    client,
+                                    symbol.clone(),
+                                    order_manager,
+                                    base_assets,
+                                    ev_tx
+                                ).await {
+                                    error!(?error, %symbol, "Couldn't initialize the symbol.");
+                                }
+                            });
+                        }
+                        Err(RecvError::Closed) => {
+                            return Ok(());
+                        }
+                        Err(RecvError::Lagged(num)) => {
+                            error!("{num} subscription requests were missed.");
+                        }
+                    }
+                }
+                message = read.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<Stream>(&text) {
+                            Ok(Stream::EventStream(stream)) => {
+                                self.process_message(stream)?;
+                            }
+                            Ok(Stream::Result(result)) => {
+                                debug!(?result, "Subscription request response is received.");
+                            }
+                            Err(error) => {
+                                error!(?error, %text, "Couldn't parse Stream.");
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        write.send(Message::Pong(data)).await?;
+                    }
+                    Some(Ok(Message::Close(close_frame))) => {
+                        return Err(BinanceFuturesError::ConnectionAbort(
+                            close_frame.map(|f| f.to_string()).unwrap_or(String::new())
+                        ));
+                    }
+                    Some(Ok(Message::Binary(_)))
+                    | Some(Ok(Message::Frame(_)))
+                    | Some(Ok(Message::Pong(_))) => {}
+                    Some(Err(error)) => {
+                        return Err(BinanceFuturesError::from(error));
+                    }
+                    None => {
+                        return Err(BinanceFuturesError::ConnectionInterrupted);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Cancels all orders of the symbol to start with the clean state, and publishes the balance of
+/// its base asset as the initial position.
+async fn init_symbol(
+    client: BinanceSpotClient,
+    symbol: String,
+    order_manager: SharedOrderManager,
+    base_assets: SharedBaseAssetMap,
+    ev_tx: UnboundedSender<PublishEvent>,
+) -> Result<(), BinanceFuturesError> {
+    // todo: rate-limit throttling.
+    client.cancel_all_orders(&symbol).await?;
+    let orders = order_manager.lock().unwrap().cancel_all_from_rest(&symbol);
+    for order in orders {
+        ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                symbol: symbol.clone(),
+                order,
+            }))
+            .unwrap();
+    }
+
+    let exchange_info = client.get_exchange_info(&symbol).await?;
+    let base_asset = exchange_info
+        .symbols
+        .into_iter()
+        .find(|info| info.symbol == symbol)
+        .ok_or(BinanceFuturesError::InstrumentNotFound)?
+        .base_asset;
+    base_assets
+        .lock()
+        .unwrap()
+        .insert(symbol.clone(), base_asset.clone());
+
+    let account = client.get_account().await?;
+    let qty = account
+        .balances
+        .iter()
+        .find(|balance| balance.asset == base_asset)
+        .map(|balance| balance.free + balance.locked)
+        .unwrap_or(0.0);
+    ev_tx
+        .send(PublishEvent::LiveEvent(LiveEvent::Position {
+            symbol,
+            qty,
+            exch_ts: account.update_time * 1_000_000,
+        }))
+        .unwrap();
+    Ok(())
+}
@@ -1,10 +1,15 @@
 use std::{
     collections::{HashMap, HashSet},
     num::{ParseFloatError, ParseIntError},
-    sync::{Arc, Mutex},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
 };
 
-use hftbacktest::types::{ErrorKind, LiveError, LiveEvent, Order, Value};
+use hftbacktest::types::{ErrorKind, ErrorReason, LiveError, LiveEvent, Order, Value, VenueError};
 use serde::Deserialize;
 use thiserror::Error;
 use tokio::sync::{broadcast, broadcast::Sender, mpsc::UnboundedSender};
@@ -37,6 +42,8 @@ pub enum BybitError {
     AuthError { code: i64, msg: String },
     #[error("OrderError: {code} - {msg}")]
     OrderError { code: i64, msg: String },
+    #[error("OrderRejected: {0}")]
+    OrderRejected(String),
     #[error("InvalidPxQty: {0}")]
     InvalidPxQty(#[from] ParseFloatError),
     #[error("InvalidOrderId: {0}")]
@@ -83,6 +90,7 @@ impl BybitError {
                 map.insert("msg".to_string(), Value::String(msg.clone()));
                 map
             }),
+            BybitError::OrderRejected(_) => Value::String(self.to_string()),
             BybitError::InvalidPxQty(_) => Value::String(self.to_string()),
             BybitError::InvalidOrderId(_) => Value::String(self.to_string()),
             BybitError::PrefixUnmatched => Value::String(self.to_string()),
@@ -99,6 +107,47 @@ impl BybitError {
             BybitError::Config(_) => Value::String(self.to_string()),
         }
     }
+
+    /// Returns the normalized reason for the error.
+    pub fn reason(&self) -> ErrorReason {
+        match self {
+            BybitError::OrderError { code, .. } => match *code {
+                // Too many visits, IP rate limit, and system level frequency protection.
+                10006 | 10018 | 10429 => ErrorReason::RateLimited,
+                // Insufficient wallet balance and available balance.
+                110004 | 110007 | 110012 => ErrorReason::InsufficientMargin,
+                // The spot LIMIT-MAKER order is rejected due to invalid price.
+                170218 => ErrorReason::PostOnlyWouldTake,
+                // Order price exceeds the allowable range.
+                110003 => ErrorReason::PriceOutOfBand,
+                // Order does not exist.
+                110001 => ErrorReason::UnknownOrder,
+                // OrderLinkedID is duplicate.
+                110072 => ErrorReason::DuplicateOrderId,
+                // Invalid API key, signature error, permission denied, and unmatched IP.
+                10003 | 10004 | 10005 | 10010 => ErrorReason::AuthFailure,
+                _ => ErrorReason::Other,
+            },
+            // Derivatives post-only orders are accepted and then canceled with this reject reason.
+            BybitError::OrderRejected(reason) if reason == "EC_PostOnlyWillTakeLiquidity" => {
+                ErrorReason::PostOnlyWouldTake
+            }
+            BybitError::AuthError { .. } => ErrorReason::AuthFailure,
+            BybitError::OrderNotFound => ErrorReason::UnknownOrder,
+            BybitError::OrderAlreadyExist => ErrorReason::DuplicateOrderId,
+            _ => ErrorReason::Other,
+        }
+    }
+
+    /// Returns the normalized error with the exchange's error code and message.
+    pub fn to_venue_error(&self) -> VenueError {
+        match self {
+            BybitError::AuthError { code, msg } | BybitError::OrderError { code, msg } => {
+                VenueError::new(self.reason(), msg.clone()).code(*code)
+            }
+            _ => VenueError::new(self.reason(), self.to_string()),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -111,6 +160,15 @@ pub struct Config {
     secret: String,
     category: String,
     order_prefix: String,
+    /// The interval in seconds at which the order states are reconciled with the exchange through
+    /// the REST API, in addition to after every reconnection of the private stream. `0` disables
+    /// the periodic reconciliation. The default is `60`.
+    #[serde(default = "default_reconciliation_interval")]
+    reconciliation_interval: u64,
+}
+
+fn default_reconciliation_interval() -> u64 {
+    60
 }
 
 type SharedSymbolSet = Arc<Mutex<HashSet<String>>>;
@@ -122,6 +180,7 @@ pub struct Bybit {
     symbols: SharedSymbolSet,
     client: BybitClient,
     symbol_tx: Sender<String>,
+    dcp_armed: Arc<AtomicBool>,
 }
 
 impl Bybit {
@@ -175,6 +234,9 @@ impl Bybit {
         let instruments = self.symbols.clone();
         let client = self.client.clone();
         let symbol_tx = self.symbol_tx.clone();
+        let reconciliation_interval = Duration::from_secs(self.config.reconciliation_interval);
+        let connected = AtomicBool::new(false);
+        let dcp_armed = self.dcp_armed.clone();
 
         tokio::spawn(async move {
             let _ = Retry::new(ExponentialBackoff::default())
@@ -192,6 +254,9 @@ impl Bybit {
                     Ok(())
                 })
                 .retry(|| async {
+                    // Disconnection Protection is re-armed by the next refresh for each new
+                    // private connection.
+                    dcp_armed.store(false, Ordering::SeqCst);
                     let mut stream = private_stream::PrivateStream::new(
                         api_key.clone(),
                         secret.clone(),
@@ -201,6 +266,8 @@ impl Bybit {
                         category.clone(),
                         client.clone(),
                         symbol_tx.subscribe(),
+                        connected.swap(true, Ordering::Relaxed),
+                        reconciliation_interval,
                     );
 
                     // // todo: fix the operation order.
@@ -277,6 +344,7 @@ impl ConnectorBuilder for Bybit {
             client,
             symbols: Default::default(),
             symbol_tx,
+            dcp_armed: Default::default(),
         })
     }
 }
@@ -301,6 +369,7 @@ impl Connector for Bybit {
     }
 
     fn submit(&self, asset: String, order: Order, ev_tx: UnboundedSender<PublishEvent>) {
+        let order_id = order.order_id;
         match self
             .order_manager
             .lock()
@@ -317,10 +386,10 @@ impl Connector for Bybit {
             }
             Err(error) => {
                 ev_tx
-                    .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                        ErrorKind::OrderError,
-                        error.to_value(),
-                    ))))
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::OrderError, error.to_value())
+                            .with_venue(error.to_venue_error().symbol(asset).order_id(order_id)),
+                    )))
                     .unwrap();
             }
         }
@@ -342,12 +411,90 @@ impl Connector for Bybit {
             }
             Err(error) => {
                 ev_tx
-                    .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                        ErrorKind::OrderError,
-                        error.to_value(),
-                    ))))
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::OrderError, error.to_value()).with_venue(
+                            error
+                                .to_venue_error()
+                                .symbol(asset)
+                                .order_id(order.order_id),
+                        ),
+                    )))
                     .unwrap();
             }
         }
     }
+
+    fn refresh_dead_man_switch(&self, _symbol: String, timeout: Duration) {
+        // Disconnection Protection is account-wide and is triggered by the loss of the private
+        // connection rather than by a countdown, so it only needs to be set once per private
+        // connection.
+        if self.dcp_armed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let product = match self.config.category.as_str() {
+            "spot" => "SPOT",
+            "option" => "OPTIONS",
+            _ => "DERIVATIVES",
+        };
+        // The time window must be between 3 and 300 seconds.
+        let time_window = timeout.as_secs().clamp(3, 300);
+        let client = self.client.clone();
+        let dcp_armed = self.dcp_armed.clone();
+        tokio::spawn(async move {
+            if let Err(error) = client
+                .set_disconnected_cancel_all(product, time_window)
+                .await
+            {
+                error!(?error, "Couldn't set Disconnection Protection.");
+                dcp_armed.store(false, Ordering::SeqCst);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::types::ErrorReason;
+
+    use crate::bybit::BybitError;
+
+    fn order_error(code: i64) -> BybitError {
+        BybitError::OrderError {
+            code,
+            msg: String::new(),
+        }
+    }
+
+    #[test]
+    fn maps_error_reasons() {
+        let cases = [
+            (order_error(10006), ErrorReason::RateLimited),
+            (order_error(10429), ErrorReason::RateLimited),
+            (order_error(110007), ErrorReason::InsufficientMargin),
+            (order_error(170218), ErrorReason::PostOnlyWouldTake),
+            (order_error(110003), ErrorReason::PriceOutOfBand),
+            (order_error(110001), ErrorReason::UnknownOrder),
+            (order_error(110072), ErrorReason::DuplicateOrderId),
+            (order_error(10003), ErrorReason::AuthFailure),
+            (order_error(10001), ErrorReason::Other),
+            (
+                BybitError::OrderRejected("EC_PostOnlyWillTakeLiquidity".to_string()),
+                ErrorReason::PostOnlyWouldTake,
+            ),
+            (BybitError::OrderRejected("EC_NoError".to_string()), ErrorReason::Other),
+            (
+                BybitError::AuthError {
+                    code: 0,
+                    msg: String::new(),
+                },
+                ErrorReason::AuthFailure,
+            ),
+            (BybitError::OrderNotFound, ErrorReason::UnknownOrder),
+            (BybitError::OrderAlreadyExist, ErrorReason::DuplicateOrderId),
+            (BybitError::ConnectionInterrupted, ErrorReason::Other),
+        ];
+        for (error, reason) in cases {
+            assert_eq!(error.reason(), reason, "{error}");
+        }
+    }
 }
@@ -259,6 +259,28 @@ where
     pub data: T,
 }
 
+/// The order returned by the Get Open & Closed Orders and Get Order History endpoints.
+#[derive(Deserialize, Debug)]
+pub struct OrderInfo {
+    pub symbol: String,
+    #[serde(rename = "orderStatus")]
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub order_status: Status,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub qty: f64,
+    #[serde(rename = "leavesQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub leaves_qty: f64,
+    #[serde(rename = "cumExecQty")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub cum_exec_qty: f64,
+    #[serde(rename = "updatedTime")]
+    #[serde(deserialize_with = "from_str_to_i64")]
+    pub updated_time: i64,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Position {
     #[serde(rename = "positionIdx")]
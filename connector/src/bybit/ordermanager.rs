@@ -9,7 +9,7 @@ use hftbacktest::{
 use crate::{
     bybit::{
         BybitError,
-        msg::{Execution, FastExecution, Order as BybitOrder, PrivateOrder},
+        msg::{Execution, FastExecution, Order as BybitOrder, OrderInfo, PrivateOrder},
     },
     connector::GetOrders,
     utils::{RefSymbolOrderId, SymbolOrderId, generate_rand_string},
@@ -50,6 +50,7 @@ impl OrderManager {
             .ok_or(BybitError::OrderNotFound)?;
         order.order.req = Status::None;
         order.order.status = data.order_status;
+        order.order.leaves_qty = data.leaves_qty;
         order.order.exch_timestamp = data.updated_time * 1_000_000;
         let is_active = order.order.active();
         if !is_active {
@@ -203,6 +204,54 @@ impl OrderManager {
         Ok(order_info)
     }
 
+    /// Returns the order link IDs of the symbol's active orders that have no request in flight,
+    /// which are expected to be open on the exchange.
+    pub fn reconcilable_orders(&self, symbol: &str) -> Vec<OrderLinkId> {
+        self.orders
+            .iter()
+            .filter(|(_, order_ext)| {
+                order_ext.symbol == symbol
+                    && order_ext.order.active()
+                    && order_ext.order.req == Status::None
+            })
+            .map(|(order_link_id, _)| order_link_id.clone())
+            .collect()
+    }
+
+    /// Applies the order state queried through the REST API during the reconciliation, which may
+    /// have diverged from the managed state if the private stream missed updates. Returns the
+    /// repaired order only if its state differs from the managed one.
+    pub fn reconcile(&mut self, data: &OrderInfo) -> Result<Option<OrderExt>, BybitError> {
+        if !data.order_link_id.starts_with(&self.prefix) {
+            return Err(BybitError::PrefixUnmatched);
+        }
+        let order = self
+            .orders
+            .get_mut(&data.order_link_id)
+            .ok_or(BybitError::OrderNotFound)?;
+        if order.order.req != Status::None
+            || data.updated_time * 1_000_000 < order.order.exch_timestamp
+            || (order.order.status == data.order_status
+                && (order.order.leaves_qty - data.leaves_qty).abs() < order.order.qty * 1e-9)
+        {
+            // The response of the request in flight will update the order, or the managed state is
+            // newer or already up to date.
+            return Ok(None);
+        }
+        // The executed quantity is what was missed since the last known state.
+        order.order.exec_qty = order.order.leaves_qty - data.leaves_qty;
+        order.order.status = data.order_status;
+        order.order.leaves_qty = data.leaves_qty;
+        order.order.exch_timestamp = data.updated_time * 1_000_000;
+        if !order.order.active() {
+            self.order_id_map
+                .remove(&RefSymbolOrderId::new(&order.symbol, order.order.order_id));
+            Ok(self.orders.remove(&data.order_link_id))
+        } else {
+            Ok(Some(order.clone()))
+        }
+    }
+
     pub fn cancel_all(&mut self, symbol: &str) -> Vec<Order> {
         let mut removed_order_ids = Vec::new();
         for (order_link_id, order_ext) in &mut self.orders {
@@ -1,8 +1,11 @@
-use std::time::Duration;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use chrono::Utc;
 use futures_util::{SinkExt, StreamExt, stream::SplitSink};
-use hftbacktest::prelude::LiveEvent;
+use hftbacktest::prelude::{ErrorKind, ErrorReason, LiveError, LiveEvent, Value};
 use tokio::{
     net::TcpStream,
     select,
@@ -11,6 +14,7 @@ use tokio::{
         mpsc::UnboundedSender,
     },
     time,
+    time::Instant,
 };
 use tokio_tungstenite::{
     MaybeTlsStream,
@@ -18,7 +22,7 @@ use tokio_tungstenite::{
     connect_async,
     tungstenite::{Bytes, Message, client::IntoClientRequest},
 };
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 
 use crate::{
     bybit::{
@@ -41,6 +45,8 @@ pub struct PrivateStream {
     category: String,
     client: BybitClient,
     symbol_rx: Receiver<String>,
+    reconnected: bool,
+    reconciliation_interval: Duration,
 }
 
 impl PrivateStream {
@@ -53,6 +59,8 @@ impl PrivateStream {
         category: String,
         client: BybitClient,
         symbol_rx: Receiver<String>,
+        reconnected: bool,
+        reconciliation_interval: Duration,
     ) -> Self {
         Self {
             api_key,
@@ -63,6 +71,8 @@ impl PrivateStream {
             category,
             client,
             symbol_rx,
+            reconnected,
+            reconciliation_interval,
         }
     }
 
@@ -110,11 +120,23 @@ impl PrivateStream {
                         let category = self.category.clone();
                         let order_manager = self.order_manager.clone();
                         let ev_tx = self.ev_tx.clone();
+                        let reconnected = self.reconnected;
 
                         tokio::spawn(async move {
                             for symbol in symbols {
-                                // Cancel all orders in order to start with the clean state.
-                                if let Err(error) = cancel_all(
+                                if reconnected {
+                                    // Updates may have been missed while disconnected, so the
+                                    // order states are reconciled with the exchange instead of
+                                    // starting over.
+                                    reconcile(
+                                        client.clone(),
+                                        category.clone(),
+                                        symbol.clone(),
+                                        order_manager.clone(),
+                                        ev_tx.clone(),
+                                    )
+                                    .await;
+                                } else if let Err(error) = cancel_all(
                                     client.clone(),
                                     category.clone(),
                                     symbol.clone(),
@@ -123,6 +145,7 @@ impl PrivateStream {
                                 )
                                 .await
                                 {
+                                    // Cancel all orders in order to start with the clean state.
                                     error!(
                                         ?error,
                                         %category,
@@ -226,6 +249,21 @@ impl PrivateStream {
                     let mut order_manager = self.order_manager.lock().unwrap();
                     match order_manager.update_order(private_order) {
                         Ok(OrderExt { symbol, order }) => {
+                            let rejected =
+                                BybitError::OrderRejected(private_order.reject_reason.clone());
+                            if rejected.reason() != ErrorReason::Other {
+                                self.ev_tx
+                                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                                        LiveError::with(ErrorKind::OrderError, rejected.to_value())
+                                            .with_venue(
+                                                rejected
+                                                    .to_venue_error()
+                                                    .symbol(symbol.clone())
+                                                    .order_id(order.order_id),
+                                            ),
+                                    )))
+                                    .unwrap();
+                            }
                             self.ev_tx
                                 .send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
                                 .unwrap();
@@ -248,6 +286,10 @@ impl PrivateStream {
         let (ws_stream, _) = connect_async(request).await?;
         let (mut write, mut read) = ws_stream.split();
         let mut interval = time::interval(Duration::from_secs(10));
+        // The periodic reconciliation is disabled if the interval is zero.
+        let periodic = !self.reconciliation_interval.is_zero();
+        let period = self.reconciliation_interval.max(Duration::from_secs(1));
+        let mut reconciliation_interval = time::interval_at(Instant::now() + period, period);
 
         let expires = Utc::now().timestamp_millis() + 5000;
         let signature = sign_hmac_sha256(&self.secret, &format!("GET/realtime{expires}"));
@@ -271,6 +313,44 @@ impl PrivateStream {
                     let s = serde_json::to_string(&op).unwrap();
                     write.send(Message::Text(s.into())).await?;
                 }
+                _ = reconciliation_interval.tick(), if periodic => {
+                    let symbols = self
+                        .symbols
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let client = self.client.clone();
+                    let category = self.category.clone();
+                    let order_manager = self.order_manager.clone();
+                    let ev_tx = self.ev_tx.clone();
+
+                    tokio::spawn(async move {
+                        for symbol in symbols {
+                            reconcile(
+                                client.clone(),
+                                category.clone(),
+                                symbol.clone(),
+                                order_manager.clone(),
+                                ev_tx.clone()
+                            ).await;
+                            if let Err(error) = get_position(
+                                client.clone(),
+                                category.clone(),
+                                symbol.clone(),
+                                ev_tx.clone()
+                            ).await {
+                                error!(
+                                    ?error,
+                                    %category,
+                                    %symbol,
+                                    "Couldn't get the position."
+                                );
+                            }
+                        }
+                    });
+                }
                 msg = self.symbol_rx.recv() => {
                     match msg {
                         Ok(symbol) => {
@@ -410,3 +490,105 @@ pub async fn cancel_all(
     }
     Ok(())
 }
+
+/// Reconciles the order states of the symbol with the exchange through the REST API, and
+/// publishes the repaired orders, followed by a [`ErrorKind::StateRepaired`] error summarizing the
+/// repair if any.
+pub async fn reconcile(
+    client: BybitClient,
+    category: String,
+    symbol: String,
+    order_manager: SharedOrderManager,
+    ev_tx: UnboundedSender<PublishEvent>,
+) {
+    match reconcile_orders(client, &category, &symbol, order_manager, ev_tx.clone()).await {
+        Ok((0, 0)) => {}
+        Ok((repaired, orphaned)) => {
+            info!(%symbol, repaired, orphaned, "The order states are reconciled.");
+            let mut value = HashMap::new();
+            value.insert("symbol".to_string(), Value::String(symbol));
+            value.insert("repaired".to_string(), Value::Int(repaired as i64));
+            value.insert("orphaned".to_string(), Value::Int(orphaned as i64));
+            ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                    ErrorKind::StateRepaired,
+                    Value::Map(value),
+                ))))
+                .unwrap();
+        }
+        Err(error) => {
+            error!(?error, %category, %symbol, "Couldn't reconcile the order states.");
+        }
+    }
+}
+
+/// Returns the number of the repaired orders and the number of the canceled orphan orders, which
+/// are open on the exchange with the connector's prefix but unknown to the [`OrderManager`].
+///
+/// [`OrderManager`]: crate::bybit::ordermanager::OrderManager
+async fn reconcile_orders(
+    client: BybitClient,
+    category: &str,
+    symbol: &str,
+    order_manager: SharedOrderManager,
+    ev_tx: UnboundedSender<PublishEvent>,
+) -> Result<(usize, usize), BybitError> {
+    // todo: rate-limit throttling.
+    // Takes the orders expected to be open before querying, since orders submitted afterward may
+    // not be included in the response.
+    let mut missing: HashSet<_> = order_manager
+        .lock()
+        .unwrap()
+        .reconcilable_orders(symbol)
+        .into_iter()
+        .collect();
+    let open_orders = client.get_open_orders(category, symbol).await?;
+
+    let mut repaired = 0;
+    let mut orphans = Vec::new();
+    for open_order in &open_orders {
+        missing.remove(&open_order.order_link_id);
+        match order_manager.lock().unwrap().reconcile(open_order) {
+            Ok(Some(OrderExt { symbol, order })) => {
+                repaired += 1;
+                ev_tx
+                    .send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
+                    .unwrap();
+            }
+            Ok(None) | Err(BybitError::PrefixUnmatched) => {}
+            Err(BybitError::OrderNotFound) => {
+                orphans.push(open_order.order_link_id.clone());
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    // The orders that are no longer open have been filled, canceled, or rejected without the
+    // updates being received.
+    for order_link_id in missing {
+        let Some(closed_order) = client
+            .get_order_history(category, symbol, &order_link_id)
+            .await?
+        else {
+            // The order history may not be available yet.
+            continue;
+        };
+        if let Some(OrderExt { symbol, order }) =
+            order_manager.lock().unwrap().reconcile(&closed_order)?
+        {
+            repaired += 1;
+            ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
+                .unwrap();
+        }
+    }
+
+    // The orphan orders cannot be mapped to the bots' orders, such as when the submission
+    // response was lost, so they are canceled.
+    for order_link_id in &orphans {
+        if let Err(error) = client.cancel_order(category, symbol, order_link_id).await {
+            error!(?error, %symbol, %order_link_id, "Couldn't cancel the orphan order.");
+        }
+    }
+    Ok((repaired, orphans.len()))
+}
@@ -4,7 +4,7 @@ use serde::Deserialize;
 use crate::{
     bybit::{
         BybitError,
-        msg::{Position, RestResponse},
+        msg::{OrderInfo, Position, RestResponse},
     },
     utils::sign_hmac_sha256,
 };
@@ -94,6 +94,60 @@ impl BybitClient {
         }
     }
 
+    pub async fn cancel_order(
+        &self,
+        category: &str,
+        symbol: &str,
+        order_link_id: &str,
+    ) -> Result<(), BybitError> {
+        let resp: serde_json::Value = self
+            .post(
+                "/v5/order/cancel",
+                format!(
+                    "{{\"category\":\"{category}\",\"symbol\":\"{symbol}\",\
+                     \"orderLinkId\":\"{order_link_id}\"}}"
+                ),
+                &self.api_key,
+                &self.secret,
+            )
+            .await?;
+        match resp.get("retCode").and_then(|code| code.as_i64()) {
+            Some(0) => Ok(()),
+            _ => Err(BybitError::OpError(
+                resp.get("retMsg")
+                    .and_then(|msg| msg.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Sets the time window of Disconnection Protection, after which all orders of the product are
+    /// canceled if the private connection is lost.
+    pub async fn set_disconnected_cancel_all(
+        &self,
+        product: &str,
+        time_window: u64,
+    ) -> Result<(), BybitError> {
+        let resp: serde_json::Value = self
+            .post(
+                "/v5/order/disconnected-cancel-all",
+                format!("{{\"product\":\"{product}\",\"timeWindow\":{time_window}}}"),
+                &self.api_key,
+                &self.secret,
+            )
+            .await?;
+        match resp.get("retCode").and_then(|code| code.as_i64()) {
+            Some(0) => Ok(()),
+            _ => Err(BybitError::OpError(
+                resp.get("retMsg")
+                    .and_then(|msg| msg.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            )),
+        }
+    }
+
     pub async fn get_position_information(
         &self,
         category: &str,
@@ -114,4 +168,49 @@ impl BybitClient {
             Ok(position)
         }
     }
+
+    /// Returns the open orders of the symbol, up to 50.
+    pub async fn get_open_orders(
+        &self,
+        category: &str,
+        symbol: &str,
+    ) -> Result<Vec<OrderInfo>, BybitError> {
+        let resp: RestResponse = self
+            .get(
+                "/v5/order/realtime",
+                &format!("category={category}&symbol={symbol}&openOnly=0&limit=50"),
+                &self.api_key,
+                &self.secret,
+            )
+            .await?;
+        if resp.ret_code != 0 {
+            Err(BybitError::OpError(resp.ret_msg))
+        } else {
+            let orders: Vec<OrderInfo> = serde_json::from_value(resp.result.list.unwrap())?;
+            Ok(orders)
+        }
+    }
+
+    /// Returns the closed order, or `None` if it cannot be found.
+    pub async fn get_order_history(
+        &self,
+        category: &str,
+        symbol: &str,
+        order_link_id: &str,
+    ) -> Result<Option<OrderInfo>, BybitError> {
+        let resp: RestResponse = self
+            .get(
+                "/v5/order/history",
+                &format!("category={category}&symbol={symbol}&orderLinkId={order_link_id}"),
+                &self.api_key,
+                &self.secret,
+            )
+            .await?;
+        if resp.ret_code != 0 {
+            Err(BybitError::OpError(resp.ret_msg))
+        } else {
+            let orders: Vec<OrderInfo> = serde_json::from_value(resp.result.list.unwrap())?;
+            Ok(orders.into_iter().next())
+        }
+    }
 }
@@ -166,10 +166,10 @@ impl TradeStream {
                     msg: stream.ret_msg.clone(),
                 };
                 self.ev_tx
-                    .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                        ErrorKind::CriticalConnectionError,
-                        error.to_value(),
-                    ))))
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::CriticalConnectionError, error.to_value())
+                            .with_venue(error.to_venue_error()),
+                    )))
                     .unwrap();
                 return Err(error);
             }
@@ -189,18 +189,21 @@ impl TradeStream {
                 let mut order_man_ = self.order_manager.lock().unwrap();
                 let order_link_id = req_id.split('/').next().ok_or(BybitError::InvalidReqId)?;
                 let OrderExt { symbol, order } = order_man_.update_submit_fail(order_link_id)?;
+                let error = BybitError::OrderError {
+                    code: stream.ret_code,
+                    msg: stream.ret_msg.clone(),
+                };
+                let venue = error
+                    .to_venue_error()
+                    .symbol(&symbol)
+                    .order_id(order.order_id);
                 self.ev_tx
                     .send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
                     .unwrap();
                 self.ev_tx
-                    .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                        ErrorKind::OrderError,
-                        BybitError::OrderError {
-                            code: stream.ret_code,
-                            msg: stream.ret_msg.clone(),
-                        }
-                        .to_value(),
-                    ))))
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::OrderError, error.to_value()).with_venue(venue),
+                    )))
                     .unwrap();
             }
         } else if stream.op == "order.cancel" {
@@ -219,18 +222,21 @@ impl TradeStream {
                 let mut order_man_ = self.order_manager.lock().unwrap();
                 let order_link_id = req_id.split('/').next().ok_or(BybitError::InvalidReqId)?;
                 let OrderExt { symbol, order } = order_man_.update_cancel_fail(order_link_id)?;
+                let error = BybitError::OrderError {
+                    code: stream.ret_code,
+                    msg: stream.ret_msg.clone(),
+                };
+                let venue = error
+                    .to_venue_error()
+                    .symbol(&symbol)
+                    .order_id(order.order_id);
                 self.ev_tx
                     .send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
                     .unwrap();
                 self.ev_tx
-                    .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
-                        ErrorKind::OrderError,
-                        BybitError::OrderError {
-                            code: stream.ret_code,
-                            msg: stream.ret_msg.clone(),
-                        }
-                        .to_value(),
-                    ))))
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::OrderError, error.to_value()).with_venue(venue),
+                    )))
                     .unwrap();
             }
         } else {
@@ -1,9 +1,19 @@
 use std::{
     fmt::Debug,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use hftbacktest::types::{LiveEvent, Order};
+use hftbacktest::types::{
+    ErrorKind,
+    ErrorReason,
+    LiveError,
+    LiveEvent,
+    Order,
+    Status,
+    Value,
+    VenueError,
+};
 use tokio::sync::mpsc::UnboundedSender;
 
 /// A message will be received by the publisher thread and then published to the bots.
@@ -50,6 +60,47 @@ pub trait Connector {
     /// through the channel using [`PublishEvent`]. The returned error should not be related to the
     /// exchange; instead, it should indicate a connector internal error.
     fn cancel(&self, symbol: String, order: Order, tx: UnboundedSender<PublishEvent>);
+
+    /// Modifies the price and quantity of an open order. The given order holds the new price and
+    /// quantity. This method should not block, and the response should be returned through the
+    /// channel using [`PublishEvent`].
+    ///
+    /// The default implementation rejects the request by restoring the order's current state, for
+    /// connectors that don't support order modification.
+    fn modify(&self, symbol: String, order: Order, tx: UnboundedSender<PublishEvent>) {
+        let msg = "order modification is not supported.";
+        let venue = VenueError::new(ErrorReason::Other, msg)
+            .symbol(&symbol)
+            .order_id(order.order_id);
+        let current = self
+            .order_manager()
+            .lock()
+            .unwrap()
+            .orders(Some(symbol.clone()))
+            .into_iter()
+            .find(|o| o.order_id == order.order_id);
+        if let Some(mut current) = current {
+            current.req = Status::None;
+            tx.send(PublishEvent::LiveEvent(LiveEvent::Order {
+                symbol,
+                order: current,
+            }))
+            .unwrap();
+        }
+        tx.send(PublishEvent::LiveEvent(LiveEvent::Error(
+            LiveError::with(ErrorKind::OrderError, Value::String(msg.to_string()))
+                .with_venue(venue),
+        )))
+        .unwrap();
+    }
+
+    /// Arms or refreshes the exchange-side dead man's switch for the symbol, which cancels all open
+    /// orders of the symbol on the exchange unless it is refreshed within the timeout. This is
+    /// called periodically while the bots trading the symbol are alive, so that the orders are
+    /// canceled even if the connector itself goes down.
+    ///
+    /// The default implementation does nothing, for exchanges that don't support it.
+    fn refresh_dead_man_switch(&self, _symbol: String, _timeout: Duration) {}
 }
 
 /// Provides `orders` method to get the current working orders.
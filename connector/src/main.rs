@@ -12,7 +12,8 @@ use clap::Parser;
 use hftbacktest::{
     live::ipc::{
         TO_ALL,
-        iceoryx::{ChannelError, IceoryxBuilder},
+        iceoryx::{ChannelError, IceoryxBuilder, IceoryxSender},
+        socket::{Endpoint, SocketReceiver, SocketSender, listen},
     },
     prelude::*,
 };
@@ -28,26 +29,93 @@ use crate::{
     bybit::Bybit,
     connector::{Connector, ConnectorBuilder, GetOrders, PublishEvent},
     fuse::FusedHashMapMarketDepth,
+    metrics::Metrics,
+    monitor::{BotMonitor, Position, SharedPositions},
 };
+#[cfg(feature = "binancespot")]
+use crate::binancespot::BinanceSpot;
+#[cfg(feature = "okx")]
+use crate::okx::Okx;
+#[cfg(feature = "sim")]
+use crate::sim::Sim;
 
 #[cfg(feature = "binancefutures")]
 pub mod binancefutures;
+#[cfg(feature = "binancespot")]
+pub mod binancespot;
 #[cfg(feature = "bybit")]
 pub mod bybit;
+#[cfg(feature = "okx")]
+pub mod okx;
+#[cfg(feature = "sim")]
+pub mod sim;
 
 mod connector;
 mod fuse;
+mod metrics;
+mod monitor;
 mod utils;
 
-struct Position {
-    qty: f64,
-    exch_ts: i64,
+fn handle_request(
+    id: u64,
+    req: LiveRequest,
+    tx: &UnboundedSender<PublishEvent>,
+    connector: &mut Box<dyn Connector>,
+    monitor: &mut BotMonitor,
+    metrics: &Metrics,
+) {
+    monitor.on_request(id, &req);
+    metrics.on_request(id, &req);
+    match req {
+        LiveRequest::Order {
+            symbol: asset,
+            order,
+        } => match order.req {
+            Status::New => {
+                // Requests to the Connector submit the new order.
+                connector.submit(asset, order, tx.clone());
+            }
+            Status::Canceled => {
+                // Requests to the Connector cancel the order.
+                connector.cancel(asset, order, tx.clone());
+            }
+            Status::Replaced => {
+                // Requests to the Connector modify the order.
+                connector.modify(asset, order, tx.clone());
+            }
+            status => {
+                error!(?status, "An invalid request was received from the bot.");
+            }
+        },
+        LiveRequest::RegisterInstrument {
+            symbol,
+            tick_size,
+            lot_size: _,
+        } => {
+            // Makes prepare the publisher thread to also add the instrument.
+            tx.send(PublishEvent::RegisterInstrument {
+                id,
+                symbol: symbol.clone(),
+                tick_size,
+            })
+            .unwrap();
+            // Requests to the Connector subscribe to the necessary feeds for the
+            // instrument.
+            connector.register(symbol);
+        }
+        LiveRequest::Heartbeat => {}
+        LiveRequest::CancelAll { symbol, flatten } => {
+            monitor.cancel_all(symbol, flatten, connector.as_ref(), tx);
+        }
+    }
 }
 
 fn run_receive_task(
     name: &str,
     tx: UnboundedSender<PublishEvent>,
     connector: &mut Box<dyn Connector>,
+    monitor: &mut BotMonitor,
+    metrics: &Metrics,
 ) -> Result<(), ChannelError> {
     let node = NodeBuilder::new()
         .create::<ipc::Service>()
@@ -57,42 +125,10 @@ fn run_receive_task(
         let cycle_time = Duration::from_nanos(1000);
         match node.wait(cycle_time) {
             Ok(()) => {
-                while let Some((id, ev)) = bot_rx.receive()? {
-                    match ev {
-                        LiveRequest::Order {
-                            symbol: asset,
-                            order,
-                        } => match order.req {
-                            Status::New => {
-                                // Requests to the Connector submit the new order.
-                                connector.submit(asset, order, tx.clone());
-                            }
-                            Status::Canceled => {
-                                // Requests to the Connector cancel the order.
-                                connector.cancel(asset, order, tx.clone());
-                            }
-                            status => {
-                                error!(?status, "An invalid request was received from the bot.");
-                            }
-                        },
-                        LiveRequest::RegisterInstrument {
-                            symbol,
-                            tick_size,
-                            lot_size: _,
-                        } => {
-                            // Makes prepare the publisher thread to also add the instrument.
-                            tx.send(PublishEvent::RegisterInstrument {
-                                id,
-                                symbol: symbol.clone(),
-                                tick_size,
-                            })
-                            .unwrap();
-                            // Requests to the Connector subscribe to the necessary feeds for the
-                            // instrument.
-                            connector.register(symbol);
-                        }
-                    }
+                while let Some((id, req)) = bot_rx.receive()? {
+                    handle_request(id, req, &tx, connector, monitor, metrics);
                 }
+                monitor.check(connector.as_ref(), &tx);
             }
             Err(_error) => {
                 break;
@@ -102,16 +138,49 @@ fn run_receive_task(
     Ok(())
 }
 
-async fn run_publish_task(
-    name: &str,
+fn run_socket_receive_task(
+    bot_rx: SocketReceiver<LiveRequest>,
+    tx: UnboundedSender<PublishEvent>,
+    connector: &mut Box<dyn Connector>,
+    monitor: &mut BotMonitor,
+    metrics: &Metrics,
+) -> Result<(), ChannelError> {
+    loop {
+        if let Some((id, req)) = bot_rx.receive_timeout(Duration::from_secs(1))? {
+            handle_request(id, req, &tx, connector, monitor, metrics);
+        }
+        monitor.check(connector.as_ref(), &tx);
+    }
+}
+
+/// Sends the live events to the bots through either iceoryx or the socket listener.
+trait BotSender {
+    fn send(&self, id: u64, ev: &LiveEvent) -> Result<(), ChannelError>;
+}
+
+impl BotSender for IceoryxSender<LiveEvent> {
+    fn send(&self, id: u64, ev: &LiveEvent) -> Result<(), ChannelError> {
+        IceoryxSender::send(self, id, ev)
+    }
+}
+
+impl BotSender for SocketSender<LiveEvent> {
+    fn send(&self, id: u64, ev: &LiveEvent) -> Result<(), ChannelError> {
+        SocketSender::send(self, id, ev)
+    }
+}
+
+async fn run_publish_task<S: BotSender>(
+    bot_tx: S,
     order_manager: Arc<Mutex<dyn GetOrders>>,
+    position: SharedPositions,
+    metrics: Arc<Metrics>,
     mut rx: UnboundedReceiver<PublishEvent>,
 ) -> Result<(), ChannelError> {
     let mut depth = HashMap::new();
-    let mut position: HashMap<String, Position> = HashMap::new();
-    let bot_tx = IceoryxBuilder::new(name).bot(false).sender()?;
 
     while let Some(msg) = rx.recv().await {
+        metrics.set_queue_depth(rx.len());
         match msg {
             PublishEvent::RegisterInstrument {
                 id,
@@ -132,7 +201,7 @@ async fn run_publish_task(
                     )?;
                 }
 
-                if let Some(position) = position.get(&symbol) {
+                if let Some(position) = position.lock().unwrap().get(&symbol) {
                     bot_tx.send(
                         id,
                         &LiveEvent::Position {
@@ -165,8 +234,9 @@ async fn run_publish_task(
                 bot_tx.send(id, &LiveEvent::BatchEnd)?;
             }
             PublishEvent::LiveEvent(ev) => {
+                metrics.on_event(&ev);
                 // The live event will only be published if the result is true.
-                if handle_ev(&ev, &mut depth, &mut position) {
+                if handle_ev(&ev, &mut depth, &mut position.lock().unwrap()) {
                     bot_tx.send(TO_ALL, &ev)?;
                 }
             }
@@ -274,12 +344,35 @@ struct Args {
     name: String,
 
     /// Connector
-    /// * binancefutures: Binance USD-m Futures
+    /// * binancefutures: Binance USD-M and COIN-M Futures
+    /// * binancespot: Binance Spot (requires the `binancespot` feature)
     /// * bybit: Bybit Linear Futures
+    /// * okx: OKX (requires the `okx` feature)
+    /// * sim: Simulated exchange replaying the feed data (requires the `sim` feature)
     connector: String,
 
     /// Connector's configuration file path.
     config: String,
+
+    /// Listens for the bots on the given socket endpoint, such as `tcp://0.0.0.0:5000` or
+    /// `unix:///tmp/connector.sock`, instead of the shared memory, allowing the bots to run on a
+    /// different machine.
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// Cancels all orders of a bot if its heartbeats stop for the given number of seconds. This
+    /// also arms the exchange-side dead man's switch, if the exchange supports it.
+    #[arg(long)]
+    bot_timeout: Option<u64>,
+
+    /// Flattens the positions of a bot's symbols as well when its heartbeats stop.
+    #[arg(long)]
+    flatten_on_timeout: bool,
+
+    /// Serves the Prometheus metrics at `http://{addr}/metrics`, such as `0.0.0.0:9100`.
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    metrics: Option<std::net::SocketAddr>,
 }
 
 #[tokio::main]
@@ -317,6 +410,16 @@ async fn main() {
             connector.run(pub_tx.clone());
             Box::new(connector)
         }
+        #[cfg(feature = "binancespot")]
+        "binancespot" => {
+            let mut connector = BinanceSpot::build_from(&config)
+                .map_err(|error| {
+                    error!(?error, "Couldn't build the BinanceSpot connector.");
+                })
+                .unwrap();
+            connector.run(pub_tx.clone());
+            Box::new(connector)
+        }
         "bybit" => {
             let mut connector = Bybit::build_from(&config)
                 .map_err(|error| {
@@ -326,20 +429,81 @@ async fn main() {
             connector.run(pub_tx.clone());
             Box::new(connector)
         }
+        #[cfg(feature = "okx")]
+        "okx" => {
+            let mut connector = Okx::build_from(&config)
+                .map_err(|error| {
+                    error!(?error, "Couldn't build the OKX connector.");
+                })
+                .unwrap();
+            connector.run(pub_tx.clone());
+            Box::new(connector)
+        }
+        #[cfg(feature = "sim")]
+        "sim" => {
+            let mut connector = Sim::build_from(&config)
+                .map_err(|error| {
+                    error!(?error, "Couldn't build the Sim connector.");
+                })
+                .unwrap();
+            connector.run(pub_tx.clone());
+            Box::new(connector)
+        }
         connector => {
             error!(%connector, "This connector doesn't exist.");
             exit(1);
         }
     };
 
+    let socket = args.listen.as_ref().map(|addr| {
+        let endpoint = Endpoint::parse(addr)
+            .ok_or_else(|| {
+                error!(%addr, "The listen endpoint must be `tcp://host:port` or `unix:///path`.");
+            })
+            .unwrap();
+        listen::<LiveEvent, LiveRequest>(&endpoint)
+            .map_err(|error| {
+                error!(?error, %addr, "Couldn't listen on the endpoint.");
+            })
+            .unwrap()
+    });
+    let (socket_tx, socket_rx) = match socket {
+        Some((tx, rx)) => (Some(tx), Some(rx)),
+        None => (None, None),
+    };
+
+    let positions: SharedPositions = Default::default();
+    let mut monitor = BotMonitor::new(
+        args.bot_timeout.map(Duration::from_secs),
+        args.flatten_on_timeout,
+        positions.clone(),
+    );
+
+    let metrics = Arc::new(Metrics::new(&args.name));
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = args.metrics {
+        tokio::spawn(metrics::serve(addr, metrics.clone()));
+    }
+
     let name = args.name.clone();
     let order_manager = connector.order_manager();
+    let metrics_ = metrics.clone();
     let handle = thread::spawn(move || {
         let rt = Builder::new_current_thread().enable_all().build().unwrap();
 
         rt.block_on(async move {
-            run_publish_task(&name, order_manager, pub_rx)
-                .await
+            let result = match socket_tx {
+                Some(bot_tx) => {
+                    run_publish_task(bot_tx, order_manager, positions, metrics_, pub_rx).await
+                }
+                None => match IceoryxBuilder::new(&name).bot(false).sender() {
+                    Ok(bot_tx) => {
+                        run_publish_task(bot_tx, order_manager, positions, metrics_, pub_rx).await
+                    }
+                    Err(error) => Err(error),
+                },
+            };
+            result
                 .map_err(|error: ChannelError| {
                     error!(
                         ?error,
@@ -351,7 +515,13 @@ async fn main() {
     });
 
     let name = args.name;
-    run_receive_task(&name, pub_tx, &mut connector)
+    let result = match socket_rx {
+        Some(bot_rx) => {
+            run_socket_receive_task(bot_rx, pub_tx, &mut connector, &mut monitor, &metrics)
+        }
+        None => run_receive_task(&name, pub_tx, &mut connector, &mut monitor, &metrics),
+    };
+    result
         .map_err(|error| {
             error!(
                 ?error,
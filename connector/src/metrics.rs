@@ -0,0 +1,376 @@
+//! Prometheus metrics of the connector, enabled by the `metrics` feature. Without the feature,
+//! [`Metrics`] does nothing.
+//!
+//! All metrics are labelled by `connector`, the connector name.
+//!
+//! * `hftbacktest_connector_feed_latency_seconds` - The latency between the exchange timestamp and
+//!   the local receipt timestamp of the feed, by `symbol`.
+//! * `hftbacktest_connector_order_ack_latency_seconds` - The time from receiving an order request
+//!   from a bot until receiving its first response from the exchange, by `symbol`.
+//! * `hftbacktest_connector_rejected_orders_total` - The number of order requests that are
+//!   rejected or expire without being acknowledged, by `symbol`.
+//! * `hftbacktest_connector_requests_total` - The number of requests from the bots, by `bot` and
+//!   `request`.
+//! * `hftbacktest_connector_errors_total` - The number of errors published to the bots, by `kind`.
+//! * `hftbacktest_connector_reconnects_total` - The number of stream reconnections, counted by
+//!   the connection interrupted errors.
+//! * `hftbacktest_connector_publish_queue_depth` - The number of events waiting to be published to
+//!   the bots.
+
+#[cfg(feature = "metrics")]
+pub use self::prometheus_metrics::{Metrics, serve};
+
+#[cfg(not(feature = "metrics"))]
+pub use self::noop::Metrics;
+
+#[cfg(feature = "metrics")]
+mod prometheus_metrics {
+    use std::{
+        collections::HashMap,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use hftbacktest::prelude::*;
+    use prometheus::{
+        Encoder,
+        HistogramOpts,
+        HistogramVec,
+        IntCounter,
+        IntCounterVec,
+        IntGauge,
+        Opts,
+        Registry,
+        TextEncoder,
+        exponential_buckets,
+        labels,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tracing::{error, info};
+
+    const MAX_PENDING: usize = 10_000;
+    const PENDING_TIMEOUT: Duration = Duration::from_secs(60);
+
+    pub struct Metrics {
+        registry: Registry,
+        feed_latency: HistogramVec,
+        order_ack_latency: HistogramVec,
+        rejected_orders: IntCounterVec,
+        requests: IntCounterVec,
+        errors: IntCounterVec,
+        reconnects: IntCounter,
+        queue_depth: IntGauge,
+        /// The time at which the order requests awaiting their first response are received.
+        pending: Mutex<HashMap<(String, OrderId), (Status, Instant)>>,
+    }
+
+    impl Metrics {
+        pub fn new(name: &str) -> Self {
+            let registry = Registry::new_custom(
+                Some("hftbacktest_connector".to_string()),
+                Some(labels! { "connector".to_string() => name.to_string() }),
+            )
+            .unwrap();
+            // From 100 microseconds to about 3.3 seconds.
+            let buckets = exponential_buckets(0.0001, 2.0, 16).unwrap();
+
+            let feed_latency = HistogramVec::new(
+                HistogramOpts::new(
+                    "feed_latency_seconds",
+                    "Feed latency between the exchange and the connector.",
+                )
+                .buckets(buckets.clone()),
+                &["symbol"],
+            )
+            .unwrap();
+            let order_ack_latency = HistogramVec::new(
+                HistogramOpts::new(
+                    "order_ack_latency_seconds",
+                    "Time from receiving an order request until receiving its first response.",
+                )
+                .buckets(buckets),
+                &["symbol"],
+            )
+            .unwrap();
+            let rejected_orders = IntCounterVec::new(
+                Opts::new(
+                    "rejected_orders_total",
+                    "Number of order requests rejected or expired without being acknowledged.",
+                ),
+                &["symbol"],
+            )
+            .unwrap();
+            let requests = IntCounterVec::new(
+                Opts::new("requests_total", "Number of requests from the bots."),
+                &["bot", "request"],
+            )
+            .unwrap();
+            let errors = IntCounterVec::new(
+                Opts::new("errors_total", "Number of errors published to the bots."),
+                &["kind"],
+            )
+            .unwrap();
+            let reconnects =
+                IntCounter::new("reconnects_total", "Number of stream reconnections.").unwrap();
+            let queue_depth = IntGauge::new(
+                "publish_queue_depth",
+                "Number of events waiting to be published to the bots.",
+            )
+            .unwrap();
+
+            registry.register(Box::new(feed_latency.clone())).unwrap();
+            registry
+                .register(Box::new(order_ack_latency.clone()))
+                .unwrap();
+            registry
+                .register(Box::new(rejected_orders.clone()))
+                .unwrap();
+            registry.register(Box::new(requests.clone())).unwrap();
+            registry.register(Box::new(errors.clone())).unwrap();
+            registry.register(Box::new(reconnects.clone())).unwrap();
+            registry.register(Box::new(queue_depth.clone())).unwrap();
+
+            Self {
+                registry,
+                feed_latency,
+                order_ack_latency,
+                rejected_orders,
+                requests,
+                errors,
+                reconnects,
+                queue_depth,
+                pending: Default::default(),
+            }
+        }
+
+        /// Records the request received from the bot.
+        pub fn on_request(&self, id: u64, req: &LiveRequest) {
+            let request = match req {
+                LiveRequest::Order { symbol, order } => {
+                    let mut pending = self.pending.lock().unwrap();
+                    // Drops the requests that have never been responded to.
+                    if pending.len() >= MAX_PENDING {
+                        pending.retain(|_, (_, received)| received.elapsed() < PENDING_TIMEOUT);
+                    }
+                    pending.insert(
+                        (symbol.clone(), order.order_id),
+                        (order.req, Instant::now()),
+                    );
+                    match order.req {
+                        Status::New => "submit",
+                        Status::Canceled => "cancel",
+                        Status::Replaced => "modify",
+                        _ => "invalid",
+                    }
+                }
+                LiveRequest::RegisterInstrument { .. } => "register",
+                LiveRequest::Heartbeat => "heartbeat",
+                LiveRequest::CancelAll { .. } => "cancel_all",
+            };
+            self.requests
+                .with_label_values(&[id.to_string().as_str(), request])
+                .inc();
+        }
+
+        /// Records the live event to be published to the bots.
+        pub fn on_event(&self, ev: &LiveEvent) {
+            match ev {
+                LiveEvent::Feed { symbol, event } => {
+                    self.feed_latency
+                        .with_label_values(&[symbol.as_str()])
+                        .observe((event.local_ts - event.exch_ts) as f64 / 1_000_000_000.0);
+                }
+                LiveEvent::Order { symbol, order } => {
+                    let pending = self
+                        .pending
+                        .lock()
+                        .unwrap()
+                        .remove(&(symbol.clone(), order.order_id));
+                    if let Some((req, received)) = pending {
+                        self.order_ack_latency
+                            .with_label_values(&[symbol.as_str()])
+                            .observe(received.elapsed().as_secs_f64());
+                        if order.status == Status::Rejected
+                            || (order.status == Status::Expired && req == Status::New)
+                        {
+                            self.rejected_orders
+                                .with_label_values(&[symbol.as_str()])
+                                .inc();
+                        }
+                    }
+                }
+                LiveEvent::Error(error) => {
+                    let kind = match error.kind {
+                        ErrorKind::ConnectionInterrupted => {
+                            self.reconnects.inc();
+                            "connection_interrupted".to_string()
+                        }
+                        ErrorKind::CriticalConnectionError => {
+                            "critical_connection_error".to_string()
+                        }
+                        ErrorKind::OrderError => "order_error".to_string(),
+                        ErrorKind::StateRepaired => "state_repaired".to_string(),
+                        ErrorKind::DepthResync => "depth_resync".to_string(),
+                        ErrorKind::Custom(code) => format!("custom_{code}"),
+                    };
+                    self.errors.with_label_values(&[kind.as_str()]).inc();
+                }
+                LiveEvent::Position { .. } | LiveEvent::BatchStart | LiveEvent::BatchEnd => {}
+            }
+        }
+
+        pub fn set_queue_depth(&self, depth: usize) {
+            self.queue_depth.set(depth as i64);
+        }
+
+        /// Encodes the metrics in the Prometheus text format.
+        pub fn encode(&self) -> Vec<u8> {
+            let mut buf = Vec::new();
+            TextEncoder::new()
+                .encode(&self.registry.gather(), &mut buf)
+                .unwrap();
+            buf
+        }
+    }
+
+    /// Serves the metrics in the Prometheus text format at `http://{addr}/metrics`.
+    pub async fn serve(addr: SocketAddr, metrics: Arc<Metrics>) {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                error!(?error, %addr, "Couldn't bind the metrics endpoint.");
+                return;
+            }
+        };
+        info!(%addr, "Serving the metrics.");
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_scrape(stream, metrics.clone()));
+                }
+                Err(error) => {
+                    error!(?error, "Couldn't accept the metrics connection.");
+                }
+            }
+        }
+    }
+
+    async fn handle_scrape(mut stream: TcpStream, metrics: Arc<Metrics>) {
+        // Only the request line is needed.
+        let mut buf = [0u8; 1024];
+        let Ok(n) = stream.read(&mut buf).await else {
+            return;
+        };
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let path = request.split_whitespace().nth(1).unwrap_or_default();
+        let (status, content_type, body) = if path == "/metrics" {
+            (
+                "200 OK",
+                TextEncoder::new().format_type().to_string(),
+                metrics.encode(),
+            )
+        } else {
+            ("404 Not Found", "text/plain".to_string(), Vec::new())
+        };
+        let header = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+            Connection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = stream.write_all(header.as_bytes()).await;
+        let _ = stream.write_all(&body).await;
+        let _ = stream.shutdown().await;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+
+        use super::*;
+
+        #[tokio::test]
+        async fn scrape() {
+            let metrics = Arc::new(Metrics::new("test"));
+
+            let mut order = Order::new(
+                1,
+                1000,
+                0.1,
+                1.0,
+                Side::Buy,
+                OrdType::Limit,
+                TimeInForce::GTC,
+            );
+            order.req = Status::New;
+            metrics.on_request(
+                7,
+                &LiveRequest::Order {
+                    symbol: "btcusdt".to_string(),
+                    order: order.clone(),
+                },
+            );
+            order.req = Status::None;
+            order.status = Status::Expired;
+            metrics.on_event(&LiveEvent::Order {
+                symbol: "btcusdt".to_string(),
+                order,
+            });
+            metrics.on_event(&LiveEvent::Error(LiveError::new(
+                ErrorKind::ConnectionInterrupted,
+            )));
+
+            let addr = std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            tokio::spawn(serve(addr, metrics));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).await.unwrap();
+
+            assert!(resp.starts_with("HTTP/1.1 200 OK"));
+            assert!(resp.contains(
+                "hftbacktest_connector_requests_total{bot=\"7\",request=\"submit\",connector=\"test\"} 1"
+            ));
+            assert!(resp.contains(
+                "hftbacktest_connector_rejected_orders_total{symbol=\"btcusdt\",connector=\"test\"} 1"
+            ));
+            assert!(resp.contains(
+                "hftbacktest_connector_order_ack_latency_seconds_count{symbol=\"btcusdt\",connector=\"test\"} 1"
+            ));
+            assert!(resp.contains("hftbacktest_connector_reconnects_total{connector=\"test\"} 1"));
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod noop {
+    use hftbacktest::prelude::*;
+
+    pub struct Metrics;
+
+    impl Metrics {
+        pub fn new(_name: &str) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub fn on_request(&self, _id: u64, _req: &LiveRequest) {}
+
+        #[inline(always)]
+        pub fn on_event(&self, _ev: &LiveEvent) {}
+
+        #[inline(always)]
+        pub fn set_queue_depth(&self, _depth: usize) {}
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use hftbacktest::prelude::*;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{info, warn};
+
+use crate::connector::{Connector, PublishEvent};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct Position {
+    pub qty: f64,
+    pub exch_ts: i64,
+}
+
+pub type SharedPositions = Arc<Mutex<HashMap<String, Position>>>;
+
+#[derive(Default)]
+struct BotState {
+    last_heartbeat: Option<Instant>,
+    symbols: HashSet<String>,
+    orders: HashSet<(String, OrderId)>,
+}
+
+/// Monitors the liveness of the bots through their heartbeats. When a bot that has sent heartbeats
+/// goes silent for longer than the timeout, all of its orders are canceled and, optionally, the
+/// positions of its symbols are flattened. While the bots are alive, the exchange-side dead man's
+/// switch is refreshed, if the connector supports it, so that the orders are also canceled when
+/// the connector itself goes down.
+///
+/// It also handles the manual cancel-all and flatten command, [`LiveRequest::CancelAll`].
+pub struct BotMonitor {
+    timeout: Option<Duration>,
+    flatten: bool,
+    bots: HashMap<u64, BotState>,
+    tick_sizes: HashMap<String, f64>,
+    positions: SharedPositions,
+    last_check: Instant,
+    last_refresh: Option<Instant>,
+}
+
+impl BotMonitor {
+    /// If `timeout` is `None`, the bots' liveness is not monitored.
+    pub fn new(timeout: Option<Duration>, flatten: bool, positions: SharedPositions) -> Self {
+        Self {
+            timeout,
+            flatten,
+            bots: HashMap::new(),
+            tick_sizes: HashMap::new(),
+            positions,
+            last_check: Instant::now(),
+            last_refresh: None,
+        }
+    }
+
+    /// Tracks the bot's instruments, orders, and heartbeats from its request.
+    pub fn on_request(&mut self, id: u64, req: &LiveRequest) {
+        match req {
+            // The orders are only needed to cancel them once the bot goes silent.
+            LiveRequest::Order { symbol, order }
+                if order.req == Status::New && self.timeout.is_some() =>
+            {
+                self.bots
+                    .entry(id)
+                    .or_default()
+                    .orders
+                    .insert((symbol.clone(), order.order_id));
+            }
+            LiveRequest::RegisterInstrument {
+                symbol, tick_size, ..
+            } => {
+                self.tick_sizes.insert(symbol.clone(), *tick_size);
+                self.bots
+                    .entry(id)
+                    .or_default()
+                    .symbols
+                    .insert(symbol.clone());
+            }
+            LiveRequest::Heartbeat => {
+                self.bots.entry(id).or_default().last_heartbeat = Some(Instant::now());
+            }
+            _ => {}
+        }
+    }
+
+    /// Cancels the orders of the bots that have gone silent and refreshes the exchange-side dead
+    /// man's switch. This should be called periodically.
+    pub fn check(&mut self, connector: &dyn Connector, tx: &UnboundedSender<PublishEvent>) {
+        let Some(timeout) = self.timeout else {
+            return;
+        };
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+        self.prune_orders(connector);
+
+        let silent: Vec<u64> = self
+            .bots
+            .iter()
+            .filter(|(_, bot)| bot.last_heartbeat.is_some_and(|ts| ts.elapsed() > timeout))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in silent {
+            let bot = self.bots.remove(&id).unwrap();
+            warn!(
+                %id,
+                "The bot has stopped sending heartbeats. Cancels all of its orders."
+            );
+            for symbol in bot.symbols.iter() {
+                cancel_orders(connector, symbol, Some(&bot.orders), tx);
+                if self.flatten {
+                    self.flatten_position(connector, symbol, tx);
+                }
+            }
+        }
+
+        // Refreshes the countdown well before it expires.
+        if self
+            .last_refresh
+            .is_none_or(|ts| ts.elapsed() >= timeout / 3)
+        {
+            let symbols: HashSet<&String> = self
+                .bots
+                .values()
+                .filter(|bot| bot.last_heartbeat.is_some())
+                .flat_map(|bot| bot.symbols.iter())
+                .collect();
+            if !symbols.is_empty() {
+                for symbol in symbols {
+                    connector.refresh_dead_man_switch(symbol.clone(), timeout);
+                }
+                self.last_refresh = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Forgets the bots' orders that are no longer active in the connector so that the tracked
+    /// orders don't grow without bound.
+    fn prune_orders(&mut self, connector: &dyn Connector) {
+        let symbols: HashSet<&String> = self
+            .bots
+            .values()
+            .flat_map(|bot| bot.symbols.iter())
+            .collect();
+        let order_manager = connector.order_manager();
+        let order_manager = order_manager.lock().unwrap();
+        let active: HashSet<(String, OrderId)> = symbols
+            .into_iter()
+            .flat_map(|symbol| {
+                order_manager
+                    .orders(Some(symbol.clone()))
+                    .into_iter()
+                    .filter(|order| order.active())
+                    .map(|order| (symbol.clone(), order.order_id))
+            })
+            .collect();
+        for bot in self.bots.values_mut() {
+            bot.orders.retain(|order| active.contains(order));
+        }
+    }
+
+    /// Cancels all open orders of the given symbol, or of all registered symbols if `None`, and
+    /// flattens the positions if requested.
+    pub fn cancel_all(
+        &mut self,
+        symbol: Option<String>,
+        flatten: bool,
+        connector: &dyn Connector,
+        tx: &UnboundedSender<PublishEvent>,
+    ) {
+        let symbols = match symbol {
+            Some(symbol) => vec![symbol],
+            None => self.tick_sizes.keys().cloned().collect(),
+        };
+        for symbol in symbols.iter() {
+            info!(%symbol, %flatten, "Cancels all orders.");
+            cancel_orders(connector, symbol, None, tx);
+            if flatten {
+                self.flatten_position(connector, symbol, tx);
+            }
+        }
+    }
+
+    fn flatten_position(
+        &self,
+        connector: &dyn Connector,
+        symbol: &str,
+        tx: &UnboundedSender<PublishEvent>,
+    ) {
+        let qty = match self.positions.lock().unwrap().get(symbol) {
+            Some(position) => position.qty,
+            None => return,
+        };
+        if qty == 0.0 {
+            return;
+        }
+        let Some(tick_size) = self.tick_sizes.get(symbol) else {
+            return;
+        };
+        let side = if qty > 0.0 { Side::Sell } else { Side::Buy };
+        let mut order = Order::new(
+            rand::random::<u64>(),
+            0,
+            *tick_size,
+            qty.abs(),
+            side,
+            OrdType::Market,
+            TimeInForce::IOC,
+        );
+        order.status = Status::New;
+        order.req = Status::New;
+        order.local_timestamp = Utc::now().timestamp_nanos_opt().unwrap();
+        info!(%symbol, %qty, "Flattens the position.");
+        connector.submit(symbol.to_string(), order, tx.clone());
+    }
+}
+
+fn cancel_orders(
+    connector: &dyn Connector,
+    symbol: &str,
+    order_ids: Option<&HashSet<(String, OrderId)>>,
+    tx: &UnboundedSender<PublishEvent>,
+) {
+    let orders = connector
+        .order_manager()
+        .lock()
+        .unwrap()
+        .orders(Some(symbol.to_string()));
+    for mut order in orders {
+        let owned = order_ids
+            .is_none_or(|order_ids| order_ids.contains(&(symbol.to_string(), order.order_id)));
+        if order.active() && owned {
+            order.req = Status::Canceled;
+            connector.cancel(symbol.to_string(), order, tx.clone());
+        }
+    }
+}
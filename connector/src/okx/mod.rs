@@ -0,0 +1,477 @@
+use std::{
+    collections::{HashMap, HashSet},
+    num::ParseFloatError,
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hftbacktest::types::{ErrorKind, ErrorReason, LiveError, LiveEvent, Order, Value, VenueError};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::sync::{broadcast, broadcast::Sender, mpsc::UnboundedSender};
+use tracing::error;
+
+use crate::{
+    connector::{Connector, ConnectorBuilder, GetOrders, PublishEvent},
+    okx::{
+        ordermanager::{OrderManager, SharedOrderManager},
+        public_stream::PublicStream,
+        rest::OkxClient,
+        trade_stream::OrderOp,
+    },
+    utils::{ExponentialBackoff, PxQty, Retry},
+};
+
+#[allow(dead_code)]
+mod msg;
+mod ordermanager;
+mod private_stream;
+mod public_stream;
+mod rest;
+mod trade_stream;
+
+#[derive(Error, Debug)]
+pub enum OkxError {
+    #[error("AssetNotFound")]
+    AssetNotFound,
+    #[error("AuthError: {code} - {msg}")]
+    AuthError { code: String, msg: String },
+    #[error("OrderError: {code} - {msg}")]
+    OrderError { code: String, msg: String },
+    #[error("OrderRejected: {0}")]
+    OrderRejected(String),
+    #[error("InvalidPxQty: {0}")]
+    InvalidPxQty(#[from] ParseFloatError),
+    #[error("PrefixUnmatched")]
+    PrefixUnmatched,
+    #[error("OrderNotFound")]
+    OrderNotFound,
+    #[error("InvalidArg: {0}")]
+    InvalidArg(&'static str),
+    #[error("OrderAlreadyExist")]
+    OrderAlreadyExist,
+    #[error("Serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Reqwest: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Tungstenite: {0}")]
+    Tungstenite(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error("ConnectionAbort: {0}")]
+    ConnectionAbort(String),
+    #[error("ConnectionInterrupted")]
+    ConnectionInterrupted,
+    #[error("OpError: {0}")]
+    OpError(String),
+    #[error("Config: {0:?}")]
+    Config(#[from] toml::de::Error),
+}
+
+impl OkxError {
+    pub fn to_value(&self) -> Value {
+        match self {
+            OkxError::AssetNotFound => Value::Empty,
+            OkxError::AuthError { code, msg } | OkxError::OrderError { code, msg } => Value::Map({
+                let mut map = HashMap::new();
+                map.insert("code".to_string(), Value::String(code.clone()));
+                map.insert("msg".to_string(), Value::String(msg.clone()));
+                map
+            }),
+            OkxError::OrderRejected(_) => Value::String(self.to_string()),
+            OkxError::InvalidPxQty(_) => Value::String(self.to_string()),
+            OkxError::PrefixUnmatched => Value::String(self.to_string()),
+            OkxError::OrderNotFound => Value::String(self.to_string()),
+            OkxError::InvalidArg(_) => Value::String(self.to_string()),
+            OkxError::OrderAlreadyExist => Value::String(self.to_string()),
+            OkxError::Serde(_) => Value::String(self.to_string()),
+            OkxError::Reqwest(_) => Value::String(self.to_string()),
+            OkxError::Tungstenite(_) => Value::String(self.to_string()),
+            OkxError::ConnectionAbort(_) => Value::String(self.to_string()),
+            OkxError::ConnectionInterrupted => Value::String(self.to_string()),
+            OkxError::OpError(_) => Value::String(self.to_string()),
+            OkxError::Config(_) => Value::String(self.to_string()),
+        }
+    }
+
+    /// Returns the normalized reason for the error.
+    pub fn reason(&self) -> ErrorReason {
+        match self {
+            OkxError::OrderError { code, .. } => match code.as_str() {
+                // Too many requests and the order rate limit.
+                "50011" | "50061" => ErrorReason::RateLimited,
+                // Insufficient balance or margin.
+                "51008" => ErrorReason::InsufficientMargin,
+                // Order price is not within the price limit.
+                "51006" => ErrorReason::PriceOutOfBand,
+                // Order does not exist, and cancellation failed as the order has been filled or
+                // canceled.
+                "51603" | "51400" => ErrorReason::UnknownOrder,
+                // Duplicated clOrdId.
+                "51016" => ErrorReason::DuplicateOrderId,
+                // Invalid API key, passphrase, signature, and IP.
+                "50110" | "50111" | "50113" | "50114" => ErrorReason::AuthFailure,
+                _ => ErrorReason::Other,
+            },
+            // Post-only orders are accepted and then canceled with this cancel source.
+            OkxError::OrderRejected(cancel_source) if cancel_source == "31" => {
+                ErrorReason::PostOnlyWouldTake
+            }
+            OkxError::AuthError { .. } => ErrorReason::AuthFailure,
+            OkxError::OrderNotFound => ErrorReason::UnknownOrder,
+            OkxError::OrderAlreadyExist => ErrorReason::DuplicateOrderId,
+            _ => ErrorReason::Other,
+        }
+    }
+
+    /// Returns the normalized error with the exchange's error code and message.
+    pub fn to_venue_error(&self) -> VenueError {
+        match self {
+            OkxError::AuthError { code, msg } | OkxError::OrderError { code, msg } => {
+                let venue = VenueError::new(self.reason(), msg.clone());
+                match code.parse() {
+                    Ok(code) => venue.code(code),
+                    Err(_) => venue,
+                }
+            }
+            _ => VenueError::new(self.reason(), self.to_string()),
+        }
+    }
+}
+
+/// OKX signs requests with the Base64-encoded HMAC SHA256, unlike the hex encoding used by the
+/// other exchanges.
+pub fn sign_hmac_sha256_base64(secret: &str, s: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(s.as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// Parses OKX depth levels, which are `[price, quantity, deprecated, number of orders]`.
+pub fn parse_depth(
+    bids: Vec<(String, String, String, String)>,
+    asks: Vec<(String, String, String, String)>,
+) -> Result<(Vec<PxQty>, Vec<PxQty>), OkxError> {
+    let mut bids_ = Vec::with_capacity(bids.len());
+    for (px, qty, _, _) in bids {
+        bids_.push((px.parse()?, qty.parse()?));
+    }
+    let mut asks_ = Vec::with_capacity(asks.len());
+    for (px, qty, _, _) in asks {
+        asks_.push((px.parse()?, qty.parse()?));
+    }
+    Ok((bids_, asks_))
+}
+
+#[derive(Deserialize)]
+pub struct Config {
+    public_url: String,
+    private_url: String,
+    rest_url: String,
+    api_key: String,
+    secret: String,
+    passphrase: String,
+    inst_type: String,
+    td_mode: String,
+    order_prefix: String,
+    #[serde(default)]
+    demo: bool,
+}
+
+type SharedSymbolSet = Arc<Mutex<HashSet<String>>>;
+
+pub struct Okx {
+    config: Config,
+    order_tx: Sender<OrderOp>,
+    order_manager: SharedOrderManager,
+    symbols: SharedSymbolSet,
+    client: OkxClient,
+    symbol_tx: Sender<String>,
+}
+
+impl Okx {
+    fn connect_public_stream(&self, ev_tx: UnboundedSender<PublishEvent>) {
+        // Connects to the public stream for the market data.
+        let public_url = self.config.public_url.clone();
+        let symbol_tx = self.symbol_tx.clone();
+        let symbols = self.symbols.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: OkxError| {
+                    error!(?error, "An error occurred in the public stream connection.");
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.to_value(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream =
+                        PublicStream::new(ev_tx.clone(), symbols.clone(), symbol_tx.subscribe());
+                    if let Err(error) = stream.connect(&public_url).await {
+                        error!(?error, "A connection error occurred.");
+                        ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                                ErrorKind::ConnectionInterrupted,
+                                error.to_value(),
+                            ))))
+                            .unwrap();
+                    } else {
+                        ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::new(
+                                ErrorKind::ConnectionInterrupted,
+                            ))))
+                            .unwrap();
+                    }
+                    Err::<(), OkxError>(OkxError::ConnectionInterrupted)
+                })
+                .await;
+        });
+    }
+
+    fn connect_private_stream(&self, ev_tx: UnboundedSender<PublishEvent>) {
+        // Connects to the private stream for the position and order data.
+        let private_url = self.config.private_url.clone();
+        let api_key = self.config.api_key.clone();
+        let secret = self.config.secret.clone();
+        let passphrase = self.config.passphrase.clone();
+        let inst_type = self.config.inst_type.clone();
+        let order_manager = self.order_manager.clone();
+        let symbols = self.symbols.clone();
+        let client = self.client.clone();
+        let symbol_tx = self.symbol_tx.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: OkxError| {
+                    error!(
+                        ?error,
+                        "An error occurred in the private stream connection."
+                    );
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.to_value(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream = private_stream::PrivateStream::new(
+                        api_key.clone(),
+                        secret.clone(),
+                        passphrase.clone(),
+                        ev_tx.clone(),
+                        order_manager.clone(),
+                        symbols.clone(),
+                        inst_type.clone(),
+                        client.clone(),
+                        symbol_tx.subscribe(),
+                    );
+                    stream.connect(&private_url).await?;
+                    Ok(())
+                })
+                .await;
+        });
+    }
+
+    fn connect_trade_stream(&self, ev_tx: UnboundedSender<PublishEvent>) {
+        // OKX places and cancels orders through the private WebSocket endpoint. A separate
+        // connection is used so that order operations are not delayed by the order and position
+        // pushes.
+        let private_url = self.config.private_url.clone();
+        let api_key = self.config.api_key.clone();
+        let secret = self.config.secret.clone();
+        let passphrase = self.config.passphrase.clone();
+        let order_manager = self.order_manager.clone();
+        let order_tx = self.order_tx.clone();
+
+        tokio::spawn(async move {
+            let _ = Retry::new(ExponentialBackoff::default())
+                .error_handler(|error: OkxError| {
+                    error!(?error, "An error occurred in the trade stream connection.");
+                    ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(LiveError::with(
+                            ErrorKind::ConnectionInterrupted,
+                            error.to_value(),
+                        ))))
+                        .unwrap();
+                    Ok(())
+                })
+                .retry(|| async {
+                    let mut stream = trade_stream::TradeStream::new(
+                        api_key.clone(),
+                        secret.clone(),
+                        passphrase.clone(),
+                        ev_tx.clone(),
+                        order_manager.clone(),
+                        order_tx.subscribe(),
+                    );
+                    stream.connect(&private_url).await?;
+                    Ok(())
+                })
+                .await;
+        });
+    }
+}
+
+impl ConnectorBuilder for Okx {
+    type Error = OkxError;
+
+    fn build_from(config: &str) -> Result<Self, Self::Error> {
+        let config: Config = toml::from_str(config)?;
+        // clOrdId allows only alphanumerics up to 32 characters.
+        if !config
+            .order_prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric())
+        {
+            panic!("order prefix should consist of alphanumeric characters.");
+        }
+        if config.order_prefix.len() > 8 {
+            panic!("order prefix length should be not greater than 8.");
+        }
+        let (order_tx, _) = broadcast::channel(500);
+        let (symbol_tx, _) = broadcast::channel(500);
+        let order_manager = Arc::new(Mutex::new(OrderManager::new(&config.order_prefix)));
+        let client = OkxClient::new(
+            &config.rest_url,
+            &config.api_key,
+            &config.secret,
+            &config.passphrase,
+            config.demo,
+        );
+        Ok(Okx {
+            config,
+            order_tx,
+            order_manager,
+            client,
+            symbols: Default::default(),
+            symbol_tx,
+        })
+    }
+}
+
+impl Connector for Okx {
+    fn register(&mut self, symbol: String) {
+        let mut symbols = self.symbols.lock().unwrap();
+        if !symbols.contains(&symbol) {
+            symbols.insert(symbol.clone());
+            self.symbol_tx.send(symbol).unwrap();
+        }
+    }
+
+    fn order_manager(&self) -> Arc<Mutex<dyn GetOrders + Send + 'static>> {
+        self.order_manager.clone()
+    }
+
+    fn run(&mut self, ev_tx: UnboundedSender<PublishEvent>) {
+        self.connect_public_stream(ev_tx.clone());
+        self.connect_private_stream(ev_tx.clone());
+        self.connect_trade_stream(ev_tx);
+    }
+
+    fn submit(&self, asset: String, order: Order, ev_tx: UnboundedSender<PublishEvent>) {
+        let order_id = order.order_id;
+        match self
+            .order_manager
+            .lock()
+            .unwrap()
+            .new_order(&asset, &self.config.td_mode, order)
+        {
+            Ok(okx_order) => {
+                self.order_tx
+                    .send(OrderOp {
+                        op: "order",
+                        okx_order,
+                    })
+                    .unwrap();
+            }
+            Err(error) => {
+                ev_tx
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::OrderError, error.to_value())
+                            .with_venue(error.to_venue_error().symbol(asset).order_id(order_id)),
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn cancel(&self, asset: String, order: Order, ev_tx: UnboundedSender<PublishEvent>) {
+        match self
+            .order_manager
+            .lock()
+            .unwrap()
+            .cancel_order(&asset, order.order_id)
+        {
+            Ok(okx_order) => {
+                self.order_tx
+                    .send(OrderOp {
+                        op: "cancel-order",
+                        okx_order,
+                    })
+                    .unwrap();
+            }
+            Err(error) => {
+                ev_tx
+                    .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                        LiveError::with(ErrorKind::OrderError, error.to_value()).with_venue(
+                            error
+                                .to_venue_error()
+                                .symbol(asset)
+                                .order_id(order.order_id),
+                        ),
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::types::ErrorReason;
+
+    use crate::okx::OkxError;
+
+    fn order_error(code: &str) -> OkxError {
+        OkxError::OrderError {
+            code: code.to_string(),
+            msg: String::new(),
+        }
+    }
+
+    #[test]
+    fn maps_error_reasons() {
+        let cases = [
+            (order_error("50011"), ErrorReason::RateLimited),
+            (order_error("50061"), ErrorReason::RateLimited),
+            (order_error("51008"), ErrorReason::InsufficientMargin),
+            (order_error("51006"), ErrorReason::PriceOutOfBand),
+            (order_error("51603"), ErrorReason::UnknownOrder),
+            (order_error("51400"), ErrorReason::UnknownOrder),
+            (order_error("51016"), ErrorReason::DuplicateOrderId),
+            (order_error("50113"), ErrorReason::AuthFailure),
+            (order_error("51000"), ErrorReason::Other),
+            (OkxError::OrderRejected("31".to_string()), ErrorReason::PostOnlyWouldTake),
+            (OkxError::OrderRejected(String::new()), ErrorReason::Other),
+            (
+                OkxError::AuthError {
+                    code: "60009".to_string(),
+                    msg: String::new(),
+                },
+                ErrorReason::AuthFailure,
+            ),
+            (OkxError::OrderNotFound, ErrorReason::UnknownOrder),
+            (OkxError::OrderAlreadyExist, ErrorReason::DuplicateOrderId),
+            (OkxError::ConnectionInterrupted, ErrorReason::Other),
+        ];
+        for (error, reason) in cases {
+            assert_eq!(error.reason(), reason, "{error}");
+        }
+    }
+}
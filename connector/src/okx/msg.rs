@@ -0,0 +1,321 @@
+use std::{fmt, fmt::Debug};
+
+use hftbacktest::types::{OrdType, Side, Status, TimeInForce};
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    de,
+    de::{Error, Unexpected, Visitor},
+};
+
+use crate::utils::{from_str_to_f64, from_str_to_i64};
+
+struct SideVisitor;
+
+impl Visitor<'_> for SideVisitor {
+    type Value = Side;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string containing \"buy\" or \"sell\"")
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match s {
+            "buy" => Ok(Side::Buy),
+            "sell" => Ok(Side::Sell),
+            s => Err(Error::invalid_value(Unexpected::Other(s), &"buy or sell")),
+        }
+    }
+}
+
+fn from_str_to_side<'de, D>(deserializer: D) -> Result<Side, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_str(SideVisitor)
+}
+
+struct StatusVisitor;
+
+impl Visitor<'_> for StatusVisitor {
+    type Value = Status;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string containing an order state")
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match s {
+            "live" => Ok(Status::New),
+            "partially_filled" => Ok(Status::PartiallyFilled),
+            "filled" => Ok(Status::Filled),
+            "canceled" => Ok(Status::Canceled),
+            "mmp_canceled" => Ok(Status::Canceled),
+            s => Err(Error::invalid_value(
+                Unexpected::Other(s),
+                &"live, partially_filled, filled, canceled or mmp_canceled",
+            )),
+        }
+    }
+}
+
+fn from_str_to_status<'de, D>(deserializer: D) -> Result<Status, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_str(StatusVisitor)
+}
+
+/// OKX expresses the time-in-force through the order type, such as `post_only` or `ioc`.
+pub fn to_ord_type(order_type: OrdType, time_in_force: TimeInForce) -> Option<&'static str> {
+    match (order_type, time_in_force) {
+        (OrdType::Market, _) => Some("market"),
+        (OrdType::Limit, TimeInForce::GTC) => Some("limit"),
+        (OrdType::Limit, TimeInForce::GTX) => Some("post_only"),
+        (OrdType::Limit, TimeInForce::FOK) => Some("fok"),
+        (OrdType::Limit, TimeInForce::IOC) => Some("ioc"),
+        (OrdType::Limit, TimeInForce::Unsupported) | (OrdType::Unsupported, _) => None,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Arg {
+    pub channel: String,
+    #[serde(rename = "instId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inst_id: Option<String>,
+    #[serde(rename = "instType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inst_type: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Op<T>
+where
+    T: Serialize + Debug,
+{
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub op: &'static str,
+    pub args: Vec<T>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LoginArg {
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    pub passphrase: String,
+    pub timestamp: String,
+    pub sign: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EventMsg {
+    pub event: String,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub msg: String,
+    pub arg: Option<Arg>,
+    #[serde(rename = "connId")]
+    pub conn_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PushMsg {
+    pub arg: Arg,
+    pub action: Option<String>,
+    pub data: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum StreamMsg {
+    Push(PushMsg),
+    Event(EventMsg),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderBook {
+    pub asks: Vec<(String, String, String, String)>,
+    pub bids: Vec<(String, String, String, String)>,
+    #[serde(deserialize_with = "from_str_to_i64")]
+    pub ts: i64,
+    #[serde(rename = "seqId")]
+    pub seq_id: i64,
+    #[serde(rename = "prevSeqId")]
+    #[serde(default)]
+    pub prev_seq_id: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Trade {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "tradeId")]
+    pub trade_id: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub px: f64,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub sz: f64,
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+    #[serde(deserialize_with = "from_str_to_i64")]
+    pub ts: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PrivateOrder {
+    #[serde(rename = "instType")]
+    pub inst_type: String,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "ordId")]
+    pub ord_id: String,
+    #[serde(rename = "clOrdId")]
+    pub cl_ord_id: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub px: f64,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub sz: f64,
+    #[serde(deserialize_with = "from_str_to_side")]
+    pub side: Side,
+    #[serde(rename = "ordType")]
+    pub ord_type: String,
+    #[serde(deserialize_with = "from_str_to_status")]
+    pub state: Status,
+    #[serde(rename = "fillPx")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub fill_px: f64,
+    #[serde(rename = "fillSz")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub fill_sz: f64,
+    #[serde(rename = "accFillSz")]
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub acc_fill_sz: f64,
+    #[serde(rename = "fillTime")]
+    #[serde(deserialize_with = "from_str_to_i64")]
+    pub fill_time: i64,
+    /// `T`: taker, `M`: maker, or empty if it is not a fill.
+    #[serde(rename = "execType")]
+    #[serde(default)]
+    pub exec_type: String,
+    /// The reason why the order is canceled by the exchange, or empty.
+    #[serde(rename = "cancelSource")]
+    #[serde(default)]
+    pub cancel_source: String,
+    #[serde(rename = "uTime")]
+    #[serde(deserialize_with = "from_str_to_i64")]
+    pub u_time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Position {
+    #[serde(rename = "instType")]
+    pub inst_type: String,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(deserialize_with = "from_str_to_f64")]
+    pub pos: f64,
+    /// `net` in one-way mode, `long` or `short` in long/short mode.
+    #[serde(rename = "posSide")]
+    pub pos_side: String,
+    #[serde(rename = "uTime")]
+    #[serde(deserialize_with = "from_str_to_i64")]
+    pub u_time: i64,
+}
+
+impl Position {
+    /// Returns the signed position quantity in contracts.
+    pub fn qty(&self) -> f64 {
+        if self.pos_side == "short" {
+            -self.pos.abs()
+        } else {
+            self.pos
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PendingOrder {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "ordId")]
+    pub ord_id: String,
+    #[serde(rename = "clOrdId")]
+    pub cl_ord_id: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Order {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "tdMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub td_mode: Option<String>,
+    #[serde(rename = "clOrdId")]
+    pub cl_ord_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    #[serde(rename = "ordType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ord_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sz: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub px: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CancelOrder {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "ordId")]
+    pub ord_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderOpResult {
+    #[serde(rename = "clOrdId")]
+    #[serde(default)]
+    pub cl_ord_id: String,
+    #[serde(rename = "ordId")]
+    #[serde(default)]
+    pub ord_id: String,
+    #[serde(rename = "sCode")]
+    pub s_code: String,
+    #[serde(rename = "sMsg")]
+    pub s_msg: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderOpResponse {
+    pub id: Option<String>,
+    pub op: String,
+    pub code: String,
+    pub msg: String,
+    #[serde(default)]
+    pub data: Vec<OrderOpResult>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum TradeStreamMsg {
+    OrderOp(OrderOpResponse),
+    Event(EventMsg),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RestResponse {
+    pub code: String,
+    pub msg: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
@@ -0,0 +1,527 @@
+use std::sync::{Arc, Mutex};
+
+use hashbrown::HashMap;
+use hftbacktest::{
+    prelude::get_precision,
+    types::{Order, OrderId, Side, Status},
+};
+
+use crate::{
+    connector::GetOrders,
+    okx::{
+        OkxError,
+        msg::{Order as OkxOrder, PrivateOrder, to_ord_type},
+    },
+    utils::{RefSymbolOrderId, SymbolOrderId, generate_rand_string},
+};
+
+pub type SharedOrderManager = Arc<Mutex<OrderManager>>;
+
+pub type ClOrdId = String;
+
+#[derive(Clone)]
+pub struct OrderExt {
+    pub symbol: String,
+    pub order: Order,
+}
+
+/// Unlike Binance, OKX delivers every order update, including fills, through the `orders`
+/// channel, and the trade operation responses only need to be handled when they fail. So, an order
+/// can be removed as soon as the `orders` channel reports that it is no longer active.
+pub struct OrderManager {
+    prefix: String,
+    orders: HashMap<ClOrdId, OrderExt>,
+    order_id_map: HashMap<SymbolOrderId, ClOrdId>,
+}
+
+impl OrderManager {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            orders: Default::default(),
+            order_id_map: Default::default(),
+        }
+    }
+
+    pub fn update_order(&mut self, data: &PrivateOrder) -> Result<OrderExt, OkxError> {
+        if !data.cl_ord_id.starts_with(&self.prefix) {
+            return Err(OkxError::PrefixUnmatched);
+        }
+        let order_ext = self
+            .orders
+            .get_mut(&data.cl_ord_id)
+            .ok_or(OkxError::OrderNotFound)?;
+        order_ext.order.req = Status::None;
+        order_ext.order.status = data.state;
+        order_ext.order.qty = data.sz;
+        order_ext.order.leaves_qty = data.sz - data.acc_fill_sz;
+        order_ext.order.exch_timestamp = data.u_time * 1_000_000;
+        if data.fill_sz > 0.0 {
+            order_ext.order.exec_price_tick =
+                (data.fill_px / order_ext.order.tick_size).round() as i64;
+            order_ext.order.exec_qty = data.fill_sz;
+            order_ext.order.maker = data.exec_type == "M";
+            order_ext.order.exch_timestamp = data.fill_time * 1_000_000;
+        } else {
+            order_ext.order.exec_qty = 0.0;
+        }
+
+        if !order_ext.order.active() {
+            self.order_id_map.remove(&RefSymbolOrderId::new(
+                &order_ext.symbol,
+                order_ext.order.order_id,
+            ));
+            Ok(self.orders.remove(&data.cl_ord_id).unwrap())
+        } else {
+            Ok(order_ext.clone())
+        }
+    }
+
+    pub fn new_order(
+        &mut self,
+        symbol: &str,
+        td_mode: &str,
+        order: Order,
+    ) -> Result<OkxOrder, OkxError> {
+        let price_prec = get_precision(order.tick_size);
+        // OKX allows only alphanumeric characters in `clOrdId`.
+        let cl_ord_id = format!("{}{}", self.prefix, generate_rand_string(16));
+        let okx_order = OkxOrder {
+            inst_id: symbol.to_string(),
+            td_mode: Some(td_mode.to_string()),
+            cl_ord_id: cl_ord_id.clone(),
+            side: Some({
+                match order.side {
+                    Side::Buy => "buy".to_string(),
+                    Side::Sell => "sell".to_string(),
+                    Side::None | Side::Unsupported => return Err(OkxError::InvalidArg("side")),
+                }
+            }),
+            ord_type: Some(
+                to_ord_type(order.order_type, order.time_in_force)
+                    .ok_or(OkxError::InvalidArg("order_type"))?
+                    .to_string(),
+            ),
+            sz: Some(format!("{:.5}", order.qty)),
+            px: Some(format!(
+                "{:.prec$}",
+                order.price_tick as f64 * order.tick_size,
+                prec = price_prec
+            )),
+        };
+
+        let symbol_order_id = SymbolOrderId::new(symbol.to_string(), order.order_id);
+        if self.order_id_map.contains_key(&symbol_order_id) {
+            return Err(OkxError::OrderAlreadyExist);
+        }
+
+        if self.orders.contains_key(&cl_ord_id) {
+            return Err(OkxError::OrderAlreadyExist);
+        }
+
+        self.order_id_map.insert(symbol_order_id, cl_ord_id.clone());
+        self.orders.insert(
+            cl_ord_id,
+            OrderExt {
+                symbol: symbol.to_string(),
+                order,
+            },
+        );
+        Ok(okx_order)
+    }
+
+    pub fn cancel_order(&mut self, symbol: &str, order_id: OrderId) -> Result<OkxOrder, OkxError> {
+        let cl_ord_id = self
+            .order_id_map
+            .get(&RefSymbolOrderId::new(symbol, order_id))
+            .ok_or(OkxError::OrderNotFound)?;
+        Ok(OkxOrder {
+            inst_id: symbol.to_string(),
+            td_mode: None,
+            cl_ord_id: cl_ord_id.clone(),
+            side: None,
+            ord_type: None,
+            sz: None,
+            px: None,
+        })
+    }
+
+    pub fn update_submit_fail(&mut self, cl_ord_id: &str) -> Result<OrderExt, OkxError> {
+        let mut order = self
+            .orders
+            .remove(cl_ord_id)
+            .ok_or(OkxError::OrderNotFound)?;
+        order.order.req = Status::None;
+        order.order.status = Status::Expired;
+        self.order_id_map
+            .remove(&RefSymbolOrderId::new(&order.symbol, order.order.order_id));
+        Ok(order)
+    }
+
+    pub fn update_cancel_fail(&mut self, cl_ord_id: &str) -> Result<OrderExt, OkxError> {
+        let order_ext = self
+            .orders
+            .get_mut(cl_ord_id)
+            .ok_or(OkxError::OrderNotFound)?;
+        order_ext.order.req = Status::None;
+        Ok(order_ext.clone())
+    }
+
+    pub fn cancel_all(&mut self, symbol: &str) -> Vec<Order> {
+        let mut removed_order_ids = Vec::new();
+        for (cl_ord_id, order_ext) in &mut self.orders {
+            if order_ext.symbol != symbol {
+                continue;
+            }
+
+            order_ext.order.req = Status::None;
+            order_ext.order.status = Status::Canceled;
+
+            self.order_id_map
+                .remove(&RefSymbolOrderId::new(symbol, order_ext.order.order_id));
+            removed_order_ids.push(cl_ord_id.clone());
+        }
+
+        removed_order_ids
+            .iter()
+            .map(|id| self.orders.remove(id).unwrap().order)
+            .collect()
+    }
+}
+
+impl GetOrders for OrderManager {
+    fn orders(&self, symbol: Option<String>) -> Vec<Order> {
+        self.orders
+            .iter()
+            .filter(|(_, order)| {
+                symbol.as_ref().map(|s| order.symbol == *s).unwrap_or(true) && order.order.active()
+            })
+            .map(|(_, order)| &order.order)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::types::{OrdType, Order, Side, Status, TimeInForce};
+
+    use crate::{
+        connector::GetOrders,
+        okx::{
+            OkxError,
+            msg::{PrivateOrder, StreamMsg, TradeStreamMsg},
+            ordermanager::OrderManager,
+        },
+    };
+
+    // Recorded from the `orders` channel, with `clOrdId` replaced by a placeholder since the client
+    // order id is generated randomly.
+    const ORDER_LIVE: &str = r#"
+    {
+      "arg": {
+        "channel": "orders",
+        "instType": "SWAP",
+        "uid": "77982378738415879"
+      },
+      "data": [
+        {
+          "accFillSz": "0",
+          "avgPx": "0",
+          "cTime": "1705384184502",
+          "cancelSource": "",
+          "category": "normal",
+          "clOrdId": "CL_ORD_ID",
+          "execType": "",
+          "fee": "0",
+          "feeCcy": "USDT",
+          "fillPx": "",
+          "fillSz": "0",
+          "fillTime": "",
+          "instId": "BTC-USDT-SWAP",
+          "instType": "SWAP",
+          "ordId": "665951654130348032",
+          "ordType": "limit",
+          "posSide": "net",
+          "px": "100.0",
+          "side": "buy",
+          "state": "live",
+          "sz": "2",
+          "tdMode": "cross",
+          "tradeId": "",
+          "uTime": "1705384184502"
+        }
+      ]
+    }
+    "#;
+    const ORDER_PARTIALLY_FILLED: &str = r#"
+    {
+      "arg": {
+        "channel": "orders",
+        "instType": "SWAP",
+        "uid": "77982378738415879"
+      },
+      "data": [
+        {
+          "accFillSz": "0.5",
+          "avgPx": "100.0",
+          "cTime": "1705384184502",
+          "cancelSource": "",
+          "category": "normal",
+          "clOrdId": "CL_ORD_ID",
+          "execType": "M",
+          "fee": "-0.01",
+          "feeCcy": "USDT",
+          "fillPx": "100.0",
+          "fillSz": "0.5",
+          "fillTime": "1705384185000",
+          "instId": "BTC-USDT-SWAP",
+          "instType": "SWAP",
+          "ordId": "665951654130348032",
+          "ordType": "limit",
+          "posSide": "net",
+          "px": "100.0",
+          "side": "buy",
+          "state": "partially_filled",
+          "sz": "2",
+          "tdMode": "cross",
+          "tradeId": "1",
+          "uTime": "1705384185000"
+        }
+      ]
+    }
+    "#;
+    const ORDER_FILLED: &str = r#"
+    {
+      "arg": {
+        "channel": "orders",
+        "instType": "SWAP",
+        "uid": "77982378738415879"
+      },
+      "data": [
+        {
+          "accFillSz": "2",
+          "avgPx": "100.0",
+          "cTime": "1705384184502",
+          "cancelSource": "",
+          "category": "normal",
+          "clOrdId": "CL_ORD_ID",
+          "execType": "T",
+          "fee": "-0.06",
+          "feeCcy": "USDT",
+          "fillPx": "100.0",
+          "fillSz": "1.5",
+          "fillTime": "1705384186000",
+          "instId": "BTC-USDT-SWAP",
+          "instType": "SWAP",
+          "ordId": "665951654130348032",
+          "ordType": "limit",
+          "posSide": "net",
+          "px": "100.0",
+          "side": "buy",
+          "state": "filled",
+          "sz": "2",
+          "tdMode": "cross",
+          "tradeId": "2",
+          "uTime": "1705384186000"
+        }
+      ]
+    }
+    "#;
+    const ORDER_CANCELED: &str = r#"
+    {
+      "arg": {
+        "channel": "orders",
+        "instType": "SWAP",
+        "uid": "77982378738415879"
+      },
+      "data": [
+        {
+          "accFillSz": "0",
+          "avgPx": "0",
+          "cTime": "1705384184502",
+          "cancelSource": "31",
+          "category": "normal",
+          "clOrdId": "CL_ORD_ID",
+          "execType": "",
+          "fee": "0",
+          "feeCcy": "USDT",
+          "fillPx": "",
+          "fillSz": "0",
+          "fillTime": "",
+          "instId": "BTC-USDT-SWAP",
+          "instType": "SWAP",
+          "ordId": "665951654130348032",
+          "ordType": "post_only",
+          "posSide": "net",
+          "px": "100.0",
+          "side": "buy",
+          "state": "canceled",
+          "sz": "2",
+          "tdMode": "cross",
+          "tradeId": "",
+          "uTime": "1705384184510"
+        }
+      ]
+    }
+    "#;
+    // Recorded from the trade operation responses.
+    const ORDER_OP_FAILED: &str = r#"
+    {
+      "id": "CL_ORD_ID",
+      "op": "order",
+      "code": "1",
+      "msg": "",
+      "data": [
+        {
+          "clOrdId": "CL_ORD_ID",
+          "ordId": "",
+          "sCode": "51008",
+          "sMsg": "Order failed. Insufficient USDT balance in account.",
+          "tag": "",
+          "ts": "1695190491421"
+        }
+      ],
+      "inTime": "1695190491421339",
+      "outTime": "1695190491423240"
+    }
+    "#;
+    const CANCEL_OP_FAILED: &str = r#"
+    {
+      "id": "CL_ORD_ID",
+      "op": "cancel-order",
+      "code": "1",
+      "msg": "",
+      "data": [
+        {
+          "clOrdId": "CL_ORD_ID",
+          "ordId": "665951654130348032",
+          "sCode": "51400",
+          "sMsg": "Cancellation failed as the order has been filled, canceled or does not exist.",
+          "ts": "1695190491421"
+        }
+      ],
+      "inTime": "1695190491421339",
+      "outTime": "1695190491423240"
+    }
+    "#;
+
+    fn new_order(order_manager: &mut OrderManager, order_id: u64) -> String {
+        let mut order = Order::new(
+            order_id,
+            1000,
+            0.1,
+            2.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        order.req = Status::New;
+        order_manager
+            .new_order("BTC-USDT-SWAP", "cross", order)
+            .unwrap()
+            .cl_ord_id
+    }
+
+    fn parse_orders(payload: &str, cl_ord_id: &str) -> Vec<PrivateOrder> {
+        match serde_json::from_str::<StreamMsg>(&payload.replace("CL_ORD_ID", cl_ord_id)).unwrap() {
+            StreamMsg::Push(push) => {
+                assert_eq!(push.arg.channel, "orders");
+                serde_json::from_value(push.data).unwrap()
+            }
+            StreamMsg::Event(event) => panic!("unexpected event: {event:?}"),
+        }
+    }
+
+    fn parse_failed_op(payload: &str, cl_ord_id: &str) -> (String, String) {
+        match serde_json::from_str::<TradeStreamMsg>(&payload.replace("CL_ORD_ID", cl_ord_id))
+            .unwrap()
+        {
+            TradeStreamMsg::OrderOp(resp) => {
+                assert_eq!(resp.data.len(), 1);
+                (resp.op, resp.data[0].s_code.clone())
+            }
+            TradeStreamMsg::Event(event) => panic!("unexpected event: {event:?}"),
+        }
+    }
+
+    #[test]
+    fn update_order_through_fills() {
+        let mut order_manager = OrderManager::new("prefix");
+        let cl_ord_id = new_order(&mut order_manager, 1);
+        assert!(cl_ord_id.starts_with("prefix"));
+
+        let data = parse_orders(ORDER_LIVE, &cl_ord_id);
+        let order = order_manager.update_order(&data[0]).unwrap().order;
+        assert_eq!(order.req, Status::None);
+        assert_eq!(order.status, Status::New);
+        assert_eq!(order.leaves_qty, 2.0);
+        assert_eq!(order.exec_qty, 0.0);
+        assert_eq!(order.exch_timestamp, 1_705_384_184_502_000_000);
+
+        let data = parse_orders(ORDER_PARTIALLY_FILLED, &cl_ord_id);
+        let order = order_manager.update_order(&data[0]).unwrap().order;
+        assert_eq!(order.status, Status::PartiallyFilled);
+        assert_eq!(order.exec_price_tick, 1000);
+        assert_eq!(order.exec_qty, 0.5);
+        assert_eq!(order.leaves_qty, 1.5);
+        assert!(order.maker);
+        assert_eq!(order.exch_timestamp, 1_705_384_185_000_000_000);
+        assert_eq!(order_manager.orders(None).len(), 1);
+
+        // The order is removed once it is no longer active.
+        let data = parse_orders(ORDER_FILLED, &cl_ord_id);
+        let order = order_manager.update_order(&data[0]).unwrap().order;
+        assert_eq!(order.status, Status::Filled);
+        assert_eq!(order.exec_qty, 1.5);
+        assert_eq!(order.leaves_qty, 0.0);
+        assert!(!order.maker);
+        assert!(order_manager.orders(None).is_empty());
+        assert!(matches!(
+            order_manager.update_order(&data[0]),
+            Err(OkxError::OrderNotFound)
+        ));
+
+        let data = parse_orders(ORDER_LIVE, "unknown");
+        assert!(matches!(
+            order_manager.update_order(&data[0]),
+            Err(OkxError::PrefixUnmatched)
+        ));
+    }
+
+    #[test]
+    fn update_order_on_cancel_and_failures() {
+        let mut order_manager = OrderManager::new("prefix");
+        let cl_ord_id = new_order(&mut order_manager, 1);
+
+        let data = parse_orders(ORDER_CANCELED, &cl_ord_id);
+        assert_eq!(data[0].cancel_source, "31");
+        let order = order_manager.update_order(&data[0]).unwrap().order;
+        assert_eq!(order.status, Status::Canceled);
+        assert_eq!(order.leaves_qty, 2.0);
+        assert!(order_manager.orders(None).is_empty());
+
+        // The failed submission removes the order.
+        let cl_ord_id = new_order(&mut order_manager, 2);
+        let (op, code) = parse_failed_op(ORDER_OP_FAILED, &cl_ord_id);
+        assert_eq!((op.as_str(), code.as_str()), ("order", "51008"));
+        let order = order_manager.update_submit_fail(&cl_ord_id).unwrap().order;
+        assert_eq!(order.req, Status::None);
+        assert_eq!(order.status, Status::Expired);
+        assert!(order_manager.orders(None).is_empty());
+        // The order id can be reused once the order is removed.
+        let cl_ord_id = new_order(&mut order_manager, 2);
+        let data = parse_orders(ORDER_LIVE, &cl_ord_id);
+        order_manager.update_order(&data[0]).unwrap();
+
+        // The failed cancellation only clears the request.
+        let cancel = order_manager.cancel_order("BTC-USDT-SWAP", 2).unwrap();
+        assert_eq!(cancel.cl_ord_id, cl_ord_id);
+        let (op, code) = parse_failed_op(CANCEL_OP_FAILED, &cl_ord_id);
+        assert_eq!((op.as_str(), code.as_str()), ("cancel-order", "51400"));
+        let order = order_manager.update_cancel_fail(&cl_ord_id).unwrap().order;
+        assert_eq!(order.req, Status::None);
+        assert_eq!(order.status, Status::New);
+        assert_eq!(order_manager.orders(None).len(), 1);
+    }
+}
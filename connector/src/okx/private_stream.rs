@@ -0,0 +1,363 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt, stream::SplitSink};
+use hftbacktest::prelude::{ErrorKind, ErrorReason, LiveError, LiveEvent};
+use tokio::{
+    net::TcpStream,
+    select,
+    sync::{
+        broadcast::{Receiver, error::RecvError},
+        mpsc::UnboundedSender,
+    },
+    time,
+};
+use tokio_tungstenite::{
+    MaybeTlsStream,
+    WebSocketStream,
+    connect_async,
+    tungstenite::{Bytes, Message, client::IntoClientRequest},
+};
+use tracing::{debug, error};
+
+use crate::{
+    connector::PublishEvent,
+    okx::{
+        OkxError,
+        SharedSymbolSet,
+        msg::{Arg, LoginArg, Op, Position, PrivateOrder, StreamMsg},
+        ordermanager::{OrderExt, SharedOrderManager},
+        rest::OkxClient,
+        sign_hmac_sha256_base64,
+    },
+};
+
+pub struct PrivateStream {
+    api_key: String,
+    secret: String,
+    passphrase: String,
+    ev_tx: UnboundedSender<PublishEvent>,
+    order_manager: SharedOrderManager,
+    symbols: SharedSymbolSet,
+    inst_type: String,
+    client: OkxClient,
+    symbol_rx: Receiver<String>,
+}
+
+impl PrivateStream {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        api_key: String,
+        secret: String,
+        passphrase: String,
+        ev_tx: UnboundedSender<PublishEvent>,
+        order_manager: SharedOrderManager,
+        symbols: SharedSymbolSet,
+        inst_type: String,
+        client: OkxClient,
+        symbol_rx: Receiver<String>,
+    ) -> Self {
+        Self {
+            api_key,
+            secret,
+            passphrase,
+            ev_tx,
+            order_manager,
+            symbols,
+            inst_type,
+            client,
+            symbol_rx,
+        }
+    }
+
+    async fn handle_private_stream(
+        &self,
+        text: &str,
+        write: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
+    ) -> Result<(), OkxError> {
+        let stream = serde_json::from_str::<StreamMsg>(text)?;
+        match stream {
+            StreamMsg::Event(resp) => {
+                debug!(?resp, "Event");
+                if resp.event == "login" {
+                    let op = Op {
+                        id: None,
+                        op: "subscribe",
+                        args: ["orders", "positions"]
+                            .iter()
+                            .map(|channel| Arg {
+                                channel: channel.to_string(),
+                                inst_id: None,
+                                inst_type: Some(self.inst_type.clone()),
+                            })
+                            .collect(),
+                    };
+                    let s = serde_json::to_string(&op).unwrap();
+                    write.send(Message::Text(s.into())).await?;
+                } else if resp.event == "subscribe" {
+                    if resp.arg.map(|arg| arg.channel == "orders").unwrap_or(false) {
+                        // Since the order updates are received from now on, cancels all orders in
+                        // order to start with the clean state.
+                        let symbols = self
+                            .symbols
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        for symbol in symbols {
+                            self.spawn_init(symbol);
+                        }
+                    }
+                } else if resp.event == "error" {
+                    if resp.code.starts_with("600") {
+                        // 60009: Login failed, 60024: Wrong passphrase, etc.
+                        let error = OkxError::AuthError {
+                            code: resp.code,
+                            msg: resp.msg,
+                        };
+                        self.ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                                LiveError::with(
+                                    ErrorKind::CriticalConnectionError,
+                                    error.to_value(),
+                                )
+                                .with_venue(error.to_venue_error()),
+                            )))
+                            .unwrap();
+                        return Err(error);
+                    }
+                    return Err(OkxError::OpError(format!("{} - {}", resp.code, resp.msg)));
+                }
+            }
+            StreamMsg::Push(stream) => {
+                if stream.arg.channel == "orders" {
+                    let data: Vec<PrivateOrder> = serde_json::from_value(stream.data)?;
+                    debug!(?data, "Order");
+                    let mut order_manager = self.order_manager.lock().unwrap();
+                    for private_order in &data {
+                        match order_manager.update_order(private_order) {
+                            Ok(OrderExt { symbol, order }) => {
+                                let rejected =
+                                    OkxError::OrderRejected(private_order.cancel_source.clone());
+                                if rejected.reason() != ErrorReason::Other {
+                                    self.ev_tx
+                                        .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                                            LiveError::with(
+                                                ErrorKind::OrderError,
+                                                rejected.to_value(),
+                                            )
+                                            .with_venue(
+                                                rejected
+                                                    .to_venue_error()
+                                                    .symbol(symbol.clone())
+                                                    .order_id(order.order_id),
+                                            ),
+                                        )))
+                                        .unwrap();
+                                }
+                                self.ev_tx
+                                    .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                                        symbol,
+                                        order,
+                                    }))
+                                    .unwrap();
+                            }
+                            Err(OkxError::PrefixUnmatched) => {
+                                // The order is not created by this connector.
+                            }
+                            Err(error) => {
+                                error!(?error, ?private_order, "Couldn't update the order data");
+                            }
+                        }
+                    }
+                } else if stream.arg.channel == "positions" {
+                    let data: Vec<Position> = serde_json::from_value(stream.data)?;
+                    debug!(?data, "Position");
+                    for position in data {
+                        self.ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Position {
+                                qty: position.qty(),
+                                exch_ts: position.u_time * 1_000_000,
+                                symbol: position.inst_id,
+                            }))
+                            .unwrap();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn spawn_init(&self, symbol: String) {
+        let client = self.client.clone();
+        let inst_type = self.inst_type.clone();
+        let order_manager = self.order_manager.clone();
+        let ev_tx = self.ev_tx.clone();
+
+        tokio::spawn(async move {
+            // Cancel all orders in order to start with the clean state.
+            if let Err(error) = cancel_all(
+                client.clone(),
+                inst_type.clone(),
+                symbol.clone(),
+                order_manager.clone(),
+                ev_tx.clone(),
+            )
+            .await
+            {
+                error!(?error, %inst_type, %symbol, "Couldn't cancel all orders.");
+            }
+
+            // Fetches the initial states such as positions.
+            if let Err(error) = get_position(
+                client.clone(),
+                inst_type.clone(),
+                symbol.clone(),
+                ev_tx.clone(),
+            )
+            .await
+            {
+                error!(?error, %inst_type, %symbol, "Couldn't get the position.");
+            }
+        });
+    }
+
+    pub async fn connect(&mut self, url: &str) -> Result<(), OkxError> {
+        let request = url.into_client_request()?;
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+        // OKX closes the connection if there is no message for 30 seconds.
+        let mut interval = time::interval(Duration::from_secs(15));
+
+        write
+            .send(login_msg(&self.api_key, &self.secret, &self.passphrase))
+            .await?;
+
+        loop {
+            select! {
+                _ = interval.tick() => {
+                    write.send(Message::Text("ping".into())).await?;
+                }
+                msg = self.symbol_rx.recv() => {
+                    match msg {
+                        Ok(symbol) => {
+                            self.spawn_init(symbol);
+                        }
+                        Err(RecvError::Closed) => {
+                            return Ok(());
+                        }
+                        Err(RecvError::Lagged(num)) => {
+                            error!("{num} subscription requests were missed.");
+                        }
+                    }
+                }
+                message = read.next() => {
+                    match message {
+                        Some(Ok(Message::Text(text))) => {
+                            if text.as_str() == "pong" {
+                                continue;
+                            }
+                            match self.handle_private_stream(&text, &mut write).await {
+                                Ok(_) => {}
+                                Err(error @ OkxError::AuthError { .. }) => {
+                                    return Err(error);
+                                }
+                                Err(error) => {
+                                    error!(%text, ?error, "Couldn't properly handle StreamMsg");
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Ping(_))) => {
+                            write.send(Message::Pong(Bytes::default())).await?;
+                        }
+                        Some(Ok(Message::Close(close_frame))) => {
+                            return Err(OkxError::ConnectionAbort(
+                                close_frame.map(|f| f.to_string()).unwrap_or(String::new())
+                            ));
+                        }
+                        Some(Ok(Message::Binary(_)))
+                        | Some(Ok(Message::Frame(_)))
+                        | Some(Ok(Message::Pong(_))) => {}
+                        Some(Err(error)) => {
+                            return Err(OkxError::from(error));
+                        }
+                        None => {
+                            return Err(OkxError::ConnectionInterrupted);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Builds the WebSocket login request, which signs `timestamp + "GET" + "/users/self/verify"`
+/// with the timestamp in seconds.
+pub fn login_msg(api_key: &str, secret: &str, passphrase: &str) -> Message {
+    let timestamp = Utc::now().timestamp().to_string();
+    let sign = sign_hmac_sha256_base64(secret, &format!("{timestamp}GET/users/self/verify"));
+    let op = Op {
+        id: None,
+        op: "login",
+        args: vec![LoginArg {
+            api_key: api_key.to_string(),
+            passphrase: passphrase.to_string(),
+            timestamp,
+            sign,
+        }],
+    };
+    Message::Text(serde_json::to_string(&op).unwrap().into())
+}
+
+pub async fn get_position(
+    client: OkxClient,
+    inst_type: String,
+    symbol: String,
+    ev_tx: UnboundedSender<PublishEvent>,
+) -> Result<(), OkxError> {
+    // todo: rate-limit throttling.
+    let positions = client.get_positions(&inst_type, &symbol).await?;
+    if positions.is_empty() {
+        // OKX doesn't return a closed position.
+        ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Position {
+                symbol,
+                qty: 0.0,
+                exch_ts: 0,
+            }))
+            .unwrap();
+        return Ok(());
+    }
+    positions.into_iter().for_each(|position| {
+        ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Position {
+                symbol: symbol.clone(),
+                qty: position.qty(),
+                exch_ts: position.u_time * 1_000_000,
+            }))
+            .unwrap();
+    });
+    Ok(())
+}
+
+pub async fn cancel_all(
+    client: OkxClient,
+    inst_type: String,
+    symbol: String,
+    order_manager: SharedOrderManager,
+    ev_tx: UnboundedSender<PublishEvent>,
+) -> Result<(), OkxError> {
+    // todo: rate-limit throttling.
+    client.cancel_all_orders(&inst_type, &symbol).await?;
+    let orders = order_manager.lock().unwrap().cancel_all(&symbol);
+    for order in orders {
+        ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                symbol: symbol.clone(),
+                order,
+            }))
+            .unwrap();
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use hftbacktest::prelude::{
+    Event,
+    LOCAL_ASK_DEPTH_BBO_EVENT,
+    LOCAL_ASK_DEPTH_EVENT,
+    LOCAL_BID_DEPTH_BBO_EVENT,
+    LOCAL_BID_DEPTH_EVENT,
+    LOCAL_BUY_TRADE_EVENT,
+    LOCAL_DEPTH_CLEAR_EVENT,
+    LOCAL_SELL_TRADE_EVENT,
+    LiveEvent,
+    Side,
+};
+use tokio::{
+    select,
+    sync::{
+        broadcast::{Receiver, error::RecvError},
+        mpsc::UnboundedSender,
+    },
+    time,
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Bytes, Message, client::IntoClientRequest},
+};
+use tracing::{debug, error};
+
+use crate::{
+    connector::PublishEvent,
+    okx::{
+        OkxError,
+        SharedSymbolSet,
+        msg,
+        msg::{Arg, Op, OrderBook, StreamMsg},
+        parse_depth,
+    },
+};
+
+pub struct PublicStream {
+    ev_tx: UnboundedSender<PublishEvent>,
+    symbols: SharedSymbolSet,
+    symbol_rx: Receiver<String>,
+}
+
+impl PublicStream {
+    pub fn new(
+        ev_tx: UnboundedSender<PublishEvent>,
+        symbols: SharedSymbolSet,
+        symbol_rx: Receiver<String>,
+    ) -> Self {
+        Self {
+            ev_tx,
+            symbols,
+            symbol_rx,
+        }
+    }
+
+    fn subscribe_msg(symbol: &str) -> Message {
+        // Subscribes to the books and bbo-tbt channels to obtain a wider range of depth and the
+        // most frequent best bid and offer updates. The different updates are handled by data
+        // fusion.
+        // Please see: `<https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-order-book-channel>`
+        let args = ["books", "bbo-tbt", "trades"]
+            .iter()
+            .map(|channel| Arg {
+                channel: channel.to_string(),
+                inst_id: Some(symbol.to_string()),
+                inst_type: None,
+            })
+            .collect();
+        let op = Op {
+            id: None,
+            op: "subscribe",
+            args,
+        };
+        Message::Text(serde_json::to_string(&op).unwrap().into())
+    }
+
+    fn send_depth(&self, symbol: &str, ev: u64, exch_ts: i64, levels: Vec<(f64, f64)>) {
+        for (px, qty) in levels {
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                    symbol: symbol.to_string(),
+                    event: Event {
+                        ev,
+                        exch_ts,
+                        local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                        order_id: 0,
+                        px,
+                        qty,
+                        ival: 0,
+                        fval: 0.0,
+                    },
+                }))
+                .unwrap();
+        }
+    }
+
+    async fn handle_public_stream(&self, text: &str) -> Result<(), OkxError> {
+        let stream = serde_json::from_str::<StreamMsg>(text)?;
+        match stream {
+            StreamMsg::Event(resp) => {
+                debug!(?resp, "Event");
+                if resp.event == "error" {
+                    return Err(OkxError::OpError(format!("{} - {}", resp.code, resp.msg)));
+                }
+            }
+            StreamMsg::Push(stream) => {
+                let symbol = stream.arg.inst_id.ok_or(OkxError::InvalidArg("instId"))?;
+                if stream.arg.channel == "bbo-tbt" {
+                    let data: Vec<OrderBook> = serde_json::from_value(stream.data)?;
+                    for book in data {
+                        let exch_ts = book.ts * 1_000_000;
+                        let (bids, asks) = parse_depth(book.bids, book.asks)?;
+                        self.send_depth(&symbol, LOCAL_BID_DEPTH_BBO_EVENT, exch_ts, bids);
+                        self.send_depth(&symbol, LOCAL_ASK_DEPTH_BBO_EVENT, exch_ts, asks);
+                    }
+                } else if stream.arg.channel.starts_with("books") {
+                    let data: Vec<OrderBook> = serde_json::from_value(stream.data)?;
+                    let is_snapshot = stream.action.as_deref() == Some("snapshot");
+                    for book in data {
+                        let exch_ts = book.ts * 1_000_000;
+                        if is_snapshot {
+                            // A snapshot is sent right after the subscription, including on
+                            // reconnection, so the existing depth must be cleared.
+                            self.ev_tx
+                                .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                                    symbol: symbol.clone(),
+                                    event: Event {
+                                        ev: LOCAL_DEPTH_CLEAR_EVENT,
+                                        exch_ts,
+                                        local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                                        order_id: 0,
+                                        px: 0.0,
+                                        qty: 0.0,
+                                        ival: 0,
+                                        fval: 0.0,
+                                    },
+                                }))
+                                .unwrap();
+                        }
+                        let (bids, asks) = parse_depth(book.bids, book.asks)?;
+                        self.send_depth(&symbol, LOCAL_BID_DEPTH_EVENT, exch_ts, bids);
+                        self.send_depth(&symbol, LOCAL_ASK_DEPTH_EVENT, exch_ts, asks);
+                    }
+                } else if stream.arg.channel == "trades" {
+                    let data: Vec<msg::Trade> = serde_json::from_value(stream.data)?;
+                    for item in data {
+                        self.ev_tx
+                            .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                                symbol: item.inst_id.clone(),
+                                event: Event {
+                                    ev: {
+                                        if item.side == Side::Sell {
+                                            LOCAL_SELL_TRADE_EVENT
+                                        } else {
+                                            LOCAL_BUY_TRADE_EVENT
+                                        }
+                                    },
+                                    exch_ts: item.ts * 1_000_000,
+                                    local_ts: Utc::now().timestamp_nanos_opt().unwrap(),
+                                    order_id: 0,
+                                    px: item.px,
+                                    qty: item.sz,
+                                    ival: 0,
+                                    fval: 0.0,
+                                },
+                            }))
+                            .unwrap();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn connect(&mut self, url: &str) -> Result<(), OkxError> {
+        let request = url.into_client_request()?;
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+        // OKX closes the connection if there is no message for 30 seconds.
+        let mut interval = time::interval(Duration::from_secs(15));
+
+        // Resubscribes to the already registered symbols in case of reconnection.
+        let symbols = self
+            .symbols
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        for symbol in symbols {
+            write.send(Self::subscribe_msg(&symbol)).await?;
+        }
+
+        loop {
+            select! {
+                _ = interval.tick() => {
+                    write.send(Message::Text("ping".into())).await?;
+                }
+                msg = self.symbol_rx.recv() => match msg {
+                    Ok(symbol) => {
+                        write.send(Self::subscribe_msg(&symbol)).await?;
+                    }
+                    Err(RecvError::Closed) => {
+                        return Ok(());
+                    }
+                    Err(RecvError::Lagged(num)) => {
+                        error!("{num} subscription requests were missed.");
+                    }
+                },
+                message = read.next() => {
+                    match message {
+                        Some(Ok(Message::Text(text))) => {
+                            if text.as_str() == "pong" {
+                                continue;
+                            }
+                            if let Err(error) = self.handle_public_stream(&text).await {
+                                error!(?error, %text, "Couldn't handle StreamMsg.");
+                            }
+                        }
+                        Some(Ok(Message::Ping(_))) => {
+                            write.send(Message::Pong(Bytes::default())).await?;
+                        }
+                        Some(Ok(Message::Close(close_frame))) => {
+                            return Err(OkxError::ConnectionAbort(
+                                close_frame
+                                    .map(|f| f.to_string())
+                                    .unwrap_or(String::new())
+                            ));
+                        }
+                        Some(Ok(Message::Binary(_)))
+                        | Some(Ok(Message::Frame(_)))
+                        | Some(Ok(Message::Pong(_))) => {}
+                        Some(Err(error)) => {
+                            return Err(OkxError::from(error));
+                        }
+                        None => {
+                            return Err(OkxError::ConnectionInterrupted);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::okx::{
+    OkxError,
+    msg::{CancelOrder, OrderOpResult, PendingOrder, Position, RestResponse},
+    sign_hmac_sha256_base64,
+};
+
+#[derive(Clone)]
+pub struct OkxClient {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    secret: String,
+    passphrase: String,
+    demo: bool,
+}
+
+impl OkxClient {
+    pub fn new(url: &str, api_key: &str, secret: &str, passphrase: &str, demo: bool) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            api_key: api_key.to_string(),
+            secret: secret.to_string(),
+            passphrase: passphrase.to_string(),
+            demo,
+        }
+    }
+
+    async fn request<T: for<'a> Deserialize<'a>>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: String,
+    ) -> Result<T, reqwest::Error> {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let signature = sign_hmac_sha256_base64(
+            &self.secret,
+            &format!("{timestamp}{}{path}{body}", method.as_str()),
+        );
+        let mut builder = self
+            .client
+            .request(method, format!("{}{}", self.url, path))
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/json")
+            .header("OK-ACCESS-KEY", &self.api_key)
+            .header("OK-ACCESS-SIGN", signature)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", &self.passphrase);
+        if self.demo {
+            builder = builder.header("x-simulated-trading", "1");
+        }
+        if !body.is_empty() {
+            builder = builder.body(body);
+        }
+        builder.send().await?.json().await
+    }
+
+    pub async fn get_positions(
+        &self,
+        inst_type: &str,
+        inst_id: &str,
+    ) -> Result<Vec<Position>, OkxError> {
+        let resp: RestResponse = self
+            .request(
+                reqwest::Method::GET,
+                &format!("/api/v5/account/positions?instType={inst_type}&instId={inst_id}"),
+                String::new(),
+            )
+            .await?;
+        if resp.code != "0" {
+            Err(OkxError::OpError(resp.msg))
+        } else {
+            Ok(serde_json::from_value(resp.data)?)
+        }
+    }
+
+    pub async fn get_pending_orders(
+        &self,
+        inst_type: &str,
+        inst_id: &str,
+    ) -> Result<Vec<PendingOrder>, OkxError> {
+        let resp: RestResponse = self
+            .request(
+                reqwest::Method::GET,
+                &format!("/api/v5/trade/orders-pending?instType={inst_type}&instId={inst_id}"),
+                String::new(),
+            )
+            .await?;
+        if resp.code != "0" {
+            Err(OkxError::OpError(resp.msg))
+        } else {
+            Ok(serde_json::from_value(resp.data)?)
+        }
+    }
+
+    pub async fn cancel_all_orders(&self, inst_type: &str, inst_id: &str) -> Result<(), OkxError> {
+        // OKX doesn't provide a cancel-all endpoint for derivatives, so it cancels the pending
+        // orders in batches of up to 20, which is the batch size limit.
+        let pending_orders = self.get_pending_orders(inst_type, inst_id).await?;
+        for chunk in pending_orders.chunks(20) {
+            let orders: Vec<_> = chunk
+                .iter()
+                .map(|order| CancelOrder {
+                    inst_id: order.inst_id.clone(),
+                    ord_id: order.ord_id.clone(),
+                })
+                .collect();
+            let resp: RestResponse = self
+                .request(
+                    reqwest::Method::POST,
+                    "/api/v5/trade/cancel-batch-orders",
+                    serde_json::to_string(&orders)?,
+                )
+                .await?;
+            if resp.code != "0" {
+                // Order-level failures, such as an order that has been filled in the meantime,
+                // are reported in `data` with the code 1 or 2. Only a request-level failure is
+                // treated as an error.
+                let results: Vec<OrderOpResult> =
+                    serde_json::from_value(resp.data).unwrap_or_default();
+                if results.is_empty() {
+                    return Err(OkxError::OpError(resp.msg));
+                }
+            }
+        }
+        Ok(())
+    }
+}
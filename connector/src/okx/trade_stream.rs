@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use hftbacktest::types::{ErrorKind, LiveError, LiveEvent};
+use tokio::{
+    select,
+    sync::{
+        broadcast::{Receiver, error::RecvError},
+        mpsc::UnboundedSender,
+    },
+    time,
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Bytes, Message, client::IntoClientRequest},
+};
+use tracing::{debug, error, info};
+
+use crate::{
+    connector::PublishEvent,
+    okx::{
+        OkxError,
+        msg::{Op, Order, OrderOpResponse, TradeStreamMsg},
+        ordermanager::{OrderExt, SharedOrderManager},
+        private_stream::login_msg,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct OrderOp {
+    pub op: &'static str,
+    pub okx_order: Order,
+}
+
+pub struct TradeStream {
+    api_key: String,
+    secret: String,
+    passphrase: String,
+    ev_tx: UnboundedSender<PublishEvent>,
+    order_manager: SharedOrderManager,
+    order_rx: Receiver<OrderOp>,
+}
+
+impl TradeStream {
+    pub fn new(
+        api_key: String,
+        secret: String,
+        passphrase: String,
+        ev_tx: UnboundedSender<PublishEvent>,
+        order_manager: SharedOrderManager,
+        order_rx: Receiver<OrderOp>,
+    ) -> Self {
+        Self {
+            api_key,
+            secret,
+            passphrase,
+            ev_tx,
+            order_manager,
+            order_rx,
+        }
+    }
+
+    pub async fn connect(&mut self, url: &str) -> Result<(), OkxError> {
+        let request = url.into_client_request()?;
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut write, mut read) = ws_stream.split();
+        // OKX closes the connection if there is no message for 30 seconds.
+        let mut interval = time::interval(Duration::from_secs(15));
+
+        write
+            .send(login_msg(&self.api_key, &self.secret, &self.passphrase))
+            .await?;
+
+        loop {
+            select! {
+                _ = interval.tick() => {
+                    write.send(Message::Text("ping".into())).await?;
+                }
+                order = self.order_rx.recv() => {
+                    match order {
+                        Ok(order) => {
+                            // The request id is the client order id, so that the order can be
+                            // identified even if the response doesn't contain the order result.
+                            let op = Op {
+                                id: Some(order.okx_order.cl_ord_id.clone()),
+                                op: order.op,
+                                args: vec![order.okx_order],
+                            };
+                            let s = serde_json::to_string(&op).unwrap();
+                            write.send(Message::Text(s.into())).await?;
+                        }
+                        Err(RecvError::Closed) => {
+                            return Ok(());
+                        }
+                        Err(RecvError::Lagged(num)) => {
+                            error!("{num} order requests were missed.");
+                        }
+                    }
+                }
+                message = read.next() => {
+                    match message {
+                        Some(Ok(Message::Text(text))) => {
+                            if text.as_str() == "pong" {
+                                continue;
+                            }
+                            match self.handle_trade_stream(&text).await {
+                                Ok(_) => {}
+                                Err(error @ OkxError::AuthError { .. }) => {
+                                    return Err(error);
+                                }
+                                Err(error) => {
+                                    error!(?error, %text, "Couldn't properly handle TradeStreamMsg.");
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Ping(_))) => {
+                            write.send(Message::Pong(Bytes::default())).await?;
+                        }
+                        Some(Ok(Message::Close(close_frame))) => {
+                            return Err(OkxError::ConnectionAbort(
+                                close_frame.map(|f| f.to_string()).unwrap_or(String::new())
+                            ));
+                        }
+                        Some(Ok(Message::Binary(_)))
+                        | Some(Ok(Message::Frame(_)))
+                        | Some(Ok(Message::Pong(_))) => {}
+                        Some(Err(error)) => {
+                            return Err(OkxError::from(error));
+                        }
+                        None => {
+                            return Err(OkxError::ConnectionInterrupted);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_op_fail(&self, resp: &OrderOpResponse) -> Result<(), OkxError> {
+        // If the request itself fails, such as due to a rate limit, `data` can be empty, and then
+        // the request id, which is the client order id, is used.
+        let failed = if resp.data.is_empty() {
+            vec![(
+                resp.id.clone().ok_or(OkxError::InvalidArg("id"))?,
+                resp.code.clone(),
+                resp.msg.clone(),
+            )]
+        } else {
+            resp.data
+                .iter()
+                .filter(|result| result.s_code != "0")
+                .map(|result| {
+                    (
+                        result.cl_ord_id.clone(),
+                        result.s_code.clone(),
+                        result.s_msg.clone(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut order_manager = self.order_manager.lock().unwrap();
+        for (cl_ord_id, code, msg) in failed {
+            let OrderExt { symbol, order } = if resp.op == "order" {
+                order_manager.update_submit_fail(&cl_ord_id)?
+            } else {
+                order_manager.update_cancel_fail(&cl_ord_id)?
+            };
+            let error = OkxError::OrderError { code, msg };
+            let venue = error
+                .to_venue_error()
+                .symbol(&symbol)
+                .order_id(order.order_id);
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Order { symbol, order }))
+                .unwrap();
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                    LiveError::with(ErrorKind::OrderError, error.to_value()).with_venue(venue),
+                )))
+                .unwrap();
+        }
+        Ok(())
+    }
+
+    async fn handle_trade_stream(&self, text: &str) -> Result<(), OkxError> {
+        let stream = serde_json::from_str::<TradeStreamMsg>(text)?;
+        match stream {
+            TradeStreamMsg::Event(resp) => {
+                debug!(?resp, "Event");
+                if resp.event == "error" {
+                    let error = OkxError::AuthError {
+                        code: resp.code,
+                        msg: resp.msg,
+                    };
+                    self.ev_tx
+                        .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                            LiveError::with(ErrorKind::CriticalConnectionError, error.to_value())
+                                .with_venue(error.to_venue_error()),
+                        )))
+                        .unwrap();
+                    return Err(error);
+                }
+            }
+            TradeStreamMsg::OrderOp(resp) => {
+                if resp.op == "order" || resp.op == "cancel-order" {
+                    if resp.code != "0" {
+                        /*
+                        1: Operation failed.
+                        2: Bulk operation partially succeeded.
+                        50011: Rate limit reached.
+                        51000: Parameter error.
+                        51008: Order failed. Insufficient balance.
+                        51400: Cancellation failed as the order has been filled, canceled or
+                               does not exist.
+                         */
+                        self.handle_op_fail(&resp)?;
+                    }
+                } else {
+                    info!(?resp, "trade stream");
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+mod simulator;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+        mpsc::{Receiver, Sender, channel},
+    },
+    thread,
+};
+
+use hftbacktest::{
+    backtest::BacktestError,
+    prelude::OrderId,
+    types::{BuildError, ErrorKind, ErrorReason, LiveError, LiveEvent, Order, Value, VenueError},
+};
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::error;
+
+use crate::{
+    connector::{Connector, ConnectorBuilder, GetOrders, PublishEvent},
+    sim::simulator::Simulator,
+};
+
+#[derive(Error, Debug)]
+pub enum SimError {
+    #[error("Config: {0:?}")]
+    Config(#[from] toml::de::Error),
+    #[error("InvalidArg: {0}")]
+    InvalidArg(&'static str),
+    #[error("SymbolNotFound: {0}")]
+    SymbolNotFound(String),
+    #[error("SimulationEnded")]
+    SimulationEnded,
+    #[error("Build: {0}")]
+    Build(#[from] BuildError),
+    #[error("Backtest: {0}")]
+    Backtest(#[from] BacktestError),
+    #[error("Io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl SimError {
+    pub fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    /// Returns the normalized reason for the error.
+    pub fn reason(&self) -> ErrorReason {
+        match self {
+            SimError::Backtest(BacktestError::OrderIdExist) => ErrorReason::DuplicateOrderId,
+            SimError::Backtest(BacktestError::OrderNotFound) => ErrorReason::UnknownOrder,
+            _ => ErrorReason::Other,
+        }
+    }
+
+    /// Returns the normalized error with the error message.
+    pub fn to_venue_error(&self) -> VenueError {
+        VenueError::new(self.reason(), self.to_string())
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LatencyConfig {
+    /// Uses [`ConstantLatency`](hftbacktest::backtest::models::ConstantLatency), in nanoseconds.
+    Constant {
+        entry_latency: i64,
+        response_latency: i64,
+    },
+    /// Uses [`IntpOrderLatency`](hftbacktest::backtest::models::IntpOrderLatency) with the order
+    /// latency data files.
+    Intp {
+        data: Vec<String>,
+        #[serde(default)]
+        latency_offset: i64,
+    },
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueueConfig {
+    RiskAdverse,
+    PowerProb { n: f64 },
+    PowerProb2 { n: f64 },
+    PowerProb3 { n: f64 },
+    LogProb,
+    LogProb2,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExchangeConfig {
+    NoPartialFill,
+    PartialFill,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SymbolConfig {
+    symbol: String,
+    tick_size: f64,
+    lot_size: f64,
+    /// The `Event` feed data files to replay, in order.
+    data: Vec<String>,
+    /// The market depth snapshot file applied before the replay, such as the end-of-day snapshot
+    /// of the previous day.
+    #[serde(default)]
+    initial_snapshot: Option<String>,
+    latency: LatencyConfig,
+    queue: QueueConfig,
+    #[serde(default = "default_exchange")]
+    exchange: ExchangeConfig,
+    #[serde(default)]
+    maker_fee: f64,
+    #[serde(default)]
+    taker_fee: f64,
+    #[serde(default = "default_contract_size")]
+    contract_size: f64,
+}
+
+fn default_exchange() -> ExchangeConfig {
+    ExchangeConfig::PartialFill
+}
+
+fn default_contract_size() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    /// The replay speed relative to the real time. For example, `10.0` replays 10 seconds of the
+    /// feed data per second.
+    #[serde(default = "default_speed")]
+    speed: f64,
+    symbols: Vec<SymbolConfig>,
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+/// A request forwarded to the simulator thread.
+enum Command {
+    Register(String),
+    Submit { symbol: String, order: Order },
+    Cancel { symbol: String, order: Order },
+    Modify { symbol: String, order: Order },
+}
+
+/// Keeps the open orders of the simulated exchange, as last published to the bots.
+#[derive(Default)]
+pub struct OrderManager {
+    orders: HashMap<String, HashMap<OrderId, Order>>,
+}
+
+impl OrderManager {
+    fn update(&mut self, symbol: &str, orders: HashMap<OrderId, Order>) {
+        self.orders.insert(symbol.to_string(), orders);
+    }
+}
+
+impl GetOrders for OrderManager {
+    fn orders(&self, symbol: Option<String>) -> Vec<Order> {
+        match symbol {
+            Some(symbol) => self
+                .orders
+                .get(&symbol)
+                .map(|orders| orders.values().cloned().collect())
+                .unwrap_or_default(),
+            None => self
+                .orders
+                .values()
+                .flat_map(|orders| orders.values().cloned())
+                .collect(),
+        }
+    }
+}
+
+pub type SharedOrderManager = Arc<Mutex<OrderManager>>;
+
+/// A connector to a simulated exchange for paper trading and integration testing of live bots
+/// without a real exchange.
+///
+/// It replays the `Event` feed data in real time, or accelerated by `speed`, and matches the
+/// bots' orders against it using the backtest exchange, queue, and latency models, as a backtest
+/// does. The feed, orders, and positions are published in the same way as a real connector, with
+/// the timestamps mapped to the wall clock. The replay starts when the first instrument is
+/// registered.
+///
+/// The orders of all bots are submitted to the same simulated account, so the bots must use
+/// distinct order IDs.
+pub struct Sim {
+    config: Config,
+    order_manager: SharedOrderManager,
+    cmd_tx: Sender<Command>,
+    cmd_rx: Option<Receiver<Command>>,
+}
+
+impl Sim {
+    fn send(&self, cmd: Command, ev_tx: &UnboundedSender<PublishEvent>) {
+        if self.cmd_tx.send(cmd).is_err() {
+            let error = SimError::SimulationEnded;
+            ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Error(
+                    LiveError::with(ErrorKind::OrderError, error.to_value())
+                        .with_venue(error.to_venue_error()),
+                )))
+                .unwrap();
+        }
+    }
+}
+
+impl ConnectorBuilder for Sim {
+    type Error = SimError;
+
+    fn build_from(config: &str) -> Result<Self, Self::Error> {
+        let config: Config = toml::from_str(config)?;
+        if config.speed <= 0.0 {
+            return Err(SimError::InvalidArg("speed"));
+        }
+        if config.symbols.iter().any(|symbol| symbol.data.is_empty()) {
+            return Err(SimError::InvalidArg("data"));
+        }
+        let (cmd_tx, cmd_rx) = channel();
+        Ok(Sim {
+            config,
+            order_manager: Default::default(),
+            cmd_tx,
+            cmd_rx: Some(cmd_rx),
+        })
+    }
+}
+
+impl Connector for Sim {
+    fn register(&mut self, symbol: String) {
+        if !self.config.symbols.iter().any(|s| s.symbol == symbol) {
+            error!(%symbol, "The symbol is not configured in the simulator.");
+            return;
+        }
+        let _ = self.cmd_tx.send(Command::Register(symbol));
+    }
+
+    fn order_manager(&self) -> Arc<Mutex<dyn GetOrders + Send + 'static>> {
+        self.order_manager.clone()
+    }
+
+    fn run(&mut self, ev_tx: UnboundedSender<PublishEvent>) {
+        let Some(cmd_rx) = self.cmd_rx.take() else {
+            return;
+        };
+        let config = self.config.clone();
+        let order_manager = self.order_manager.clone();
+        // The backtester isn't `Send`, so it is built on the simulator thread.
+        thread::spawn(move || {
+            let mut simulator = Simulator::new(config, order_manager, ev_tx)
+                .map_err(|error| {
+                    error!(?error, "Couldn't build the simulator.");
+                })
+                .unwrap();
+            simulator.run(cmd_rx);
+        });
+    }
+
+    fn submit(&self, symbol: String, order: Order, ev_tx: UnboundedSender<PublishEvent>) {
+        self.send(Command::Submit { symbol, order }, &ev_tx);
+    }
+
+    fn cancel(&self, symbol: String, order: Order, ev_tx: UnboundedSender<PublishEvent>) {
+        self.send(Command::Cancel { symbol, order }, &ev_tx);
+    }
+
+    fn modify(&self, symbol: String, order: Order, ev_tx: UnboundedSender<PublishEvent>) {
+        self.send(Command::Modify { symbol, order }, &ev_tx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hftbacktest::{backtest::BacktestError, types::ErrorReason};
+
+    use crate::sim::SimError;
+
+    #[test]
+    fn maps_error_reasons() {
+        let cases = [
+            (SimError::Backtest(BacktestError::OrderIdExist), ErrorReason::DuplicateOrderId),
+            (SimError::Backtest(BacktestError::OrderNotFound), ErrorReason::UnknownOrder),
+            (SimError::Backtest(BacktestError::InvalidOrderStatus), ErrorReason::Other),
+            (SimError::SymbolNotFound("btcusdt".to_string()), ErrorReason::Other),
+            (SimError::SimulationEnded, ErrorReason::Other),
+        ];
+        for (error, reason) in cases {
+            assert_eq!(error.reason(), reason, "{error}");
+        }
+    }
+}
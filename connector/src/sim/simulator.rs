@@ -0,0 +1,656 @@
+use std::{
+    collections::HashMap,
+    io::Error as IoError,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use chrono::Utc;
+use hftbacktest::{
+    backtest::{
+        Backtest,
+        BacktestBuilder,
+        ExchangeKind,
+        L2AssetBuilder,
+        assettype::LinearAsset,
+        data::{Data, DataSource, read_npy_file, read_npz_file},
+        models::{
+            CommonFees,
+            ConstantLatency,
+            IntpOrderLatency,
+            LatencyModel,
+            LogProbQueueFunc,
+            LogProbQueueFunc2,
+            PowerProbQueueFunc,
+            PowerProbQueueFunc2,
+            PowerProbQueueFunc3,
+            ProbQueueModel,
+            QueueModel,
+            RiskAdverseQueueModel,
+            TradingValueFeeModel,
+        },
+    },
+    prelude::*,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{error, info};
+
+use crate::{
+    connector::PublishEvent,
+    sim::{
+        Command,
+        Config,
+        ExchangeConfig,
+        LatencyConfig,
+        QueueConfig,
+        SharedOrderManager,
+        SimError,
+        SymbolConfig,
+    },
+};
+
+/// The interval at which the simulation catches up with the wall clock.
+const STEP_INTERVAL: Duration = Duration::from_millis(1);
+
+fn now() -> i64 {
+    Utc::now().timestamp_nanos_opt().unwrap()
+}
+
+/// Maps the timestamps of the feed data to the wall clock.
+struct Clock {
+    sim_start: i64,
+    wall_start: i64,
+    speed: f64,
+}
+
+impl Clock {
+    fn new(sim_start: i64, speed: f64) -> Self {
+        Self {
+            sim_start,
+            wall_start: now(),
+            speed,
+        }
+    }
+
+    /// Returns the timestamp of the feed data that should be replayed by now.
+    fn sim_now(&self) -> i64 {
+        self.sim_start + ((now() - self.wall_start) as f64 * self.speed) as i64
+    }
+
+    /// Converts the timestamp of the feed data to the wall clock.
+    fn to_wall(&self, timestamp: i64) -> i64 {
+        self.wall_start + ((timestamp - self.sim_start) as f64 / self.speed) as i64
+    }
+}
+
+fn load_data(filepath: &str) -> Result<Data<Event>, IoError> {
+    if filepath.ends_with(".npy") {
+        read_npy_file(filepath)
+    } else {
+        read_npz_file(filepath, "data")
+    }
+}
+
+/// Reads the feed data files in order, independently of the backtester, to publish the events
+/// that the local receives.
+struct Feed {
+    files: Vec<String>,
+    next_file: usize,
+    data: Data<Event>,
+    row: usize,
+}
+
+impl Feed {
+    fn new(files: Vec<String>) -> Self {
+        Self {
+            files,
+            next_file: 0,
+            data: Data::empty(),
+            row: 0,
+        }
+    }
+
+    /// Returns the next local event received by the given timestamp.
+    fn next(&mut self, timestamp: i64) -> Result<Option<Event>, IoError> {
+        loop {
+            while self.row < self.data.len() {
+                let event = &self.data[self.row];
+                if event.is(LOCAL_EVENT) {
+                    if event.local_ts > timestamp {
+                        return Ok(None);
+                    }
+                    self.row += 1;
+                    return Ok(Some(event.clone()));
+                }
+                self.row += 1;
+            }
+            if self.next_file >= self.files.len() {
+                return Ok(None);
+            }
+            self.data = load_data(&self.files[self.next_file])?;
+            self.next_file += 1;
+            self.row = 0;
+        }
+    }
+}
+
+struct SymbolState {
+    symbol: String,
+    registered: bool,
+    feed: Feed,
+    /// The orders as last published, keyed by the order ID.
+    orders: HashMap<OrderId, Order>,
+    position: Option<f64>,
+}
+
+/// Runs the backtester in step with the wall clock and publishes its feed, order, and position
+/// updates as live events.
+pub struct Simulator {
+    hbt: Backtest<HashMapMarketDepth, HkPriceAction>,
+    symbols: Vec<SymbolState>,
+    speed: f64,
+    clock: Option<Clock>,
+    ended: bool,
+    order_manager: SharedOrderManager,
+    ev_tx: UnboundedSender<PublishEvent>,
+}
+
+impl Simulator {
+    pub fn new(
+        config: Config,
+        order_manager: SharedOrderManager,
+        ev_tx: UnboundedSender<PublishEvent>,
+    ) -> Result<Self, SimError> {
+        let mut builder = Backtest::builder();
+        for symbol in config.symbols.iter() {
+            builder = match &symbol.latency {
+                LatencyConfig::Constant {
+                    entry_latency,
+                    response_latency,
+                } => add_asset_with_latency(
+                    builder,
+                    symbol,
+                    ConstantLatency::new(*entry_latency, *response_latency),
+                )?,
+                LatencyConfig::Intp {
+                    data,
+                    latency_offset,
+                } => add_asset_with_latency(
+                    builder,
+                    symbol,
+                    IntpOrderLatency::new(
+                        data.iter().map(|f| DataSource::File(f.clone())).collect(),
+                        *latency_offset,
+                    ),
+                )?,
+            };
+        }
+        let hbt = builder.build()?;
+        let symbols = config
+            .symbols
+            .into_iter()
+            .map(|symbol| SymbolState {
+                symbol: symbol.symbol,
+                registered: false,
+                feed: Feed::new(symbol.data),
+                orders: Default::default(),
+                position: None,
+            })
+            .collect();
+        Ok(Self {
+            hbt,
+            symbols,
+            speed: config.speed,
+            clock: None,
+            ended: false,
+            order_manager,
+            ev_tx,
+        })
+    }
+
+    pub fn run(&mut self, cmd_rx: Receiver<Command>) {
+        loop {
+            match cmd_rx.recv_timeout(STEP_INTERVAL) {
+                Ok(cmd) => self.handle(cmd),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            while let Ok(cmd) = cmd_rx.try_recv() {
+                self.handle(cmd);
+            }
+            self.step()
+                .map_err(|error| {
+                    error!(?error, "An error occurred while running the simulation.");
+                })
+                .unwrap();
+        }
+    }
+
+    fn handle(&mut self, cmd: Command) {
+        let (symbol, order_id) = match &cmd {
+            Command::Register(symbol) => (symbol.clone(), None),
+            Command::Submit { symbol, order }
+            | Command::Cancel { symbol, order }
+            | Command::Modify { symbol, order } => (symbol.clone(), Some(order.order_id)),
+        };
+        let result = match cmd {
+            Command::Register(symbol) => self.register(&symbol),
+            Command::Submit { symbol, order } => self.submit(&symbol, order),
+            Command::Cancel { symbol, order } => self.cancel(&symbol, order),
+            Command::Modify { symbol, order } => self.modify(&symbol, order),
+        };
+        if let Err(error) = result {
+            error!(?error, "Couldn't process the order request.");
+            let mut venue = error.to_venue_error().symbol(symbol);
+            if let Some(order_id) = order_id {
+                venue = venue.order_id(order_id);
+            }
+            self.publish(LiveEvent::Error(
+                LiveError::with(ErrorKind::OrderError, error.to_value()).with_venue(venue),
+            ));
+        }
+    }
+
+    /// Returns the asset number of the registered symbol that accepts orders.
+    fn asset_no(&self, symbol: &str) -> Result<usize, SimError> {
+        if self.ended {
+            return Err(SimError::SimulationEnded);
+        }
+        self.symbols
+            .iter()
+            .position(|s| s.symbol == symbol && s.registered)
+            .ok_or_else(|| SimError::SymbolNotFound(symbol.to_string()))
+    }
+
+    fn register(&mut self, symbol: &str) -> Result<(), SimError> {
+        let asset_no = self
+            .symbols
+            .iter()
+            .position(|s| s.symbol == symbol)
+            .ok_or_else(|| SimError::SymbolNotFound(symbol.to_string()))?;
+        if self.symbols[asset_no].registered {
+            return Ok(());
+        }
+        if self.clock.is_none() {
+            // Starts the replay from the first event.
+            self.hbt.elapse(0)?;
+            self.clock = Some(Clock::new(self.hbt.current_timestamp(), self.speed));
+            info!(speed = self.speed, "The simulation has started.");
+        }
+        let clock = self.clock.as_ref().unwrap();
+        let timestamp = self.hbt.current_timestamp();
+        let state = &mut self.symbols[asset_no];
+        state.registered = true;
+
+        // Skips the feed that has already been replayed and publishes the current market depth
+        // instead.
+        while state.feed.next(timestamp)?.is_some() {}
+        let mut events = self.hbt.depth(asset_no).snapshot();
+        for event in events.iter_mut() {
+            event.exch_ts = timestamp;
+            event.local_ts = timestamp;
+        }
+        for event in events {
+            self.publish(LiveEvent::Feed {
+                symbol: symbol.to_string(),
+                event: to_feed(event, clock),
+            });
+        }
+        Ok(())
+    }
+
+    fn submit(&mut self, symbol: &str, order: Order) -> Result<(), SimError> {
+        let asset_no = self.asset_no(symbol)?;
+        match order.side {
+            Side::Buy => self.hbt.submit_buy_order(
+                asset_no,
+                order.order_id,
+                order.price(),
+                order.qty,
+                order.time_in_force,
+                order.order_type,
+                false,
+            )?,
+            Side::Sell => self.hbt.submit_sell_order(
+                asset_no,
+                order.order_id,
+                order.price(),
+                order.qty,
+                order.time_in_force,
+                order.order_type,
+                false,
+            )?,
+            _ => return Err(SimError::InvalidArg("side")),
+        };
+        Ok(())
+    }
+
+    fn cancel(&mut self, symbol: &str, order: Order) -> Result<(), SimError> {
+        let asset_no = self.asset_no(symbol)?;
+        self.hbt.cancel(asset_no, order.order_id, false)?;
+        Ok(())
+    }
+
+    fn modify(&mut self, symbol: &str, order: Order) -> Result<(), SimError> {
+        let asset_no = self.asset_no(symbol)?;
+        self.hbt
+            .modify(asset_no, order.order_id, order.price(), order.qty, false)?;
+        Ok(())
+    }
+
+    /// Advances the simulation to the wall clock and publishes the updates.
+    fn step(&mut self) -> Result<(), SimError> {
+        let Some(clock) = self.clock.as_ref() else {
+            return Ok(());
+        };
+        if !self.ended {
+            let target = clock.sim_now();
+            let current = self.hbt.current_timestamp();
+            if target > current && !self.hbt.elapse(target - current)? {
+                self.ended = true;
+                info!("The simulation has reached the end of the data.");
+            }
+        }
+        // Publishes all remaining feed once the backtester has processed all data.
+        let timestamp = if self.ended {
+            i64::MAX
+        } else {
+            self.hbt.current_timestamp()
+        };
+
+        for asset_no in 0..self.symbols.len() {
+            if !self.symbols[asset_no].registered {
+                continue;
+            }
+            self.publish_feed(asset_no, timestamp)?;
+            self.publish_orders(asset_no);
+            self.publish_position(asset_no);
+        }
+        Ok(())
+    }
+
+    fn publish_feed(&mut self, asset_no: usize, timestamp: i64) -> Result<(), SimError> {
+        let clock = self.clock.as_ref().unwrap();
+        let state = &mut self.symbols[asset_no];
+        while let Some(event) = state.feed.next(timestamp)? {
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Feed {
+                    symbol: state.symbol.clone(),
+                    event: to_feed(event, clock),
+                }))
+                .unwrap();
+        }
+        Ok(())
+    }
+
+    fn publish_orders(&mut self, asset_no: usize) {
+        let clock = self.clock.as_ref().unwrap();
+        let state = &mut self.symbols[asset_no];
+        for order in self.hbt.orders(asset_no).values() {
+            // An order is published once it receives a response from the exchange, and then
+            // whenever the exchange updates it.
+            let updated = match state.orders.get(&order.order_id) {
+                Some(prev) => {
+                    order.exch_timestamp != prev.exch_timestamp || order.status != prev.status
+                }
+                None => order.req == Status::None,
+            };
+            if !updated {
+                continue;
+            }
+            state.orders.insert(order.order_id, order.clone());
+
+            let mut order = order.clone();
+            if order.exch_timestamp > 0 {
+                order.exch_timestamp = clock.to_wall(order.exch_timestamp);
+            }
+            order.local_timestamp = clock.to_wall(order.local_timestamp);
+            self.ev_tx
+                .send(PublishEvent::LiveEvent(LiveEvent::Order {
+                    symbol: state.symbol.clone(),
+                    order,
+                }))
+                .unwrap();
+        }
+
+        // The orders in the final status have been published.
+        self.hbt.clear_inactive_orders(Some(asset_no));
+        let orders = self.hbt.orders(asset_no);
+        state
+            .orders
+            .retain(|order_id, _| orders.contains_key(order_id));
+        let open_orders = state
+            .orders
+            .iter()
+            .filter(|(_, order)| order.active())
+            .map(|(order_id, order)| (*order_id, order.clone()))
+            .collect();
+        self.order_manager
+            .lock()
+            .unwrap()
+            .update(&state.symbol, open_orders);
+    }
+
+    fn publish_position(&mut self, asset_no: usize) {
+        let clock = self.clock.as_ref().unwrap();
+        let position = self.hbt.position(asset_no);
+        let state = &mut self.symbols[asset_no];
+        if state.position == Some(position) {
+            return;
+        }
+        state.position = Some(position);
+        self.ev_tx
+            .send(PublishEvent::LiveEvent(LiveEvent::Position {
+                symbol: state.symbol.clone(),
+                qty: position,
+                exch_ts: clock.to_wall(self.hbt.current_timestamp()),
+            }))
+            .unwrap();
+    }
+
+    fn publish(&self, ev: LiveEvent) {
+        self.ev_tx.send(PublishEvent::LiveEvent(ev)).unwrap();
+    }
+}
+
+/// Converts the feed event to the one published by a live connector, which has no depth snapshot
+/// events and the timestamps in the wall clock.
+fn to_feed(mut event: Event, clock: &Clock) -> Event {
+    if event.is(DEPTH_SNAPSHOT_EVENT) {
+        event.ev = (event.ev & !0xff) | DEPTH_EVENT;
+    }
+    event.exch_ts = clock.to_wall(event.exch_ts);
+    event.local_ts = clock.to_wall(event.local_ts);
+    event
+}
+
+fn add_asset_with_latency<LM>(
+    builder: BacktestBuilder<HashMapMarketDepth, HkPriceAction>,
+    symbol: &SymbolConfig,
+    latency_model: LM,
+) -> Result<BacktestBuilder<HashMapMarketDepth, HkPriceAction>, SimError>
+where
+    LM: LatencyModel + Clone + 'static,
+{
+    match symbol.queue {
+        QueueConfig::RiskAdverse => {
+            add_asset(builder, symbol, latency_model, RiskAdverseQueueModel::new())
+        }
+        QueueConfig::PowerProb { n } => add_asset(
+            builder,
+            symbol,
+            latency_model,
+            ProbQueueModel::new(PowerProbQueueFunc::new(n)),
+        ),
+        QueueConfig::PowerProb2 { n } => add_asset(
+            builder,
+            symbol,
+            latency_model,
+            ProbQueueModel::new(PowerProbQueueFunc2::new(n)),
+        ),
+        QueueConfig::PowerProb3 { n } => add_asset(
+            builder,
+            symbol,
+            latency_model,
+            ProbQueueModel::new(PowerProbQueueFunc3::new(n)),
+        ),
+        QueueConfig::LogProb => add_asset(
+            builder,
+            symbol,
+            latency_model,
+            ProbQueueModel::new(LogProbQueueFunc::new()),
+        ),
+        QueueConfig::LogProb2 => add_asset(
+            builder,
+            symbol,
+            latency_model,
+            ProbQueueModel::new(LogProbQueueFunc2::new()),
+        ),
+    }
+}
+
+fn add_asset<LM, QM>(
+    builder: BacktestBuilder<HashMapMarketDepth, HkPriceAction>,
+    symbol: &SymbolConfig,
+    latency_model: LM,
+    queue_model: QM,
+) -> Result<BacktestBuilder<HashMapMarketDepth, HkPriceAction>, SimError>
+where
+    LM: LatencyModel + Clone + 'static,
+    QM: QueueModel<HashMapMarketDepth> + 'static,
+{
+    let snapshot = symbol
+        .initial_snapshot
+        .as_ref()
+        .map(|filepath| load_data(filepath))
+        .transpose()?;
+    let tick_size = symbol.tick_size;
+    let lot_size = symbol.lot_size;
+    let asset = L2AssetBuilder::new()
+        .data(
+            symbol
+                .data
+                .iter()
+                .map(|f| DataSource::File(f.clone()))
+                .collect(),
+        )
+        .latency_model(latency_model)
+        .asset_type(LinearAsset::new(symbol.contract_size))
+        .fee_model(TradingValueFeeModel::new(CommonFees::new(
+            symbol.maker_fee,
+            symbol.taker_fee,
+        )))
+        .exchange(match symbol.exchange {
+            ExchangeConfig::NoPartialFill => ExchangeKind::NoPartialFillExchange,
+            ExchangeConfig::PartialFill => ExchangeKind::PartialFillExchange,
+        })
+        .queue_model(queue_model)
+        // The price action is only used by the bots.
+        .price_action(HkPriceAction::new(vec![], vec![]))
+        .depth(move || {
+            let mut depth = HashMapMarketDepth::new(tick_size, lot_size);
+            if let Some(snapshot) = snapshot.as_ref() {
+                depth.apply_snapshot(snapshot);
+            }
+            depth
+        })
+        .build()?;
+    Ok(builder.add_asset(asset))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, sync::Arc};
+
+    use hftbacktest::backtest::data::write_npy;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    fn event(ev: u64, exch_ts: i64, px: f64, qty: f64) -> Event {
+        Event {
+            ev: ev | EXCH_EVENT | LOCAL_EVENT,
+            exch_ts,
+            local_ts: exch_ts + 1_000_000,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn fill_order() {
+        let filepath = std::env::temp_dir().join("hftbacktest_sim_fill_order.npy");
+        let mut data = vec![
+            event(BUY_EVENT | DEPTH_SNAPSHOT_EVENT, 0, 99.9, 5.0),
+            event(SELL_EVENT | DEPTH_SNAPSHOT_EVENT, 0, 100.1, 5.0),
+        ];
+        for i in 1..100 {
+            data.push(event(BUY_EVENT | TRADE_EVENT, i * 100_000_000, 100.1, 0.5));
+        }
+        write_npy(&mut File::create(&filepath).unwrap(), &data).unwrap();
+
+        let config: Config = toml::from_str(&format!(
+            r#"
+            speed = 1000.0
+
+            [[symbols]]
+            symbol = "btcusdt"
+            tick_size = 0.1
+            lot_size = 0.001
+            data = ["{}"]
+            latency = {{ type = "constant", entry_latency = 1000000, response_latency = 1000000 }}
+            queue = {{ type = "risk_adverse" }}
+            "#,
+            filepath.display()
+        ))
+        .unwrap();
+        let order_manager: SharedOrderManager = Arc::new(Default::default());
+        let (ev_tx, mut ev_rx) = unbounded_channel();
+        let mut simulator = Simulator::new(config, order_manager, ev_tx).unwrap();
+
+        simulator.handle(Command::Register("btcusdt".to_string()));
+        let mut order = Order::new(
+            1,
+            1001,
+            0.1,
+            1.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        order.req = Status::New;
+        simulator.handle(Command::Submit {
+            symbol: "btcusdt".to_string(),
+            order,
+        });
+        while !simulator.ended {
+            simulator.step().unwrap();
+        }
+        simulator.step().unwrap();
+
+        let mut num_feed = 0;
+        let mut filled = false;
+        let mut position = 0.0;
+        while let Ok(PublishEvent::LiveEvent(ev)) = ev_rx.try_recv() {
+            match ev {
+                LiveEvent::Feed { event, .. } => {
+                    assert!(!event.is(DEPTH_SNAPSHOT_EVENT));
+                    num_feed += 1;
+                }
+                LiveEvent::Order { order, .. } => {
+                    assert_eq!(order.order_id, 1);
+                    filled = order.status == Status::Filled;
+                }
+                LiveEvent::Position { qty, .. } => position = qty,
+                ev => panic!("unexpected event: {ev:?}"),
+            }
+        }
+        // The depth snapshot and the trades.
+        assert_eq!(num_feed, 101);
+        assert!(filled);
+        assert_eq!(position, 1.0);
+    }
+}
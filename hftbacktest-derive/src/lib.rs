@@ -264,14 +264,22 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                                     .parallel_load(#asset.parallel_load)
                                     .data(#asset.data.clone())
                                     .build()
-                                    .unwrap()
+                                    .map_err(|err| BuildError::Error(err.into()))?
                             } else {
                                 Reader::builder()
                                     .parallel_load(#asset.parallel_load)
                                     .data(#asset.data.clone())
                                     .preprocessor(FeedLatencyAdjustment::new(#asset.latency_offset))
                                     .build()
-                                    .unwrap()
+                                    .map_err(|err| BuildError::Error(err.into()))?
+                            };
+
+                            let initial_snapshot = match #asset.initial_snapshot.as_ref() {
+                                Some(source) => Some(
+                                    read_data_source(source)
+                                        .map_err(|err| BuildError::Error(err.into()))?,
+                                ),
+                                None => None,
                             };
 
                             let ob_local_to_exch = OrderBus::new();
@@ -282,15 +290,8 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                             let fee_model = #fm_ident::new(#(#fm_args.clone()),*);
 
                             let mut market_depth = #depth_construct;
-                            match #asset.initial_snapshot.as_ref() {
-                                Some(DataSource::File(file)) => {
-                                    let data = read_npz_file(&file, "data").unwrap();
-                                    market_depth.apply_snapshot(&data);
-                                }
-                                Some(DataSource::Data(data)) => {
-                                    market_depth.apply_snapshot(data);
-                                }
-                                None => {}
+                            if let Some(data) = &initial_snapshot {
+                                market_depth.apply_snapshot(data);
                             }
 
                             let local: Box<dyn LocalProcessor<#marketdepth>> = Box::new(#local_ident::new(
@@ -303,15 +304,8 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                             ));
 
                             let mut market_depth = #depth_construct;
-                            match #asset.initial_snapshot.as_ref() {
-                                Some(DataSource::File(file)) => {
-                                    let data = read_npz_file(&file, "data").unwrap();
-                                    market_depth.apply_snapshot(&data);
-                                }
-                                Some(DataSource::Data(data)) => {
-                                    market_depth.apply_snapshot(data);
-                                }
-                                None => {}
+                            if let Some(data) = &initial_snapshot {
+                                market_depth.apply_snapshot(data);
                             }
 
                             let queue_model = #qm_construct;
@@ -325,11 +319,11 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                                 ob_local_to_exch,
                             ));
 
-                            Asset {
+                            Ok(Asset {
                                 local,
                                 exch,
                                 reader
-                            }
+                            })
                         },
                     });
                     }
@@ -338,16 +332,20 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
         }
     }
 
+    // Evaluates to `Result<Asset, BuildError>`, so that an error in reading the data is returned
+    // instead of panicking.
     let output = quote! {
-        match (
-            &#asset.asset_type,
-            &#asset.latency_model,
-            &#asset.queue_model,
-            &#asset.exch_kind,
-            &#asset.fee_model,
-        ) {
-            #(#arms)*
-        }
+        (|| -> Result<_, BuildError> {
+            match (
+                &#asset.asset_type,
+                &#asset.latency_model,
+                &#asset.queue_model,
+                &#asset.exch_kind,
+                &#asset.fee_model,
+            ) {
+                #(#arms)*
+            }
+        })()
     };
 
     output.into()
@@ -37,6 +37,14 @@ fn prepare_live() -> LiveBot<IceoryxUnifiedChannel, HashMapMarketDepth> {
                     let error = error.value();
                     error!(?error, "OrderError");
                 }
+                ErrorKind::StateRepaired => {
+                    let error = error.value();
+                    error!(?error, "StateRepaired");
+                }
+                ErrorKind::DepthResync => {
+                    let error = error.value();
+                    error!(?error, "DepthResync");
+                }
                 ErrorKind::Custom(errno) => {
                     error!(%errno, "custom");
                 }
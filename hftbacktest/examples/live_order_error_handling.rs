@@ -8,7 +8,7 @@ use hftbacktest::{
         LoggingRecorder,
         ipc::iceoryx::IceoryxUnifiedChannel,
     },
-    prelude::{Bot, ErrorKind, HashMapMarketDepth, Value},
+    prelude::{Bot, ErrorKind, ErrorReason, HashMapMarketDepth, Value},
 };
 use tracing::error;
 
@@ -35,6 +35,17 @@ fn prepare_live() -> LiveBot<IceoryxUnifiedChannel, HashMapMarketDepth> {
                     error!("CriticalConnectionError");
                 }
                 ErrorKind::OrderError => {
+                    // The normalized reason is available regardless of the exchange.
+                    match error.reason() {
+                        Some(ErrorReason::RateLimited) => {
+                            error!(venue = ?error.venue(), "RateLimited");
+                            return Ok(());
+                        }
+                        Some(ErrorReason::AuthFailure) => {
+                            return Err(BotError::Custom("AuthFailure".to_string()));
+                        }
+                        _ => {}
+                    }
                     let error = error.value();
                     match error {
                         Value::String(err) => {
@@ -46,6 +57,14 @@ fn prepare_live() -> LiveBot<IceoryxUnifiedChannel, HashMapMarketDepth> {
                         _ => {}
                     }
                 }
+                ErrorKind::StateRepaired => {
+                    let error = error.value();
+                    error!(?error, "StateRepaired");
+                }
+                ErrorKind::DepthResync => {
+                    let error = error.value();
+                    error!(?error, "DepthResync");
+                }
                 ErrorKind::Custom(errno) => {
                     if errno == 1000 {
                         // Aborts the connection.
@@ -0,0 +1,77 @@
+use clap::Parser;
+use hftbacktest::{
+    backtest::{
+        data::{NpyDTyped, read_npz_file},
+        reconcile::Reconciler,
+        recorder::Record,
+    },
+    types::OrderRecord,
+};
+
+/// Compares the order log captured from a live session against the same day's backtest, and
+/// reports the discrepancies and their PnL impact.
+#[derive(Parser, Debug)]
+#[command(about = None, long_about = None)]
+struct Args {
+    /// The order log written by `SessionCapture`, `{symbol}_{seq}_log.npz`.
+    #[arg(long, num_args = 1..)]
+    live: Vec<String>,
+    /// The file written by `BacktestRecorder::to_npz`.
+    #[arg(long)]
+    backtest: String,
+    #[arg(long, default_value_t = 0)]
+    asset_no: usize,
+    /// The price at which the positions are marked. The last mid-price of the backtest is used
+    /// if not provided.
+    #[arg(long)]
+    mark_price: Option<f64>,
+    /// In nanoseconds.
+    #[arg(long, default_value_t = 1_000_000_000)]
+    match_window: i64,
+    /// In nanoseconds.
+    #[arg(long, default_value_t = 1_000_000)]
+    fill_time_tolerance: i64,
+    /// In nanoseconds.
+    #[arg(long, default_value_t = 1_000_000)]
+    latency_tolerance: i64,
+    #[arg(long, default_value_t = 1e-9)]
+    price_tolerance: f64,
+    /// Prints every discrepancy.
+    #[arg(long)]
+    verbose: bool,
+}
+
+fn read<D: NpyDTyped + Clone>(path: &str, name: &str) -> Vec<D> {
+    let data = read_npz_file::<D>(path, name).unwrap();
+    (0..data.len()).map(|i| data[i].clone()).collect()
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut live = Vec::new();
+    for path in args.live.iter() {
+        live.extend(read::<OrderRecord>(path, "orders"));
+    }
+    let backtest = read::<OrderRecord>(&args.backtest, &format!("{}_orders", args.asset_no));
+    let mark_price = args.mark_price.unwrap_or_else(|| {
+        read::<Record>(&args.backtest, &args.asset_no.to_string())
+            .last()
+            .expect("no record")
+            .price
+    });
+
+    let report = Reconciler::new(mark_price)
+        .match_window(args.match_window)
+        .fill_time_tolerance(args.fill_time_tolerance)
+        .latency_tolerance(args.latency_tolerance)
+        .price_tolerance(args.price_tolerance)
+        .reconcile(&live, &backtest);
+
+    if args.verbose {
+        for discrepancy in report.discrepancies.iter() {
+            println!("{discrepancy:?}");
+        }
+    }
+    println!("{report}");
+}
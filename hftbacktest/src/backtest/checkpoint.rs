@@ -0,0 +1,82 @@
+use std::{fs, path::Path};
+
+use bincode::{Decode, Encode, config};
+
+use crate::backtest::BacktestError;
+
+/// The magic string at the beginning of a checkpoint file, followed by the format version.
+const MAGIC: &[u8; 8] = b"HBTCKP\x01\x00";
+
+/// Writes the state of the backtesting components into a checkpoint.
+///
+/// Each component writes its values in order, and must read them back in the same order in
+/// [`CheckpointReader`].
+#[derive(Default)]
+pub struct CheckpointWriter {
+    buf: Vec<u8>,
+}
+
+impl CheckpointWriter {
+    /// Constructs an empty `CheckpointWriter`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends the value to the checkpoint.
+    pub fn write<T: Encode>(&mut self, value: &T) -> Result<(), BacktestError> {
+        bincode::encode_into_std_write(value, &mut self.buf, config::standard())?;
+        Ok(())
+    }
+
+    /// Returns the encoded checkpoint.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads the state of the backtesting components from a checkpoint written by
+/// [`CheckpointWriter`].
+pub struct CheckpointReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> CheckpointReader<'a> {
+    /// Constructs a `CheckpointReader` over the encoded checkpoint.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    /// Reads the next value from the checkpoint.
+    pub fn read<T: Decode<()>>(&mut self) -> Result<T, BacktestError> {
+        let (value, len) = bincode::decode_from_slice(self.buf, config::standard())?;
+        self.buf = &self.buf[len..];
+        Ok(value)
+    }
+
+    /// Returns `true` if all values have been read.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+/// Writes the encoded checkpoint to the file.
+pub fn write_checkpoint_file<P: AsRef<Path>>(
+    path: P,
+    checkpoint: &[u8],
+) -> Result<(), BacktestError> {
+    let mut bytes = Vec::with_capacity(MAGIC.len() + checkpoint.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(checkpoint);
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Reads the encoded checkpoint from the file written by [`write_checkpoint_file`].
+pub fn read_checkpoint_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, BacktestError> {
+    let mut bytes = fs::read(path)?;
+    if !bytes.starts_with(MAGIC) {
+        return Err(BacktestError::InvalidCheckpoint("not a checkpoint file"));
+    }
+    bytes.drain(..MAGIC.len());
+    Ok(bytes)
+}
@@ -0,0 +1,421 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufReader, Error, ErrorKind},
+};
+
+use arrow_array::{Array, RecordBatch};
+use arrow_ipc::reader::FileReader;
+use arrow_schema::{DataType, Schema, TimeUnit};
+use parquet::arrow::{ProjectionMask, arrow_reader::ParquetRecordBatchReaderBuilder};
+
+use crate::backtest::data::{
+    Data,
+    DataPtr,
+    NpyDTyped,
+    npy::{FieldLayout, field_layout},
+};
+
+/// Maps the fields of the data structure, such as [`Event`](crate::types::Event), onto the
+/// columns of a Parquet or Arrow IPC file.
+///
+/// By default, each field is read from the column with the same name. The column must have the
+/// same primitive type as the field and must not contain nulls, except that an `i64` field can
+/// also be read from a nanosecond timestamp column. Fields whose names start with `_`, which are
+/// used for the alignment, are filled with zero.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnMapping {
+    columns: HashMap<String, String>,
+    zero_filled: HashSet<String>,
+}
+
+impl ColumnMapping {
+    /// Constructs a `ColumnMapping` that reads each field from the column with the same name.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the field from the specified column.
+    pub fn column(self, field: &str, column: &str) -> Self {
+        let mut columns = self.columns;
+        columns.insert(field.to_string(), column.to_string());
+        Self { columns, ..self }
+    }
+
+    /// Fills the field with zero, for the file that doesn't have a corresponding column, such as
+    /// `order_id` in Level-2 feed data.
+    pub fn zero_filled(self, field: &str) -> Self {
+        let mut zero_filled = self.zero_filled;
+        zero_filled.insert(field.to_string());
+        Self {
+            zero_filled,
+            ..self
+        }
+    }
+}
+
+/// A field of the data structure and the column from which it is read.
+struct FieldColumn {
+    column: String,
+    offset: usize,
+    width: usize,
+}
+
+fn is_compatible(ty: &str, data_type: &DataType) -> bool {
+    matches!(
+        (ty.get(1..).unwrap_or_default(), data_type),
+        ("i8", DataType::Int64)
+            | ("i8", DataType::Timestamp(TimeUnit::Nanosecond, _))
+            | ("i4", DataType::Int32)
+            | ("i2", DataType::Int16)
+            | ("i1", DataType::Int8)
+            | ("u8", DataType::UInt64)
+            | ("u4", DataType::UInt32)
+            | ("u2", DataType::UInt16)
+            | ("u1", DataType::UInt8)
+            | ("f8", DataType::Float64)
+            | ("f4", DataType::Float32)
+    )
+}
+
+/// Validates the schema against the data structure `D`, and returns the columns to read.
+fn resolve_columns<D: NpyDTyped>(
+    schema: &Schema,
+    mapping: &ColumnMapping,
+) -> Result<Vec<FieldColumn>, Error> {
+    let mut columns = Vec::new();
+    for FieldLayout {
+        field,
+        offset,
+        width,
+    } in field_layout::<D>()?
+    {
+        if !field.name.starts_with('_') && !mapping.zero_filled.contains(&field.name) {
+            let name = mapping.columns.get(&field.name).unwrap_or(&field.name);
+            let (_, column) = schema.column_with_name(name).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("column `{name}` for field `{}` is missing", field.name),
+                )
+            })?;
+            if !is_compatible(&field.ty, column.data_type()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "column `{name}: {}` is incompatible with field `{}: {}`",
+                        column.data_type(),
+                        field.name,
+                        field.ty
+                    ),
+                ));
+            }
+            columns.push(FieldColumn {
+                column: name.clone(),
+                offset,
+                width,
+            });
+        }
+    }
+    Ok(columns)
+}
+
+/// Returns the sorted indices of the columns to read, for the column projection.
+fn projection(schema: &Schema, columns: &[FieldColumn]) -> Vec<usize> {
+    let mut indices: Vec<_> = columns
+        .iter()
+        .map(|column| schema.index_of(&column.column).unwrap())
+        .collect();
+    indices.sort();
+    indices.dedup();
+    indices
+}
+
+/// Copies the columns of the record batches into the rows of `Data`.
+fn decode<D: NpyDTyped + Clone>(
+    batches: &[RecordBatch],
+    columns: &[FieldColumn],
+) -> Result<Data<D>, Error> {
+    let len: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    if len == 0 {
+        return Ok(Data::empty());
+    }
+
+    let size = size_of::<D>();
+    let mut buf = DataPtr::new(len * size);
+    buf[..].fill(0);
+
+    let mut row = 0;
+    for batch in batches {
+        for column in columns {
+            let array = batch.column_by_name(&column.column).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("column `{}` is missing", column.column),
+                )
+            })?;
+            if array.null_count() > 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("column `{}` contains nulls", column.column),
+                ));
+            }
+            let array_data = array.to_data();
+            let values = &array_data.buffers()[0].as_slice()[array_data.offset() * column.width..];
+            for i in 0..batch.num_rows() {
+                let dst = (row + i) * size + column.offset;
+                buf[dst..(dst + column.width)]
+                    .copy_from_slice(&values[(i * column.width)..((i + 1) * column.width)]);
+            }
+        }
+        row += batch.num_rows();
+    }
+
+    let data = unsafe { Data::from_data_ptr(buf, 0) };
+    Ok(data)
+}
+
+fn open_parquet<D: NpyDTyped>(
+    filepath: &str,
+    mapping: &ColumnMapping,
+) -> Result<(ParquetRecordBatchReaderBuilder<File>, Vec<FieldColumn>), Error> {
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(filepath)?).map_err(Error::other)?;
+    let columns = resolve_columns::<D>(builder.schema(), mapping)?;
+    Ok((builder, columns))
+}
+
+fn read_parquet<D: NpyDTyped + Clone>(
+    filepath: &str,
+    row_groups: Option<Vec<usize>>,
+    mapping: &ColumnMapping,
+) -> Result<Data<D>, Error> {
+    let (builder, columns) = open_parquet::<D>(filepath, mapping)?;
+    let mask = ProjectionMask::roots(
+        builder.parquet_schema(),
+        projection(builder.schema(), &columns),
+    );
+    let mut builder = builder.with_projection(mask);
+    if let Some(row_groups) = row_groups {
+        builder = builder.with_row_groups(row_groups);
+    }
+    let batches = builder
+        .build()
+        .map_err(Error::other)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::other)?;
+    decode(&batches, &columns)
+}
+
+/// Reads a Parquet file, reading only the columns mapped onto the fields of the data structure
+/// `D` by the [`ColumnMapping`].
+pub fn read_parquet_file<D: NpyDTyped + Clone>(
+    filepath: &str,
+    mapping: &ColumnMapping,
+) -> Result<Data<D>, Error> {
+    read_parquet(filepath, None, mapping)
+}
+
+fn open_arrow_ipc<D: NpyDTyped>(
+    filepath: &str,
+    mapping: &ColumnMapping,
+) -> Result<(FileReader<BufReader<File>>, Vec<FieldColumn>), Error> {
+    let reader = FileReader::try_new_buffered(File::open(filepath)?, None).map_err(Error::other)?;
+    let columns = resolve_columns::<D>(&reader.schema(), mapping)?;
+    let projection = projection(&reader.schema(), &columns);
+    let reader = FileReader::try_new_buffered(File::open(filepath)?, Some(projection))
+        .map_err(Error::other)?;
+    Ok((reader, columns))
+}
+
+/// Reads an Arrow IPC file, reading only the columns mapped onto the fields of the data structure
+/// `D` by the [`ColumnMapping`].
+pub fn read_arrow_ipc_file<D: NpyDTyped + Clone>(
+    filepath: &str,
+    mapping: &ColumnMapping,
+) -> Result<Data<D>, Error> {
+    let (reader, columns) = open_arrow_ipc::<D>(filepath, mapping)?;
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::other)?;
+    decode(&batches, &columns)
+}
+
+/// A Parquet or Arrow IPC file, which is read in row groups or record batches respectively.
+#[derive(Clone, Debug)]
+pub(crate) enum ArrowSource {
+    Parquet(ColumnMapping),
+    ArrowIpc(ColumnMapping),
+}
+
+impl ArrowSource {
+    /// Validates the schema of the file and returns the number of row groups or record batches.
+    pub fn num_chunks<D: NpyDTyped>(&self, filepath: &str) -> Result<usize, Error> {
+        match self {
+            ArrowSource::Parquet(mapping) => {
+                let (builder, _) = open_parquet::<D>(filepath, mapping)?;
+                Ok(builder.metadata().num_row_groups())
+            }
+            ArrowSource::ArrowIpc(mapping) => {
+                let (reader, _) = open_arrow_ipc::<D>(filepath, mapping)?;
+                Ok(reader.num_batches())
+            }
+        }
+    }
+
+    /// Reads the row group or record batch at the specified index.
+    pub fn read_chunk<D: NpyDTyped + Clone>(
+        &self,
+        filepath: &str,
+        index: usize,
+    ) -> Result<Data<D>, Error> {
+        match self {
+            ArrowSource::Parquet(mapping) => read_parquet(filepath, Some(vec![index]), mapping),
+            ArrowSource::ArrowIpc(mapping) => {
+                let (mut reader, columns) = open_arrow_ipc::<D>(filepath, mapping)?;
+                reader.set_index(index).map_err(Error::other)?;
+                let batches = reader
+                    .next()
+                    .transpose()
+                    .map_err(Error::other)?
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                decode(&batches, &columns)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, sync::Arc};
+
+    use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, UInt64Array};
+    use arrow_ipc::writer::FileWriter;
+    use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+
+    use crate::{
+        backtest::{
+            BacktestError,
+            data::{ColumnMapping, DataSource, Reader, read_parquet_file},
+        },
+        types::{Event, LOCAL_EVENT},
+    };
+
+    fn batch(start: i64, end: i64) -> RecordBatch {
+        let ts = start..end;
+        RecordBatch::try_from_iter([
+            (
+                "ev",
+                Arc::new(UInt64Array::from_iter_values(
+                    ts.clone().map(|_| LOCAL_EVENT),
+                )) as ArrayRef,
+            ),
+            (
+                "exch_ts",
+                Arc::new(Int64Array::from_iter_values(ts.clone())),
+            ),
+            (
+                "local_ts",
+                Arc::new(Int64Array::from_iter_values(ts.clone().map(|i| i + 1))),
+            ),
+            (
+                "price",
+                Arc::new(Float64Array::from_iter_values(ts.clone().map(|i| i as f64))),
+            ),
+            (
+                "qty",
+                Arc::new(Float64Array::from_iter_values(ts.clone().map(|_| 1.0))),
+            ),
+            (
+                "ival",
+                Arc::new(Int64Array::from_iter_values(ts.clone().map(|_| 0))),
+            ),
+            (
+                "fval",
+                Arc::new(Float64Array::from_iter_values(ts.map(|_| 0.0))),
+            ),
+        ])
+        .unwrap()
+    }
+
+    fn read_all(filepath: DataSource<Event>) -> Result<Vec<Event>, BacktestError> {
+        let mut reader = Reader::builder()
+            .parallel_load(true)
+            .data(vec![filepath])
+            .build()?;
+        let mut events = Vec::new();
+        loop {
+            match reader.next_data() {
+                Ok(data) => {
+                    events.extend((0..data.len()).map(|i| data[i].clone()));
+                    reader.release(data);
+                }
+                Err(BacktestError::EndOfData) => return Ok(events),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    #[test]
+    fn read_parquet_and_arrow_ipc() -> Result<(), Box<dyn std::error::Error>> {
+        let expected: Vec<_> = (0..100)
+            .map(|i| Event {
+                ev: LOCAL_EVENT,
+                exch_ts: i,
+                local_ts: i + 1,
+                px: i as f64,
+                qty: 1.0,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            })
+            .collect();
+        let mapping = ColumnMapping::new()
+            .column("px", "price")
+            .zero_filled("order_id");
+
+        let parquet_file =
+            std::env::temp_dir().join(format!("read_parquet_{}.parquet", std::process::id()));
+        let parquet_file = parquet_file.to_str().unwrap().to_string();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(30)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            File::create(&parquet_file)?,
+            batch(0, 0).schema(),
+            Some(props),
+        )?;
+        writer.write(&batch(0, 100))?;
+        writer.close()?;
+
+        let events = read_all(DataSource::Parquet(parquet_file.clone(), mapping.clone()))?;
+        assert_eq!(events, expected);
+
+        let ipc_file =
+            std::env::temp_dir().join(format!("read_arrow_ipc_{}.arrow", std::process::id()));
+        let ipc_file = ipc_file.to_str().unwrap().to_string();
+        let mut writer = FileWriter::try_new(File::create(&ipc_file)?, &batch(0, 0).schema())?;
+        writer.write(&batch(0, 60))?;
+        writer.write(&batch(60, 100))?;
+        writer.finish()?;
+
+        let events = read_all(DataSource::ArrowIpc(ipc_file.clone(), mapping.clone()))?;
+        assert_eq!(events, expected);
+
+        // `px` has no matching column without the mapping.
+        assert!(read_parquet_file::<Event>(&parquet_file, &ColumnMapping::new()).is_err());
+        assert!(
+            Reader::<Event>::builder()
+                .data(vec![DataSource::ArrowIpc(
+                    ipc_file.clone(),
+                    ColumnMapping::new()
+                )])
+                .build()
+                .is_err()
+        );
+
+        std::fs::remove_file(&parquet_file)?;
+        std::fs::remove_file(&ipc_file)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Error, ErrorKind, Read, Write},
+};
+
+use crate::backtest::data::{
+    Data,
+    DataPtr,
+    NpyDTyped,
+    NpyHeader,
+    npy::{FieldLayout, check_descr, field_layout},
+};
+
+const MAGIC: &[u8; 8] = b"HBTCOL\x01\x00";
+
+/// The number of rows in a block.
+const BLOCK_ROWS: usize = 65536;
+
+/// The maximum number of distinct values to be dictionary-encoded.
+const MAX_DICT_LEN: usize = 256;
+
+/// The maximum number of decimal places of a float column to be encoded as scaled integers.
+const MAX_DECIMALS: u8 = 12;
+
+/// Stores the values as they are.
+const ENCODING_RAW: u8 = 0;
+/// Stores the first value and the deltas between consecutive values as zigzag varints.
+const ENCODING_DELTA: u8 = 1;
+/// Stores the distinct values and an index into them per row.
+const ENCODING_DICT: u8 = 2;
+/// Stores the float values multiplied by a power of ten as integers, using the delta encoding.
+const ENCODING_DECIMAL: u8 = 3;
+
+/// The block compression for [`write_columnar`].
+#[derive(Clone, Copy, Debug)]
+pub enum Compression {
+    /// LZ4, which is faster to decode.
+    Lz4,
+    /// Zstandard with the compression level, which yields smaller files.
+    Zstd(i32),
+}
+
+impl Compression {
+    fn id(&self) -> u8 {
+        match self {
+            Compression::Lz4 => 0,
+            Compression::Zstd(_) => 1,
+        }
+    }
+
+    fn compress(&self, buf: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::Lz4 => Ok(lz4_flex::compress(buf)),
+            Compression::Zstd(level) => zstd::bulk::compress(buf, *level),
+        }
+    }
+}
+
+fn decompress(compression: u8, buf: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+    match compression {
+        0 => lz4_flex::decompress(buf, len)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string())),
+        1 => zstd::bulk::decompress(buf, len),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            "unsupported compression",
+        )),
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn encode_delta(buf: &mut Vec<u8>, values: &[i64]) {
+    let mut prev = 0i64;
+    for &value in values {
+        write_varint(buf, zigzag(value.wrapping_sub(prev)));
+        prev = value;
+    }
+}
+
+/// Returns the number of decimal places with which all values can be exactly restored from the
+/// scaled integers, if any.
+fn find_decimals(values: &[f64]) -> Option<u8> {
+    (0..=MAX_DECIMALS).find(|&decimals| {
+        let scale = 10f64.powi(decimals as i32);
+        values.iter().all(|&value| {
+            let scaled = (value * scale).round();
+            scaled.abs() < (1i64 << 53) as f64 && (scaled / scale).to_bits() == value.to_bits()
+        })
+    })
+}
+
+/// Encodes a column of the block, choosing the encoding by the type and the values.
+fn encode_column(buf: &mut Vec<u8>, layout: &FieldLayout, column: &[u8]) {
+    let values = || {
+        column
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    if layout.width == 8 {
+        let mut dict = HashMap::new();
+        for value in values() {
+            let len = dict.len();
+            dict.entry(value).or_insert(len);
+            if dict.len() > MAX_DICT_LEN {
+                break;
+            }
+        }
+        if dict.len() <= MAX_DICT_LEN {
+            buf.push(ENCODING_DICT);
+            let mut dict_values = vec![0u64; dict.len()];
+            for (&value, &index) in &dict {
+                dict_values[index] = value;
+            }
+            write_varint(buf, dict_values.len() as u64);
+            for value in dict_values {
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+            buf.extend(values().map(|value| *dict.get(&value).unwrap() as u8));
+            return;
+        }
+
+        match &layout.field.ty[1..] {
+            "i8" | "u8" => {
+                buf.push(ENCODING_DELTA);
+                encode_delta(buf, &values().map(|value| value as i64).collect::<Vec<_>>());
+                return;
+            }
+            "f8" => {
+                let floats: Vec<_> = values().map(f64::from_bits).collect();
+                if let Some(decimals) = find_decimals(&floats) {
+                    let scale = 10f64.powi(decimals as i32);
+                    buf.push(ENCODING_DECIMAL);
+                    buf.push(decimals);
+                    encode_delta(
+                        buf,
+                        &floats
+                            .iter()
+                            .map(|value| (value * scale).round() as i64)
+                            .collect::<Vec<_>>(),
+                    );
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+    buf.push(ENCODING_RAW);
+    buf.extend_from_slice(column);
+}
+
+/// Writes a structured array in the columnar compressed format, which is much smaller than the
+/// `numpy` format and fast to decode.
+///
+/// The rows are stored in blocks of up to 65,536 rows, each of which is compressed after encoding
+/// each column of 8-byte fields in the most compact of the following ways, falling back to the
+/// raw values.
+/// * Dictionary encoding, if the column has at most 256 distinct values in the block, such as
+///   `ev` flags.
+/// * Delta encoding for the integers, such as timestamps.
+/// * Delta encoding of the integers scaled by a power of ten for the floats that can be exactly
+///   restored, such as prices and quantities quantized to the tick size and the lot size.
+///
+/// The file can be read by [`read_columnar_file`], or used as a
+/// [`DataSource::File`](crate::backtest::data::DataSource::File) with the `.hbtc` extension.
+pub fn write_columnar<W: Write, D: NpyDTyped>(
+    write: &mut W,
+    data: &[D],
+    compression: Compression,
+) -> std::io::Result<()> {
+    let header = NpyHeader {
+        descr: D::descr(),
+        fortran_order: false,
+        shape: vec![data.len()],
+    };
+    let header_str = header.to_string_padding();
+    write.write_all(MAGIC)?;
+    write.write_all(&(header_str.len() as u32).to_le_bytes())?;
+    write.write_all(header_str.as_bytes())?;
+    write.write_all(&[compression.id()])?;
+
+    let layout = field_layout::<D>()?;
+    let size = size_of::<D>();
+    for rows in data.chunks(BLOCK_ROWS) {
+        let bytes =
+            unsafe { std::slice::from_raw_parts(rows.as_ptr() as *const u8, size_of_val(rows)) };
+        let mut buf = Vec::new();
+        let mut column = Vec::with_capacity(rows.len() * 8);
+        for field in &layout {
+            column.clear();
+            for row in bytes.chunks_exact(size) {
+                column.extend_from_slice(&row[field.offset..(field.offset + field.width)]);
+            }
+            encode_column(&mut buf, field, &column);
+        }
+        let compressed = compression.compress(&buf)?;
+        write.write_all(&(rows.len() as u32).to_le_bytes())?;
+        write.write_all(&(buf.len() as u32).to_le_bytes())?;
+        write.write_all(&(compressed.len() as u32).to_le_bytes())?;
+        write.write_all(&compressed)?;
+    }
+    Ok(())
+}
+
+/// Reads the encoded block, checking the bounds.
+struct BlockReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BlockReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .buf
+            .get(self.pos..(self.pos + len))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "block is truncated"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::new(ErrorKind::InvalidData, "invalid varint"))
+    }
+
+    fn delta(&mut self, rows: usize, mut f: impl FnMut(usize, i64)) -> Result<(), Error> {
+        let mut value = 0i64;
+        for row in 0..rows {
+            value = value.wrapping_add(unzigzag(self.varint()?));
+            f(row, value);
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the columns of the block into the rows.
+fn decode_block(
+    block: &mut BlockReader,
+    layout: &[FieldLayout],
+    rows: usize,
+    buf: &mut [u8],
+    size: usize,
+) -> Result<(), Error> {
+    for field in layout {
+        let offset = field.offset;
+        let width = field.width;
+        let mut put = |row: usize, bytes: &[u8]| {
+            let dst = row * size + offset;
+            buf[dst..(dst + width)].copy_from_slice(bytes);
+        };
+        match block.u8()? {
+            ENCODING_RAW => {
+                let column = block.bytes(rows * width)?;
+                for row in 0..rows {
+                    put(row, &column[(row * width)..((row + 1) * width)]);
+                }
+            }
+            ENCODING_DELTA if width == 8 => {
+                block.delta(rows, |row, value| put(row, &value.to_le_bytes()))?;
+            }
+            ENCODING_DICT if width == 8 => {
+                let len = block.varint()? as usize;
+                let dict = block.bytes(len * 8)?;
+                for (row, &index) in block.bytes(rows)?.iter().enumerate() {
+                    let index = index as usize;
+                    if index >= len {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "dictionary index is out of range",
+                        ));
+                    }
+                    put(row, &dict[(index * 8)..((index + 1) * 8)]);
+                }
+            }
+            ENCODING_DECIMAL if width == 8 => {
+                let scale = 10f64.powi(block.u8()? as i32);
+                block.delta(rows, |row, value| {
+                    put(row, &(value as f64 / scale).to_le_bytes())
+                })?;
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unsupported encoding of field `{}`", field.field.name),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a file in the columnar compressed format written by [`write_columnar`].
+pub fn read_columnar_file<D: NpyDTyped + Clone>(filepath: &str) -> std::io::Result<Data<D>> {
+    let mut buf = Vec::new();
+    File::open(filepath)?.read_to_end(&mut buf)?;
+    let mut file = BlockReader { buf: &buf, pos: 0 };
+
+    if file.bytes(MAGIC.len())? != MAGIC {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "must start with HBTCOL version 1.0",
+        ));
+    }
+    let header_len = file.u32()? as usize;
+    let header = String::from_utf8(file.bytes(header_len)?.to_vec())
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+    let header = NpyHeader::from_header(&header)?;
+    check_descr::<D>(&header.descr)?;
+    let len = match header.shape.as_slice() {
+        [len] => *len,
+        _ => {
+            return Err(Error::new(ErrorKind::InvalidData, "only 1-d is supported"));
+        }
+    };
+    let compression = file.u8()?;
+    if len == 0 {
+        return Ok(Data::empty());
+    }
+
+    let layout = field_layout::<D>()?;
+    let size = size_of::<D>();
+    let mut data = DataPtr::new(len * size);
+    data[..].fill(0);
+    let mut row = 0;
+    while row < len {
+        let rows = file.u32()? as usize;
+        let raw_len = file.u32()? as usize;
+        let compressed_len = file.u32()? as usize;
+        if rows == 0 || row + rows > len {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid block size"));
+        }
+        let raw = decompress(compression, file.bytes(compressed_len)?, raw_len)?;
+        let mut block = BlockReader { buf: &raw, pos: 0 };
+        decode_block(
+            &mut block,
+            &layout,
+            rows,
+            &mut data[(row * size)..((row + rows) * size)],
+            size,
+        )?;
+        row += rows;
+    }
+
+    let data = unsafe { Data::from_data_ptr(data, 0) };
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::{Compression, DataSource, Reader, read_columnar_file, write_columnar},
+        types::{BUY_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT, TRADE_EVENT},
+    };
+
+    #[test]
+    fn write_and_read_columnar() -> Result<(), Box<dyn std::error::Error>> {
+        let events: Vec<_> = (0..200_000i64)
+            .map(|i| Event {
+                ev: EXCH_EVENT
+                    | LOCAL_EVENT
+                    | TRADE_EVENT
+                    | if i % 3 == 0 { BUY_EVENT } else { SELL_EVENT },
+                exch_ts: 1_700_000_000_000_000_000 + i * 1_000_123,
+                local_ts: 1_700_000_000_000_000_000 + i * 1_000_123 + 1_500_000 + i % 7,
+                px: 60000.0 + (i % 100) as f64 * 0.1,
+                qty: 0.001 * (i % 13 + 1) as f64,
+                order_id: 0,
+                ival: i,
+                fval: (i as f64).sqrt(),
+            })
+            .collect();
+
+        for (name, compression) in [("lz4", Compression::Lz4), ("zstd", Compression::Zstd(3))] {
+            let filepath =
+                std::env::temp_dir().join(format!("columnar_{name}_{}.hbtc", std::process::id()));
+            let filepath = filepath.to_str().unwrap().to_string();
+            write_columnar(&mut std::fs::File::create(&filepath)?, &events, compression)?;
+            assert!(std::fs::metadata(&filepath)?.len() < (size_of_val(&events[..]) / 5) as u64);
+
+            let data = read_columnar_file::<Event>(&filepath)?;
+            assert_eq!(data.len(), events.len());
+            for (i, event) in events.iter().enumerate() {
+                assert_eq!(&data[i], event);
+            }
+
+            let mut reader = Reader::<Event>::builder()
+                .data(vec![DataSource::File(filepath.clone())])
+                .build()?;
+            let data = reader.next_data()?;
+            assert_eq!(data.len(), events.len());
+            reader.release(data);
+
+            std::fs::remove_file(&filepath)?;
+        }
+        Ok(())
+    }
+}
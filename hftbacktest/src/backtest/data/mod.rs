@@ -1,3 +1,7 @@
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "columnar")]
+mod columnar;
 mod npy;
 mod reader;
 
@@ -5,13 +9,40 @@ use std::{
     marker::PhantomData,
     mem::size_of,
     ops::{Index, IndexMut},
-    ptr::null_mut,
+    ptr::{null_mut, slice_from_raw_parts_mut},
     rc::Rc,
     slice::SliceIndex,
+    sync::Arc,
 };
 
-pub use npy::{Field, NpyDTyped, NpyHeader, read_npy_file, read_npz_file, write_npy};
-pub use reader::{Cache, DataPreprocess, DataSource, FeedLatencyAdjustment, Reader, ReaderBuilder};
+#[cfg(feature = "arrow")]
+pub use arrow::{ColumnMapping, read_arrow_ipc_file, read_parquet_file};
+#[cfg(feature = "columnar")]
+pub use columnar::{Compression, read_columnar_file, write_columnar};
+pub use npy::{
+    Field,
+    NpyDTyped,
+    NpyHeader,
+    read_npy_file,
+    read_npy_mmap,
+    read_npz_file,
+    write_npy,
+};
+pub use reader::{
+    Cache,
+    DataPreprocess,
+    DataSource,
+    EventFilter,
+    FeedLatencyAdjustment,
+    PriceQtyScale,
+    Reader,
+    ReaderBuilder,
+    SyntheticFeedLatency,
+    TimestampShift,
+    read_data_source,
+};
+
+use memmap2::MmapMut;
 
 use crate::utils::{AlignedArray, CACHE_LINE_SIZE};
 
@@ -102,6 +133,22 @@ where
     pub fn data_eq(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.ptr, &other.ptr)
     }
+
+    /// Returns `true` if this refers to the memory of [`SharedData`], which must not be written to.
+    pub(crate) fn is_shared(&self) -> bool {
+        self.ptr._shared.is_some()
+    }
+
+    /// Returns a copy of the data that owns its memory.
+    pub(crate) fn copy(&self) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+        // SAFETY: The rows are within the data.
+        let rows =
+            unsafe { std::slice::from_raw_parts(self.get_unchecked(0) as *const D, self.len()) };
+        Self::from_data(rows)
+    }
 }
 
 impl<D> Index<usize> for Data<D>
@@ -139,6 +186,10 @@ where
 pub struct DataPtr {
     ptr: *mut [u8],
     managed: bool,
+    // Keeps the memory-mapped region alive, if any.
+    _mmap: Option<MmapMut>,
+    // Keeps the shared memory alive, if any.
+    _shared: Option<Arc<SharedDataPtr>>,
 }
 
 impl DataPtr {
@@ -147,6 +198,19 @@ impl DataPtr {
         Self {
             ptr: arr.into_raw(),
             managed: true,
+            _mmap: None,
+            _shared: None,
+        }
+    }
+
+    /// Constructs a `DataPtr` over a memory-mapped region, which is unmapped when the resulting
+    /// `DataPtr` is dropped.
+    pub fn from_mmap(mut mmap: MmapMut) -> Self {
+        Self {
+            ptr: slice_from_raw_parts_mut(mmap.as_mut_ptr(), mmap.len()),
+            managed: false,
+            _mmap: Some(mmap),
+            _shared: None,
         }
     }
 
@@ -161,6 +225,8 @@ impl DataPtr {
         Self {
             ptr,
             managed: false,
+            _mmap: None,
+            _shared: None,
         }
     }
 
@@ -182,6 +248,8 @@ impl Default for DataPtr {
         Self {
             ptr: null_mut::<[u8; 0]>() as *mut [u8],
             managed: false,
+            _mmap: None,
+            _shared: None,
         }
     }
 }
@@ -217,3 +285,84 @@ impl Drop for DataPtr {
         }
     }
 }
+
+/// [`DataPtr`] of [`SharedData`], which is only read once shared.
+#[derive(Debug)]
+struct SharedDataPtr(DataPtr);
+
+// SAFETY: The memory isn't written to after it's shared, and the `DataPtr` is only dropped when
+// the last reference is released.
+unsafe impl Send for SharedDataPtr {}
+unsafe impl Sync for SharedDataPtr {}
+
+/// Read-only data that can be sent to and shared between threads, such as the feed data shared by
+/// the backtests of a parameter sweep running in parallel.
+///
+/// Loading it through [`DataSource::Shared`] doesn't copy the memory, unless a preprocessor needs
+/// to modify the data.
+#[derive(Debug)]
+pub struct SharedData<D>
+where
+    D: POD + Clone,
+{
+    ptr: Arc<SharedDataPtr>,
+    offset: usize,
+    _d_marker: PhantomData<D>,
+}
+
+impl<D> SharedData<D>
+where
+    D: POD + Clone,
+{
+    /// Constructs `SharedData` from the data. The memory is taken over if the data isn't referred
+    /// to elsewhere; otherwise, it's copied.
+    pub fn new(data: Data<D>) -> Self {
+        let data = if Rc::strong_count(&data.ptr) == 1 && !data.is_shared() {
+            data
+        } else {
+            data.copy()
+        };
+        let offset = data.offset;
+        let ptr = Rc::try_unwrap(data.ptr).unwrap();
+        Self {
+            ptr: Arc::new(SharedDataPtr(ptr)),
+            offset,
+            _d_marker: PhantomData,
+        }
+    }
+
+    /// Returns the length of the array.
+    pub fn len(&self) -> usize {
+        (self.ptr.0.len() - self.offset) / size_of::<D>()
+    }
+
+    /// Returns `true` if the `SharedData` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns [`Data`] that refers to the shared memory.
+    pub(crate) fn to_data(&self) -> Data<D> {
+        let ptr = DataPtr {
+            ptr: self.ptr.0.ptr,
+            managed: false,
+            _mmap: None,
+            _shared: Some(self.ptr.clone()),
+        };
+        // SAFETY: The memory layout is the same as the one of the data this is constructed from.
+        unsafe { Data::from_data_ptr(ptr, self.offset) }
+    }
+}
+
+impl<D> Clone for SharedData<D>
+where
+    D: POD + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            ptr: self.ptr.clone(),
+            offset: self.offset,
+            _d_marker: PhantomData,
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::{
     io::{Error, ErrorKind, Read, Write},
 };
 
+use memmap2::MmapOptions;
+
 use crate::{
     backtest::data::{Data, DataPtr, POD, npy::parser::Value},
     utils::CACHE_LINE_SIZE,
@@ -118,7 +120,7 @@ impl NpyHeader {
         })
     }
 
-    fn to_string_padding(&self) -> String {
+    pub(crate) fn to_string_padding(&self) -> String {
         let descr = self.descr();
         let fortran_order = self.fortran_order();
         let shape = self.shape();
@@ -165,18 +167,61 @@ fn check_field_consistency(
     Ok(discrepancies)
 }
 
-pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
-    reader: &mut R,
-    size: usize,
-) -> std::io::Result<Data<D>> {
-    let mut buf = DataPtr::new(size);
+/// Validates the fields of the file against the data structure `D`. Only the field types need to
+/// match; a field name mismatch is warned.
+pub(crate) fn check_descr<D: NpyDTyped>(descr: &DType) -> std::io::Result<()> {
+    if D::descr() != *descr {
+        match check_field_consistency(&D::descr(), descr) {
+            Ok(diff) => {
+                println!("Warning: Field name mismatch - {diff:?}");
+            }
+            Err(err) => {
+                return Err(Error::new(ErrorKind::InvalidData, err));
+            }
+        }
+    }
+    Ok(())
+}
 
-    let mut read_size = 0;
-    while read_size < size {
-        read_size += reader.read(&mut buf[read_size..])?;
+/// The location of a field within a row of the data structure.
+#[cfg(any(feature = "arrow", feature = "columnar"))]
+pub(crate) struct FieldLayout {
+    pub field: Field,
+    pub offset: usize,
+    pub width: usize,
+}
+
+/// Returns the layout of the fields of the `#[repr(C)]` data structure `D`.
+#[cfg(any(feature = "arrow", feature = "columnar"))]
+pub(crate) fn field_layout<D: NpyDTyped>() -> std::io::Result<Vec<FieldLayout>> {
+    let mut layout = Vec::new();
+    let mut offset = 0usize;
+    for field in D::descr() {
+        let width = field
+            .ty
+            .get(2..)
+            .and_then(|width| width.parse::<usize>().ok())
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("field `{}: {}` is unsupported", field.name, field.ty),
+                )
+            })?;
+        offset = offset.next_multiple_of(width);
+        layout.push(FieldLayout {
+            field,
+            offset,
+            width,
+        });
+        offset += width;
     }
+    Ok(layout)
+}
 
-    if buf[0..6].to_vec() != b"\x93NUMPY" {
+/// Validates the `numpy` file header in the buffer against the data structure `D`, and returns
+/// the offset where the data begins.
+fn check_npy_header<D: NpyDTyped>(buf: &[u8]) -> std::io::Result<usize> {
+    if buf.len() < 10 || buf[0..6].to_vec() != b"\x93NUMPY" {
         return Err(Error::new(
             ErrorKind::InvalidData,
             "must start with \\x93NUMPY",
@@ -189,6 +234,9 @@ pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
         ));
     }
     let header_len = u16::from_le_bytes(buf[8..10].try_into().unwrap()) as usize;
+    if buf.len() < 10 + header_len {
+        return Err(Error::new(ErrorKind::InvalidData, "header is truncated"));
+    }
     let header = String::from_utf8(buf[10..(10 + header_len)].to_vec())
         .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
     let header = NpyHeader::from_header(&header).unwrap();
@@ -200,16 +248,7 @@ pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
         ));
     }
 
-    if D::descr() != header.descr {
-        match check_field_consistency(&D::descr(), &header.descr) {
-            Ok(diff) => {
-                println!("Warning: Field name mismatch - {diff:?}");
-            }
-            Err(err) => {
-                return Err(Error::new(ErrorKind::InvalidData, err));
-            }
-        }
-    }
+    check_descr::<D>(&header.descr)?;
 
     if header.shape.len() != 1 {
         return Err(Error::new(ErrorKind::InvalidData, "only 1-d is supported"));
@@ -222,7 +261,22 @@ pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
         ));
     }
 
-    let data = unsafe { Data::from_data_ptr(buf, 10 + header_len) };
+    Ok(10 + header_len)
+}
+
+pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
+    reader: &mut R,
+    size: usize,
+) -> std::io::Result<Data<D>> {
+    let mut buf = DataPtr::new(size);
+
+    let mut read_size = 0;
+    while read_size < size {
+        read_size += reader.read(&mut buf[read_size..])?;
+    }
+
+    let offset = check_npy_header::<D>(&buf[..])?;
+    let data = unsafe { Data::from_data_ptr(buf, offset) };
     Ok(data)
 }
 
@@ -237,6 +291,21 @@ pub fn read_npy_file<D: NpyDTyped + Clone>(filepath: &str) -> std::io::Result<Da
     read_npy(&mut file, size)
 }
 
+/// Memory-maps a structured array `numpy` file without reading it into memory, so that the pages
+/// are loaded from the file on demand and the data is shared with the OS page cache.
+///
+/// The mapping is copy-on-write, so modifications, such as by a
+/// [`DataPreprocess`](crate::backtest::data::DataPreprocess), are private to the resulting `Data`
+/// and are not written back to the file. The file must not be modified while it is mapped.
+pub fn read_npy_mmap<D: NpyDTyped + Clone>(filepath: &str) -> std::io::Result<Data<D>> {
+    let file = File::open(filepath)?;
+    // SAFETY: The mapping is private, and the file is required not to be modified while mapped.
+    let mmap = unsafe { MmapOptions::new().map_copy(&file)? };
+    let offset = check_npy_header::<D>(&mmap)?;
+    let data = unsafe { Data::from_data_ptr(DataPtr::from_mmap(mmap), offset) };
+    Ok(data)
+}
+
 /// Reads a structured array `numpy` zip archived file. Currently, it doesn't check if the data
 /// structure is the same as what the file contains. Users should be cautious about this.
 pub fn read_npz_file<D: NpyDTyped + Clone>(filepath: &str, name: &str) -> std::io::Result<Data<D>> {
@@ -248,6 +317,51 @@ pub fn read_npz_file<D: NpyDTyped + Clone>(filepath: &str, name: &str) -> std::i
     read_npy(&mut file, size)
 }
 
+/// Opens a structured array `numpy` file, or the `data` array in a `numpy` zip archived file, and
+/// calls `f` with the reader and the size of the array file.
+pub(crate) fn with_npy_reader<T>(
+    filepath: &str,
+    f: impl FnOnce(&mut dyn Read, usize) -> std::io::Result<T>,
+) -> std::io::Result<T> {
+    if filepath.ends_with(".npz") {
+        let mut archive = zip::ZipArchive::new(File::open(filepath)?)?;
+        let mut file = archive.by_name("data.npy")?;
+        let size = file.size() as usize;
+        f(&mut file, size)
+    } else {
+        let mut file = File::open(filepath)?;
+        let size = file.metadata()?.len() as usize;
+        f(&mut file, size)
+    }
+}
+
+/// Reads and validates the header of a structured array `numpy` file of `size` bytes, and returns
+/// the number of rows. The reader is left at the beginning of the data.
+pub(crate) fn read_npy_header<R: Read + ?Sized, D: NpyDTyped>(
+    reader: &mut R,
+    size: usize,
+) -> std::io::Result<usize> {
+    let mut buf = vec![0; 10];
+    reader.read_exact(&mut buf)?;
+    let header_len = u16::from_le_bytes(buf[8..10].try_into().unwrap()) as usize;
+    buf.resize(10 + header_len, 0);
+    reader.read_exact(&mut buf[10..])?;
+    let offset = check_npy_header::<D>(&buf)?;
+    Ok(size.saturating_sub(offset) / size_of::<D>())
+}
+
+/// Reads the next `rows` rows from a reader positioned within the data of a structured array
+/// `numpy` file.
+pub(crate) fn read_npy_rows<R: Read + ?Sized, D: NpyDTyped + Clone>(
+    reader: &mut R,
+    rows: usize,
+) -> std::io::Result<Data<D>> {
+    let mut buf = DataPtr::new(rows * size_of::<D>());
+    reader.read_exact(&mut buf[..])?;
+    let data = unsafe { Data::from_data_ptr(buf, 0) };
+    Ok(data)
+}
+
 pub fn write_npy<W: Write, T: NpyDTyped>(write: &mut W, data: &[T]) -> std::io::Result<()> {
     let descr = T::descr();
     let header = NpyHeader {
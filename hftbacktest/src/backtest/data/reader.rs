@@ -1,7 +1,9 @@
 use std::{
     cell::RefCell,
-    collections::HashMap,
-    io::{Error as IoError, ErrorKind},
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{Error as IoError, ErrorKind, Read, Seek, SeekFrom},
+    ops::Range,
     rc::Rc,
     sync::{
         Arc,
@@ -10,18 +12,37 @@ use std::{
     thread,
 };
 
+use rand::{SeedableRng, distr::Distribution, rngs::StdRng};
 use uuid::Uuid;
 
+#[cfg(feature = "columnar")]
+use crate::backtest::data::read_columnar_file;
+#[cfg(feature = "arrow")]
+use crate::backtest::data::{
+    ColumnMapping,
+    arrow::ArrowSource,
+    read_arrow_ipc_file,
+    read_parquet_file,
+};
 use crate::{
     backtest::{
         BacktestError,
         data::{
             Data,
             POD,
-            npy::{NpyDTyped, read_npy_file, read_npz_file},
+            SharedData,
+            npy::{
+                NpyDTyped,
+                read_npy_file,
+                read_npy_header,
+                read_npy_mmap,
+                read_npy_rows,
+                read_npz_file,
+                with_npy_reader,
+            },
         },
     },
-    types::Event,
+    types::{DEPTH_CLEAR_EVENT, DEPTH_SNAPSHOT_EVENT, Event},
 };
 
 /// Data source for the [`Reader`].
@@ -30,13 +51,47 @@ pub enum DataSource<D>
 where
     D: POD + Clone,
 {
-    /// Data needs to be loaded from the specified file. This should be a `numpy` file.
+    /// Data needs to be loaded from the specified file. This should be a `numpy` file, or a
+    /// columnar compressed file with the `.hbtc` extension if the `columnar` feature is enabled.
     ///
     /// It will be loaded when needed and released
     /// when no [Processor](`crate::backtest::proc::Processor`) is reading the data.
     File(String),
+    /// Data needs to be memory-mapped from the specified file, instead of being read into memory.
+    /// This should be an uncompressed `numpy` file.
+    ///
+    /// Like [`DataSource::File`], it will be mapped when needed and unmapped when no
+    /// [Processor](`crate::backtest::proc::Processor`) is reading the data. See [`read_npy_mmap`]
+    /// for details.
+    Mmap(String),
+    /// Data needs to be streamed from the specified file in chunks of the specified number of
+    /// rows, so that a file larger than memory can be used. This should be a `numpy` file.
+    ///
+    /// Each chunk is read when needed, on a loader thread that keeps the file open, and is
+    /// released when no [Processor](`crate::backtest::proc::Processor`) is reading it. Processors
+    /// move across chunk boundaries as they do across files.
+    Chunked(String, usize),
+    /// Data needs to be loaded from the specified Parquet file, whose columns are mapped onto the
+    /// fields of the data by the [`ColumnMapping`].
+    ///
+    /// Each row group is loaded as separate data when needed, reading only the mapped columns, and
+    /// is prefetched if parallel loading is enabled. The schema is validated when the [`Reader`]
+    /// is built.
+    #[cfg(feature = "arrow")]
+    Parquet(String, ColumnMapping),
+    /// Data needs to be loaded from the specified Arrow IPC file, whose columns are mapped onto
+    /// the fields of the data by the [`ColumnMapping`].
+    ///
+    /// Like [`DataSource::Parquet`], each record batch is loaded as separate data.
+    #[cfg(feature = "arrow")]
+    ArrowIpc(String, ColumnMapping),
     /// Data is loaded and set by the user.
     Data(Data<D>),
+    /// Data is loaded by the user and shared with other backtests, possibly on other threads.
+    ///
+    /// The data is used without being copied, unless there is a preprocessor that needs to modify
+    /// it.
+    Shared(SharedData<D>),
 }
 
 #[derive(Debug)]
@@ -185,16 +240,18 @@ where
 {
     key: String,
     result: Result<DataSend<D>, IoError>,
+    end_of_range: bool,
 }
 
 impl<D> LoadDataResult<D>
 where
     D: NpyDTyped + Clone,
 {
-    pub fn ok(key: String, data: Data<D>) -> Self {
+    pub fn ok(key: String, data: Data<D>, end_of_range: bool) -> Self {
         Self {
             key,
             result: Ok(DataSend(data)),
+            end_of_range,
         }
     }
 
@@ -202,6 +259,127 @@ where
         Self {
             key,
             result: Err(error),
+            end_of_range: false,
+        }
+    }
+}
+
+/// The time range of the data to be read, with the function selecting the rows to be read.
+struct TimeRange<D>
+where
+    D: POD + Clone,
+{
+    start: i64,
+    end: i64,
+    select: fn(&Data<D>, i64, i64) -> Range<usize>,
+    /// Returns the timestamp by which the rows are ordered.
+    timestamp: fn(&D) -> i64,
+}
+
+impl<D> Clone for TimeRange<D>
+where
+    D: POD + Clone,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for TimeRange<D> where D: POD + Clone {}
+
+impl<D> TimeRange<D>
+where
+    D: POD + Clone,
+{
+    /// Returns the selected rows of the data, and whether the data reaches the end of the range.
+    fn apply(&self, data: Data<D>) -> (Data<D>, bool) {
+        let range = (self.select)(&data, self.start, self.end);
+        let end_of_range = range.end < data.len();
+        if range.len() == data.len() {
+            (data, end_of_range)
+        } else if range.is_empty() {
+            (Data::empty(), end_of_range)
+        } else {
+            // SAFETY: The range is within the data.
+            let rows = unsafe {
+                std::slice::from_raw_parts(data.get_unchecked(range.start) as *const D, range.len())
+            };
+            (Data::from_data(rows), end_of_range)
+        }
+    }
+}
+
+/// Selects the rows whose `local_ts` is within the range, together with the preceding rows from
+/// the nearest depth snapshot before the start, so that the market depth can be built correctly.
+/// If there is no snapshot before the start in the data, all preceding rows are selected, unless
+/// the data lies entirely before the start.
+///
+/// The rows are scanned rather than searched, since the exchange-only rows interleaved in the data
+/// may not be in `local_ts` order.
+fn select_event_range(data: &Data<Event>, start: i64, end: i64) -> Range<usize> {
+    let Some(first) = (0..data.len()).find(|&row| data[row].local_ts >= start) else {
+        return data.len()..data.len();
+    };
+    let last = (first..data.len())
+        .rev()
+        .find(|&row| data[row].local_ts < end)
+        .map_or(first, |row| row + 1);
+
+    let is_snapshot = |row: usize| {
+        matches!(
+            data[row].ev & 0xff,
+            DEPTH_CLEAR_EVENT | DEPTH_SNAPSHOT_EVENT
+        )
+    };
+    let from = match (0..first).rev().find(|&row| is_snapshot(row)) {
+        Some(mut row) => {
+            while row > 0 && is_snapshot(row - 1) {
+                row -= 1;
+            }
+            row
+        }
+        None => 0,
+    };
+    from..last
+}
+
+/// Processes the loaded data on the loader thread before it is fed into backtesting.
+struct Processing<D>
+where
+    D: POD + Clone,
+{
+    preprocessors: Vec<Arc<dyn DataPreprocess<D> + Sync + Send + 'static>>,
+    time_range: Option<TimeRange<D>>,
+}
+
+impl<D> Clone for Processing<D>
+where
+    D: POD + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            preprocessors: self.preprocessors.clone(),
+            time_range: self.time_range,
+        }
+    }
+}
+
+impl<D> Processing<D>
+where
+    D: POD + Clone,
+{
+    /// Returns the processed data, and whether the data reaches the end of the time range.
+    fn apply(&self, key: &str, mut data: Data<D>) -> Result<(Data<D>, bool), IoError> {
+        // The shared data is read-only, while the preprocessors modify the data in place.
+        if !self.preprocessors.is_empty() && data.is_shared() {
+            data = data.copy();
+        }
+        for preprocessor in &self.preprocessors {
+            preprocessor.preprocess_keyed(key, &mut data)?;
+        }
+        match &self.time_range {
+            Some(time_range) => Ok(time_range.apply(data)),
+            None => Ok((data, false)),
         }
     }
 }
@@ -212,10 +390,15 @@ where
     D: NpyDTyped + POD + Clone,
 {
     data_key_list: Vec<String>,
+    mmap_keys: HashSet<String>,
+    chunk_rows: HashMap<String, usize>,
+    #[cfg(feature = "arrow")]
+    arrow_sources: HashMap<String, ArrowSource>,
     cache: Cache<D>,
     temporary_data: HashMap<String, Data<D>>,
     parallel_load: bool,
-    preprocessor: Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
+    preprocessors: Vec<Arc<dyn DataPreprocess<D> + Sync + Send + 'static>>,
+    time_range: Option<TimeRange<D>>,
 }
 
 impl<D> Default for ReaderBuilder<D>
@@ -225,10 +408,15 @@ where
     fn default() -> Self {
         Self {
             data_key_list: Default::default(),
+            mmap_keys: Default::default(),
+            chunk_rows: Default::default(),
+            #[cfg(feature = "arrow")]
+            arrow_sources: Default::default(),
             cache: Default::default(),
             temporary_data: Default::default(),
             parallel_load: false,
-            preprocessor: None,
+            preprocessors: Vec::new(),
+            time_range: None,
         }
     }
 }
@@ -255,13 +443,24 @@ where
         }
     }
 
-    /// Sets a [`DataPreprocess`].
+    /// Adds a [`DataPreprocess`]. Multiple preprocessors can be added, and they are applied to
+    /// the data in the order in which they are added.
     pub fn preprocessor<Preprocessor>(self, preprocessor: Preprocessor) -> Self
     where
         Preprocessor: DataPreprocess<D> + Sync + Send + 'static,
     {
+        self.shared_preprocessor(Arc::new(preprocessor))
+    }
+
+    /// Adds a [`DataPreprocess`] that is shared with other readers.
+    pub fn shared_preprocessor(
+        self,
+        preprocessor: Arc<dyn DataPreprocess<D> + Sync + Send + 'static>,
+    ) -> Self {
+        let mut preprocessors = self.preprocessors;
+        preprocessors.push(preprocessor);
         Self {
-            preprocessor: Some(Arc::new(Box::new(preprocessor))),
+            preprocessors,
             ..self
         }
     }
@@ -270,21 +469,52 @@ where
     /// the chronological order.
     pub fn data(self, data: Vec<DataSource<D>>) -> Self {
         let mut data_key_list = self.data_key_list;
+        let mut mmap_keys = self.mmap_keys;
+        let mut chunk_rows = self.chunk_rows;
+        #[cfg(feature = "arrow")]
+        let mut arrow_sources = self.arrow_sources;
         let mut temporary_data = self.temporary_data;
         for item in data {
             match item {
                 DataSource::File(filepath) => {
                     data_key_list.push(filepath);
                 }
+                DataSource::Mmap(filepath) => {
+                    data_key_list.push(filepath.clone());
+                    mmap_keys.insert(filepath);
+                }
+                DataSource::Chunked(filepath, rows) => {
+                    data_key_list.push(filepath.clone());
+                    chunk_rows.insert(filepath, rows);
+                }
+                #[cfg(feature = "arrow")]
+                DataSource::Parquet(filepath, mapping) => {
+                    data_key_list.push(filepath.clone());
+                    arrow_sources.insert(filepath, ArrowSource::Parquet(mapping));
+                }
+                #[cfg(feature = "arrow")]
+                DataSource::ArrowIpc(filepath, mapping) => {
+                    data_key_list.push(filepath.clone());
+                    arrow_sources.insert(filepath, ArrowSource::ArrowIpc(mapping));
+                }
                 DataSource::Data(data) => {
                     let key = Uuid::new_v4().to_string();
                     data_key_list.push(key.clone());
                     temporary_data.insert(key, data);
                 }
+                DataSource::Shared(data) => {
+                    let key = Uuid::new_v4().to_string();
+                    data_key_list.push(key.clone());
+                    temporary_data.insert(key, data.to_data());
+                }
             }
         }
         Self {
             data_key_list,
+            mmap_keys,
+            chunk_rows,
+            #[cfg(feature = "arrow")]
+            arrow_sources,
             temporary_data,
             ..self
         }
@@ -292,25 +522,151 @@ where
 
     /// Builds a [`Reader`].
     pub fn build(self) -> Result<Reader<D>, IoError> {
+        // Splits the chunked files into the chunks, which are read as separate data.
+        let mut data_key_list = Vec::new();
+        let mut chunks = HashMap::new();
+        for key in &self.data_key_list {
+            let Some(num_chunks) = self.num_chunks(key)? else {
+                data_key_list.push(key.clone());
+                continue;
+            };
+            for index in 0..num_chunks {
+                let chunk_key = chunk_key(key, index);
+                data_key_list.push(chunk_key.clone());
+                chunks.insert(
+                    chunk_key,
+                    Chunk {
+                        filepath: key.clone(),
+                        index,
+                    },
+                );
+            }
+        }
+
+        if let Some(time_range) = &self.time_range {
+            if self.preprocessors.is_empty() {
+                let mut keys = Vec::with_capacity(data_key_list.len());
+                for key in data_key_list {
+                    if !self.is_before(&key, chunks.get(&key), time_range)? {
+                        keys.push(key);
+                    }
+                }
+                data_key_list = keys;
+            }
+        }
+
+        let processing = Processing {
+            preprocessors: self.preprocessors.clone(),
+            time_range: self.time_range,
+        };
         let mut cache = self.cache.clone();
-        for (key, mut data) in self.temporary_data {
-            if let Some(p) = &self.preprocessor {
-                p.preprocess(&mut data)?;
+        let mut end_keys = HashSet::new();
+        for (key, data) in self.temporary_data {
+            // The key of the temporary data is random, so the position is used instead to keep
+            // the preprocessing reproducible.
+            let position = self.data_key_list.iter().position(|k| *k == key).unwrap();
+            let (data, end_of_range) = processing.apply(&format!("#{position}"), data)?;
+            if end_of_range {
+                end_keys.insert(key.clone());
             }
             cache.insert(key, data)
         }
 
         let (tx, rx) = channel();
         Ok(Reader {
-            data_key_list: self.data_key_list.clone(),
+            data_key_list,
+            mmap_keys: Rc::new(self.mmap_keys),
+            chunks: Rc::new(chunks),
+            chunk_rows: Rc::new(self.chunk_rows),
+            #[cfg(feature = "arrow")]
+            arrow_sources: Rc::new(self.arrow_sources),
+            streams: Default::default(),
             cache,
             data_num: 0,
             tx,
             rx: Rc::new(rx),
             parallel_load: self.parallel_load,
-            preprocessor: self.preprocessor.clone(),
+            processing,
+            start: self.time_range.map(|time_range| time_range.start),
+            end_keys: Rc::new(RefCell::new(end_keys)),
         })
     }
+
+    /// Returns whether the data of the key lies entirely before the start of the time range,
+    /// judging by the last row of an uncompressed `npy` file or chunk, which is read without
+    /// reading the whole data. Other data is judged after being read.
+    fn is_before(
+        &self,
+        key: &str,
+        chunk: Option<&Chunk>,
+        time_range: &TimeRange<D>,
+    ) -> Result<bool, IoError> {
+        let filepath = chunk.map_or(key, |chunk| chunk.filepath.as_str());
+        if !filepath.ends_with(".npy") {
+            return Ok(false);
+        }
+        let mut file = File::open(filepath)?;
+        let size = file.metadata()?.len() as usize;
+        let len = read_npy_header::<_, D>(&mut file, size)?;
+        let end = match chunk {
+            Some(chunk) => {
+                let rows = self.chunk_rows[filepath];
+                ((chunk.index + 1) * rows).min(len)
+            }
+            None => len,
+        };
+        if end == 0 {
+            return Ok(false);
+        }
+        file.seek(SeekFrom::Current(((end - 1) * size_of::<D>()) as i64))?;
+        let last = read_npy_rows::<_, D>(&mut file, 1)?;
+        Ok((time_range.timestamp)(&last[0]) < time_range.start)
+    }
+
+    /// Returns the number of chunks if the file is read in chunks.
+    fn num_chunks(&self, filepath: &str) -> Result<Option<usize>, IoError> {
+        if let Some(&rows) = self.chunk_rows.get(filepath) {
+            if rows == 0 {
+                return Err(IoError::new(
+                    ErrorKind::InvalidInput,
+                    "chunk size must be greater than zero",
+                ));
+            }
+            let len = with_npy_reader(filepath, |reader, size| {
+                read_npy_header::<_, D>(reader, size)
+            })?;
+            return Ok(Some(len.div_ceil(rows)));
+        }
+        #[cfg(feature = "arrow")]
+        if let Some(source) = self.arrow_sources.get(filepath) {
+            return source.num_chunks::<D>(filepath).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+impl ReaderBuilder<Event> {
+    /// Sets the time range of the data to be read, from `start` inclusive to `end` exclusive,
+    /// based on `local_ts`, by which the data is ordered. The rows before the nearest depth
+    /// snapshot preceding `start` and the rows from `end` are skipped within each data, and no
+    /// further data is read once `end` is reached. The rows between the snapshot and `start` are
+    /// kept so that the market depth is built correctly; the backtester processes them before the
+    /// strategy starts at `start`.
+    ///
+    /// The data entirely before `start` is skipped, so the snapshot must be in the data covering
+    /// `start`. Unless there are preprocessors, which may change the timestamps, an uncompressed
+    /// `npy` file or chunk entirely before `start` is skipped by its last row without being read.
+    pub fn time_range(self, start: i64, end: i64) -> Self {
+        Self {
+            time_range: Some(TimeRange {
+                start,
+                end,
+                select: select_event_range,
+                timestamp: |ev| ev.local_ts,
+            }),
+            ..self
+        }
+    }
 }
 
 /// Provides `Data` reading based on the given sequence of data through `Cache`.
@@ -320,12 +676,20 @@ where
     D: NpyDTyped + Clone,
 {
     data_key_list: Vec<String>,
+    mmap_keys: Rc<HashSet<String>>,
+    chunks: Rc<HashMap<String, Chunk>>,
+    chunk_rows: Rc<HashMap<String, usize>>,
+    #[cfg(feature = "arrow")]
+    arrow_sources: Rc<HashMap<String, ArrowSource>>,
+    streams: Rc<RefCell<HashMap<String, Sender<usize>>>>,
     cache: Cache<D>,
     data_num: usize,
     tx: Sender<LoadDataResult<D>>,
     rx: Rc<Receiver<LoadDataResult<D>>>,
     parallel_load: bool,
-    preprocessor: Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
+    processing: Processing<D>,
+    start: Option<i64>,
+    end_keys: Rc<RefCell<HashSet<String>>>,
 }
 
 impl<D> Reader<D>
@@ -337,6 +701,23 @@ where
         ReaderBuilder::default()
     }
 
+    /// Returns the start of the time range set by [`ReaderBuilder::time_range`], if any.
+    pub fn start(&self) -> Option<i64> {
+        self.start
+    }
+
+    /// Returns the position in the data list of the next [`Data`] to be retrieved by
+    /// [`next_data`](Self::next_data()).
+    pub fn position(&self) -> usize {
+        self.data_num
+    }
+
+    /// Moves to the position in the data list from which [`next_data`](Self::next_data()) retrieves
+    /// the [`Data`].
+    pub fn seek(&mut self, position: usize) {
+        self.data_num = position.min(self.data_key_list.len());
+    }
+
     /// Releases this [`Data`] from the `Cache`. The `Cache` will delete the [`Data`] if there are
     /// no readers accessing it.
     pub fn release(&mut self, data: Data<D>) {
@@ -361,7 +742,11 @@ where
                     LoadDataResult {
                         key,
                         result: Ok(data),
+                        end_of_range,
                     } => {
+                        if end_of_range {
+                            self.end_keys.borrow_mut().insert(key.clone());
+                        }
                         self.cache.set(&key, data.unwrap());
                     }
                     LoadDataResult {
@@ -373,7 +758,11 @@ where
             }
 
             let data = self.cache.get(&key);
-            self.data_num += 1;
+            if self.end_keys.borrow().contains(&key) {
+                self.data_num = self.data_key_list.len();
+            } else {
+                self.data_num += 1;
+            }
             Ok(data)
         } else {
             Err(BacktestError::EndOfData)
@@ -384,63 +773,188 @@ where
         if !self.cache.contains(key) {
             self.cache.prepare(key.to_string());
 
-            if key.ends_with(".npy") {
-                let tx = self.tx.clone();
-                let filepath = key.to_string();
-                let preprocessor = self.preprocessor.clone();
+            if let Some(chunk) = self.chunks.get(key) {
+                self.load_chunk(chunk);
+                return Ok(());
+            }
 
-                let _ = thread::spawn(move || {
-                    let load_data = |filepath: &str| {
-                        let mut data = read_npy_file::<D>(filepath)?;
-                        if let Some(preprocessor) = &preprocessor {
-                            preprocessor.preprocess(&mut data)?;
-                        }
-                        Ok(data)
-                    };
-                    // SendError occurs only if Reader is already destroyed. Since no data is needed
-                    // once the Reader is destroyed, SendError is safely suppressed.
-                    match load_data(&filepath) {
-                        Ok(data) => {
-                            let _ = tx.send(LoadDataResult::ok(filepath, data));
-                        }
-                        Err(err) => {
-                            let _ = tx.send(LoadDataResult::err(filepath, err));
-                        }
-                    }
-                });
-            } else if key.ends_with(".npz") {
-                let tx = self.tx.clone();
-                let filepath = key.to_string();
-                let preprocessor = self.preprocessor.clone();
-
-                let _ = thread::spawn(move || {
-                    let load_data = |filepath: &str| {
-                        let mut data = read_npz_file::<D>(filepath, "data")?;
-                        if let Some(preprocessor) = &preprocessor {
-                            preprocessor.preprocess(&mut data)?;
-                        }
-                        Ok(data)
-                    };
-                    // SendError occurs only if Reader is already destroyed. Since no data is needed
-                    // once the Reader is destroyed, SendError is safely suppressed.
-                    match load_data(&filepath) {
-                        Ok(data) => {
-                            let _ = tx.send(LoadDataResult::ok(filepath, data));
-                        }
-                        Err(err) => {
-                            let _ = tx.send(LoadDataResult::err(filepath, err));
-                        }
-                    }
-                });
-            } else {
+            if !self.mmap_keys.contains(key) && !is_supported_file(key) {
                 return Err(BacktestError::DataError(IoError::new(
                     ErrorKind::InvalidData,
                     "unsupported data type",
                 )));
             }
+            let read_data: fn(&str) -> Result<Data<D>, IoError> = if self.mmap_keys.contains(key) {
+                read_npy_mmap
+            } else {
+                read_file
+            };
+
+            let filepath = key.to_string();
+            self.spawn_load(key.to_string(), move || read_data(&filepath));
         }
         Ok(())
     }
+
+    fn spawn_load<F>(&self, key: String, read_data: F)
+    where
+        F: FnOnce() -> Result<Data<D>, IoError> + Send + 'static,
+    {
+        let tx = self.tx.clone();
+        let processing = self.processing.clone();
+
+        let _ = thread::spawn(move || {
+            // SendError occurs only if Reader is already destroyed. Since no data is needed
+            // once the Reader is destroyed, SendError is safely suppressed.
+            match read_data().and_then(|data| processing.apply(&key, data)) {
+                Ok((data, end_of_range)) => {
+                    let _ = tx.send(LoadDataResult::ok(key, data, end_of_range));
+                }
+                Err(err) => {
+                    let _ = tx.send(LoadDataResult::err(key, err));
+                }
+            }
+        });
+    }
+
+    fn load_chunk(&self, chunk: &Chunk) {
+        #[cfg(feature = "arrow")]
+        if let Some(source) = self.arrow_sources.get(&chunk.filepath) {
+            let source = source.clone();
+            let filepath = chunk.filepath.clone();
+            let index = chunk.index;
+            // Row groups and record batches can be read independently.
+            self.spawn_load(chunk_key(&filepath, index), move || {
+                source.read_chunk(&filepath, index)
+            });
+            return;
+        }
+
+        let mut streams = self.streams.borrow_mut();
+        let stream = streams.entry(chunk.filepath.clone()).or_insert_with(|| {
+            let (req_tx, req_rx) = channel();
+            let filepath = chunk.filepath.clone();
+            let rows = *self.chunk_rows.get(&filepath).unwrap();
+            let tx = self.tx.clone();
+            let processing = self.processing.clone();
+            let _ = thread::spawn(move || stream_chunks(filepath, rows, req_rx, tx, processing));
+            req_tx
+        });
+        // The loader thread exits only after the Reader is destroyed.
+        let _ = stream.send(chunk.index);
+    }
+}
+
+/// Returns `true` if the file can be read by [`read_file`], based on its extension.
+fn is_supported_file(filepath: &str) -> bool {
+    #[cfg(feature = "columnar")]
+    if filepath.ends_with(".hbtc") {
+        return true;
+    }
+    filepath.ends_with(".npy") || filepath.ends_with(".npz")
+}
+
+/// Reads the whole file into memory, dispatching on the extension.
+fn read_file<D>(filepath: &str) -> Result<Data<D>, IoError>
+where
+    D: NpyDTyped + Clone,
+{
+    #[cfg(feature = "columnar")]
+    if filepath.ends_with(".hbtc") {
+        return read_columnar_file(filepath);
+    }
+    if filepath.ends_with(".npy") {
+        read_npy_file(filepath)
+    } else if filepath.ends_with(".npz") {
+        read_npz_file(filepath, "data")
+    } else {
+        Err(IoError::new(
+            ErrorKind::InvalidData,
+            "unsupported data type",
+        ))
+    }
+}
+
+/// Reads the whole data of the [`DataSource`] into memory, which is useful for data that is used
+/// at once, such as an initial snapshot. Unlike the [`Reader`], chunked files, Parquet row groups,
+/// and Arrow IPC record batches are not split.
+pub fn read_data_source<D>(source: &DataSource<D>) -> Result<Data<D>, IoError>
+where
+    D: NpyDTyped + Clone,
+{
+    match source {
+        DataSource::File(filepath) | DataSource::Chunked(filepath, _) => read_file(filepath),
+        DataSource::Mmap(filepath) => read_npy_mmap(filepath),
+        #[cfg(feature = "arrow")]
+        DataSource::Parquet(filepath, mapping) => read_parquet_file(filepath, mapping),
+        #[cfg(feature = "arrow")]
+        DataSource::ArrowIpc(filepath, mapping) => read_arrow_ipc_file(filepath, mapping),
+        DataSource::Data(data) => Ok(data.clone()),
+        DataSource::Shared(data) => Ok(data.to_data()),
+    }
+}
+
+/// A chunk of a file that is read in chunks, such as a [`DataSource::Chunked`] file or a row group
+/// of a Parquet file.
+#[derive(Debug)]
+struct Chunk {
+    filepath: String,
+    index: usize,
+}
+
+fn chunk_key(filepath: &str, index: usize) -> String {
+    format!("{filepath}#{index}")
+}
+
+/// Reads the requested chunks of the file in order and sends them to the [`Reader`] until the
+/// [`Reader`] is destroyed. The file is kept open so that sequential chunks are read without
+/// rereading the preceding data, which matters for compressed `npz` files. If an earlier chunk is
+/// requested again after it was released, the file is reopened.
+fn stream_chunks<D>(
+    filepath: String,
+    rows: usize,
+    requests: Receiver<usize>,
+    tx: Sender<LoadDataResult<D>>,
+    processing: Processing<D>,
+) where
+    D: NpyDTyped + Clone,
+{
+    let Ok(mut index) = requests.recv() else {
+        return;
+    };
+    loop {
+        let result = with_npy_reader(&filepath, |reader, size| {
+            let len = read_npy_header::<_, D>(reader, size)?;
+            let mut next = 0;
+            loop {
+                if index < next {
+                    return Ok(true);
+                }
+                let skip = ((index - next) * rows * size_of::<D>()) as u64;
+                std::io::copy(&mut (&mut *reader).take(skip), &mut std::io::sink())?;
+
+                let data = read_npy_rows::<_, D>(reader, rows.min(len - index * rows))?;
+                let key = chunk_key(&filepath, index);
+                let (data, end_of_range) = processing.apply(&key, data)?;
+                // SendError occurs only if Reader is already destroyed.
+                let _ = tx.send(LoadDataResult::ok(key, data, end_of_range));
+
+                next = index + 1;
+                match requests.recv() {
+                    Ok(requested) => index = requested,
+                    Err(_) => return Ok(false),
+                }
+            }
+        });
+        match result {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(err) => {
+                let _ = tx.send(LoadDataResult::err(chunk_key(&filepath, index), err));
+                return;
+            }
+        }
+    }
 }
 
 /// `DataPreprocess` offers a function to preprocess data before it is fed into the backtesting.
@@ -458,6 +972,16 @@ where
     D: POD + Clone,
 {
     fn preprocess(&self, data: &mut Data<D>) -> Result<(), IoError>;
+
+    /// Preprocesses the data loaded for the key, which identifies the file or the chunk of the
+    /// file. The same data can be loaded again after it is released, so a preprocessor whose
+    /// result is not determined by the data alone, such as a random one, should derive its state
+    /// from the key to produce the same result each time. By default, calls
+    /// [`preprocess`](Self::preprocess).
+    fn preprocess_keyed(&self, key: &str, data: &mut Data<D>) -> Result<(), IoError> {
+        let _ = key;
+        self.preprocess(data)
+    }
 }
 
 /// Pre-processes the feed data to adjust for latency. `local_ts` is offset by the specified latency
@@ -489,3 +1013,437 @@ impl DataPreprocess<Event> for FeedLatencyAdjustment {
         Ok(())
     }
 }
+
+/// Pre-processes the feed data to shift both `exch_ts` and `local_ts` by the specified offset,
+/// keeping the feed latency. This is useful for aligning the feed data of different periods or
+/// venues.
+#[derive(Clone)]
+pub struct TimestampShift {
+    offset: i64,
+}
+
+impl TimestampShift {
+    /// Constructs a `TimestampShift`.
+    pub fn new(offset: i64) -> Self {
+        Self { offset }
+    }
+}
+
+impl DataPreprocess<Event> for TimestampShift {
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        for i in 0..data.len() {
+            data[i].exch_ts += self.offset;
+            data[i].local_ts += self.offset;
+        }
+        Ok(())
+    }
+}
+
+/// Pre-processes the feed data to drop the events that don't satisfy the predicate, such as
+/// [`DEPTH_BBO_EVENT`](crate::types::DEPTH_BBO_EVENT) events to see how a strategy behaves
+/// without the best bid and offer feed.
+pub struct EventFilter {
+    predicate: Box<dyn Fn(&Event) -> bool + Sync + Send>,
+}
+
+impl EventFilter {
+    /// Constructs an `EventFilter` that retains only the events for which the predicate returns
+    /// `true`.
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&Event) -> bool + Sync + Send + 'static,
+    {
+        Self {
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Constructs an `EventFilter` that drops the events of the specified event kind, such as
+    /// [`DEPTH_BBO_EVENT`](crate::types::DEPTH_BBO_EVENT), regardless of the other flags.
+    pub fn exclude_kind(kind: u64) -> Self {
+        Self::new(move |event| event.ev & 0xff != kind)
+    }
+}
+
+impl DataPreprocess<Event> for EventFilter {
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        let mut retained = Vec::with_capacity(data.len());
+        for i in 0..data.len() {
+            if (self.predicate)(&data[i]) {
+                retained.push(data[i].clone());
+            }
+        }
+        if retained.len() != data.len() {
+            *data = Data::from_data(&retained);
+        }
+        Ok(())
+    }
+}
+
+/// Pre-processes the feed data to rescale the price and the quantity by the specified factors.
+/// This is useful when the contract specification changes, such as a redenomination or a change in
+/// the contract size, so that the feed data before and after the change can be backtested on the
+/// same basis.
+#[derive(Clone)]
+pub struct PriceQtyScale {
+    px_scale: f64,
+    qty_scale: f64,
+}
+
+impl PriceQtyScale {
+    /// Constructs a `PriceQtyScale`.
+    pub fn new(px_scale: f64, qty_scale: f64) -> Self {
+        Self {
+            px_scale,
+            qty_scale,
+        }
+    }
+}
+
+impl DataPreprocess<Event> for PriceQtyScale {
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        for i in 0..data.len() {
+            data[i].px *= self.px_scale;
+            data[i].qty *= self.qty_scale;
+        }
+        Ok(())
+    }
+}
+
+/// Pre-processes the feed data to add synthetic feed latency, in nanoseconds, sampled from the
+/// specified distribution to `local_ts`. This is useful for studying how sensitive a strategy is
+/// to the feed latency and its jitter.
+///
+/// Since the feed is received in order, `local_ts` is kept non-decreasing; an event cannot be
+/// received before the previous one. Negative samples are treated as zero.
+///
+/// The random number generator is seeded per data key, so the same data gets the same latency
+/// whenever it is loaded again, such as after it is released from the cache.
+pub struct SyntheticFeedLatency<Dist> {
+    dist: Dist,
+    seed: u64,
+}
+
+impl<Dist> SyntheticFeedLatency<Dist>
+where
+    Dist: Distribution<f64>,
+{
+    /// Constructs a `SyntheticFeedLatency` with the latency distribution and the random seed, so
+    /// that the results are reproducible.
+    pub fn new(dist: Dist, seed: u64) -> Self {
+        Self { dist, seed }
+    }
+
+    fn add_latency(&self, rng: &mut StdRng, data: &mut Data<Event>) {
+        let mut last_local_ts = i64::MIN;
+        for i in 0..data.len() {
+            let latency = self.dist.sample(rng).max(0.0) as i64;
+            let local_ts = (data[i].local_ts + latency).max(last_local_ts);
+            data[i].local_ts = local_ts;
+            last_local_ts = local_ts;
+        }
+    }
+}
+
+impl<Dist> DataPreprocess<Event> for SyntheticFeedLatency<Dist>
+where
+    Dist: Distribution<f64>,
+{
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        self.add_latency(&mut StdRng::seed_from_u64(self.seed), data);
+        Ok(())
+    }
+
+    fn preprocess_keyed(&self, key: &str, data: &mut Data<Event>) -> Result<(), IoError> {
+        // FNV-1a, which is stable across builds unlike the standard library's hasher.
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        self.add_latency(&mut StdRng::seed_from_u64(self.seed ^ hash), data);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::ErrorKind, ops::Range};
+
+    use rand::distr::Uniform;
+
+    use crate::{
+        backtest::{
+            BacktestError,
+            data::{
+                Data,
+                DataSource,
+                EventFilter,
+                PriceQtyScale,
+                Reader,
+                SyntheticFeedLatency,
+                TimestampShift,
+                read_data_source,
+                read_npy_file,
+                write_npy,
+            },
+        },
+        types::{
+            DEPTH_BBO_EVENT,
+            Event,
+            LOCAL_BID_DEPTH_BBO_EVENT,
+            LOCAL_BID_DEPTH_EVENT,
+            LOCAL_BID_DEPTH_SNAPSHOT_EVENT,
+            LOCAL_DEPTH_CLEAR_EVENT,
+            LOCAL_EVENT,
+        },
+    };
+
+    /// Returns the events whose timestamps and prices are their indices in the range.
+    fn events(range: Range<i64>) -> Vec<Event> {
+        range
+            .map(|i| Event {
+                ev: LOCAL_EVENT,
+                exch_ts: i,
+                local_ts: i + 1,
+                px: i as f64,
+                qty: 1.0,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mmap_data() -> Result<(), Box<dyn std::error::Error>> {
+        let events = events(0..100);
+        let filepath = std::env::temp_dir().join(format!("mmap_data_{}.npy", std::process::id()));
+        let filepath = filepath.to_str().unwrap().to_string();
+        write_npy(&mut File::create(&filepath)?, &events)?;
+
+        let mut reader = Reader::<Event>::builder()
+            .data(vec![DataSource::Mmap(filepath.clone())])
+            .build()?;
+        let data = reader.next_data()?;
+        let expected = read_npy_file::<Event>(&filepath)?;
+        assert_eq!(data.len(), 100);
+        for i in 0..data.len() {
+            assert_eq!(data[i], expected[i]);
+        }
+
+        reader.release(data);
+        assert!(!reader.cache.contains(&filepath));
+        std::fs::remove_file(&filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_whole_data_source() -> Result<(), Box<dyn std::error::Error>> {
+        let events = events(0..100);
+        let filepath = std::env::temp_dir().join(format!("whole_data_{}.npy", std::process::id()));
+        let filepath = filepath.to_str().unwrap().to_string();
+        write_npy(&mut File::create(&filepath)?, &events)?;
+
+        for source in [
+            DataSource::File(filepath.clone()),
+            DataSource::Mmap(filepath.clone()),
+            DataSource::Chunked(filepath.clone(), 30),
+            DataSource::Data(Data::from_data(&events)),
+        ] {
+            let data = read_data_source(&source)?;
+            assert_eq!(data.len(), 100);
+            for i in 0..data.len() {
+                assert_eq!(data[i], events[i]);
+            }
+        }
+
+        // The file exists, but its extension isn't supported.
+        let csv_filepath = filepath.replace(".npy", ".csv");
+        std::fs::write(&csv_filepath, "exch_ts,local_ts\n0,1\n")?;
+        let unsupported = DataSource::<Event>::File(csv_filepath.clone());
+        let error = read_data_source(&unsupported).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "unsupported data type");
+        std::fs::remove_file(&csv_filepath)?;
+        std::fs::remove_file(&filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn chunked_data() -> Result<(), Box<dyn std::error::Error>> {
+        let events = events(0..100);
+        let filepath =
+            std::env::temp_dir().join(format!("chunked_data_{}.npy", std::process::id()));
+        let filepath = filepath.to_str().unwrap().to_string();
+        write_npy(&mut File::create(&filepath)?, &events)?;
+
+        let mut reader = Reader::<Event>::builder()
+            .parallel_load(true)
+            .data(vec![DataSource::Chunked(filepath.clone(), 30)])
+            .build()?;
+        let mut lagging = reader.clone();
+
+        let mut ts = Vec::new();
+        let mut lens = Vec::new();
+        loop {
+            let data = match reader.next_data() {
+                Ok(data) => data,
+                Err(BacktestError::EndOfData) => break,
+                Err(err) => return Err(err.into()),
+            };
+            lens.push(data.len());
+            ts.extend((0..data.len()).map(|i| data[i].exch_ts));
+            reader.release(data);
+        }
+        assert_eq!(lens, vec![30, 30, 30, 10]);
+        assert_eq!(ts, (0..100).collect::<Vec<_>>());
+
+        // The released chunks are read again from the file.
+        let data = lagging.next_data()?;
+        assert_eq!(data.len(), 30);
+        assert_eq!(data[0].exch_ts, 0);
+        lagging.release(data);
+        let data = lagging.next_data()?;
+        assert_eq!(data[0].exch_ts, 30);
+        lagging.release(data);
+
+        std::fs::remove_file(&filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn synthetic_feed_latency_on_reload() -> Result<(), Box<dyn std::error::Error>> {
+        let events: Vec<_> = events(0..100)
+            .into_iter()
+            .map(|event| Event {
+                exch_ts: event.exch_ts * 100,
+                local_ts: event.exch_ts * 100,
+                ..event
+            })
+            .collect();
+        let filepath =
+            std::env::temp_dir().join(format!("synthetic_feed_latency_{}.npy", std::process::id()));
+        let filepath = filepath.to_str().unwrap().to_string();
+        write_npy(&mut File::create(&filepath)?, &events)?;
+
+        let mut reader = Reader::<Event>::builder()
+            .data(vec![DataSource::Chunked(filepath.clone(), 50)])
+            .preprocessor(SyntheticFeedLatency::new(Uniform::new(0.0, 50.0)?, 1))
+            .build()?;
+        let mut read_latencies = || -> Result<Vec<i64>, BacktestError> {
+            let mut latencies = Vec::new();
+            loop {
+                let data = match reader.next_data() {
+                    Ok(data) => data,
+                    Err(BacktestError::EndOfData) => break,
+                    Err(err) => return Err(err),
+                };
+                latencies.extend((0..data.len()).map(|i| data[i].local_ts - data[i].exch_ts));
+                reader.release(data);
+            }
+            reader.seek(0);
+            Ok(latencies)
+        };
+
+        // The released chunks are read again with the same latency.
+        let latencies = read_latencies()?;
+        assert_eq!(latencies.len(), 100);
+        assert_eq!(read_latencies()?, latencies);
+        // Each chunk has its own random sequence.
+        assert_ne!(latencies[..50], latencies[50..]);
+
+        std::fs::remove_file(&filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn time_range() -> Result<(), Box<dyn std::error::Error>> {
+        // The data is ordered by `local_ts`, while `exch_ts` goes backwards at times.
+        let events = |range| -> Vec<Event> {
+            events(range)
+                .into_iter()
+                .map(|event| Event {
+                    ev: match event.exch_ts {
+                        60 => LOCAL_DEPTH_CLEAR_EVENT,
+                        61 | 62 => LOCAL_BID_DEPTH_SNAPSHOT_EVENT,
+                        _ => LOCAL_EVENT,
+                    },
+                    exch_ts: event.exch_ts - event.exch_ts % 4 * 3,
+                    ..event
+                })
+                .collect()
+        };
+        let filepath = std::env::temp_dir().join(format!("time_range_{}.npy", std::process::id()));
+        let filepath = filepath.to_str().unwrap().to_string();
+        write_npy(&mut File::create(&filepath)?, &events(0..20))?;
+
+        let mut reader = Reader::<Event>::builder()
+            .data(vec![
+                DataSource::File(filepath.clone()),
+                DataSource::Data(Data::from_data(&events(20..40))),
+                DataSource::Data(Data::from_data(&events(40..100))),
+                DataSource::Data(Data::from_data(&events(100..200))),
+            ])
+            .time_range(70, 90)
+            .build()?;
+        assert_eq!(reader.start(), Some(70));
+
+        // The file entirely before the start is skipped by its last row, and the other data
+        // entirely before the start is emptied.
+        let data = reader.next_data()?;
+        assert!(data.is_empty());
+        reader.release(data);
+
+        // The rows from the nearest snapshot before the start are kept.
+        let data = reader.next_data()?;
+        assert_eq!(data.len(), 29);
+        assert_eq!(data[0].px, 60.0);
+        assert_eq!(data[data.len() - 1].local_ts, 89);
+        reader.release(data);
+
+        // The data after the end is not read.
+        assert!(matches!(reader.next_data(), Err(BacktestError::EndOfData)));
+        std::fs::remove_file(&filepath)?;
+        Ok(())
+    }
+
+    #[test]
+    fn chained_preprocessors() -> Result<(), Box<dyn std::error::Error>> {
+        let events: Vec<_> = events(0..100)
+            .into_iter()
+            .map(|event| Event {
+                ev: if event.exch_ts % 2 == 0 {
+                    LOCAL_BID_DEPTH_BBO_EVENT
+                } else {
+                    LOCAL_BID_DEPTH_EVENT
+                },
+                exch_ts: event.exch_ts * 10,
+                local_ts: event.exch_ts * 10 + 1,
+                ..event
+            })
+            .collect();
+
+        let mut reader = Reader::<Event>::builder()
+            .data(vec![DataSource::Data(Data::from_data(&events))])
+            .preprocessor(EventFilter::exclude_kind(DEPTH_BBO_EVENT))
+            .preprocessor(TimestampShift::new(1000))
+            .preprocessor(PriceQtyScale::new(0.5, 10.0))
+            .preprocessor(SyntheticFeedLatency::new(Uniform::new(0.0, 20.0)?, 1))
+            .build()?;
+
+        let data = reader.next_data()?;
+        assert_eq!(data.len(), 50);
+        for i in 0..data.len() {
+            let j = (2 * i + 1) as i64;
+            assert_eq!(data[i].ev, LOCAL_BID_DEPTH_EVENT);
+            assert_eq!(data[i].exch_ts, j * 10 + 1000);
+            assert!(data[i].local_ts >= j * 10 + 1001 && data[i].local_ts < j * 10 + 1021);
+            if i > 0 {
+                assert!(data[i].local_ts >= data[i - 1].local_ts);
+            }
+            assert_eq!(data[i].px, j as f64 * 0.5);
+            assert_eq!(data[i].qty, 10.0);
+        }
+        reader.release(data);
+        Ok(())
+    }
+}
@@ -1,6 +1,12 @@
 use std::mem;
 
-use crate::utils::{AlignedArray, CACHE_LINE_SIZE};
+use crate::{
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+    },
+    utils::{AlignedArray, CACHE_LINE_SIZE},
+};
 
 #[derive(Clone, Copy)]
 #[repr(C, align(32))]
@@ -19,11 +25,14 @@ pub enum EventIntentKind {
     LocalOrder = 1,
     ExchData = 2,
     ExchOrder = 3,
+    /// A user timer, which is not tied to an asset, so `asset_no` is always `0`.
+    Timer = 4,
 }
 
 /// Manages the event timestamps to determine the next event to be processed.
 pub struct EventSet {
     timestamp: AlignedArray<i64, CACHE_LINE_SIZE>,
+    timer: i64,
 }
 
 impl EventSet {
@@ -36,10 +45,16 @@ impl EventSet {
         for i in 0..(num_assets * 4) {
             timestamp[i] = i64::MAX;
         }
-        Self { timestamp }
+        Self {
+            timestamp,
+            timer: i64::MAX,
+        }
     }
 
     /// Returns the next event to be processed, which has the earliest timestamp.
+    ///
+    /// A timer is only returned while data or order events remain, so that timers don't extend
+    /// the backtest beyond the end of the data.
     pub fn next(&self) -> Option<EventIntent> {
         let mut evst_no = 0;
         let mut timestamp = unsafe { *self.timestamp.get_unchecked(0) };
@@ -53,6 +68,13 @@ impl EventSet {
         if timestamp == i64::MAX {
             return None;
         }
+        if self.timer < timestamp {
+            return Some(EventIntent {
+                timestamp: self.timer,
+                asset_no: 0,
+                kind: EventIntentKind::Timer,
+            });
+        }
         let asset_no = evst_no >> 2;
         let kind = unsafe { mem::transmute::<usize, EventIntentKind>(evst_no & 3) };
         Some(EventIntent {
@@ -88,6 +110,11 @@ impl EventSet {
         self.update(4 * asset_no + 3, timestamp);
     }
 
+    #[inline]
+    pub fn update_timer(&mut self, timestamp: i64) {
+        self.timer = timestamp;
+    }
+
     #[inline]
     fn invalidate(&mut self, evst_no: usize) {
         let item = unsafe { self.timestamp.get_unchecked_mut(evst_no) };
@@ -103,4 +130,30 @@ impl EventSet {
     pub fn invalidate_exch_data(&mut self, asset_no: usize) {
         self.invalidate(4 * asset_no + 2);
     }
+
+    #[inline]
+    pub fn invalidate_timer(&mut self) {
+        self.timer = i64::MAX;
+    }
+
+    /// Writes the timestamps of the data and order events into the checkpoint. The timer isn't
+    /// included, as it follows the scheduled timers.
+    pub fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.timestamp.to_vec())
+    }
+
+    /// Restores the timestamps of the data and order events from the checkpoint.
+    pub fn restore_checkpoint(
+        &mut self,
+        reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        let timestamp: Vec<i64> = reader.read()?;
+        if timestamp.len() != self.timestamp.len() {
+            return Err(BacktestError::InvalidCheckpoint(
+                "the number of assets doesn't match",
+            ));
+        }
+        self.timestamp.copy_from_slice(&timestamp);
+        Ok(())
+    }
 }
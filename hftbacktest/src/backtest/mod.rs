@@ -3,8 +3,11 @@ use std::{
     io::Error as IoError,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    path::Path,
+    sync::Arc,
 };
 
+use bincode::error::{DecodeError, EncodeError};
 pub use data::DataSource;
 use data::Reader;
 use models::FeeModel;
@@ -17,12 +20,19 @@ pub use crate::backtest::{
 use crate::{
     backtest::{
         assettype::AssetType,
-        data::{Data, FeedLatencyAdjustment, NpyDTyped},
+        checkpoint::{
+            CheckpointReader,
+            CheckpointWriter,
+            read_checkpoint_file,
+            write_checkpoint_file,
+        },
+        data::{Data, DataPreprocess, FeedLatencyAdjustment, NpyDTyped},
         evs::{EventIntentKind, EventSet},
         models::{LatencyModel, QueueModel},
         order::OrderBus,
         proc::{Local, LocalProcessor, NoPartialFillExchange, PartialFillExchange, Processor},
         state::State,
+        timer::Timers,
     },
     depth::{HashMapMarketDepth, L2MarketDepth, L3MarketDepth, MarketDepth},
     prelude::{
@@ -55,11 +65,21 @@ pub mod proc;
 /// Trading state.
 pub mod state;
 
+/// Checkpoints for pausing, resuming, and forking backtests.
+pub mod checkpoint;
+
 /// Recorder for a bot's trading statistics.
 pub mod recorder;
 
+/// Reconciliation of a live session against its backtest.
+pub mod reconcile;
+
+/// Parallel parameter sweeps.
+pub mod sweep;
+
 pub mod data;
 mod evs;
+mod timer;
 
 /// Errors that can occur during backtesting.
 #[derive(Error, Debug)]
@@ -78,6 +98,14 @@ pub enum BacktestError {
     EndOfData,
     #[error("data error: {0:?}")]
     DataError(#[from] IoError),
+    #[error("checkpoint isn't supported by {0}")]
+    CheckpointUnsupported(&'static str),
+    #[error("invalid checkpoint: {0}")]
+    InvalidCheckpoint(&'static str),
+    #[error("checkpoint encode error: {0}")]
+    CheckpointEncode(#[from] EncodeError),
+    #[error("checkpoint decode error: {0}")]
+    CheckpointDecode(#[from] DecodeError),
 }
 
 /// Backtesting Asset
@@ -144,6 +172,8 @@ where
     data: Vec<DataSource<Event>>,
     parallel_load: bool,
     latency_offset: i64,
+    preprocessors: Vec<Arc<dyn DataPreprocess<Event> + Sync + Send + 'static>>,
+    time_range: Option<(i64, i64)>,
     fee_model: Option<FM>,
     exch_kind: ExchangeKind,
     last_trades_cap: usize,
@@ -169,6 +199,8 @@ where
             data: vec![],
             parallel_load: false,
             latency_offset: 0,
+            preprocessors: Vec::new(),
+            time_range: None,
             fee_model: None,
             exch_kind: ExchangeKind::NoPartialFillExchange,
             last_trades_cap: 0,
@@ -203,6 +235,37 @@ where
         }
     }
 
+    /// Adds a [`DataPreprocess`] to be applied to the feed data before it is fed into the
+    /// backtest, such as [`TimestampShift`](crate::backtest::data::TimestampShift),
+    /// [`EventFilter`](crate::backtest::data::EventFilter),
+    /// [`PriceQtyScale`](crate::backtest::data::PriceQtyScale), and
+    /// [`SyntheticFeedLatency`](crate::backtest::data::SyntheticFeedLatency). Multiple
+    /// preprocessors can be chained, and they are applied in the order in which they are added,
+    /// after the latency offset adjustment.
+    pub fn preprocessor<Preprocessor>(self, preprocessor: Preprocessor) -> Self
+    where
+        Preprocessor: DataPreprocess<Event> + Sync + Send + 'static,
+    {
+        let mut preprocessors = self.preprocessors;
+        preprocessors.push(Arc::new(preprocessor));
+        Self {
+            preprocessors,
+            ..self
+        }
+    }
+
+    /// Sets the time range to backtest, from `start` inclusive to `end` exclusive, so that only
+    /// the feed data within the range is processed, without elapsing through the rest. The market
+    /// depth is built from the nearest snapshot before `start`, and the backtest begins at `start`.
+    /// See [`ReaderBuilder::time_range`](crate::backtest::data::ReaderBuilder::time_range) for
+    /// details.
+    pub fn time_range(self, start: i64, end: i64) -> Self {
+        Self {
+            time_range: Some((start, end)),
+            ..self
+        }
+    }
+
     /// Sets a latency model.
     pub fn latency_model(self, latency_model: LM) -> Self {
         Self {
@@ -269,20 +332,21 @@ where
 
     /// Builds an `Asset`.
     pub fn build(self) -> Result<Asset<dyn LocalProcessor<MD,PA>, dyn Processor, Event>, BuildError> {
-        let reader = if self.latency_offset == 0 {
-            Reader::builder()
-                .parallel_load(self.parallel_load)
-                .data(self.data)
-                .build()
-                .map_err(|err| BuildError::Error(err.into()))?
-        } else {
-            Reader::builder()
-                .parallel_load(self.parallel_load)
-                .data(self.data)
-                .preprocessor(FeedLatencyAdjustment::new(self.latency_offset))
-                .build()
-                .map_err(|err| BuildError::Error(err.into()))?
-        };
+        let mut reader = Reader::builder()
+            .parallel_load(self.parallel_load)
+            .data(self.data);
+        if self.latency_offset != 0 {
+            reader = reader.preprocessor(FeedLatencyAdjustment::new(self.latency_offset));
+        }
+        for preprocessor in self.preprocessors {
+            reader = reader.shared_preprocessor(preprocessor);
+        }
+        if let Some((start, end)) = self.time_range {
+            reader = reader.time_range(start, end);
+        }
+        let reader = reader
+            .build()
+            .map_err(|err| BuildError::Error(err.into()))?;
 
         let ob_local_to_exch = OrderBus::new();
         let ob_exch_to_local = OrderBus::new();
@@ -393,6 +457,8 @@ pub struct L3AssetBuilder<LM, AT, QM, MD, FM, PA> {
     data: Vec<DataSource<Event>>,
     parallel_load: bool,
     latency_offset: i64,
+    preprocessors: Vec<Arc<dyn DataPreprocess<Event> + Sync + Send + 'static>>,
+    time_range: Option<(i64, i64)>,
     fee_model: Option<FM>,
     exch_kind: ExchangeKind,
     last_trades_cap: usize,
@@ -419,6 +485,8 @@ where
             data: vec![],
             parallel_load: false,
             latency_offset: 0,
+            preprocessors: Vec::new(),
+            time_range: None,
             fee_model: None,
             exch_kind: ExchangeKind::NoPartialFillExchange,
             last_trades_cap: 0,
@@ -453,6 +521,37 @@ where
         }
     }
 
+    /// Adds a [`DataPreprocess`] to be applied to the feed data before it is fed into the
+    /// backtest, such as [`TimestampShift`](crate::backtest::data::TimestampShift),
+    /// [`EventFilter`](crate::backtest::data::EventFilter),
+    /// [`PriceQtyScale`](crate::backtest::data::PriceQtyScale), and
+    /// [`SyntheticFeedLatency`](crate::backtest::data::SyntheticFeedLatency). Multiple
+    /// preprocessors can be chained, and they are applied in the order in which they are added,
+    /// after the latency offset adjustment.
+    pub fn preprocessor<Preprocessor>(self, preprocessor: Preprocessor) -> Self
+    where
+        Preprocessor: DataPreprocess<Event> + Sync + Send + 'static,
+    {
+        let mut preprocessors = self.preprocessors;
+        preprocessors.push(Arc::new(preprocessor));
+        Self {
+            preprocessors,
+            ..self
+        }
+    }
+
+    /// Sets the time range to backtest, from `start` inclusive to `end` exclusive, so that only
+    /// the feed data within the range is processed, without elapsing through the rest. The market
+    /// depth is built from the nearest snapshot before `start`, and the backtest begins at `start`.
+    /// See [`ReaderBuilder::time_range`](crate::backtest::data::ReaderBuilder::time_range) for
+    /// details.
+    pub fn time_range(self, start: i64, end: i64) -> Self {
+        Self {
+            time_range: Some((start, end)),
+            ..self
+        }
+    }
+
     /// Sets a latency model.
     pub fn latency_model(self, latency_model: LM) -> Self {
         Self {
@@ -519,20 +618,21 @@ where
 
     /// Builds an `Asset`.
     pub fn build(self) -> Result<Asset<dyn LocalProcessor<MD,PA>, dyn Processor, Event>, BuildError> {
-        let reader = if self.latency_offset == 0 {
-            Reader::builder()
-                .parallel_load(self.parallel_load)
-                .data(self.data)
-                .build()
-                .map_err(|err| BuildError::Error(err.into()))?
-        } else {
-            Reader::builder()
-                .parallel_load(self.parallel_load)
-                .data(self.data)
-                .preprocessor(FeedLatencyAdjustment::new(self.latency_offset))
-                .build()
-                .map_err(|err| BuildError::Error(err.into()))?
-        };
+        let mut reader = Reader::builder()
+            .parallel_load(self.parallel_load)
+            .data(self.data);
+        if self.latency_offset != 0 {
+            reader = reader.preprocessor(FeedLatencyAdjustment::new(self.latency_offset));
+        }
+        for preprocessor in self.preprocessors {
+            reader = reader.shared_preprocessor(preprocessor);
+        }
+        if let Some((start, end)) = self.time_range {
+            reader = reader.time_range(start, end);
+        }
+        let reader = reader
+            .build()
+            .map_err(|err| BuildError::Error(err.into()))?;
 
         let ob_local_to_exch = OrderBus::new();
         let ob_exch_to_local = OrderBus::new();
@@ -653,6 +753,7 @@ impl<MD,PA> BacktestBuilder<MD,PA> {
         Ok(Backtest {
             cur_ts: i64::MAX,
             evs: EventSet::new(num_assets),
+            timers: Default::default(),
             local: self.local,
             exch: self.exch,
         })
@@ -665,6 +766,7 @@ impl<MD,PA> BacktestBuilder<MD,PA> {
 pub struct Backtest<MD,PA> {
     cur_ts: i64,
     evs: EventSet,
+    timers: Timers,
     local: Vec<BacktestProcessorState<Box<dyn LocalProcessor<MD,PA>>>>,
     exch: Vec<BacktestProcessorState<Box<dyn Processor>>>,
 }
@@ -686,6 +788,8 @@ impl<P: Processor> DerefMut for BacktestProcessorState<P> {
 /// Per asset backtesting state used internally to advance event buffers.
 pub struct BacktestProcessorState<P: Processor> {
     data: Data<Event>,
+    // The position of `data` in the reader's data list.
+    data_no: Option<usize>,
     processor: P,
     reader: Reader<Event>,
     row: Option<usize>,
@@ -695,12 +799,41 @@ impl<P: Processor> BacktestProcessorState<P> {
     fn new(processor: P, reader: Reader<Event>) -> BacktestProcessorState<P> {
         Self {
             data: Data::empty(),
+            data_no: None,
             processor,
             reader,
             row: None,
         }
     }
 
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.data_no)?;
+        writer.write(&self.reader.position())?;
+        writer.write(&self.row)?;
+        self.processor.save_checkpoint(writer)
+    }
+
+    /// Reloads the data at the position of the checkpoint, rather than storing it in the
+    /// checkpoint.
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        let data_no: Option<usize> = reader.read()?;
+        let position: usize = reader.read()?;
+        if data_no != self.data_no {
+            let data = match data_no {
+                Some(data_no) => {
+                    self.reader.seek(data_no);
+                    self.reader.next_data()?
+                }
+                None => Data::empty(),
+            };
+            self.reader.release(std::mem::replace(&mut self.data, data));
+            self.data_no = data_no;
+        }
+        self.reader.seek(position);
+        self.row = reader.read()?;
+        self.processor.restore_checkpoint(reader)
+    }
+
     /// Get the index of the next available row, only advancing the reader if there's no
     /// row currently available.
     fn next_row(&mut self) -> Result<usize, BacktestError> {
@@ -724,14 +857,65 @@ impl<P: Processor> BacktestProcessorState<P> {
                 }
             }
 
+            let data_no = self.reader.position();
             let next = self.reader.next_data()?;
 
             self.reader.release(std::mem::replace(&mut self.data, next));
+            self.data_no = Some(data_no);
             self.row = None;
         }
     }
 }
 
+/// Writes the checkpoint of the backtest's state, shared by the backtests.
+fn write_checkpoint<Local: Processor, Exchange: Processor>(
+    cur_ts: i64,
+    evs: &EventSet,
+    timers: &Timers,
+    local: &[BacktestProcessorState<Local>],
+    exch: &[BacktestProcessorState<Exchange>],
+) -> Result<Vec<u8>, BacktestError> {
+    let mut writer = CheckpointWriter::new();
+    writer.write(&local.len())?;
+    writer.write(&cur_ts)?;
+    evs.save_checkpoint(&mut writer)?;
+    timers.save_checkpoint(&mut writer)?;
+    for (local, exch) in local.iter().zip(exch.iter()) {
+        local.save_checkpoint(&mut writer)?;
+        exch.save_checkpoint(&mut writer)?;
+    }
+    Ok(writer.into_bytes())
+}
+
+/// Restores the backtest's state from the checkpoint written by [`write_checkpoint`].
+fn restore_checkpoint<Local: Processor, Exchange: Processor>(
+    checkpoint: &[u8],
+    cur_ts: &mut i64,
+    evs: &mut EventSet,
+    timers: &mut Timers,
+    local: &mut [BacktestProcessorState<Local>],
+    exch: &mut [BacktestProcessorState<Exchange>],
+) -> Result<(), BacktestError> {
+    let mut reader = CheckpointReader::new(checkpoint);
+    let num_assets: usize = reader.read()?;
+    if num_assets != local.len() {
+        return Err(BacktestError::InvalidCheckpoint(
+            "the number of assets doesn't match",
+        ));
+    }
+    *cur_ts = reader.read()?;
+    evs.restore_checkpoint(&mut reader)?;
+    timers.restore_checkpoint(&mut reader)?;
+    for (local, exch) in local.iter_mut().zip(exch.iter_mut()) {
+        local.restore_checkpoint(&mut reader)?;
+        exch.restore_checkpoint(&mut reader)?;
+    }
+    if !reader.is_empty() {
+        return Err(BacktestError::InvalidCheckpoint("trailing data"));
+    }
+    Ok(())
+}
+
 impl<MD,PA> Backtest<MD,PA>
 where
     MD: MarketDepth,
@@ -770,6 +954,7 @@ where
             exch,
             cur_ts: i64::MAX,
             evs: EventSet::new(num_assets),
+            timers: Default::default(),
         }
     }
 
@@ -799,17 +984,80 @@ where
         Ok(())
     }
 
-    pub fn goto_end(&mut self) -> Result<bool, BacktestError> {
-        if self.cur_ts == i64::MAX {
-            self.initialize_evs()?;
-            match self.evs.next() {
-                Some(ev) => {
-                    self.cur_ts = ev.timestamp;
-                }
-                None => {
-                    return Ok(false);
-                }
+    /// Initializes the events and the current timestamp, and then processes the data up to the
+    /// latest start of the assets' time ranges, if any. Returns `false` if there is no data.
+    fn initialize(&mut self) -> Result<bool, BacktestError> {
+        self.initialize_evs()?;
+        match self.evs.next() {
+            Some(ev) => {
+                self.cur_ts = ev.timestamp;
+            }
+            None => {
+                return Ok(false);
+            }
+        }
+        let start = self.local.iter().filter_map(|local| local.reader.start()).max();
+        match start {
+            Some(start) if start > self.cur_ts => {
+                self.goto::<false>(start, WaitOrderResponse::None)
             }
+            _ => Ok(true),
+        }
+    }
+
+    fn update_timer(&mut self) {
+        match self.timers.earliest_timestamp() {
+            Some(timestamp) => self.evs.update_timer(timestamp),
+            None => self.evs.invalidate_timer(),
+        }
+    }
+
+    /// Returns the checkpoint of the backtest's state: the readers' positions, the pending events,
+    /// the local and exchange processors, including the order books, the queue positions, the
+    /// trading states, and the price actions, and the timers.
+    ///
+    /// Restoring the checkpoint by [`restore`](Self::restore()) into a backtest built with the
+    /// identical configuration resumes it from this point with identical results. This also allows
+    /// for forking the backtest at a point in time to test alternative decisions.
+    ///
+    /// Only the L2 processors support checkpoints for now. If any asset uses an L3 processor, such
+    /// as [`L3Local`] or [`L3NoPartialFillExchange`], this returns
+    /// [`BacktestError::CheckpointUnsupported`].
+    pub fn checkpoint(&self) -> Result<Vec<u8>, BacktestError> {
+        write_checkpoint(self.cur_ts, &self.evs, &self.timers, &self.local, &self.exch)
+    }
+
+    /// Restores the state from the checkpoint returned by [`checkpoint`](Self::checkpoint()).
+    ///
+    /// The data is reloaded from the data sources of this backtest, so it must be built with the
+    /// identical configuration as the one the checkpoint is taken from.
+    pub fn restore(&mut self, checkpoint: &[u8]) -> Result<(), BacktestError> {
+        restore_checkpoint(
+            checkpoint,
+            &mut self.cur_ts,
+            &mut self.evs,
+            &mut self.timers,
+            &mut self.local,
+            &mut self.exch,
+        )?;
+        self.update_timer();
+        Ok(())
+    }
+
+    /// Writes the checkpoint to the file. See [`checkpoint`](Self::checkpoint()).
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), BacktestError> {
+        write_checkpoint_file(path, &self.checkpoint()?)
+    }
+
+    /// Restores the state from the checkpoint file written by
+    /// [`save_checkpoint`](Self::save_checkpoint()). See [`restore`](Self::restore()).
+    pub fn load_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<(), BacktestError> {
+        self.restore(&read_checkpoint_file(path)?)
+    }
+
+    pub fn goto_end(&mut self) -> Result<bool, BacktestError> {
+        if self.cur_ts == i64::MAX && !self.initialize()? {
+            return Ok(false);
         }
         self.goto::<false>(UNTIL_END_OF_DATA, WaitOrderResponse::None)
     }
@@ -912,9 +1160,22 @@ where
                                 exch.earliest_send_order_timestamp(),
                             );
                         }
+                        EventIntentKind::Timer => {
+                            self.timers.expire(ev.timestamp);
+                            self.update_timer();
+                            // Waiting for a specific order response isn't interrupted by timers.
+                            if !matches!(wait_order_response, WaitOrderResponse::Specified { .. }) {
+                                timestamp = ev.timestamp;
+                            }
+                        }
                     }
                 }
                 None => {
+                    if WAIT_NEXT_FEED {
+                        // Keeps the timestamp of the last feed or order response received before
+                        // the end of the data.
+                        self.cur_ts = timestamp;
+                    }
                     return Ok(false);
                 }
             }
@@ -981,6 +1242,26 @@ where
         self.local.get(asset_no).unwrap().orders()
     }
 
+    #[inline]
+    fn updated_orders(&self, asset_no: usize) -> &[OrderId] {
+        self.local.get(asset_no).unwrap().updated_orders()
+    }
+
+    #[inline]
+    fn clear_updated_orders(&mut self, asset_no: Option<usize>) {
+        match asset_no {
+            Some(an) => {
+                let local = self.local.get_mut(an).unwrap();
+                local.clear_updated_orders();
+            }
+            None => {
+                for local in self.local.iter_mut() {
+                    local.clear_updated_orders();
+                }
+            }
+        }
+    }
+
     #[inline]
     fn submit_buy_order(
         &mut self,
@@ -1148,16 +1429,8 @@ where
         include_order_resp: bool,
         timeout: i64,
     ) -> Result<bool, Self::Error> {
-        if self.cur_ts == i64::MAX {
-            self.initialize_evs()?;
-            match self.evs.next() {
-                Some(ev) => {
-                    self.cur_ts = ev.timestamp;
-                }
-                None => {
-                    return Ok(false);
-                }
-            }
+        if self.cur_ts == i64::MAX && !self.initialize()? {
+            return Ok(false);
         }
         if include_order_resp {
             self.goto::<true>(self.cur_ts + timeout, WaitOrderResponse::Any)
@@ -1166,18 +1439,39 @@ where
         }
     }
 
+    #[inline]
+    fn schedule_at(&mut self, timestamp: i64, timer_id: u64) {
+        // A timer in the past expires at the current timestamp, since time can't go backward.
+        let timestamp = if self.cur_ts == i64::MAX {
+            timestamp
+        } else {
+            timestamp.max(self.cur_ts)
+        };
+        self.timers.schedule(timestamp, timer_id);
+        self.update_timer();
+    }
+
+    #[inline]
+    fn cancel_timer(&mut self, timer_id: u64) -> bool {
+        let canceled = self.timers.cancel(timer_id);
+        self.update_timer();
+        canceled
+    }
+
+    #[inline]
+    fn expired_timers(&self) -> &[u64] {
+        self.timers.expired()
+    }
+
+    #[inline]
+    fn clear_expired_timers(&mut self) {
+        self.timers.clear_expired();
+    }
+
     #[inline]
     fn elapse(&mut self, duration: i64) -> Result<bool, Self::Error> {
-        if self.cur_ts == i64::MAX {
-            self.initialize_evs()?;
-            match self.evs.next() {
-                Some(ev) => {
-                    self.cur_ts = ev.timestamp;
-                }
-                None => {
-                    return Ok(false);
-                }
-            }
+        if self.cur_ts == i64::MAX && !self.initialize()? {
+            return Ok(false);
         }
         self.goto::<false>(self.cur_ts + duration, WaitOrderResponse::None)
     }
@@ -1240,6 +1534,7 @@ where
         Ok(MultiAssetSingleExchangeBacktest {
             cur_ts: i64::MAX,
             evs: EventSet::new(num_assets),
+            timers: Default::default(),
             local: self.local,
             exch: self.exch,
             _md_marker: Default::default(),
@@ -1261,6 +1556,7 @@ where
 {
     cur_ts: i64,
     evs: EventSet,
+    timers: Timers,
     local: Vec<BacktestProcessorState<Local>>,
     exch: Vec<BacktestProcessorState<Exchange>>,
     _md_marker: PhantomData<MD>,
@@ -1303,6 +1599,7 @@ where
             exch,
             cur_ts: i64::MAX,
             evs: EventSet::new(num_assets),
+            timers: Default::default(),
             _md_marker: Default::default(),
             _price_action: Default::default(),
         }
@@ -1334,6 +1631,71 @@ where
         Ok(())
     }
 
+    fn update_timer(&mut self) {
+        match self.timers.earliest_timestamp() {
+            Some(timestamp) => self.evs.update_timer(timestamp),
+            None => self.evs.invalidate_timer(),
+        }
+    }
+
+    /// Returns the checkpoint of the backtest's state: the readers' positions, the pending events,
+    /// the local and exchange processors, including the order books, the queue positions, the
+    /// trading states, and the price actions, and the timers.
+    ///
+    /// Restoring the checkpoint by [`restore`](Self::restore()) into a backtest built with the
+    /// identical configuration resumes it from this point with identical results. This also allows
+    /// for forking the backtest at a point in time to test alternative decisions.
+    pub fn checkpoint(&self) -> Result<Vec<u8>, BacktestError> {
+        write_checkpoint(self.cur_ts, &self.evs, &self.timers, &self.local, &self.exch)
+    }
+
+    /// Restores the state from the checkpoint returned by [`checkpoint`](Self::checkpoint()).
+    ///
+    /// The data is reloaded from the data sources of this backtest, so it must be built with the
+    /// identical configuration as the one the checkpoint is taken from.
+    pub fn restore(&mut self, checkpoint: &[u8]) -> Result<(), BacktestError> {
+        restore_checkpoint(
+            checkpoint,
+            &mut self.cur_ts,
+            &mut self.evs,
+            &mut self.timers,
+            &mut self.local,
+            &mut self.exch,
+        )?;
+        self.update_timer();
+        Ok(())
+    }
+
+    /// Writes the checkpoint to the file. See [`checkpoint`](Self::checkpoint()).
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), BacktestError> {
+        write_checkpoint_file(path, &self.checkpoint()?)
+    }
+
+    /// Restores the state from the checkpoint file written by
+    /// [`save_checkpoint`](Self::save_checkpoint()). See [`restore`](Self::restore()).
+    pub fn load_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<(), BacktestError> {
+        self.restore(&read_checkpoint_file(path)?)
+    }
+
+    fn initialize(&mut self) -> Result<bool, BacktestError> {
+        self.initialize_evs()?;
+        match self.evs.next() {
+            Some(ev) => {
+                self.cur_ts = ev.timestamp;
+            }
+            None => {
+                return Ok(false);
+            }
+        }
+        let start = self.local.iter().filter_map(|local| local.reader.start()).max();
+        match start {
+            Some(start) if start > self.cur_ts => {
+                self.goto::<false>(start, WaitOrderResponse::None)
+            }
+            _ => Ok(true),
+        }
+    }
+
     pub fn goto<const WAIT_NEXT_FEED: bool>(
         &mut self,
         timestamp: i64,
@@ -1430,9 +1792,22 @@ where
                                 exch.earliest_send_order_timestamp(),
                             );
                         }
+                        EventIntentKind::Timer => {
+                            self.timers.expire(ev.timestamp);
+                            self.update_timer();
+                            // Waiting for a specific order response isn't interrupted by timers.
+                            if !matches!(wait_order_response, WaitOrderResponse::Specified { .. }) {
+                                timestamp = ev.timestamp;
+                            }
+                        }
                     }
                 }
                 None => {
+                    if WAIT_NEXT_FEED {
+                        // Keeps the timestamp of the last feed or order response received before
+                        // the end of the data.
+                        self.cur_ts = timestamp;
+                    }
                     return Ok(false);
                 }
             }
@@ -1501,6 +1876,26 @@ where
         self.local.get(asset_no).unwrap().orders()
     }
 
+    #[inline]
+    fn updated_orders(&self, asset_no: usize) -> &[OrderId] {
+        self.local.get(asset_no).unwrap().updated_orders()
+    }
+
+    #[inline]
+    fn clear_updated_orders(&mut self, asset_no: Option<usize>) {
+        match asset_no {
+            Some(an) => {
+                let local = self.local.get_mut(an).unwrap();
+                local.clear_updated_orders();
+            }
+            None => {
+                for local in self.local.iter_mut() {
+                    local.clear_updated_orders();
+                }
+            }
+        }
+    }
+
     #[inline]
     fn submit_buy_order(
         &mut self,
@@ -1667,16 +2062,8 @@ where
         include_order_resp: bool,
         timeout: i64,
     ) -> Result<bool, Self::Error> {
-        if self.cur_ts == i64::MAX {
-            self.initialize_evs()?;
-            match self.evs.next() {
-                Some(ev) => {
-                    self.cur_ts = ev.timestamp;
-                }
-                None => {
-                    return Ok(false);
-                }
-            }
+        if self.cur_ts == i64::MAX && !self.initialize()? {
+            return Ok(false);
         }
         if include_order_resp {
             self.goto::<true>(self.cur_ts + timeout, WaitOrderResponse::Any)
@@ -1685,18 +2072,39 @@ where
         }
     }
 
+    #[inline]
+    fn schedule_at(&mut self, timestamp: i64, timer_id: u64) {
+        // A timer in the past expires at the current timestamp, since time can't go backward.
+        let timestamp = if self.cur_ts == i64::MAX {
+            timestamp
+        } else {
+            timestamp.max(self.cur_ts)
+        };
+        self.timers.schedule(timestamp, timer_id);
+        self.update_timer();
+    }
+
+    #[inline]
+    fn cancel_timer(&mut self, timer_id: u64) -> bool {
+        let canceled = self.timers.cancel(timer_id);
+        self.update_timer();
+        canceled
+    }
+
+    #[inline]
+    fn expired_timers(&self) -> &[u64] {
+        self.timers.expired()
+    }
+
+    #[inline]
+    fn clear_expired_timers(&mut self) {
+        self.timers.clear_expired();
+    }
+
     #[inline]
     fn elapse(&mut self, duration: i64) -> Result<bool, Self::Error> {
-        if self.cur_ts == i64::MAX {
-            self.initialize_evs()?;
-            match self.evs.next() {
-                Some(ev) => {
-                    self.cur_ts = ev.timestamp;
-                }
-                None => {
-                    return Ok(false);
-                }
-            }
+        if self.cur_ts == i64::MAX && !self.initialize()? {
+            return Ok(false);
         }
         self.goto::<false>(self.cur_ts + duration, WaitOrderResponse::None)
     }
@@ -1729,6 +2137,7 @@ mod test {
     use crate::{
         backtest::{
             Backtest,
+            BacktestError,
             DataSource,
             ExchangeKind::NoPartialFillExchange,
             L2AssetBuilder,
@@ -1742,9 +2151,19 @@ mod test {
                 TradingValueFeeModel,
             },
         },
-        depth::HashMapMarketDepth,
-        prelude::{Bot, Event},
-        types::{EXCH_EVENT, LOCAL_EVENT},
+        depth::{HashMapMarketDepth, MarketDepth},
+        prelude::{Bot, Event, HkPriceAction},
+        types::{
+            BUY_EVENT,
+            DEPTH_EVENT,
+            EXCH_EVENT,
+            LOCAL_EVENT,
+            OrdType::Limit,
+            SELL_EVENT,
+            Status,
+            TRADE_EVENT,
+            TimeInForce::GTC,
+        },
     };
 
     #[test]
@@ -1821,4 +2240,159 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn timers_wake_elapse() -> Result<(), Box<dyn Error>> {
+        let data = Data::from_data(
+            &[0, 1000, 5000]
+                .map(|ts| Event {
+                    ev: EXCH_EVENT | LOCAL_EVENT,
+                    exch_ts: ts,
+                    local_ts: ts,
+                    px: 0.0,
+                    qty: 0.0,
+                    order_id: 0,
+                    ival: 0,
+                    fval: 0.0,
+                }),
+        );
+
+        let mut backtester: Backtest<HashMapMarketDepth, HkPriceAction> = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(0.01, 1.0))
+                    .price_action(HkPriceAction::new(vec![], vec![]))
+                    .build()?,
+            )
+            .build()?;
+
+        assert!(backtester.elapse(0)?);
+        backtester.schedule_at(250, 1);
+        backtester.schedule_after(2000, 2);
+
+        assert!(backtester.elapse(10_000)?);
+        assert_eq!(backtester.current_timestamp(), 250);
+        assert_eq!(backtester.expired_timers(), &[1]);
+        backtester.clear_expired_timers();
+
+        assert!(backtester.elapse(10_000)?);
+        assert_eq!(backtester.current_timestamp(), 2000);
+        assert_eq!(backtester.expired_timers(), &[2]);
+        backtester.clear_expired_timers();
+
+        backtester.schedule_at(3000, 3);
+        assert!(backtester.cancel_timer(3));
+        assert!(!backtester.cancel_timer(3));
+        backtester.schedule_at(4000, 4);
+        assert!(backtester.wait_next_feed(false, 10_000)?);
+        assert_eq!(backtester.current_timestamp(), 4000);
+        assert_eq!(backtester.expired_timers(), &[4]);
+        backtester.clear_expired_timers();
+
+        // Timers don't extend the backtest beyond the end of the data.
+        backtester.schedule_at(100_000, 5);
+        assert!(!backtester.elapse(200_000)?);
+        assert!(backtester.expired_timers().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() -> Result<(), Box<dyn Error>> {
+        let event = |ev, ts, px, qty| Event {
+            ev: EXCH_EVENT | LOCAL_EVENT | ev,
+            exch_ts: ts,
+            local_ts: ts + 10,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        };
+        let data: Vec<_> = (0..2)
+            .map(|part| {
+                let events: Vec<_> = (0..10)
+                    .flat_map(|i| {
+                        let ts = (part * 10 + i) * 100;
+                        [
+                            event(DEPTH_EVENT | BUY_EVENT, ts, 100.0, 10.0 - i as f64 * 0.5),
+                            event(DEPTH_EVENT | SELL_EVENT, ts, 100.01, 5.0),
+                            event(TRADE_EVENT | SELL_EVENT, ts + 50, 100.0, 2.0),
+                        ]
+                    })
+                    .collect();
+                DataSource::Data(Data::from_data(&events))
+            })
+            .collect();
+        let build = || -> Result<Backtest<HashMapMarketDepth, HkPriceAction>, Box<dyn Error>> {
+            Ok(Backtest::builder()
+                .add_asset(
+                    L2AssetBuilder::default()
+                        .data(data.clone())
+                        .latency_model(ConstantLatency::new(50, 50))
+                        .asset_type(LinearAsset::new(1.0))
+                        .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                        .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                        .exchange(NoPartialFillExchange)
+                        .depth(|| HashMapMarketDepth::new(0.01, 1.0))
+                        .price_action(HkPriceAction::new(vec![1000], vec![]))
+                        .build()?,
+                )
+                .build()?)
+        };
+        let run = |backtester: &mut Backtest<HashMapMarketDepth, HkPriceAction>| {
+            let mut records = Vec::new();
+            while backtester.elapse(70)? {
+                records.push((
+                    backtester.current_timestamp(),
+                    backtester.depth(0).bid_qty_at_tick(10000),
+                    backtester.state_values(0).clone(),
+                    backtester.updated_orders(0).to_vec(),
+                    backtester.orders(0).get(&1).map(|order| order.status),
+                    backtester.expired_timers().to_vec(),
+                ));
+            }
+            Ok::<_, BacktestError>(records)
+        };
+
+        let mut backtester = build()?;
+        assert!(backtester.elapse(320)?);
+        backtester.submit_buy_order(0, 1, 100.0, 1.0, GTC, Limit, false)?;
+        assert!(backtester.elapse(100)?);
+        backtester.schedule_at(1500, 1);
+        let path = std::env::temp_dir().join(format!(
+            "hftbacktest_{}_resumes_from_checkpoint.ckpt",
+            std::process::id()
+        ));
+        backtester.save_checkpoint(&path)?;
+        let checkpoint = backtester.checkpoint()?;
+        let updated_orders = backtester.updated_orders(0).to_vec();
+        assert_eq!(updated_orders, [1]);
+        let records = run(&mut backtester)?;
+        assert!(records.iter().any(|(.., status, _)| *status == Some(Status::Filled)));
+        assert!(records.iter().any(|(.., expired)| *expired == [1]));
+
+        let mut resumed = build()?;
+        resumed.load_checkpoint(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(resumed.updated_orders(0), updated_orders);
+        assert_eq!(run(&mut resumed)?, records);
+
+        // Forks the backtest from the checkpoint.
+        let mut forked = build()?;
+        forked.restore(&checkpoint)?;
+        assert_eq!(forked.updated_orders(0), updated_orders);
+        forked.cancel(0, 1, false)?;
+        assert!(
+            run(&mut forked)?
+                .iter()
+                .all(|(.., status, _)| *status != Some(Status::Filled))
+        );
+        Ok(())
+    }
 }
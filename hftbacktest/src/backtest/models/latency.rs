@@ -5,6 +5,7 @@ use hftbacktest_derive::NpyDTyped;
 use crate::{
     backtest::{
         BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
         data::{Data, DataPreprocess, DataSource, POD, Reader},
     },
     types::Order,
@@ -17,6 +18,16 @@ pub trait LatencyModel {
 
     /// Returns the order response latency for the given timestamp and order.
     fn response(&mut self, timestamp: i64, order: &Order) -> i64;
+
+    /// Writes the state of this model into the backtest checkpoint.
+    fn save_checkpoint(&self, _writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the latency model"))
+    }
+
+    /// Restores the state of this model from the backtest checkpoint.
+    fn restore_checkpoint(&mut self, _reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the latency model"))
+    }
 }
 
 /// Provides constant order latency.
@@ -52,6 +63,14 @@ impl LatencyModel for ConstantLatency {
     fn response(&mut self, _timestamp: i64, _order: &Order) -> i64 {
         self.response_latency
     }
+
+    fn save_checkpoint(&self, _writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        Ok(())
+    }
+
+    fn restore_checkpoint(&mut self, _reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        Ok(())
+    }
 }
 
 /// The historical order latency data
@@ -98,6 +117,8 @@ unsafe impl POD for OrderLatencyRow {}
 pub struct IntpOrderLatency {
     entry_rn: usize,
     resp_rn: usize,
+    // The position of `data` in the reader's data list.
+    data_no: usize,
     reader: Reader<OrderLatencyRow>,
     data: Data<OrderLatencyRow>,
     next_data: Data<OrderLatencyRow>,
@@ -135,6 +156,7 @@ impl IntpOrderLatency {
         Ok(Self {
             entry_rn: 0,
             resp_rn: 0,
+            data_no: 0,
             reader,
             data,
             next_data,
@@ -160,6 +182,7 @@ impl IntpOrderLatency {
             let next_data = mem::replace(&mut self.next_data, next_data);
             let data = mem::replace(&mut self.data, next_data);
             self.reader.release(data);
+            self.data_no += 1;
             Ok(true)
         } else {
             Ok(false)
@@ -271,6 +294,33 @@ impl LatencyModel for IntpOrderLatency {
             }
         }
     }
+
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.entry_rn)?;
+        writer.write(&self.resp_rn)?;
+        writer.write(&self.data_no)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        self.entry_rn = reader.read()?;
+        self.resp_rn = reader.read()?;
+        let data_no: usize = reader.read()?;
+        if data_no != self.data_no {
+            self.reader.seek(data_no);
+            let data = self.reader.next_data()?;
+            let next_data = match self.reader.next_data() {
+                Ok(data) => data,
+                Err(BacktestError::EndOfData) => Data::empty(),
+                Err(e) => return Err(e),
+            };
+            let data = mem::replace(&mut self.data, data);
+            self.reader.release(data);
+            let next_data = mem::replace(&mut self.next_data, next_data);
+            self.reader.release(next_data);
+            self.data_no = data_no;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
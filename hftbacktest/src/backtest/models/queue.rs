@@ -5,7 +5,10 @@ use std::{
 };
 
 use crate::{
-    backtest::BacktestError,
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+    },
     depth::{INVALID_MAX, INVALID_MIN, MarketDepth},
     types::{
         AnyClone,
@@ -37,6 +40,24 @@ where
     fn depth(&self, order: &mut Order, prev_qty: f64, new_qty: f64, depth: &MD);
 
     fn is_filled(&self, order: &Order, depth: &MD) -> f64;
+
+    /// Writes the order's queue position estimation values into the backtest checkpoint.
+    fn save_queue_pos(
+        &self,
+        _order: &Order,
+        _writer: &mut CheckpointWriter,
+    ) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the queue model"))
+    }
+
+    /// Restores the order's queue position estimation values from the backtest checkpoint.
+    fn restore_queue_pos(
+        &self,
+        _order: &mut Order,
+        _reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the queue model"))
+    }
 }
 
 /// Provides a conservative queue position model, where your order's queue position advances only
@@ -91,6 +112,25 @@ where
             0.0
         }
     }
+
+    fn save_queue_pos(
+        &self,
+        order: &Order,
+        writer: &mut CheckpointWriter,
+    ) -> Result<(), BacktestError> {
+        writer.write(&order.q.as_any().downcast_ref::<f64>().copied())
+    }
+
+    fn restore_queue_pos(
+        &self,
+        order: &mut Order,
+        reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        if let Some(front_q_qty) = reader.read::<Option<f64>>()? {
+            order.q = Box::new(front_q_qty);
+        }
+        Ok(())
+    }
 }
 
 /// Stores the values needed for queue position estimation and adjustment for [`ProbQueueModel`].
@@ -210,6 +250,29 @@ where
             0.0
         }
     }
+
+    fn save_queue_pos(
+        &self,
+        order: &Order,
+        writer: &mut CheckpointWriter,
+    ) -> Result<(), BacktestError> {
+        let q = order.q.as_any().downcast_ref::<QueuePos>();
+        writer.write(&q.map(|q| (q.front_q_qty, q.cum_trade_qty)))
+    }
+
+    fn restore_queue_pos(
+        &self,
+        order: &mut Order,
+        reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        if let Some((front_q_qty, cum_trade_qty)) = reader.read::<Option<(f64, f64)>>()? {
+            order.q = Box::new(QueuePos {
+                front_q_qty,
+                cum_trade_qty,
+            });
+        }
+        Ok(())
+    }
 }
 
 /// This probability model uses a power function `f(x) = x ** n` to adjust the probability which is
@@ -1,6 +1,12 @@
 use std::{cell::UnsafeCell, collections::VecDeque, rc::Rc};
 
-use crate::types::Order;
+use crate::{
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+    },
+    types::Order,
+};
 
 /// Provides a bus for transporting backtesting orders between the exchange and the local model
 /// based on the given timestamp.
@@ -65,4 +71,19 @@ impl OrderBus {
     pub fn pop_front(&mut self) -> Option<(Order, i64)> {
         unsafe { &mut *self.order_list.get() }.pop_front()
     }
+
+    /// Writes the orders in the bus and their timestamps into the checkpoint.
+    pub fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(unsafe { &*self.order_list.get() })
+    }
+
+    /// Replaces the orders in the bus with the ones in the checkpoint.
+    pub fn restore_checkpoint(
+        &mut self,
+        reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        let order_list: VecDeque<(Order, i64)> = reader.read()?;
+        *unsafe { &mut *self.order_list.get() } = order_list;
+        Ok(())
+    }
 }
@@ -30,6 +30,7 @@ use crate::{
         Status,
         TimeInForce,
     },
+    utils::UpdatedOrders,
 };
 
 /// The Level3 Market-By-Order local model.
@@ -42,6 +43,7 @@ where
     PA: PriceAction,
 {
     orders: HashMap<OrderId, Order>,
+    updated_orders: UpdatedOrders,
     orders_to: OrderBus,
     orders_from: OrderBus,
     depth: MD,
@@ -74,6 +76,7 @@ where
     ) -> Self {
         Self {
             orders: Default::default(),
+            updated_orders: Default::default(),
             orders_to,
             orders_from,
             depth,
@@ -90,8 +93,9 @@ where
         if order.status == Status::Filled {
             self.state.apply_fill(&order);
         }
+        let order_id = order.order_id;
         // Applies the received order response to the local orders.
-        match self.orders.entry(order.order_id) {
+        match self.orders.entry(order_id) {
             Entry::Occupied(mut entry) => {
                 let local_order = entry.get_mut();
                 if order.req == Status::Rejected {
@@ -113,6 +117,9 @@ where
                 }
             }
         }
+        if self.orders.contains_key(&order_id) {
+            self.updated_orders.insert(order_id);
+        }
         Ok(())
     }
 }
@@ -153,6 +160,7 @@ where
         order.req = Status::New;
         order.local_timestamp = current_timestamp;
         self.orders.insert(order.order_id, order.clone());
+        self.updated_orders.insert(order_id);
 
         let order_entry_latency = self.order_latency.entry(current_timestamp, &order);
         // Negative latency indicates that the order is rejected for technical reasons, and its
@@ -194,6 +202,7 @@ where
         order.qty = qty;
 
         order.req = Status::Replaced;
+        self.updated_orders.insert(order_id);
         order.local_timestamp = current_timestamp;
 
         let order_entry_latency = self.order_latency.entry(current_timestamp, order);
@@ -226,6 +235,7 @@ where
         }
 
         order.req = Status::Canceled;
+        self.updated_orders.insert(order_id);
         let order_entry_latency = self.order_latency.entry(current_timestamp, order);
         // Negative latency indicates that the order is rejected for technical reasons, and its
         // value represents the latency that the local experiences when receiving the rejection
@@ -248,7 +258,9 @@ where
             order.status != Status::Expired
                 && order.status != Status::Filled
                 && order.status != Status::Canceled
-        })
+        });
+        self.updated_orders
+            .retain(|order_id| self.orders.contains_key(order_id));
     }
 
     fn position(&self) -> f64 {
@@ -271,6 +283,14 @@ where
         &self.orders
     }
 
+    fn updated_orders(&self) -> &[OrderId] {
+        self.updated_orders.as_slice()
+    }
+
+    fn clear_updated_orders(&mut self) {
+        self.updated_orders.clear();
+    }
+
     fn last_trades(&self) -> &[Event] {
         self.trades.as_slice()
     }
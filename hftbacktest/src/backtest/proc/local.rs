@@ -4,6 +4,7 @@ use crate::{
     backtest::{
         BacktestError,
         assettype::AssetType,
+        checkpoint::{CheckpointReader, CheckpointWriter},
         models::{FeeModel, LatencyModel},
         order::OrderBus,
         proc::{LocalProcessor, Processor},
@@ -31,6 +32,7 @@ use crate::{
         Status,
         TimeInForce,
     },
+    utils::UpdatedOrders,
 };
 
 /// The local model.
@@ -43,6 +45,7 @@ where
     PA: PriceAction,
 {
     orders: HashMap<OrderId, Order>,
+    updated_orders: UpdatedOrders,
     orders_to: OrderBus,
     orders_from: OrderBus,
     depth: MD,
@@ -74,6 +77,7 @@ where
     ) -> Self {
         Self {
             orders: Default::default(),
+            updated_orders: Default::default(),
             orders_to,
             orders_from,
             depth,
@@ -90,8 +94,9 @@ where
         if order.status == Status::Filled {
             self.state.apply_fill(&order);
         }
+        let order_id = order.order_id;
         // Applies the received order response to the local orders.
-        match self.orders.entry(order.order_id) {
+        match self.orders.entry(order_id) {
             Entry::Occupied(mut entry) => {
                 let local_order = entry.get_mut();
                 if order.req == Status::Rejected {
@@ -113,6 +118,9 @@ where
                 }
             }
         }
+        if self.orders.contains_key(&order_id) {
+            self.updated_orders.insert(order_id);
+        }
         Ok(())
     }
 }
@@ -152,6 +160,7 @@ where
         order.req = Status::New;
         order.local_timestamp = current_timestamp;
         self.orders.insert(order.order_id, order.clone());
+        self.updated_orders.insert(order_id);
 
         let order_entry_latency = self.order_latency.entry(current_timestamp, &order);
         // Negative latency indicates that the order is rejected for technical reasons, and its
@@ -193,6 +202,7 @@ where
         order.qty = qty;
 
         order.req = Status::Replaced;
+        self.updated_orders.insert(order_id);
         order.local_timestamp = current_timestamp;
 
         let order_entry_latency = self.order_latency.entry(current_timestamp, order);
@@ -225,6 +235,7 @@ where
         }
 
         order.req = Status::Canceled;
+        self.updated_orders.insert(order_id);
         let order_entry_latency = self.order_latency.entry(current_timestamp, order);
         // Negative latency indicates that the order is rejected for technical reasons, and its
         // value represents the latency that the local experiences when receiving the rejection
@@ -247,7 +258,9 @@ where
             order.status != Status::Expired
                 && order.status != Status::Filled
                 && order.status != Status::Canceled
-        })
+        });
+        self.updated_orders
+            .retain(|order_id| self.orders.contains_key(order_id));
     }
 
     fn position(&self) -> f64 {
@@ -270,6 +283,14 @@ where
         &self.orders
     }
 
+    fn updated_orders(&self) -> &[OrderId] {
+        self.updated_orders.as_slice()
+    }
+
+    fn clear_updated_orders(&mut self) {
+        self.updated_orders.clear();
+    }
+
     fn last_trades(&self) -> &[Event] {
         self.trades.as_slice()
     }
@@ -372,4 +393,37 @@ where
     fn earliest_send_order_timestamp(&self) -> i64 {
         self.orders_to.earliest_timestamp().unwrap_or(i64::MAX)
     }
+
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.orders)?;
+        writer.write(&self.updated_orders.as_slice().to_vec())?;
+        self.orders_to.save_checkpoint(writer)?;
+        self.depth.save_checkpoint(writer)?;
+        self.state.save_checkpoint(writer)?;
+        self.order_latency.save_checkpoint(writer)?;
+        writer.write(&self.trades)?;
+        writer.write(&self.last_feed_latency)?;
+        writer.write(&self.last_order_latency)?;
+        self.price_action.save_checkpoint(writer)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        self.orders = reader.read()?;
+        let updated_orders: Vec<OrderId> = reader.read()?;
+        self.updated_orders.clear();
+        for order_id in updated_orders {
+            self.updated_orders.insert(order_id);
+        }
+        self.orders_to.restore_checkpoint(reader)?;
+        self.depth.restore_checkpoint(reader)?;
+        self.state.restore_checkpoint(reader)?;
+        self.order_latency.restore_checkpoint(reader)?;
+        // Keeps the capacity, which determines whether the last trades are collected.
+        let trades: Vec<Event> = reader.read()?;
+        self.trades.clear();
+        self.trades.extend(trades);
+        self.last_feed_latency = reader.read()?;
+        self.last_order_latency = reader.read()?;
+        self.price_action.restore_checkpoint(reader)
+    }
 }
@@ -16,7 +16,10 @@ pub use l3_local::L3Local;
 pub use l3_nopartialfillexchange::L3NoPartialFillExchange;
 
 use crate::{
-    backtest::BacktestError,
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+    },
     depth::MarketDepth,
     prelude::{Event, OrdType, Order, OrderId, PriceAction, Side, StateValues, TimeInForce},
 };
@@ -89,6 +92,13 @@ where
     /// Returns a hash map of order IDs and their corresponding [`Order`]s.
     fn orders(&self) -> &HashMap<OrderId, Order>;
 
+    /// Returns the IDs of the orders updated by a request or a response since they were last
+    /// cleared.
+    fn updated_orders(&self) -> &[OrderId];
+
+    /// Clears the IDs of the updated orders.
+    fn clear_updated_orders(&mut self);
+
     /// Returns the last market trades.
     fn last_trades(&self) -> &[Event];
 
@@ -127,6 +137,14 @@ impl<P: Processor + ?Sized> Processor for Box<P> {
     fn earliest_send_order_timestamp(&self) -> i64 {
         P::earliest_send_order_timestamp(self)
     }
+
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        P::save_checkpoint(self, writer)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        P::restore_checkpoint(self, reader)
+    }
 }
 /// Processes the historical feed data and the order interaction.
 pub trait Processor {
@@ -156,4 +174,16 @@ pub trait Processor {
     /// Returns the foremost timestamp at which an order sent by this processor is to be received by
     /// the corresponding processor.
     fn earliest_send_order_timestamp(&self) -> i64;
+
+    /// Writes the state of this processor into the checkpoint. The orders in transit are written
+    /// by the processor sending them.
+    fn save_checkpoint(&self, _writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the processor"))
+    }
+
+    /// Restores the state of this processor from the checkpoint written by
+    /// [`save_checkpoint`](Self::save_checkpoint()) of an identically configured processor.
+    fn restore_checkpoint(&mut self, _reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the processor"))
+    }
 }
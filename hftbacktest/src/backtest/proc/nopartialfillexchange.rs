@@ -9,6 +9,7 @@ use crate::{
     backtest::{
         BacktestError,
         assettype::AssetType,
+        checkpoint::{CheckpointReader, CheckpointWriter},
         models::{FeeModel, LatencyModel, QueueModel},
         order::OrderBus,
         proc::Processor,
@@ -664,4 +665,38 @@ where
     fn earliest_send_order_timestamp(&self) -> i64 {
         self.orders_to.earliest_timestamp().unwrap_or(i64::MAX)
     }
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        let orders = self.orders.borrow();
+        writer.write(&orders.len())?;
+        for order in orders.values() {
+            writer.write(order)?;
+            self.queue_model.save_queue_pos(order, writer)?;
+        }
+        writer.write(&self.buy_orders)?;
+        writer.write(&self.sell_orders)?;
+        self.orders_to.save_checkpoint(writer)?;
+        self.depth.save_checkpoint(writer)?;
+        self.state.save_checkpoint(writer)?;
+        self.order_latency.save_checkpoint(writer)?;
+        writer.write(&self.filled_orders)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        let num_orders: usize = reader.read()?;
+        let mut orders = HashMap::with_capacity(num_orders);
+        for _ in 0..num_orders {
+            let mut order: Order = reader.read()?;
+            self.queue_model.restore_queue_pos(&mut order, reader)?;
+            orders.insert(order.order_id, order);
+        }
+        *self.orders.borrow_mut() = orders;
+        self.buy_orders = reader.read()?;
+        self.sell_orders = reader.read()?;
+        self.orders_to.restore_checkpoint(reader)?;
+        self.depth.restore_checkpoint(reader)?;
+        self.state.restore_checkpoint(reader)?;
+        self.order_latency.restore_checkpoint(reader)?;
+        self.filled_orders = reader.read()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,539 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+use crate::types::{ORDER_REQUEST, ORDER_RESPONSE, OrderRecord, Status};
+
+const QTY_EPSILON: f64 = 1e-10;
+
+/// Classification of a discrepancy between a live session and its backtest.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum DiscrepancyKind {
+    /// The order is filled in the live session but not in the backtest, and the backtest order
+    /// wasn't resting at the time of the fill.
+    MissedFill,
+    /// The order is filled in the backtest but not in the live session, and the live order wasn't
+    /// resting at the time of the fill.
+    ExtraFill,
+    /// Both are filled but at different times.
+    FillTimeDelta,
+    /// Both are filled but at different prices.
+    FillPriceDelta,
+    /// The time from submission to the exchange's acknowledgement differs.
+    LatencyDifference,
+    /// Only one of them is filled while the other was resting at the same price, which means that
+    /// the queue position is estimated differently from the actual one.
+    QueuePosition,
+}
+
+impl DiscrepancyKind {
+    const ALL: [DiscrepancyKind; 6] = [
+        DiscrepancyKind::MissedFill,
+        DiscrepancyKind::ExtraFill,
+        DiscrepancyKind::FillTimeDelta,
+        DiscrepancyKind::FillPriceDelta,
+        DiscrepancyKind::LatencyDifference,
+        DiscrepancyKind::QueuePosition,
+    ];
+}
+
+/// A discrepancy found by [`Reconciler`].
+#[derive(Clone, Debug)]
+pub struct Discrepancy {
+    pub kind: DiscrepancyKind,
+    /// The order ID in the live session, if there is a corresponding order.
+    pub live_order_id: Option<u64>,
+    /// The order ID in the backtest, if there is a corresponding order.
+    pub backtest_order_id: Option<u64>,
+    /// The exchange timestamp of the fill, or the submission timestamp for the latency difference.
+    pub timestamp: i64,
+    /// The quantity involved.
+    pub qty: f64,
+    /// Live minus backtest, in nanoseconds for the time and latency differences and in price for
+    /// the price difference. For fill differences, this is the fill quantity difference.
+    pub delta: f64,
+    /// The contribution to the difference between the live and the backtest PnL, which is
+    /// evaluated at the mark price excluding fees.
+    pub pnl_impact: f64,
+}
+
+/// Summary of a discrepancy class.
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub kind: DiscrepancyKind,
+    pub count: usize,
+    pub pnl_impact: f64,
+}
+
+/// The result of the reconciliation.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// The number of matched order pairs.
+    pub matched: usize,
+    /// The number of live orders that don't have a corresponding backtest order.
+    pub unmatched_live: usize,
+    /// The number of backtest orders that don't have a corresponding live order.
+    pub unmatched_backtest: usize,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl Report {
+    /// Returns the count and the PnL impact of each discrepancy class.
+    pub fn summary(&self) -> Vec<Summary> {
+        DiscrepancyKind::ALL
+            .iter()
+            .map(|kind| {
+                let (count, pnl_impact) = self
+                    .discrepancies
+                    .iter()
+                    .filter(|discrepancy| discrepancy.kind == *kind)
+                    .fold((0, 0.0), |(count, pnl_impact), discrepancy| {
+                        (count + 1, pnl_impact + discrepancy.pnl_impact)
+                    });
+                Summary {
+                    kind: *kind,
+                    count,
+                    pnl_impact,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the difference between the live and the backtest PnL explained by the
+    /// discrepancies.
+    pub fn pnl_difference(&self) -> f64 {
+        self.discrepancies
+            .iter()
+            .map(|discrepancy| discrepancy.pnl_impact)
+            .sum()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "matched: {}, unmatched live: {}, unmatched backtest: {}",
+            self.matched, self.unmatched_live, self.unmatched_backtest
+        )?;
+        writeln!(f, "{:<20}{:>10}{:>20}", "kind", "count", "pnl impact")?;
+        for summary in self.summary() {
+            writeln!(
+                f,
+                "{:<20}{:>10}{:>20.6}",
+                format!("{:?}", summary.kind),
+                summary.count,
+                summary.pnl_impact
+            )?;
+        }
+        write!(f, "{:<30}{:>20.6}", "total", self.pnl_difference())
+    }
+}
+
+struct Fill {
+    exch_ts: i64,
+    qty: f64,
+    price: f64,
+}
+
+/// An order reconstructed from the order log.
+struct OrderHistory {
+    order_id: u64,
+    side: i64,
+    price: f64,
+    submit_ts: i64,
+    ack_ts: Option<i64>,
+    end_ts: i64,
+    fills: Vec<Fill>,
+}
+
+impl OrderHistory {
+    fn filled_qty(&self) -> f64 {
+        self.fills.iter().map(|fill| fill.qty).sum()
+    }
+
+    fn avg_fill_price(&self) -> f64 {
+        let qty = self.filled_qty();
+        if qty < QTY_EPSILON {
+            return 0.0;
+        }
+        self.fills
+            .iter()
+            .map(|fill| fill.qty * fill.price)
+            .sum::<f64>()
+            / qty
+    }
+
+    fn first_fill_ts(&self) -> Option<i64> {
+        self.fills.first().map(|fill| fill.exch_ts)
+    }
+
+    fn is_resting_at(&self, timestamp: i64) -> bool {
+        self.ack_ts.is_some_and(|ack_ts| ack_ts <= timestamp) && timestamp < self.end_ts
+    }
+
+    fn latency(&self) -> Option<i64> {
+        self.ack_ts.map(|ack_ts| ack_ts - self.submit_ts)
+    }
+}
+
+fn build_histories(records: &[OrderRecord]) -> Vec<OrderHistory> {
+    let mut by_id: HashMap<u64, Vec<&OrderRecord>> = HashMap::new();
+    for record in records {
+        by_id.entry(record.order_id).or_default().push(record);
+    }
+
+    let mut histories: Vec<_> = by_id
+        .into_values()
+        .filter_map(|mut records| {
+            records.sort_by_key(|record| record.local_ts);
+            let first = *records.first()?;
+            let submit_ts = records
+                .iter()
+                .find(|record| record.kind == ORDER_REQUEST)
+                .map(|record| record.local_ts)
+                .unwrap_or(first.local_ts);
+            let mut history = OrderHistory {
+                order_id: first.order_id,
+                side: first.side,
+                price: first.price,
+                submit_ts,
+                ack_ts: None,
+                end_ts: i64::MAX,
+                fills: Vec::new(),
+            };
+            let mut leaves_qty = first.qty;
+            for record in records
+                .iter()
+                .filter(|record| record.kind == ORDER_RESPONSE)
+            {
+                let exch_ts = if record.exch_ts > 0 {
+                    record.exch_ts
+                } else {
+                    record.local_ts
+                };
+                if history.ack_ts.is_none() {
+                    history.ack_ts = Some(exch_ts);
+                }
+                let filled = record.status == Status::Filled as u64
+                    || record.status == Status::PartiallyFilled as u64;
+                if filled && record.leaves_qty < leaves_qty - QTY_EPSILON {
+                    history.fills.push(Fill {
+                        exch_ts,
+                        qty: leaves_qty - record.leaves_qty,
+                        price: if record.exec_price > 0.0 {
+                            record.exec_price
+                        } else {
+                            record.price
+                        },
+                    });
+                    leaves_qty = record.leaves_qty;
+                }
+                if (record.status == Status::Filled as u64
+                    || record.status == Status::Canceled as u64
+                    || record.status == Status::Expired as u64
+                    || record.status == Status::Rejected as u64)
+                    && history.end_ts == i64::MAX
+                {
+                    history.end_ts = exch_ts;
+                }
+            }
+            Some(history)
+        })
+        .collect();
+    histories.sort_by_key(|history| (history.submit_ts, history.order_id));
+    histories
+}
+
+/// Reconciles a live session against its backtest by aligning orders by their submission time,
+/// side and price, then classifying each discrepancy in fills and latencies.
+///
+/// The order logs can be obtained from [`SessionCapture`](crate::live::SessionCapture) for the
+/// live session and from [`BacktestRecorder`](crate::backtest::recorder::BacktestRecorder) for the
+/// backtest. The PnL impact is the difference between the live and the backtest PnL, marked to
+/// the given mark price and excluding fees, so the sum of all impacts is the total PnL difference
+/// caused by the fill differences.
+///
+/// The reconciliation is deterministic: ties are broken by the submission time and the order ID.
+pub struct Reconciler {
+    mark_price: f64,
+    match_window: i64,
+    fill_time_tolerance: i64,
+    latency_tolerance: i64,
+    price_tolerance: f64,
+}
+
+impl Reconciler {
+    /// Constructs a `Reconciler` with the price at which the positions are marked.
+    pub fn new(mark_price: f64) -> Self {
+        Self {
+            mark_price,
+            match_window: 1_000_000_000,
+            fill_time_tolerance: 1_000_000,
+            latency_tolerance: 1_000_000,
+            price_tolerance: 1e-9,
+        }
+    }
+
+    /// Sets the maximum difference in submission time, in nanoseconds, for orders to be aligned.
+    /// The default value is 1 second.
+    pub fn match_window(self, match_window: i64) -> Self {
+        Self {
+            match_window,
+            ..self
+        }
+    }
+
+    /// Sets the fill time difference, in nanoseconds, below which fills are considered
+    /// simultaneous. The default value is 1 millisecond.
+    pub fn fill_time_tolerance(self, fill_time_tolerance: i64) -> Self {
+        Self {
+            fill_time_tolerance,
+            ..self
+        }
+    }
+
+    /// Sets the latency difference, in nanoseconds, below which latencies are considered the
+    /// same. The default value is 1 millisecond.
+    pub fn latency_tolerance(self, latency_tolerance: i64) -> Self {
+        Self {
+            latency_tolerance,
+            ..self
+        }
+    }
+
+    /// Sets the price difference below which prices are considered the same. It is also used to
+    /// align orders by price. The default value is `1e-9`.
+    pub fn price_tolerance(self, price_tolerance: f64) -> Self {
+        Self {
+            price_tolerance,
+            ..self
+        }
+    }
+
+    /// Reconciles the live order log against the backtest order log of the same asset.
+    pub fn reconcile(&self, live: &[OrderRecord], backtest: &[OrderRecord]) -> Report {
+        let live = build_histories(live);
+        let backtest = build_histories(backtest);
+
+        let mut backtest_matched = vec![false; backtest.len()];
+        let mut report = Report {
+            matched: 0,
+            unmatched_live: 0,
+            unmatched_backtest: 0,
+            discrepancies: Vec::new(),
+        };
+
+        for live_order in live.iter() {
+            let candidate = backtest
+                .iter()
+                .enumerate()
+                .filter(|(i, bt_order)| {
+                    !backtest_matched[*i]
+                        && bt_order.side == live_order.side
+                        && (bt_order.price - live_order.price).abs() <= self.price_tolerance
+                        && (bt_order.submit_ts - live_order.submit_ts).abs() <= self.match_window
+                })
+                .min_by_key(|(_, bt_order)| (bt_order.submit_ts - live_order.submit_ts).abs());
+            match candidate {
+                Some((i, bt_order)) => {
+                    backtest_matched[i] = true;
+                    report.matched += 1;
+                    self.compare(live_order, bt_order, &mut report.discrepancies);
+                }
+                None => {
+                    report.unmatched_live += 1;
+                    self.unmatched_fill(live_order, true, &mut report.discrepancies);
+                }
+            }
+        }
+
+        for (bt_order, _) in backtest
+            .iter()
+            .zip(backtest_matched.iter())
+            .filter(|(_, matched)| !**matched)
+        {
+            report.unmatched_backtest += 1;
+            self.unmatched_fill(bt_order, false, &mut report.discrepancies);
+        }
+
+        report
+            .discrepancies
+            .sort_by_key(|discrepancy| discrepancy.timestamp);
+        report
+    }
+
+    fn unmatched_fill(&self, order: &OrderHistory, live: bool, out: &mut Vec<Discrepancy>) {
+        let qty = order.filled_qty();
+        if qty < QTY_EPSILON {
+            return;
+        }
+        let sign = if live { 1.0 } else { -1.0 };
+        out.push(Discrepancy {
+            kind: if live {
+                DiscrepancyKind::MissedFill
+            } else {
+                DiscrepancyKind::ExtraFill
+            },
+            live_order_id: live.then_some(order.order_id),
+            backtest_order_id: (!live).then_some(order.order_id),
+            timestamp: order.first_fill_ts().unwrap_or(order.submit_ts),
+            qty,
+            delta: sign * qty,
+            pnl_impact: sign * order.side as f64 * qty * (self.mark_price - order.avg_fill_price()),
+        });
+    }
+
+    fn compare(&self, live: &OrderHistory, bt: &OrderHistory, out: &mut Vec<Discrepancy>) {
+        if let (Some(live_latency), Some(bt_latency)) = (live.latency(), bt.latency()) {
+            if (live_latency - bt_latency).abs() > self.latency_tolerance {
+                out.push(Discrepancy {
+                    kind: DiscrepancyKind::LatencyDifference,
+                    live_order_id: Some(live.order_id),
+                    backtest_order_id: Some(bt.order_id),
+                    timestamp: live.submit_ts,
+                    qty: 0.0,
+                    delta: (live_latency - bt_latency) as f64,
+                    pnl_impact: 0.0,
+                });
+            }
+        }
+
+        let live_qty = live.filled_qty();
+        let bt_qty = bt.filled_qty();
+        let side = live.side as f64;
+
+        let common_qty = live_qty.min(bt_qty);
+        if common_qty > QTY_EPSILON {
+            let live_price = live.avg_fill_price();
+            let bt_price = bt.avg_fill_price();
+            if (live_price - bt_price).abs() > self.price_tolerance {
+                out.push(Discrepancy {
+                    kind: DiscrepancyKind::FillPriceDelta,
+                    live_order_id: Some(live.order_id),
+                    backtest_order_id: Some(bt.order_id),
+                    timestamp: live.first_fill_ts().unwrap_or(live.submit_ts),
+                    qty: common_qty,
+                    delta: live_price - bt_price,
+                    pnl_impact: side * common_qty * (bt_price - live_price),
+                });
+            }
+            if let (Some(live_ts), Some(bt_ts)) = (live.first_fill_ts(), bt.first_fill_ts()) {
+                if (live_ts - bt_ts).abs() > self.fill_time_tolerance {
+                    out.push(Discrepancy {
+                        kind: DiscrepancyKind::FillTimeDelta,
+                        live_order_id: Some(live.order_id),
+                        backtest_order_id: Some(bt.order_id),
+                        timestamp: live_ts,
+                        qty: common_qty,
+                        delta: (live_ts - bt_ts) as f64,
+                        pnl_impact: 0.0,
+                    });
+                }
+            }
+        }
+
+        let qty_diff = live_qty - bt_qty;
+        if qty_diff.abs() > QTY_EPSILON {
+            // The order with more fills determines when the unmatched quantity is filled, and
+            // whether the other order was still resting at that time.
+            let (filled, other) = if qty_diff > 0.0 {
+                (live, bt)
+            } else {
+                (bt, live)
+            };
+            let mut remaining = common_qty;
+            let fill = filled
+                .fills
+                .iter()
+                .find(|fill| {
+                    remaining -= fill.qty;
+                    remaining < -QTY_EPSILON
+                })
+                .unwrap();
+            let kind = if other.is_resting_at(fill.exch_ts) {
+                DiscrepancyKind::QueuePosition
+            } else if qty_diff > 0.0 {
+                DiscrepancyKind::MissedFill
+            } else {
+                DiscrepancyKind::ExtraFill
+            };
+            out.push(Discrepancy {
+                kind,
+                live_order_id: Some(live.order_id),
+                backtest_order_id: Some(bt.order_id),
+                timestamp: fill.exch_ts,
+                qty: qty_diff.abs(),
+                delta: qty_diff,
+                pnl_impact: side * qty_diff * (self.mark_price - filled.avg_fill_price()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(kind: u64, ts: i64, order_id: u64, leaves_qty: f64, status: Status) -> OrderRecord {
+        OrderRecord {
+            kind,
+            local_ts: ts,
+            exch_ts: if kind == ORDER_REQUEST { 0 } else { ts },
+            order_id,
+            side: 1,
+            price: 100.0,
+            qty: 1.0,
+            leaves_qty,
+            exec_price: if leaves_qty < 1.0 { 100.0 } else { 0.0 },
+            exec_qty: 1.0 - leaves_qty,
+            status: status as u64,
+            req: Status::New as u64,
+            time_in_force: 0,
+            order_type: 0,
+        }
+    }
+
+    #[test]
+    fn classify_discrepancies() {
+        let live = vec![
+            // Filled in both, but later in the backtest.
+            record(ORDER_REQUEST, 1_000, 1, 1.0, Status::New),
+            record(ORDER_RESPONSE, 1_100, 1, 1.0, Status::New),
+            record(ORDER_RESPONSE, 50_000_000, 1, 0.0, Status::Filled),
+            // Filled only in the live while the backtest order was resting.
+            record(ORDER_REQUEST, 100_000_000_000, 2, 1.0, Status::New),
+            record(ORDER_RESPONSE, 100_000_000_100, 2, 1.0, Status::New),
+            record(ORDER_RESPONSE, 100_100_000_000, 2, 0.0, Status::Filled),
+        ];
+        let backtest = vec![
+            record(ORDER_REQUEST, 1_000, 11, 1.0, Status::New),
+            record(ORDER_RESPONSE, 1_100, 11, 1.0, Status::New),
+            record(ORDER_RESPONSE, 80_000_000, 11, 0.0, Status::Filled),
+            record(ORDER_REQUEST, 100_000_000_000, 12, 1.0, Status::New),
+            record(ORDER_RESPONSE, 100_000_000_100, 12, 1.0, Status::New),
+            record(ORDER_RESPONSE, 100_200_000_000, 12, 1.0, Status::Canceled),
+            // Filled only in the backtest.
+            record(ORDER_REQUEST, 200_000_000_000, 13, 1.0, Status::New),
+            record(ORDER_RESPONSE, 200_000_000_100, 13, 0.0, Status::Filled),
+        ];
+
+        let report = Reconciler::new(101.0).reconcile(&live, &backtest);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.unmatched_live, 0);
+        assert_eq!(report.unmatched_backtest, 1);
+
+        let kinds: Vec<_> = report.discrepancies.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiscrepancyKind::FillTimeDelta,
+                DiscrepancyKind::QueuePosition,
+                DiscrepancyKind::ExtraFill,
+            ]
+        );
+        assert!((report.pnl_difference() - 0.0).abs() < 1e-9);
+    }
+}
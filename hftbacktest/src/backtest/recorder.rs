@@ -1,4 +1,5 @@
 use std::{
+    collections::{HashMap, hash_map::Entry},
     fs::File,
     io::{BufWriter, Error, Write},
     path::Path,
@@ -11,28 +12,34 @@ use crate::{
     backtest::data::{POD, write_npy},
     depth::MarketDepth,
     prelude::PriceAction, 
-    types::{Bot, Recorder},
+    types::{Bot, ORDER_REQUEST, ORDER_RESPONSE, Order, OrderId, OrderRecord, Recorder},
 };
 
+/// The state values of an asset recorded by [`BacktestRecorder`].
 #[repr(C)]
-#[derive(NpyDTyped)]
-struct Record {
-    timestamp: i64,
-    price: f64,
-    position: f64,
-    balance: f64,
-    fee: f64,
-    num_trades: i64,
-    trading_volume: f64,
-    trading_value: f64,
+#[derive(Clone, Debug, NpyDTyped)]
+pub struct Record {
+    pub timestamp: i64,
+    /// The mid-price.
+    pub price: f64,
+    pub position: f64,
+    pub balance: f64,
+    pub fee: f64,
+    pub num_trades: i64,
+    pub trading_volume: f64,
+    pub trading_value: f64,
 }
 
 unsafe impl POD for Record {}
 
 /// Provides recording of the backtesting strategy's state values, which are needed to compute
-/// performance metrics.
+/// performance metrics, and the order log.
+///
+/// The order log is built from [`Bot::updated_orders`], which is cleared on each recording.
 pub struct BacktestRecorder {
     values: Vec<Vec<Record>>,
+    orders: Vec<Vec<OrderRecord>>,
+    last_orders: Vec<HashMap<OrderId, (i64, f64)>>,
 }
 
 impl Recorder for BacktestRecorder {
@@ -60,6 +67,50 @@ impl Recorder for BacktestRecorder {
                 trading_value: state_values.trading_value,
                 num_trades: state_values.num_trades,
             });
+
+            let orders = unsafe { self.orders.get_unchecked_mut(asset_no) };
+            let last_orders = unsafe { self.last_orders.get_unchecked_mut(asset_no) };
+            // Only the orders updated since the last recording are examined, and only the changes
+            // observed at recording time are logged, so the order log is as fine-grained as the
+            // recording interval, except for the exchange timestamps.
+            for order_id in hbt.updated_orders(asset_no) {
+                let Some(order) = hbt.orders(asset_no).get(order_id) else {
+                    continue;
+                };
+                match last_orders.entry(order.order_id) {
+                    Entry::Occupied(mut entry) => {
+                        if *entry.get() != (order.exch_timestamp, order.leaves_qty) {
+                            *entry.get_mut() = (order.exch_timestamp, order.leaves_qty);
+                            orders.push(OrderRecord::new(ORDER_RESPONSE, timestamp, order));
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert((order.exch_timestamp, order.leaves_qty));
+                        orders.push(OrderRecord::new(
+                            ORDER_REQUEST,
+                            order.local_timestamp,
+                            &Order {
+                                exch_timestamp: 0,
+                                leaves_qty: order.qty,
+                                exec_qty: 0.0,
+                                exec_price_tick: 0,
+                                ..order.clone()
+                            },
+                        ));
+                        if order.exch_timestamp > 0 {
+                            orders.push(OrderRecord::new(ORDER_RESPONSE, timestamp, order));
+                        }
+                    }
+                }
+            }
+            // The orders cleared by `clear_inactive_orders` are dropped once they outnumber the
+            // bot's orders, so that the cost is amortized.
+            let num_orders = hbt.orders(asset_no).len();
+            if last_orders.len() > 2 * num_orders.max(64) {
+                let bot_orders = hbt.orders(asset_no);
+                last_orders.retain(|order_id, _| bot_orders.contains_key(order_id));
+            }
+            hbt.clear_updated_orders(Some(asset_no));
         }
         Ok(())
     }
@@ -81,6 +132,8 @@ impl BacktestRecorder {
                 }
                 vec
             },
+            orders: (0..hbt.num_assets()).map(|_| Vec::new()).collect(),
+            last_orders: (0..hbt.num_assets()).map(|_| HashMap::new()).collect(),
         }
     }
 
@@ -129,6 +182,9 @@ impl BacktestRecorder {
         Ok(())
     }
 
+    /// Saves record data into a `npz` file at the specified path. The state values of each asset
+    /// are stored under `{asset_no}`, and the order log, as [`OrderRecord`], under
+    /// `{asset_no}_orders`.
     pub fn to_npz<P>(&self, path: P) -> Result<(), Error>
     where
         P: AsRef<Path>,
@@ -146,10 +202,20 @@ impl BacktestRecorder {
             write_npy(&mut zip, values)?;
         }
 
+        for (asset_no, orders) in self.orders.iter().enumerate() {
+            zip.start_file(format!("{asset_no}_orders.npy"), options)?;
+            write_npy(&mut zip, orders)?;
+        }
+
         zip.finish()?;
         Ok(())
     }
 
+    /// Returns the order log of the given asset.
+    pub fn orders(&self, asset_no: usize) -> &[OrderRecord] {
+        &self.orders[asset_no]
+    }
+
     
     /// 统计 self.values 中的状态数据，返回累计收益、最大回撤和夏普比率
     ///
@@ -226,3 +292,110 @@ impl BacktestRecorder {
         Ok((peak, cum_return, trough, max_dd, sharpe, fee))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        backtest::{
+            Backtest,
+            DataSource,
+            ExchangeKind::NoPartialFillExchange,
+            L2AssetBuilder,
+            assettype::LinearAsset,
+            data::Data,
+            models::{
+                CommonFees,
+                ConstantLatency,
+                PowerProbQueueFunc3,
+                ProbQueueModel,
+                TradingValueFeeModel,
+            },
+            recorder::BacktestRecorder,
+        },
+        depth::HashMapMarketDepth,
+        prelude::{Bot, Event, HkPriceAction, OrdType, Recorder, Status, TimeInForce},
+        types::{
+            EXCH_EVENT,
+            LOCAL_ASK_DEPTH_EVENT,
+            LOCAL_BID_DEPTH_EVENT,
+            ORDER_REQUEST,
+            ORDER_RESPONSE,
+        },
+    };
+
+    fn event(ev: u64, ts: i64, px: f64) -> Event {
+        Event {
+            ev: ev | EXCH_EVENT,
+            exch_ts: ts,
+            local_ts: ts,
+            px,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn records_updated_orders() -> Result<(), Box<dyn Error>> {
+        let data = Data::from_data(&[
+            event(LOCAL_BID_DEPTH_EVENT, 100, 100.0),
+            event(LOCAL_ASK_DEPTH_EVENT, 100, 101.0),
+            event(LOCAL_BID_DEPTH_EVENT, 10_000, 100.0),
+        ]);
+        let mut hbt: Backtest<HashMapMarketDepth, HkPriceAction> = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(1.0, 1.0))
+                    .price_action(HkPriceAction::new(vec![], vec![]))
+                    .build()?,
+            )
+            .build()?;
+        let mut recorder = BacktestRecorder::new(&hbt);
+
+        hbt.elapse(1000)?;
+        hbt.submit_buy_order(0, 1, 101.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)?;
+        hbt.submit_buy_order(0, 2, 90.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)?;
+        assert_eq!(hbt.updated_orders(0), &[1, 2]);
+        recorder.record(&mut hbt)?;
+        assert!(hbt.updated_orders(0).is_empty());
+
+        // Order 1 is filled and order 2 is accepted.
+        hbt.elapse(1000)?;
+        recorder.record(&mut hbt)?;
+        // Nothing is updated.
+        hbt.elapse(1000)?;
+        recorder.record(&mut hbt)?;
+
+        // Order 2 is canceled and cleared before it is recorded.
+        hbt.cancel(0, 2, false)?;
+        hbt.elapse(1000)?;
+        hbt.clear_inactive_orders(Some(0));
+        assert!(hbt.updated_orders(0).is_empty());
+        recorder.record(&mut hbt)?;
+
+        let log: Vec<_> = recorder
+            .orders(0)
+            .iter()
+            .map(|record| (record.kind, record.order_id, record.local_ts, record.status))
+            .collect();
+        assert_eq!(
+            log,
+            vec![
+                (ORDER_REQUEST, 1, 1100, Status::None as u64),
+                (ORDER_REQUEST, 2, 1100, Status::None as u64),
+                (ORDER_RESPONSE, 1, 2100, Status::Filled as u64),
+                (ORDER_RESPONSE, 2, 2100, Status::New as u64),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,10 @@
 use crate::{
-    backtest::{assettype::AssetType, models::FeeModel},
+    backtest::{
+        BacktestError,
+        assettype::AssetType,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+        models::FeeModel,
+    },
     types::{Order, StateValues},
 };
 
@@ -59,4 +64,16 @@ where
     pub fn values(&self) -> &StateValues {
         &self.state_values
     }
+
+    pub fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.state_values)
+    }
+
+    pub fn restore_checkpoint(
+        &mut self,
+        reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        self.state_values = reader.read()?;
+        Ok(())
+    }
 }
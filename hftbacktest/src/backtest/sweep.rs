@@ -0,0 +1,861 @@
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Error, Write},
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::Path,
+    sync::{Condvar, Mutex},
+    thread,
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    backtest::recorder::BacktestRecorder,
+    depth::MarketDepth,
+    priceaction::PriceAction,
+    types::Bot,
+};
+
+/// Named parameter values of a run in a parameter sweep.
+///
+/// Values are `f64`; integer parameters can be cast from them, such as `params["grid_num"] as
+/// usize`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Params(Vec<(String, f64)>);
+
+impl Params {
+    /// Constructs empty `Params`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the value of the parameter.
+    pub fn set(&mut self, name: &str, value: f64) {
+        match self.0.iter_mut().find(|(param, _)| param == name) {
+            Some((_, param_value)) => *param_value = value,
+            None => self.0.push((name.to_string(), value)),
+        }
+    }
+
+    /// Returns the value of the parameter, or `None` if there is no such parameter.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns an iterator over the names and values of the parameters.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+impl std::ops::Index<&str> for Params {
+    type Output = f64;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        self.0
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("no parameter named `{name}`"))
+    }
+}
+
+/// Statistics of an asset in a run, computed by [`BacktestRecorder::stats`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub peak: f64,
+    pub cum_return: f64,
+    pub trough: f64,
+    pub max_drawdown: f64,
+    pub sharpe: f64,
+    pub fee: f64,
+}
+
+/// The result of a run in a parameter sweep.
+#[derive(Clone, Debug)]
+pub struct SweepRecord {
+    /// The order in which the run is started.
+    pub run_no: usize,
+    pub params: Params,
+    /// The statistics of each asset, or the error message if the run fails.
+    pub result: Result<Vec<Stats>, String>,
+}
+
+/// What a [`Search`] proposes to do next.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Proposal {
+    /// Starts a run with the parameters.
+    Run(Params),
+    /// Waits until a run in progress completes, and then asks again. If no run is in progress,
+    /// the search ends.
+    Wait,
+    /// The search is over.
+    Done,
+}
+
+/// Proposes the parameters of the runs in a parameter sweep.
+///
+/// As it's given the results of the completed runs and can wait for the runs in progress, an
+/// adaptive search such as [`TpeSearch`] can be implemented on top of this.
+pub trait Search: Send {
+    /// Returns what to do next. `results` contains the records of the runs completed so far, in
+    /// the order of completion.
+    fn next(&mut self, results: &[SweepRecord]) -> Proposal;
+}
+
+/// Searches every combination of the given parameter values.
+#[derive(Clone, Debug, Default)]
+pub struct GridSearch {
+    params: Vec<(String, Vec<f64>)>,
+    next: usize,
+}
+
+impl GridSearch {
+    /// Constructs an empty `GridSearch`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a parameter with the values to search.
+    pub fn param(self, name: &str, values: Vec<f64>) -> Self {
+        let mut params = self.params;
+        params.push((name.to_string(), values));
+        Self { params, ..self }
+    }
+
+    /// Returns the number of combinations.
+    pub fn len(&self) -> usize {
+        if self.params.is_empty() {
+            0
+        } else {
+            self.params.iter().map(|(_, values)| values.len()).product()
+        }
+    }
+
+    /// Returns `true` if there is no combination to search.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Search for GridSearch {
+    fn next(&mut self, _results: &[SweepRecord]) -> Proposal {
+        if self.next >= self.len() {
+            return Proposal::Done;
+        }
+        // The last parameter varies the fastest.
+        let mut index = self.next;
+        let mut params = Params::new();
+        for (name, values) in self.params.iter().rev() {
+            params.set(name, values[index % values.len()]);
+            index /= values.len();
+        }
+        params.0.reverse();
+        self.next += 1;
+        Proposal::Run(params)
+    }
+}
+
+/// Searches the given number of parameter sets, each of whose values is drawn uniformly from its
+/// range.
+#[derive(Clone, Debug)]
+pub struct RandomSearch {
+    params: Vec<(String, f64, f64)>,
+    num_runs: usize,
+    rng: StdRng,
+    next: usize,
+}
+
+impl RandomSearch {
+    /// Constructs a `RandomSearch` of `num_runs` runs, drawing the values with the seed.
+    pub fn new(num_runs: usize, seed: u64) -> Self {
+        Self {
+            params: Vec::new(),
+            num_runs,
+            rng: StdRng::seed_from_u64(seed),
+            next: 0,
+        }
+    }
+
+    /// Adds a parameter whose values are drawn from `low..high`.
+    pub fn param(self, name: &str, low: f64, high: f64) -> Self {
+        let mut params = self.params;
+        params.push((name.to_string(), low, high));
+        Self { params, ..self }
+    }
+}
+
+impl Search for RandomSearch {
+    fn next(&mut self, _results: &[SweepRecord]) -> Proposal {
+        if self.next >= self.num_runs {
+            return Proposal::Done;
+        }
+        self.next += 1;
+        Proposal::Run(sample_uniform(&self.params, &mut self.rng))
+    }
+}
+
+fn sample_uniform(params: &[(String, f64, f64)], rng: &mut StdRng) -> Params {
+    let mut sampled = Params::new();
+    for (name, low, high) in params {
+        sampled.set(name, uniform(*low, *high, rng));
+    }
+    sampled
+}
+
+fn uniform(low: f64, high: f64, rng: &mut StdRng) -> f64 {
+    if low < high {
+        rng.random_range(low..high)
+    } else {
+        low
+    }
+}
+
+/// Computes the objective of a run from the statistics of the assets.
+type Objective = dyn Fn(&[Stats]) -> f64 + Send;
+
+/// Searches for the parameters maximizing the objective with the Tree-structured Parzen Estimator
+/// (TPE), a Bayesian optimization method.
+///
+/// The first runs draw the parameters uniformly from their ranges. Once they complete, the
+/// completed runs are split into the better ones, by the objective, and the others, and each
+/// subsequent run takes, among the candidates drawn around the better runs, the one most likely
+/// to be better than worse according to the kernel density estimates of the two groups. Failed
+/// runs are left out.
+pub struct TpeSearch {
+    params: Vec<(String, f64, f64)>,
+    objective: Box<Objective>,
+    num_runs: usize,
+    num_startup_runs: usize,
+    num_candidates: usize,
+    gamma: f64,
+    rng: StdRng,
+    next: usize,
+}
+
+impl TpeSearch {
+    /// Constructs a `TpeSearch` of `num_runs` runs that maximizes the objective computed from the
+    /// statistics of the assets, drawing the values with the seed.
+    pub fn new<O>(num_runs: usize, seed: u64, objective: O) -> Self
+    where
+        O: Fn(&[Stats]) -> f64 + Send + 'static,
+    {
+        Self {
+            params: Vec::new(),
+            objective: Box::new(objective),
+            num_runs,
+            num_startup_runs: 10,
+            num_candidates: 24,
+            gamma: 0.25,
+            rng: StdRng::seed_from_u64(seed),
+            next: 0,
+        }
+    }
+
+    /// Adds a parameter whose values are searched within `low..high`.
+    pub fn param(self, name: &str, low: f64, high: f64) -> Self {
+        let mut params = self.params;
+        params.push((name.to_string(), low, high));
+        Self { params, ..self }
+    }
+
+    /// Sets the number of the first runs with uniformly drawn parameters, which must complete
+    /// before the estimation starts. The default value is `10`.
+    pub fn num_startup_runs(self, num_startup_runs: usize) -> Self {
+        Self {
+            num_startup_runs: num_startup_runs.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the number of candidates drawn for each run. The default value is `24`.
+    pub fn num_candidates(self, num_candidates: usize) -> Self {
+        Self {
+            num_candidates: num_candidates.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the fraction of the completed runs regarded as the better ones. The default value is
+    /// `0.25`.
+    pub fn gamma(self, gamma: f64) -> Self {
+        Self {
+            gamma: gamma.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    fn propose(&mut self, observations: &[(&Params, f64)]) -> Params {
+        let num_good = ((self.gamma * observations.len() as f64).ceil() as usize)
+            .clamp(1, observations.len() - 1);
+        let (good, bad) = observations.split_at(num_good);
+
+        let mut best: Option<(f64, Params)> = None;
+        for _ in 0..self.num_candidates {
+            let mut candidate = Params::new();
+            let mut score = 0.0;
+            for (name, low, high) in &self.params {
+                let good = kernels(good.iter().filter_map(|(p, _)| p.get(name)), *low, *high);
+                let bad = kernels(bad.iter().filter_map(|(p, _)| p.get(name)), *low, *high);
+                let value = sample_parzen(&good, *low, *high, &mut self.rng);
+                score += parzen_density(&good, *low, *high, value).ln()
+                    - parzen_density(&bad, *low, *high, value).ln();
+                candidate.set(name, value);
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score > *best_score)
+            {
+                best = Some((score, candidate));
+            }
+        }
+        best.map(|(_, params)| params).unwrap_or_default()
+    }
+}
+
+impl Search for TpeSearch {
+    fn next(&mut self, results: &[SweepRecord]) -> Proposal {
+        if self.next >= self.num_runs {
+            return Proposal::Done;
+        }
+        if self.next < self.num_startup_runs {
+            self.next += 1;
+            return Proposal::Run(sample_uniform(&self.params, &mut self.rng));
+        }
+        if results.len() < self.num_startup_runs {
+            return Proposal::Wait;
+        }
+
+        let mut observations: Vec<(&Params, f64)> = results
+            .iter()
+            .filter_map(|record| {
+                let stats = record.result.as_ref().ok()?;
+                let objective = (self.objective)(stats);
+                objective.is_finite().then_some((&record.params, objective))
+            })
+            .collect();
+        self.next += 1;
+        if observations.len() < 2 {
+            return Proposal::Run(sample_uniform(&self.params, &mut self.rng));
+        }
+        observations.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        Proposal::Run(self.propose(&observations))
+    }
+}
+
+/// Returns the means and the standard deviations of the Gaussian kernels at the points within the
+/// range. As in the original TPE, the bandwidth of each kernel is the larger distance to its
+/// neighbors, including the bounds of the range.
+fn kernels(points: impl Iterator<Item = f64>, low: f64, high: f64) -> Vec<(f64, f64)> {
+    let mut points: Vec<f64> = points.collect();
+    points.sort_by(f64::total_cmp);
+    let range = (high - low).max(f64::MIN_POSITIVE);
+    let min_sigma = range / (points.len() as f64 + 1.0).min(100.0);
+    (0..points.len())
+        .map(|i| {
+            let left = if i == 0 { low } else { points[i - 1] };
+            let right = points.get(i + 1).copied().unwrap_or(high);
+            let sigma = (points[i] - left)
+                .max(right - points[i])
+                .clamp(min_sigma, range);
+            (points[i], sigma)
+        })
+        .collect()
+}
+
+/// Draws a value from the mixture of the Gaussian kernels and the uniform prior.
+fn sample_parzen(kernels: &[(f64, f64)], low: f64, high: f64, rng: &mut StdRng) -> f64 {
+    if low >= high {
+        return low;
+    }
+    let component = rng.random_range(0..=kernels.len());
+    if component == kernels.len() {
+        return uniform(low, high, rng);
+    }
+    let (mean, sigma) = kernels[component];
+    // Box-Muller transform.
+    let u1: f64 = 1.0 - rng.random::<f64>();
+    let u2: f64 = rng.random();
+    let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+    (mean + z * sigma).clamp(low, high)
+}
+
+/// Returns the density of the mixture of the Gaussian kernels and the uniform prior.
+fn parzen_density(kernels: &[(f64, f64)], low: f64, high: f64, value: f64) -> f64 {
+    if low >= high {
+        return 1.0;
+    }
+    let density: f64 = kernels
+        .iter()
+        .map(|(mean, sigma)| {
+            let z = (value - mean) / sigma;
+            (-0.5 * z * z).exp() / (sigma * (2.0 * std::f64::consts::PI).sqrt())
+        })
+        .sum();
+    (density + 1.0 / (high - low)) / (kernels.len() + 1) as f64
+}
+
+/// The results of a parameter sweep, ordered by the run number.
+#[derive(Clone, Debug, Default)]
+pub struct SweepResults {
+    records: Vec<SweepRecord>,
+}
+
+impl SweepResults {
+    /// Returns the records of the runs.
+    pub fn records(&self) -> &[SweepRecord] {
+        &self.records
+    }
+
+    /// Saves the results into a CSV file, with a row for each asset of each run. The columns are
+    /// `run_no`, the parameters, `asset_no`, `peak`, `cum_return`, `trough`, `max_drawdown`,
+    /// `sharpe`, `fee`, and `error`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut names: Vec<&str> = Vec::new();
+        for record in &self.records {
+            for (name, _) in record.params.iter() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "run_no")?;
+        for name in &names {
+            write!(file, ",{name}")?;
+        }
+        writeln!(
+            file,
+            ",asset_no,peak,cum_return,trough,max_drawdown,sharpe,fee,error"
+        )?;
+        for record in &self.records {
+            write!(file, "{}", record.run_no)?;
+            for name in &names {
+                match record.params.get(name) {
+                    Some(value) => write!(file, ",{value}")?,
+                    None => write!(file, ",")?,
+                }
+            }
+            match &record.result {
+                Ok(stats) => {
+                    for (asset_no, stats) in stats.iter().enumerate() {
+                        if asset_no > 0 {
+                            write!(file, "{}", record.run_no)?;
+                            for name in &names {
+                                match record.params.get(name) {
+                                    Some(value) => write!(file, ",{value}")?,
+                                    None => write!(file, ",")?,
+                                }
+                            }
+                        }
+                        writeln!(
+                            file,
+                            ",{},{},{},{},{},{},{},",
+                            asset_no,
+                            stats.peak,
+                            stats.cum_return,
+                            stats.trough,
+                            stats.max_drawdown,
+                            stats.sharpe,
+                            stats.fee
+                        )?;
+                    }
+                }
+                Err(error) => {
+                    // Quotes the message, which may contain commas.
+                    writeln!(file, ",,,,,,,,\"{}\"", error.replace('"', "\"\""))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs backtests over the parameters proposed by a [`Search`] on a pool of threads.
+///
+/// Since a backtest can't be sent between threads, each run builds its own backtest on the worker
+/// thread. To avoid reloading the feed data in every run, load it once into
+/// [`SharedData`](crate::backtest::data::SharedData) and pass it to the asset builders as
+/// [`DataSource::Shared`](crate::backtest::DataSource::Shared).
+pub struct SweepRunner<S> {
+    search: S,
+    num_threads: usize,
+}
+
+impl<S> SweepRunner<S>
+where
+    S: Search,
+{
+    /// Constructs a `SweepRunner` that runs on as many threads as the available parallelism.
+    pub fn new(search: S) -> Self {
+        Self {
+            search,
+            num_threads: thread::available_parallelism()
+                .map(|num| num.get())
+                .unwrap_or(1),
+        }
+    }
+
+    /// Sets the number of threads to run the backtests on.
+    pub fn num_threads(self, num_threads: usize) -> Self {
+        Self {
+            num_threads: num_threads.max(1),
+            ..self
+        }
+    }
+
+    /// Runs the sweep. For each run, `build` builds the backtest with the parameters, and
+    /// `strategy` runs it, recording its state into the [`BacktestRecorder`]. The backtest is
+    /// closed afterward, and the statistics of each asset are collected from the recorder.
+    ///
+    /// A failed run doesn't stop the sweep; its error, or its panic message if it panics, is
+    /// recorded in the results instead.
+    pub fn run<I, MD, PA, B, BE, F, FE>(self, build: B, strategy: F) -> SweepResults
+    where
+        MD: MarketDepth,
+        PA: PriceAction,
+        I: Bot<MD, PA>,
+        I::Error: Debug,
+        B: Fn(&Params) -> Result<I, BE> + Sync,
+        BE: Debug,
+        F: Fn(&mut I, &mut BacktestRecorder, &Params) -> Result<(), FE> + Sync,
+        FE: Debug,
+    {
+        let state = Mutex::new(SweepState {
+            search: self.search,
+            num_started: 0,
+            num_running: 0,
+            records: Vec::new(),
+        });
+        // Notifies the workers waiting for the runs in progress of a completion.
+        let completed = Condvar::new();
+        let run_once = |params: &Params| -> Result<Vec<Stats>, String> {
+            let mut hbt = build(params).map_err(|error| format!("{error:?}"))?;
+            let mut recorder = BacktestRecorder::new(&hbt);
+            strategy(&mut hbt, &mut recorder, params).map_err(|error| format!("{error:?}"))?;
+            hbt.close().map_err(|error| format!("{error:?}"))?;
+            (0..hbt.num_assets())
+                .map(|asset_no| {
+                    let (peak, cum_return, trough, max_drawdown, sharpe, fee) = recorder
+                        .stats(asset_no)
+                        .map_err(|error| format!("{error:?}"))?;
+                    Ok(Stats {
+                        peak,
+                        cum_return,
+                        trough,
+                        max_drawdown,
+                        sharpe,
+                        fee,
+                    })
+                })
+                .collect()
+        };
+
+        thread::scope(|scope| {
+            for _ in 0..self.num_threads {
+                scope.spawn(|| {
+                    while let Some((run_no, params)) = next_run(&state, &completed) {
+                        let result = catch_unwind(AssertUnwindSafe(|| run_once(&params)))
+                            .unwrap_or_else(|payload| {
+                                let message = payload
+                                    .downcast_ref::<&str>()
+                                    .map(|message| message.to_string())
+                                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                                    .unwrap_or_default();
+                                Err(format!("panicked: {message}"))
+                            });
+                        let mut state = state.lock().unwrap();
+                        state.num_running -= 1;
+                        state.records.push(SweepRecord {
+                            run_no,
+                            params,
+                            result,
+                        });
+                        completed.notify_all();
+                    }
+                });
+            }
+        });
+
+        let mut records = state.into_inner().unwrap().records;
+        records.sort_by_key(|record| record.run_no);
+        SweepResults { records }
+    }
+}
+
+/// The search and the completed runs, which the search proposes the next run from.
+struct SweepState<S> {
+    search: S,
+    num_started: usize,
+    num_running: usize,
+    records: Vec<SweepRecord>,
+}
+
+/// Returns the run number and the parameters of the next run, waiting for the runs in progress
+/// if the search asks to, or `None` if the search is over.
+fn next_run<S: Search>(
+    state: &Mutex<SweepState<S>>,
+    completed: &Condvar,
+) -> Option<(usize, Params)> {
+    let mut state = state.lock().unwrap();
+    loop {
+        let SweepState {
+            search, records, ..
+        } = &mut *state;
+        match search.next(records) {
+            Proposal::Run(params) => {
+                let run_no = state.num_started;
+                state.num_started += 1;
+                state.num_running += 1;
+                return Some((run_no, params));
+            }
+            Proposal::Wait if state.num_running > 0 => {
+                state = completed.wait(state).unwrap();
+            }
+            Proposal::Wait | Proposal::Done => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        backtest::{
+            Backtest,
+            DataSource,
+            ExchangeKind::NoPartialFillExchange,
+            L2AssetBuilder,
+            assettype::LinearAsset,
+            data::{Data, SharedData},
+            models::{
+                CommonFees,
+                ConstantLatency,
+                PowerProbQueueFunc3,
+                ProbQueueModel,
+                TradingValueFeeModel,
+            },
+            recorder::BacktestRecorder,
+            sweep::{
+                GridSearch,
+                Params,
+                Proposal,
+                RandomSearch,
+                Search,
+                Stats,
+                SweepRecord,
+                SweepRunner,
+                TpeSearch,
+            },
+        },
+        depth::HashMapMarketDepth,
+        prelude::{Bot, Event, HkPriceAction},
+        types::{
+            BUY_EVENT,
+            DEPTH_EVENT,
+            EXCH_EVENT,
+            LOCAL_EVENT,
+            OrdType::Limit,
+            Recorder,
+            SELL_EVENT,
+            TRADE_EVENT,
+            TimeInForce::GTC,
+        },
+    };
+
+    type TestBacktest = Backtest<HashMapMarketDepth, HkPriceAction>;
+
+    fn proposals<S: Search>(search: &mut S) -> Vec<Params> {
+        std::iter::from_fn(|| match search.next(&[]) {
+            Proposal::Run(params) => Some(params),
+            _ => None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn search_params() {
+        let mut grid = GridSearch::new()
+            .param("a", vec![1.0, 2.0])
+            .param("b", vec![10.0, 20.0, 30.0]);
+        assert_eq!(grid.len(), 6);
+        let params = proposals(&mut grid);
+        assert_eq!(params.len(), 6);
+        assert_eq!((params[0]["a"], params[0]["b"]), (1.0, 10.0));
+        assert_eq!((params[1]["a"], params[1]["b"]), (1.0, 20.0));
+        assert_eq!((params[5]["a"], params[5]["b"]), (2.0, 30.0));
+
+        let mut random = RandomSearch::new(5, 1).param("a", -1.0, 1.0);
+        let params = proposals(&mut random);
+        assert_eq!(params.len(), 5);
+        assert!(
+            params
+                .iter()
+                .all(|params| (-1.0..1.0).contains(&params["a"]))
+        );
+    }
+
+    #[test]
+    fn sweep_over_shared_data() -> Result<(), Box<dyn Error>> {
+        let event = |ev, ts, px, qty| Event {
+            ev: EXCH_EVENT | LOCAL_EVENT | ev,
+            exch_ts: ts,
+            local_ts: ts + 10,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        };
+        let events: Vec<_> = (0..20)
+            .flat_map(|i| {
+                let ts = i * 100;
+                let px = 100.0 - i as f64 * 0.01;
+                [
+                    event(DEPTH_EVENT | BUY_EVENT, ts, px, 5.0),
+                    event(DEPTH_EVENT | SELL_EVENT, ts, px + 0.01, 5.0),
+                    event(TRADE_EVENT | SELL_EVENT, ts + 50, px, 10.0),
+                ]
+            })
+            .collect();
+        let data = SharedData::new(Data::from_data(&events));
+
+        let build = |params: &Params| {
+            Backtest::builder()
+                .add_asset(
+                    L2AssetBuilder::default()
+                        .data(vec![DataSource::Shared(data.clone())])
+                        .latency_model(ConstantLatency::new(params["latency"] as i64, 10))
+                        .asset_type(LinearAsset::new(1.0))
+                        .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                        .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                        .exchange(NoPartialFillExchange)
+                        .depth(|| HashMapMarketDepth::new(0.01, 1.0))
+                        .price_action(HkPriceAction::new(vec![], vec![]))
+                        .build()?,
+                )
+                .build()
+        };
+        let strategy =
+            |hbt: &mut TestBacktest, recorder: &mut BacktestRecorder, params: &Params| {
+                hbt.elapse(20)?;
+                hbt.submit_buy_order(0, 1, params["price"], 1.0, GTC, Limit, false)?;
+                while hbt.elapse(100)? {
+                    recorder.record(hbt)?;
+                }
+                Ok::<_, Box<dyn Error>>(())
+            };
+
+        let search = GridSearch::new()
+            .param("latency", vec![10.0, 10_000.0])
+            .param("price", vec![99.95, 100.0]);
+        let results = SweepRunner::new(search.clone())
+            .num_threads(3)
+            .run(build, strategy);
+        assert_eq!(results.records().len(), 4);
+        for (run_no, record) in results.records().iter().enumerate() {
+            assert_eq!(record.run_no, run_no);
+            let stats = record.result.as_ref().unwrap();
+            assert_eq!(stats.len(), 1);
+            // The buy order is filled as the price falls, unless the order arrives after the end
+            // of the data.
+            let filled = record.params["latency"] < 1000.0;
+            assert_eq!(stats[0].cum_return != 0.0, filled);
+        }
+
+        // Runs on a single thread give the same results.
+        let sequential = SweepRunner::new(search).num_threads(1).run(build, strategy);
+        for (record, expected) in sequential.records().iter().zip(results.records()) {
+            assert_eq!(record.params, expected.params);
+            assert_eq!(record.result, expected.result);
+        }
+
+        let path = std::env::temp_dir().join("hftbacktest_sweep_over_shared_data.csv");
+        results.to_csv(&path)?;
+        let csv = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.starts_with("run_no,latency,price,asset_no,"));
+        Ok(())
+    }
+
+    #[test]
+    fn tpe_search_converges() {
+        // Maximizes `-(x - 0.7)^2`.
+        let record = |run_no: usize, params: Params| {
+            let x = params["x"];
+            SweepRecord {
+                run_no,
+                params,
+                result: Ok(vec![Stats {
+                    peak: 0.0,
+                    cum_return: 0.0,
+                    trough: 0.0,
+                    max_drawdown: 0.0,
+                    sharpe: -(x - 0.7).powi(2),
+                    fee: 0.0,
+                }]),
+            }
+        };
+        let mut tpe = TpeSearch::new(60, 1, |stats: &[Stats]| stats[0].sharpe)
+            .param("x", 0.0, 1.0)
+            .num_startup_runs(10);
+
+        let startup = proposals(&mut tpe);
+        assert_eq!(startup.len(), 10);
+        // The estimation waits for the startup runs to complete.
+        assert_eq!(tpe.next(&[]), Proposal::Wait);
+
+        let mut results: Vec<_> = startup
+            .into_iter()
+            .enumerate()
+            .map(|(run_no, params)| record(run_no, params))
+            .collect();
+        loop {
+            match tpe.next(&results) {
+                Proposal::Run(params) => results.push(record(results.len(), params)),
+                Proposal::Wait => panic!("no run is in progress"),
+                Proposal::Done => break,
+            }
+        }
+        assert_eq!(results.len(), 60);
+
+        // The last runs concentrate around the optimum, whereas the mean distance of uniformly
+        // drawn values from 0.7 is 0.29.
+        let mean_distance = results[40..]
+            .iter()
+            .map(|record| (record.params["x"] - 0.7).abs())
+            .sum::<f64>()
+            / 20.0;
+        assert!(mean_distance < 0.1, "{mean_distance}");
+    }
+
+    #[test]
+    fn records_panicked_runs() {
+        let build = |params: &Params| -> Result<TestBacktest, &str> {
+            if params["panic"] == 1.0 {
+                panic!("failed to build");
+            }
+            Err("no data")
+        };
+        let strategy = |_: &mut TestBacktest, _: &mut BacktestRecorder, _: &Params| Ok::<_, ()>(());
+
+        let search = GridSearch::new().param("panic", vec![0.0, 1.0, 0.0]);
+        let results = SweepRunner::new(search).num_threads(2).run(build, strategy);
+        let errors: Vec<_> = results
+            .records()
+            .iter()
+            .map(|record| record.result.clone().unwrap_err())
+            .collect();
+        assert_eq!(
+            errors,
+            ["\"no data\"", "panicked: failed to build", "\"no data\""]
+        );
+    }
+}
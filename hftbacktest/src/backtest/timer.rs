@@ -0,0 +1,83 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::backtest::{
+    BacktestError,
+    checkpoint::{CheckpointReader, CheckpointWriter},
+};
+
+/// Manages the user timers scheduled in backtesting, ordered by their timestamps.
+#[derive(Default)]
+pub struct Timers {
+    queue: BTreeSet<(i64, u64)>,
+    scheduled: HashMap<u64, i64>,
+    expired: Vec<u64>,
+}
+
+impl Timers {
+    /// Schedules the timer at the timestamp, replacing the one with the same ID, if any.
+    pub fn schedule(&mut self, timestamp: i64, timer_id: u64) {
+        if let Some(prev_timestamp) = self.scheduled.insert(timer_id, timestamp) {
+            self.queue.remove(&(prev_timestamp, timer_id));
+        }
+        self.queue.insert((timestamp, timer_id));
+    }
+
+    /// Cancels the timer. Returns `false` if there is no such timer scheduled.
+    pub fn cancel(&mut self, timer_id: u64) -> bool {
+        match self.scheduled.remove(&timer_id) {
+            Some(timestamp) => {
+                self.queue.remove(&(timestamp, timer_id));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the earliest timestamp of the scheduled timers.
+    pub fn earliest_timestamp(&self) -> Option<i64> {
+        self.queue.first().map(|(timestamp, _)| *timestamp)
+    }
+
+    /// Expires the timers scheduled at or before the timestamp.
+    pub fn expire(&mut self, timestamp: i64) {
+        while let Some(&(timer_timestamp, timer_id)) = self.queue.first() {
+            if timer_timestamp > timestamp {
+                break;
+            }
+            self.queue.pop_first();
+            self.scheduled.remove(&timer_id);
+            self.expired.push(timer_id);
+        }
+    }
+
+    /// Returns the IDs of the expired timers in the order of expiration.
+    pub fn expired(&self) -> &[u64] {
+        &self.expired
+    }
+
+    /// Clears the expired timers.
+    pub fn clear_expired(&mut self) {
+        self.expired.clear();
+    }
+
+    /// Writes the scheduled and expired timers into the checkpoint.
+    pub fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.queue)?;
+        writer.write(&self.expired)
+    }
+
+    /// Restores the scheduled and expired timers from the checkpoint.
+    pub fn restore_checkpoint(
+        &mut self,
+        reader: &mut CheckpointReader,
+    ) -> Result<(), BacktestError> {
+        self.queue = reader.read()?;
+        self.scheduled = self
+            .queue
+            .iter()
+            .map(|&(timestamp, timer_id)| (timer_id, timestamp))
+            .collect();
+        self.expired = reader.read()?;
+        Ok(())
+    }
+}
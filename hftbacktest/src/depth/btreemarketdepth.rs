@@ -10,7 +10,11 @@ use super::{
     MarketDepth,
 };
 use crate::{
-    backtest::{BacktestError, data::Data},
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+        data::Data,
+    },
     prelude::{OrderId, Side},
     types::{BUY_EVENT, Event, SELL_EVENT},
 };
@@ -209,6 +213,25 @@ impl MarketDepth for BTreeMarketDepth {
     fn ask_qty_at_tick(&self, price_tick: i64) -> f64 {
         *self.ask_depth.get(&price_tick).unwrap_or(&0.0)
     }
+
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.timestamp)?;
+        writer.write(&self.bid_depth)?;
+        writer.write(&self.ask_depth)?;
+        writer.write(&self.best_bid_tick)?;
+        writer.write(&self.best_ask_tick)?;
+        writer.write(&self.orders)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        self.timestamp = reader.read()?;
+        self.bid_depth = reader.read()?;
+        self.ask_depth = reader.read()?;
+        self.best_bid_tick = reader.read()?;
+        self.best_ask_tick = reader.read()?;
+        self.orders = reader.read()?;
+        Ok(())
+    }
 }
 
 impl ApplySnapshot for BTreeMarketDepth {
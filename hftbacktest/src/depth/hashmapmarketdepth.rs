@@ -2,7 +2,11 @@ use std::collections::{HashMap, hash_map::Entry};
 
 use super::{ApplySnapshot, INVALID_MAX, INVALID_MIN, L3MarketDepth, L3Order, MarketDepth};
 use crate::{
-    backtest::{BacktestError, data::Data},
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+        data::Data,
+    },
     prelude::{L2MarketDepth, OrderId, Side},
     types::{BUY_EVENT, DEPTH_SNAPSHOT_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT},
 };
@@ -298,6 +302,29 @@ impl MarketDepth for HashMapMarketDepth {
     fn ask_qty_at_tick(&self, price_tick: i64) -> f64 {
         *self.ask_depth.get(&price_tick).unwrap_or(&0.0)
     }
+
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.timestamp)?;
+        writer.write(&self.ask_depth)?;
+        writer.write(&self.bid_depth)?;
+        writer.write(&self.best_bid_tick)?;
+        writer.write(&self.best_ask_tick)?;
+        writer.write(&self.low_bid_tick)?;
+        writer.write(&self.high_ask_tick)?;
+        writer.write(&self.orders)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        self.timestamp = reader.read()?;
+        self.ask_depth = reader.read()?;
+        self.bid_depth = reader.read()?;
+        self.best_bid_tick = reader.read()?;
+        self.best_ask_tick = reader.read()?;
+        self.low_bid_tick = reader.read()?;
+        self.high_ask_tick = reader.read()?;
+        self.orders = reader.read()?;
+        Ok(())
+    }
 }
 
 impl ApplySnapshot for HashMapMarketDepth {
@@ -16,8 +16,14 @@ mod fuse;
 #[cfg(any(feature = "unstable_fuse", doc))]
 pub use fuse::FusedHashMapMarketDepth;
 
+use bincode::{Decode, Encode};
+
 use crate::{
-    backtest::data::Data,
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+        data::Data,
+    },
     types::{Event, OrderId},
 };
 
@@ -56,6 +62,16 @@ pub trait MarketDepth {
 
     /// Returns the quantity at the ask market depth for a given price in ticks.
     fn ask_qty_at_tick(&self, price_tick: i64) -> f64;
+
+    /// Writes the market depth into the backtest checkpoint.
+    fn save_checkpoint(&self, _writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the market depth"))
+    }
+
+    /// Restores the market depth from the backtest checkpoint.
+    fn restore_checkpoint(&mut self, _reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the market depth"))
+    }
 }
 
 /// Provides Level2-specific market depth functions.
@@ -102,7 +118,7 @@ pub trait ApplySnapshot {
 }
 
 /// Level3 order from the market feed.
-#[derive(Debug)]
+#[derive(Debug, Decode, Encode)]
 pub struct L3Order {
     pub order_id: OrderId,
     pub side: Side,
@@ -2,7 +2,11 @@ use std::collections::{HashMap, hash_map::Entry};
 
 use super::{ApplySnapshot, INVALID_MAX, INVALID_MIN, L3MarketDepth, L3Order, MarketDepth};
 use crate::{
-    backtest::{BacktestError, data::Data},
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+        data::Data,
+    },
     prelude::{L2MarketDepth, OrderId, Side},
     types::{BUY_EVENT, Event, SELL_EVENT},
 };
@@ -408,6 +412,35 @@ impl MarketDepth for ROIVectorMarketDepth {
             }
         }
     }
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(&self.timestamp)?;
+        writer.write(&self.ask_depth)?;
+        writer.write(&self.bid_depth)?;
+        writer.write(&self.best_bid_tick)?;
+        writer.write(&self.best_ask_tick)?;
+        writer.write(&self.low_bid_tick)?;
+        writer.write(&self.high_ask_tick)?;
+        writer.write(&self.orders)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        self.timestamp = reader.read()?;
+        let ask_depth: Vec<f64> = reader.read()?;
+        let bid_depth: Vec<f64> = reader.read()?;
+        if ask_depth.len() != self.ask_depth.len() || bid_depth.len() != self.bid_depth.len() {
+            return Err(BacktestError::InvalidCheckpoint(
+                "the range of interest doesn't match",
+            ));
+        }
+        self.ask_depth = ask_depth;
+        self.bid_depth = bid_depth;
+        self.best_bid_tick = reader.read()?;
+        self.best_ask_tick = reader.read()?;
+        self.low_bid_tick = reader.read()?;
+        self.high_ask_tick = reader.read()?;
+        self.orders = reader.read()?;
+        Ok(())
+    }
 }
 
 impl ApplySnapshot for ROIVectorMarketDepth {
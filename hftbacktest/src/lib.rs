@@ -27,6 +27,7 @@
 //! - `unstable_fuse`: Enables the market depth fusion feature, which aggregates different market
 //!                    depth streams to provide the finest granularity and the most frequent,
 //!                    up-to-date market depth information
+//! - `metrics`: Enables the Prometheus metrics of a live trading bot.
 //!
 
 /// Provides backtesting features.
@@ -43,6 +44,16 @@ pub mod live;
 /// Defines HftBacktest types.
 pub mod types;
 
+/// Re-exports the Prometheus crate used by the live trading bot's metrics.
+#[cfg(feature = "metrics")]
+pub use prometheus;
+
+/// Provides a pre-trade risk layer shared by backtesting and live trading.
+pub mod risk;
+
+/// Provides an event-driven strategy runner shared by backtesting and live trading.
+pub mod strategy;
+
 /// Provides common types.
 pub mod prelude;
 
@@ -8,9 +8,13 @@ use rand::Rng;
 use thiserror::Error;
 use tracing::{debug, error, info};
 
+#[cfg(feature = "backtest")]
+use crate::live::SessionCapture;
+#[cfg(feature = "metrics")]
+use crate::live::metrics::BotMetrics;
 use crate::{
     depth::{L2MarketDepth, MarketDepth},
-    live::{Instrument, ipc::Channel},
+    live::{Instrument, ipc::Channel, timer::TimerWheel},
     prelude::PriceAction, 
     types::{
         Bot,
@@ -70,6 +74,11 @@ pub struct LiveBotBuilder<MD,PA> {
     instruments: Vec<Instrument<MD,PA>>,
     error_handler: Option<ErrorHandler>,
     order_hook: Option<OrderRecvHook>,
+    #[cfg(feature = "backtest")]
+    capture: Option<SessionCapture>,
+    heartbeat_interval: Duration,
+    #[cfg(feature = "metrics")]
+    metrics: Option<prometheus::Registry>,
 }
 
 impl<MD,PA> Default for LiveBotBuilder<MD,PA> {
@@ -86,6 +95,11 @@ impl<MD,PA> LiveBotBuilder<MD,PA> {
             instruments: Default::default(),
             error_handler: None,
             order_hook: None,
+            #[cfg(feature = "backtest")]
+            capture: None,
+            heartbeat_interval: Duration::from_secs(1),
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
@@ -123,6 +137,45 @@ impl<MD,PA> LiveBotBuilder<MD,PA> {
         }
     }
 
+    /// Records the live session so that it can be replayed through the backtester. See
+    /// [`SessionCapture`].
+    #[cfg(feature = "backtest")]
+    pub fn capture(self, capture: SessionCapture) -> Self {
+        Self {
+            capture: Some(capture),
+            ..self
+        }
+    }
+
+    /// Sets the interval at which heartbeats are sent to the connectors, allowing them to cancel
+    /// the bot's orders when the bot stops. The default value is 1 second, and zero disables
+    /// heartbeats.
+    pub fn heartbeat_interval(self, heartbeat_interval: Duration) -> Self {
+        Self {
+            heartbeat_interval,
+            ..self
+        }
+    }
+
+    /// Registers the bot's metrics in the given Prometheus registry. All metrics are labelled by
+    /// `bot`, the bot ID, and the instrument metrics also by `connector` and `symbol`.
+    ///
+    /// * `hftbacktest_bot_feed_latency_seconds` - The latency between the exchange timestamp and
+    ///   the local timestamp of the feed, as returned by [`feed_latency`](Bot::feed_latency).
+    /// * `hftbacktest_bot_order_ack_latency_seconds` - The time from sending an order request
+    ///   until receiving its first response.
+    /// * `hftbacktest_bot_order_requests_total` - The number of order requests.
+    /// * `hftbacktest_bot_rejected_orders_total` - The number of orders that are rejected or
+    ///   expire before being acknowledged.
+    /// * `hftbacktest_bot_errors_total` - The number of errors from the connectors by `kind`.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(self, registry: prometheus::Registry) -> Self {
+        Self {
+            metrics: Some(registry),
+            ..self
+        }
+    }
+
     /// Sets the bot ID. It must be unique among all bots connected to the same `Connector`.
     pub fn id(self, id: u64) -> Self {
         Self { id, ..self }
@@ -136,6 +189,15 @@ impl<MD,PA> LiveBotBuilder<MD,PA> {
         let id = self.id;
         let mut channel = CH::build(&self.instruments)?;
 
+        #[cfg(feature = "backtest")]
+        let mut capture = self.capture;
+        #[cfg(feature = "backtest")]
+        if let Some(capture) = capture.as_mut() {
+            capture
+                .init(self.instruments.iter().map(|inst| inst.symbol.clone()))
+                .map_err(|error| BuildError::Error(anyhow::Error::from(error)))?;
+        }
+
         // Requests to prepare a given asset for trading.
         // The Connector will send the current orders on this asset.
         for (inst_no, instrument) in self.instruments.iter().enumerate() {
@@ -157,12 +219,45 @@ impl<MD,PA> LiveBotBuilder<MD,PA> {
                 .map_err(|error| BuildError::Error(anyhow::Error::from(error)))?;
         }
 
+        // Heartbeats only need to be sent once per connector.
+        let mut heartbeat_targets = Vec::new();
+        for (inst_no, instrument) in self.instruments.iter().enumerate() {
+            if !self.instruments[..inst_no]
+                .iter()
+                .any(|prev| prev.connector_name == instrument.connector_name)
+            {
+                heartbeat_targets.push(inst_no);
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        let metrics = self
+            .metrics
+            .map(|registry| {
+                let instruments: Vec<_> = self
+                    .instruments
+                    .iter()
+                    .map(|inst| (inst.connector_name.as_str(), inst.symbol.as_str()))
+                    .collect();
+                BotMetrics::new(&registry, id, &instruments)
+            })
+            .transpose()
+            .map_err(|error| BuildError::Error(anyhow::Error::from(error)))?;
+
         Ok(LiveBot {
             id,
             channel,
             instruments: self.instruments,
             error_handler: self.error_handler,
             order_hook: self.order_hook,
+            #[cfg(feature = "backtest")]
+            capture,
+            heartbeat_interval: self.heartbeat_interval,
+            heartbeat_targets,
+            last_heartbeat: Instant::now(),
+            timers: TimerWheel::new(Utc::now().timestamp_nanos_opt().unwrap()),
+            #[cfg(feature = "metrics")]
+            metrics,
         })
     }
 }
@@ -195,6 +290,14 @@ pub struct LiveBot<CH, MD, PA> {
     instruments: Vec<Instrument<MD, PA>>,
     error_handler: Option<ErrorHandler>,
     order_hook: Option<OrderRecvHook>,
+    #[cfg(feature = "backtest")]
+    capture: Option<SessionCapture>,
+    heartbeat_interval: Duration,
+    heartbeat_targets: Vec<usize>,
+    last_heartbeat: Instant,
+    timers: TimerWheel,
+    #[cfg(feature = "metrics")]
+    metrics: Option<BotMetrics>,
 }
 
 impl<CH, MD, PA> LiveBot<CH, MD, PA>
@@ -214,6 +317,10 @@ where
                 // println!("Event::Feed:px: {:?}", event);
                 let instrument = unsafe { self.instruments.get_unchecked_mut(inst_no) };
                 instrument.last_feed_latency = Some((event.exch_ts, event.local_ts));
+                #[cfg(feature = "metrics")]
+                if let Some(metrics) = self.metrics.as_ref() {
+                    metrics.on_feed(inst_no, event.exch_ts, event.local_ts);
+                }
                 if event.is(LOCAL_BID_DEPTH_EVENT) {
                     instrument
                         .depth
@@ -233,7 +340,10 @@ where
                     }
                     if instrument.last_trades.capacity() > 0 {
                         instrument.last_trades.push(event);
-                    }                    
+                    }
+                }
+                if WAIT_NEXT_FEED {
+                    return Ok(true);
                 }
             }
             LiveEvent::Order { order, .. } => {
@@ -247,14 +357,28 @@ where
                     _ => false,
                 };
                 let instrument = unsafe { self.instruments.get_unchecked_mut(inst_no) };
-                instrument.last_order_latency = Some((
-                    order.local_timestamp,
-                    order.exch_timestamp,
-                    Utc::now().timestamp_nanos_opt().unwrap(),
-                ));
+                let resp_ts = Utc::now().timestamp_nanos_opt().unwrap();
+                instrument.last_order_latency =
+                    Some((order.local_timestamp, order.exch_timestamp, resp_ts));
+                instrument.updated_orders.insert(order.order_id);
                 match instrument.orders.entry(order.order_id) {
                     Entry::Occupied(mut entry) => {
                         let ex_order = entry.get_mut();
+                        // The request is pending until the first response.
+                        #[cfg(feature = "metrics")]
+                        if let Some(metrics) = self.metrics.as_ref() {
+                            if ex_order.req != Status::None {
+                                let rejected = order.status == Status::Rejected
+                                    || (order.status == Status::Expired
+                                        && ex_order.req == Status::New);
+                                metrics.on_order_response(
+                                    inst_no,
+                                    ex_order.local_timestamp,
+                                    resp_ts,
+                                    rejected,
+                                );
+                            }
+                        }
                         if let Some(hook) = self.order_hook.as_mut() {
                             hook(ex_order, &order)?;
                         }
@@ -283,6 +407,10 @@ where
                     .position = qty;
             }
             LiveEvent::Error(error) => {
+                #[cfg(feature = "metrics")]
+                if let Some(metrics) = self.metrics.as_ref() {
+                    metrics.on_error(&error.kind);
+                }
                 if let Some(handler) = self.error_handler.as_mut() {
                     handler(error)?;
                 }
@@ -294,6 +422,25 @@ where
         Ok(false)
     }
 
+    fn send_heartbeat(&mut self) -> Result<(), BotError> {
+        if self.heartbeat_interval.is_zero()
+            || self.last_heartbeat.elapsed() < self.heartbeat_interval
+        {
+            return Ok(());
+        }
+        self.last_heartbeat = Instant::now();
+        for inst_no in self.heartbeat_targets.iter() {
+            self.channel
+                .send(self.id, *inst_no, LiveRequest::Heartbeat)?;
+        }
+        Ok(())
+    }
+
+    /// Expires the timers due by now. Returns `true` if any timer expires.
+    fn advance_timers(&mut self) -> bool {
+        self.timers.advance(Utc::now().timestamp_nanos_opt().unwrap())
+    }
+
     fn elapse_<const WAIT_NEXT_FEED: bool>(
         &mut self,
         duration: i64,
@@ -304,19 +451,45 @@ where
         let mut remaining_duration = duration;
         let mut batch_mode = false;
         let mut wait_resp_received = false;
+        // Waiting for a specific order response isn't interrupted by timers.
+        let wake_on_timer = !matches!(wait_order_response, WaitOrderResponse::Specified { .. });
+        // A timer that expires during a batch wakes the bot up at the end of the batch.
+        let mut timer_fired = false;
 
         loop {
-            match self.channel.recv_timeout(self.id, remaining_duration) {
+            self.send_heartbeat()?;
+            if self.advance_timers() && wake_on_timer {
+                timer_fired = true;
+            }
+            if timer_fired && !batch_mode {
+                return Ok(true);
+            }
+            // Wakes up in time to send the next heartbeat.
+            let mut timeout = if self.heartbeat_interval.is_zero() {
+                remaining_duration
+            } else {
+                remaining_duration.min(self.heartbeat_interval)
+            };
+            // Wakes up in time to expire the next timer.
+            if let Some(deadline) = self.timers.next_deadline() {
+                let now = Utc::now().timestamp_nanos_opt().unwrap();
+                timeout = timeout.min(Duration::from_nanos((deadline - now).max(0) as u64));
+            }
+            match self.channel.recv_timeout(self.id, timeout) {
                 Ok((_, LiveEvent::BatchStart)) => {
                     batch_mode = true;
                 }
                 Ok((_, LiveEvent::BatchEnd)) => {
                     batch_mode = false;
-                    if wait_resp_received {
+                    if wait_resp_received || timer_fired {
                         return Ok(true);
                     }
                 }
                 Ok((inst_no, ev)) => {
+                    #[cfg(feature = "backtest")]
+                    if let Some(capture) = self.capture.as_mut() {
+                        capture.record_event(inst_no, &ev);
+                    }
                     if self.process_event::<WAIT_NEXT_FEED>(inst_no, ev, wait_order_response)? {
                         wait_resp_received = true;
                         if !batch_mode {
@@ -325,7 +498,10 @@ where
                     }
                 }
                 Err(BotError::Timeout) => {
-                    return Ok(true);
+                    if timeout == remaining_duration {
+                        self.advance_timers();
+                        return Ok(true);
+                    }
                 }
                 Err(BotError::Interrupted) => {
                     return Ok(false);
@@ -337,6 +513,7 @@ where
             if !batch_mode {
                 let elapsed = instant.elapsed();
                 if elapsed > duration {
+                    self.advance_timers();
                     return Ok(true);
                 }
                 remaining_duration = duration - elapsed;
@@ -386,6 +563,15 @@ where
         };
         let order_id = order.order_id;
         instrument.orders.insert(order_id, order.clone());
+        instrument.updated_orders.insert(order_id);
+        #[cfg(feature = "backtest")]
+        if let Some(capture) = self.capture.as_mut() {
+            capture.record_request(asset_no, &order);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.on_order_request(asset_no);
+        }
 
         self.channel
             .send(self.id, asset_no, LiveRequest::Order { symbol, order })?;
@@ -473,6 +659,29 @@ where
         &self.instruments.get(asset_no).unwrap().orders
     }
 
+    #[inline]
+    fn updated_orders(&self, asset_no: usize) -> &[OrderId] {
+        self.instruments.get(asset_no).unwrap().updated_orders.as_slice()
+    }
+
+    #[inline]
+    fn clear_updated_orders(&mut self, asset_no: Option<usize>) {
+        match asset_no {
+            Some(asset_no) => {
+                self.instruments
+                    .get_mut(asset_no)
+                    .unwrap()
+                    .updated_orders
+                    .clear();
+            }
+            None => {
+                for instrument in self.instruments.iter_mut() {
+                    instrument.updated_orders.clear();
+                }
+            }
+        }
+    }
+
     #[inline]
     fn submit_buy_order(
         &mut self,
@@ -546,7 +755,45 @@ where
         qty: f64,
         wait: bool,
     ) -> Result<bool, Self::Error> {
-        todo!();
+        let instrument = self
+            .instruments
+            .get_mut(asset_no)
+            .ok_or(BotError::InstrumentNotFound)?;
+        let symbol = instrument.symbol.clone();
+        let tick_size = instrument.tick_size;
+        let order = instrument
+            .orders
+            .get_mut(&order_id)
+            .ok_or(BotError::OrderNotFound)?;
+        if !order.cancellable() {
+            return Err(BotError::InvalidOrderStatus);
+        }
+        order.req = Status::Replaced;
+        order.local_timestamp = Utc::now().timestamp_nanos_opt().unwrap();
+        instrument.updated_orders.insert(order_id);
+
+        // The local order keeps the current price and quantity until the modification is
+        // confirmed.
+        let mut order = order.clone();
+        order.price_tick = (price / tick_size).round() as i64;
+        order.qty = qty;
+        #[cfg(feature = "backtest")]
+        if let Some(capture) = self.capture.as_mut() {
+            capture.record_request(asset_no, &order);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.on_order_request(asset_no);
+        }
+
+        self.channel
+            .send(self.id, asset_no, LiveRequest::Order { symbol, order })?;
+
+        if wait {
+            // fixme: timeout should be specified by the argument.
+            return self.wait_order_response(asset_no, order_id, 60_000_000_000);
+        }
+        Ok(true)
     }
 
     #[inline]
@@ -570,6 +817,15 @@ where
         }
         order.req = Status::Canceled;
         order.local_timestamp = Utc::now().timestamp_nanos_opt().unwrap();
+        instrument.updated_orders.insert(order_id);
+        #[cfg(feature = "backtest")]
+        if let Some(capture) = self.capture.as_mut() {
+            capture.record_request(asset_no, order);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.on_order_request(asset_no);
+        }
 
         self.channel.send(
             self.id,
@@ -592,12 +848,12 @@ where
         match asset_no {
             Some(inst_no) => {
                 if let Some(instrument) = self.instruments.get_mut(inst_no) {
-                    instrument.orders.retain(|_, order| order.active());
+                    instrument.clear_inactive_orders();
                 }
             }
             None => {
                 for instrument in self.instruments.iter_mut() {
-                    instrument.orders.retain(|_, order| order.active());
+                    instrument.clear_inactive_orders();
                 }
             }
         }
@@ -626,6 +882,26 @@ where
         }
     }
 
+    #[inline]
+    fn schedule_at(&mut self, timestamp: i64, timer_id: u64) {
+        self.timers.schedule(timestamp, timer_id);
+    }
+
+    #[inline]
+    fn cancel_timer(&mut self, timer_id: u64) -> bool {
+        self.timers.cancel(timer_id)
+    }
+
+    #[inline]
+    fn expired_timers(&self) -> &[u64] {
+        self.timers.expired()
+    }
+
+    #[inline]
+    fn clear_expired_timers(&mut self) {
+        self.timers.clear_expired();
+    }
+
     #[inline]
     fn elapse(&mut self, duration: i64) -> Result<bool, Self::Error> {
         self.elapse_::<false>(duration, WaitOrderResponse::None)
@@ -637,6 +913,12 @@ where
     }
 
     fn close(&mut self) -> Result<(), Self::Error> {
+        #[cfg(feature = "backtest")]
+        if let Some(capture) = self.capture.as_mut() {
+            capture
+                .flush()
+                .map_err(|error| BotError::Custom(error.to_string()))?;
+        }
         Ok(())
     }
 
@@ -648,3 +930,103 @@ where
         self.instruments.get(asset_no).unwrap().last_order_latency
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        cell::RefCell,
+        collections::VecDeque,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        depth::HashMapMarketDepth,
+        live::{BotError, Instrument, LiveBot, LiveBotBuilder, ipc::Channel},
+        prelude::{Bot, BuildError, HkPriceAction},
+        types::{Event, LOCAL_BID_DEPTH_EVENT, LiveEvent, LiveRequest},
+    };
+
+    thread_local! {
+        /// The events that [`ScriptedChannel`] delivers, each after the delay.
+        pub(crate) static SCRIPT: RefCell<VecDeque<(Duration, LiveEvent)>> = Default::default();
+    }
+
+    /// A channel that delivers the events in [`SCRIPT`] for the first instrument.
+    pub(crate) struct ScriptedChannel;
+
+    impl Channel for ScriptedChannel {
+        fn build<MD, PA>(_instruments: &[Instrument<MD, PA>]) -> Result<Self, BuildError> {
+            Ok(Self)
+        }
+
+        fn recv_timeout(
+            &mut self,
+            _id: u64,
+            timeout: Duration,
+        ) -> Result<(usize, LiveEvent), BotError> {
+            match SCRIPT.with_borrow_mut(|script| script.pop_front()) {
+                Some((delay, ev)) => {
+                    thread::sleep(delay);
+                    Ok((0, ev))
+                }
+                None => {
+                    thread::sleep(timeout);
+                    Err(BotError::Timeout)
+                }
+            }
+        }
+
+        fn send(
+            &mut self,
+            _id: u64,
+            _inst_no: usize,
+            _request: LiveRequest,
+        ) -> Result<(), BotError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn timer_expired_during_batch_wakes_up_at_batch_end() {
+        let mut hbt: LiveBot<ScriptedChannel, HashMapMarketDepth, HkPriceAction> =
+            LiveBotBuilder::new()
+                .register(Instrument::new(
+                    "scripted",
+                    "btcusdt",
+                    0.1,
+                    1.0,
+                    HashMapMarketDepth::new(0.1, 1.0),
+                    0,
+                    HkPriceAction::new(vec![], vec![]),
+                ))
+                .heartbeat_interval(Duration::ZERO)
+                .build()
+                .unwrap();
+        let feed = LiveEvent::Feed {
+            symbol: "btcusdt".to_string(),
+            event: Event {
+                ev: LOCAL_BID_DEPTH_EVENT,
+                exch_ts: 0,
+                local_ts: 0,
+                px: 100.0,
+                qty: 1.0,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            },
+        };
+        SCRIPT.with_borrow_mut(|script| {
+            script.push_back((Duration::ZERO, LiveEvent::BatchStart));
+            // The timer expires while this event in the batch is received.
+            script.push_back((Duration::from_millis(20), feed));
+            script.push_back((Duration::from_millis(10), LiveEvent::BatchEnd));
+        });
+
+        hbt.schedule_after(5_000_000, 1);
+        let instant = Instant::now();
+        assert!(hbt.elapse(10_000_000_000).unwrap());
+        assert!(instant.elapsed() < Duration::from_secs(1));
+        assert_eq!(hbt.expired_timers(), &[1]);
+    }
+}
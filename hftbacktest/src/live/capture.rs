@@ -0,0 +1,468 @@
+use std::{
+    fs,
+    fs::File,
+    io::Error,
+    mem,
+    path::{Path, PathBuf},
+    sync::mpsc::{Sender, channel},
+    thread,
+    thread::JoinHandle,
+};
+
+use chrono::Utc;
+use hftbacktest_derive::NpyDTyped;
+use tracing::{error, info};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    backtest::data::{POD, write_npy},
+    types::{
+        EXCH_EVENT,
+        Event,
+        LOCAL_EVENT,
+        LiveEvent,
+        ORDER_REQUEST,
+        ORDER_RESPONSE,
+        Order,
+        OrderRecord,
+    },
+};
+
+/// A position update recorded by [`SessionCapture`].
+#[repr(C)]
+#[derive(Clone, Debug, NpyDTyped)]
+pub struct PositionRecord {
+    /// The local timestamp at which the update is received by the bot.
+    pub local_ts: i64,
+    pub exch_ts: i64,
+    pub qty: f64,
+}
+
+unsafe impl POD for PositionRecord {}
+
+struct InstrumentCapture {
+    symbol: String,
+    seq: usize,
+    feed: Vec<Event>,
+    orders: Vec<OrderRecord>,
+    positions: Vec<PositionRecord>,
+}
+
+impl InstrumentCapture {
+    fn is_empty(&self) -> bool {
+        self.feed.is_empty() && self.orders.is_empty() && self.positions.is_empty()
+    }
+
+    /// Takes the buffered data to be written into the next files.
+    fn take(&mut self) -> CaptureFiles {
+        let prefix = format!("{}_{:05}", self.symbol, self.seq);
+        self.seq += 1;
+        CaptureFiles {
+            prefix,
+            feed: mem::take(&mut self.feed),
+            orders: mem::take(&mut self.orders),
+            positions: mem::take(&mut self.positions),
+        }
+    }
+}
+
+/// The data to be written into the files with the prefix.
+struct CaptureFiles {
+    prefix: String,
+    feed: Vec<Event>,
+    orders: Vec<OrderRecord>,
+    positions: Vec<PositionRecord>,
+}
+
+/// The thread writing the rotated files, so that the compression doesn't stall the bot.
+struct Writer {
+    tx: Sender<CaptureFiles>,
+    handle: JoinHandle<()>,
+}
+
+impl Writer {
+    fn spawn(path: PathBuf) -> Self {
+        let (tx, rx) = channel::<CaptureFiles>();
+        let handle = thread::spawn(move || {
+            for files in rx {
+                if let Err(error) = write(&path, &files) {
+                    error!(
+                        ?error,
+                        prefix = files.prefix,
+                        "Couldn't write the captured session."
+                    );
+                }
+            }
+        });
+        Self { tx, handle }
+    }
+}
+
+/// Records every [`LiveEvent`] a [`LiveBot`](crate::live::LiveBot) processes, along with the
+/// order requests the bot sends, so that a live session can be replayed through
+/// [`Backtest`](crate::backtest::Backtest) with the same strategy code.
+///
+/// For each instrument, the following files are written into the output directory.
+/// * `{symbol}_{seq}.npz` - The feed in the [`Event`] format, stored under `data`. Each event is
+///   flagged as both an exchange and a local event, and its local timestamp is replaced with the
+///   time at which the bot received it, so the file can be used as backtest data as is.
+/// * `{symbol}_{seq}_log.npz` - The order log stored under `orders` as [`OrderRecord`], and the
+///   position updates stored under `positions` as [`PositionRecord`].
+///
+/// Files are rotated once the number of buffered feed events reaches the rotation size, and the
+/// remaining data is written when the bot is closed. The rotated files are written on a separate
+/// thread.
+///
+/// This is available only with the `backtest` feature, which provides the npz writer and the
+/// backtester that replays the capture.
+pub struct SessionCapture {
+    path: PathBuf,
+    rotation_size: usize,
+    instruments: Vec<InstrumentCapture>,
+    writer: Option<Writer>,
+}
+
+impl SessionCapture {
+    /// Constructs a `SessionCapture` that writes files into the given directory.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            rotation_size: 5_000_000,
+            instruments: Vec::new(),
+            writer: None,
+        }
+    }
+
+    /// Sets the number of feed events per file. The default value is `5_000_000`.
+    pub fn rotation_size(mut self, rotation_size: usize) -> Self {
+        self.rotation_size = rotation_size;
+        self
+    }
+
+    pub(crate) fn init(&mut self, symbols: impl Iterator<Item = String>) -> Result<(), Error> {
+        fs::create_dir_all(&self.path)?;
+        self.instruments = symbols
+            .map(|symbol| InstrumentCapture {
+                symbol,
+                seq: 0,
+                feed: Vec::new(),
+                orders: Vec::new(),
+                positions: Vec::new(),
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Records the event received from the connector.
+    pub(crate) fn record_event(&mut self, inst_no: usize, ev: &LiveEvent) {
+        let local_ts = Utc::now().timestamp_nanos_opt().unwrap();
+        let Some(instrument) = self.instruments.get_mut(inst_no) else {
+            return;
+        };
+        match ev {
+            LiveEvent::Feed { event, .. } => {
+                let mut event = event.clone();
+                event.ev |= EXCH_EVENT | LOCAL_EVENT;
+                event.local_ts = local_ts;
+                instrument.feed.push(event);
+                if instrument.feed.len() >= self.rotation_size {
+                    let files = instrument.take();
+                    let path = &self.path;
+                    let writer = self
+                        .writer
+                        .get_or_insert_with(|| Writer::spawn(path.clone()));
+                    // The writer thread only ends once its sender is dropped.
+                    writer.tx.send(files).unwrap();
+                }
+            }
+            LiveEvent::Order { order, .. } => {
+                instrument
+                    .orders
+                    .push(OrderRecord::new(ORDER_RESPONSE, local_ts, order));
+            }
+            LiveEvent::Position { qty, exch_ts, .. } => {
+                instrument.positions.push(PositionRecord {
+                    local_ts,
+                    exch_ts: *exch_ts,
+                    qty: *qty,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the order request sent to the connector.
+    pub(crate) fn record_request(&mut self, inst_no: usize, order: &Order) {
+        if let Some(instrument) = self.instruments.get_mut(inst_no) {
+            instrument.orders.push(OrderRecord::new(
+                ORDER_REQUEST,
+                order.local_timestamp,
+                order,
+            ));
+        }
+    }
+
+    /// Writes all buffered data, after waiting for the rotated files to be written.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if let Some(Writer { tx, handle }) = self.writer.take() {
+            drop(tx);
+            if handle.join().is_err() {
+                error!("The session capture writer panicked.");
+            }
+        }
+        for instrument in self.instruments.iter_mut() {
+            if !instrument.is_empty() {
+                write(&self.path, &instrument.take())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SessionCapture {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush() {
+            error!(?error, "Couldn't write the captured session.");
+        }
+    }
+}
+
+fn write(path: &Path, files: &CaptureFiles) -> Result<(), Error> {
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::DEFLATE)
+        .compression_level(Some(9));
+
+    let prefix = &files.prefix;
+    let file_path = path.join(format!("{prefix}.npz"));
+    let mut zip = ZipWriter::new(File::create(&file_path)?);
+    zip.start_file("data.npy", options)?;
+    write_npy(&mut zip, &files.feed)?;
+    zip.finish()?;
+
+    let mut zip = ZipWriter::new(File::create(path.join(format!("{prefix}_log.npz")))?);
+    zip.start_file("orders.npy", options)?;
+    write_npy(&mut zip, &files.orders)?;
+    zip.start_file("positions.npy", options)?;
+    write_npy(&mut zip, &files.positions)?;
+    zip.finish()?;
+
+    info!(
+        ?file_path,
+        events = files.feed.len(),
+        "The captured session is written."
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::VecDeque, error::Error, fs, time::Duration};
+
+    use crate::{
+        backtest::{
+            Backtest,
+            DataSource,
+            ExchangeKind::NoPartialFillExchange,
+            L2AssetBuilder,
+            assettype::LinearAsset,
+            data::read_npz_file,
+            models::{
+                CommonFees,
+                ConstantLatency,
+                PowerProbQueueFunc3,
+                ProbQueueModel,
+                TradingValueFeeModel,
+            },
+        },
+        depth::{HashMapMarketDepth, MarketDepth},
+        live::{
+            BotError,
+            Instrument,
+            LiveBot,
+            LiveBotBuilder,
+            PositionRecord,
+            SessionCapture,
+            ipc::Channel,
+        },
+        prelude::{Bot, BuildError, HkPriceAction, OrdType, Status, TimeInForce},
+        types::{
+            EXCH_EVENT,
+            Event,
+            LOCAL_ASK_DEPTH_EVENT,
+            LOCAL_BID_DEPTH_EVENT,
+            LOCAL_EVENT,
+            LiveEvent,
+            LiveRequest,
+            ORDER_REQUEST,
+            ORDER_RESPONSE,
+            OrderRecord,
+        },
+    };
+
+    thread_local! {
+        /// The events that [`QueueChannel`] delivers.
+        static QUEUE: RefCell<VecDeque<LiveEvent>> = Default::default();
+    }
+
+    struct QueueChannel;
+
+    impl Channel for QueueChannel {
+        fn build<MD, PA>(_instruments: &[Instrument<MD, PA>]) -> Result<Self, BuildError> {
+            Ok(Self)
+        }
+
+        fn recv_timeout(
+            &mut self,
+            _id: u64,
+            _timeout: Duration,
+        ) -> Result<(usize, LiveEvent), BotError> {
+            QUEUE
+                .with_borrow_mut(|queue| queue.pop_front())
+                .map(|ev| (0, ev))
+                .ok_or(BotError::Timeout)
+        }
+
+        fn send(
+            &mut self,
+            _id: u64,
+            _inst_no: usize,
+            _request: LiveRequest,
+        ) -> Result<(), BotError> {
+            Ok(())
+        }
+    }
+
+    fn feed(ev: u64, exch_ts: i64, px: f64) -> LiveEvent {
+        LiveEvent::Feed {
+            symbol: "btcusdt".to_string(),
+            event: Event {
+                ev,
+                exch_ts,
+                local_ts: 0,
+                px,
+                qty: 1.0,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            },
+        }
+    }
+
+    #[test]
+    fn replays_captured_session() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("session_capture_{}", std::process::id()));
+        let mut live: LiveBot<QueueChannel, HashMapMarketDepth, HkPriceAction> =
+            LiveBotBuilder::new()
+                .register(Instrument::new(
+                    "queue",
+                    "btcusdt",
+                    0.1,
+                    1.0,
+                    HashMapMarketDepth::new(0.1, 1.0),
+                    0,
+                    HkPriceAction::new(vec![], vec![]),
+                ))
+                .heartbeat_interval(Duration::ZERO)
+                .capture(SessionCapture::new(&path))
+                .build()?;
+
+        QUEUE.with_borrow_mut(|queue| {
+            queue.push_back(feed(LOCAL_BID_DEPTH_EVENT, 1, 100.0));
+            queue.push_back(feed(LOCAL_ASK_DEPTH_EVENT, 2, 101.0));
+        });
+        live.elapse(1_000_000)?;
+        live.submit_buy_order(0, 1, 99.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)?;
+        let mut order = live.orders(0).get(&1).unwrap().clone();
+        order.status = Status::New;
+        QUEUE.with_borrow_mut(|queue| {
+            queue.push_back(LiveEvent::Order {
+                symbol: "btcusdt".to_string(),
+                order,
+            });
+            queue.push_back(LiveEvent::Position {
+                symbol: "btcusdt".to_string(),
+                qty: 1.0,
+                exch_ts: 3,
+            });
+            queue.push_back(feed(LOCAL_BID_DEPTH_EVENT, 4, 100.5));
+        });
+        live.elapse(1_000_000)?;
+        live.close()?;
+
+        let mut hbt: Backtest<HashMapMarketDepth, HkPriceAction> = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::File(
+                        path.join("btcusdt_00000.npz").to_string_lossy().to_string(),
+                    )])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(0.1, 1.0))
+                    .price_action(HkPriceAction::new(vec![], vec![]))
+                    .build()?,
+            )
+            .build()?;
+        hbt.goto_end()?;
+        assert_eq!(hbt.depth(0).best_bid(), live.depth(0).best_bid());
+        assert_eq!(hbt.depth(0).best_ask(), live.depth(0).best_ask());
+        assert_eq!(hbt.depth(0).best_bid(), 100.5);
+
+        let log_path = path.join("btcusdt_00000_log.npz");
+        let log_path = log_path.to_string_lossy();
+        let orders = read_npz_file::<OrderRecord>(&log_path, "orders")?;
+        let orders: Vec<_> = (0..orders.len())
+            .map(|i| (orders[i].kind, orders[i].order_id, orders[i].status))
+            .collect();
+        assert_eq!(
+            orders,
+            vec![
+                (ORDER_REQUEST, 1, Status::New as u64),
+                (ORDER_RESPONSE, 1, Status::New as u64),
+            ]
+        );
+        let positions = read_npz_file::<PositionRecord>(&log_path, "positions")?;
+        assert_eq!(positions.len(), 1);
+        assert_eq!((positions[0].exch_ts, positions[0].qty), (3, 1.0));
+
+        fs::remove_dir_all(&path)?;
+        Ok(())
+    }
+    #[test]
+    fn rotates_files() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("session_rotation_{}", std::process::id()));
+        let mut capture = SessionCapture::new(&path).rotation_size(2);
+        capture.init(["btcusdt".to_string()].into_iter())?;
+        for exch_ts in 1..=5 {
+            capture.record_event(0, &feed(LOCAL_BID_DEPTH_EVENT, exch_ts, 100.0));
+        }
+        capture.record_event(
+            0,
+            &LiveEvent::Position {
+                symbol: "btcusdt".to_string(),
+                qty: 1.0,
+                exch_ts: 6,
+            },
+        );
+        capture.flush()?;
+
+        // The two rotated files are written by the writer thread, and the rest by the flush.
+        let mut feeds = Vec::new();
+        for seq in 0..3 {
+            let data_path = path.join(format!("btcusdt_{seq:05}.npz"));
+            let data = read_npz_file::<Event>(&data_path.to_string_lossy(), "data")?;
+            feeds.push((0..data.len()).map(|i| data[i].exch_ts).collect::<Vec<_>>());
+            assert!((0..data.len()).all(|i| data[i].is(EXCH_EVENT | LOCAL_EVENT)));
+        }
+        assert_eq!(feeds, vec![vec![1, 2], vec![3, 4], vec![5]]);
+        let log_path = path.join("btcusdt_00002_log.npz");
+        let positions = read_npz_file::<PositionRecord>(&log_path.to_string_lossy(), "positions")?;
+        assert_eq!(positions.len(), 1);
+        assert_eq!((positions[0].exch_ts, positions[0].qty), (6, 1.0));
+
+        fs::remove_dir_all(&path)?;
+        Ok(())
+    }
+}
@@ -57,6 +57,8 @@ pub enum ChannelError {
     Encode(#[from] EncodeError),
     #[error("{0:?}")]
     FromUtf8(#[from] FromUtf8Error),
+    #[error("{0:?}")]
+    Io(#[from] std::io::Error),
 }
 
 pub struct IceoryxBuilder {
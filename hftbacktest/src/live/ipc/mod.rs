@@ -1,13 +1,21 @@
 use std::time::Duration;
 
 use crate::{
-    live::{BotError, Instrument},
+    live::{
+        BotError,
+        Instrument,
+        ipc::{
+            iceoryx::IceoryxUnifiedChannel,
+            socket::{Endpoint, SocketUnifiedChannel},
+        },
+    },
     prelude::BuildError,
     types::{LiveEvent, LiveRequest},
 };
 
 mod config;
 pub mod iceoryx;
+pub mod socket;
 
 pub const TO_ALL: u64 = 0;
 
@@ -29,3 +37,103 @@ pub trait Channel {
     /// Sends a [`LiveRequest`] to the connector corresponding to the `inst_no`.
     fn send(&mut self, id: u64, inst_no: usize, request: LiveRequest) -> Result<(), BotError>;
 }
+
+/// A [`Channel`] that selects the transport by the connector names of the instruments. If they
+/// are all socket [`Endpoint`]s, such as `tcp://10.0.0.1:5000` or `unix:///tmp/bf.sock`, it
+/// communicates over [`SocketUnifiedChannel`], so iceoryx isn't needed; otherwise, it uses
+/// [`IceoryxUnifiedChannel`].
+///
+/// This allows the transport to be chosen at runtime, such as from the Python bindings.
+pub enum UnifiedChannel {
+    Iceoryx(IceoryxUnifiedChannel),
+    Socket(SocketUnifiedChannel),
+}
+
+impl Channel for UnifiedChannel {
+    fn build<MD, PA>(instruments: &[Instrument<MD, PA>]) -> Result<Self, BuildError>
+    where
+        Self: Sized,
+    {
+        let num_endpoints = instruments
+            .iter()
+            .filter(|instrument| Endpoint::parse(&instrument.connector_name).is_some())
+            .count();
+        if num_endpoints == 0 {
+            Ok(UnifiedChannel::Iceoryx(IceoryxUnifiedChannel::build(
+                instruments,
+            )?))
+        } else if num_endpoints == instruments.len() {
+            Ok(UnifiedChannel::Socket(SocketUnifiedChannel::build(
+                instruments,
+            )?))
+        } else {
+            Err(BuildError::InvalidArgument(
+                "connector names must be either all socket endpoints or all iceoryx names",
+            ))
+        }
+    }
+
+    fn recv_timeout(&mut self, id: u64, timeout: Duration) -> Result<(usize, LiveEvent), BotError> {
+        match self {
+            UnifiedChannel::Iceoryx(channel) => channel.recv_timeout(id, timeout),
+            UnifiedChannel::Socket(channel) => channel.recv_timeout(id, timeout),
+        }
+    }
+
+    fn send(&mut self, id: u64, inst_no: usize, request: LiveRequest) -> Result<(), BotError> {
+        match self {
+            UnifiedChannel::Iceoryx(channel) => channel.send(id, inst_no, request),
+            UnifiedChannel::Socket(channel) => channel.send(id, inst_no, request),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use crate::{
+        depth::HashMapMarketDepth,
+        live::{
+            Instrument,
+            ipc::{Channel, UnifiedChannel},
+        },
+        prelude::{BuildError, HkPriceAction},
+    };
+
+    fn instrument(
+        connector_name: &str,
+        symbol: &str,
+    ) -> Instrument<HashMapMarketDepth, HkPriceAction> {
+        Instrument::new(
+            connector_name,
+            symbol,
+            0.1,
+            1.0,
+            HashMapMarketDepth::new(0.1, 1.0),
+            0,
+            HkPriceAction::new(vec![], vec![]),
+        )
+    }
+
+    #[test]
+    fn selects_transport_by_connector_name() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+
+        let channel = UnifiedChannel::build(&[
+            instrument(&endpoint, "btcusdt"),
+            instrument(&endpoint, "ethusdt"),
+        ])
+        .unwrap();
+        assert!(matches!(channel, UnifiedChannel::Socket(_)));
+
+        assert!(matches!(
+            UnifiedChannel::build(&[
+                instrument(&endpoint, "btcusdt"),
+                instrument("binancefutures", "ethusdt"),
+            ]),
+            Err(BuildError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,830 @@
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    fmt,
+    io,
+    io::{Read, Write},
+    marker::PhantomData,
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError, Sender, channel},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+#[cfg(unix)]
+use std::{
+    fs,
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+};
+
+use bincode::{Decode, Encode, config};
+use tracing::{error, info, warn};
+
+use crate::{
+    live::{
+        BotError,
+        Instrument,
+        ipc::{Channel, TO_ALL, iceoryx::ChannelError},
+    },
+    prelude::{LiveEvent, LiveRequest},
+    types::{BuildError, ErrorKind, LiveError, Value},
+};
+
+/// `len: u32`, `kind: u8`, and `id: u64` in little-endian.
+const HEADER_SIZE: usize = 13;
+const MAX_FRAME_SIZE: usize = 1 << 20;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// The connection is considered lost if nothing is received during this period, since the peer
+/// sends a heartbeat every [`HEARTBEAT_INTERVAL`].
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// The address of a socket [`Channel`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `tcp://host:port`
+    Tcp(String),
+    /// `unix:///path/to/socket`
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "tcp://{addr}"),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+impl Endpoint {
+    /// Parses `tcp://host:port` or `unix:///path/to/socket`. Returns `None` if the given string is
+    /// not a socket endpoint.
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            return Some(Endpoint::Tcp(addr.to_string()));
+        }
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix://") {
+            return Some(Endpoint::Unix(PathBuf::from(path)));
+        }
+        None
+    }
+
+    fn connect(&self) -> io::Result<Stream> {
+        let stream = match self {
+            Endpoint::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => Stream::Unix(UnixStream::connect(path)?),
+        };
+        stream.set_timeouts()?;
+        Ok(stream)
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Stream::Tcp(stream) => Ok(Stream::Tcp(stream.try_clone()?)),
+            #[cfg(unix)]
+            Stream::Unix(stream) => Ok(Stream::Unix(stream.try_clone()?)),
+        }
+    }
+
+    fn set_timeouts(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => {
+                stream.set_read_timeout(Some(HEARTBEAT_TIMEOUT))?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))
+            }
+            #[cfg(unix)]
+            Stream::Unix(stream) => {
+                stream.set_read_timeout(Some(HEARTBEAT_TIMEOUT))?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))
+            }
+        }
+    }
+
+    fn shutdown(&self) {
+        let _ = match self {
+            Stream::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.shutdown(Shutdown::Both),
+        };
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn bind(endpoint: &Endpoint) -> io::Result<Self> {
+        match endpoint {
+            Endpoint::Tcp(addr) => Ok(Listener::Tcp(TcpListener::bind(addr)?)),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                // Removes the socket file left by the previous run.
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+                Ok(Listener::Unix(UnixListener::bind(path)?))
+            }
+        }
+    }
+
+    fn accept(&self) -> io::Result<Stream> {
+        let stream = match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => Stream::Unix(listener.accept()?.0),
+        };
+        stream.set_timeouts()?;
+        Ok(stream)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+enum FrameKind {
+    Data = 0,
+    Heartbeat = 1,
+}
+
+/// Encodes a length-prefixed frame. A heartbeat frame has no payload.
+fn encode_frame<T: Encode>(id: u64, data: Option<&T>) -> Result<Vec<u8>, ChannelError> {
+    let payload = match data {
+        Some(data) => bincode::encode_to_vec(data, config::standard())?,
+        None => Vec::new(),
+    };
+    let kind = if data.is_some() {
+        FrameKind::Data
+    } else {
+        FrameKind::Heartbeat
+    };
+    let mut frame = Vec::with_capacity(HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.push(kind as u8);
+    frame.extend_from_slice(&id.to_le_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Reads a frame into `buf` and returns its kind and ID.
+fn read_frame<R: Read>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<(FrameKind, u64)> {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+    let kind = match header[4] {
+        0 => FrameKind::Data,
+        1 => FrameKind::Heartbeat,
+        kind => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid frame kind {kind}"),
+            ));
+        }
+    };
+    let id = u64::from_le_bytes(header[5..13].try_into().unwrap());
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame size {len} exceeds the limit"),
+        ));
+    }
+    buf.resize(len, 0);
+    reader.read_exact(buf)?;
+    Ok((kind, id))
+}
+
+struct Connection {
+    stream: Stream,
+    /// The IDs of the bots that have sent requests through this connection.
+    bot_ids: HashSet<u64>,
+}
+
+type SharedConnections = Arc<Mutex<HashMap<u64, Connection>>>;
+
+/// Listens for the bots on the given endpoint. This is the connector side of the socket
+/// [`Channel`].
+///
+/// Each accepted connection runs its own reader thread, and all connections receive a heartbeat
+/// every second. The received requests are delivered through the returned [`SocketReceiver`], and
+/// the events sent through the returned [`SocketSender`] are routed to the connections of the
+/// destination bot, or to all connections if the destination is [`TO_ALL`].
+pub fn listen<S, R>(
+    endpoint: &Endpoint,
+) -> Result<(SocketSender<S>, SocketReceiver<R>), ChannelError>
+where
+    R: Decode<()> + Send + 'static,
+{
+    let listener = Listener::bind(endpoint)?;
+    let connections: SharedConnections = Default::default();
+    let (tx, rx) = channel();
+
+    let connections_ = connections.clone();
+    thread::spawn(move || {
+        let mut next_conn_no = 0;
+        loop {
+            let stream = match listener.accept() {
+                Ok(stream) => stream,
+                Err(error) => {
+                    error!(?error, "Couldn't accept the connection.");
+                    continue;
+                }
+            };
+            let reader = match stream.try_clone() {
+                Ok(reader) => reader,
+                Err(error) => {
+                    error!(?error, "Couldn't clone the stream.");
+                    continue;
+                }
+            };
+            next_conn_no += 1;
+            let conn_no = next_conn_no;
+            info!(conn_no, "A bot is connected.");
+            connections_.lock().unwrap().insert(
+                conn_no,
+                Connection {
+                    stream,
+                    bot_ids: Default::default(),
+                },
+            );
+
+            let connections = connections_.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                if let Err(error) = read_requests(conn_no, reader, &connections, &tx) {
+                    info!(conn_no, ?error, "A bot is disconnected.");
+                }
+                if let Some(conn) = connections.lock().unwrap().remove(&conn_no) {
+                    conn.stream.shutdown();
+                }
+            });
+        }
+    });
+
+    let connections_ = connections.clone();
+    thread::spawn(move || {
+        let heartbeat = encode_frame::<()>(TO_ALL, None).unwrap();
+        loop {
+            thread::sleep(HEARTBEAT_INTERVAL);
+            let mut connections = connections_.lock().unwrap();
+            connections.retain(|conn_no, conn| match conn.stream.write_all(&heartbeat) {
+                Ok(()) => true,
+                Err(error) => {
+                    warn!(conn_no, ?error, "Couldn't send the heartbeat.");
+                    conn.stream.shutdown();
+                    false
+                }
+            });
+        }
+    });
+
+    Ok((
+        SocketSender {
+            connections,
+            _t_marker: Default::default(),
+        },
+        SocketReceiver { rx },
+    ))
+}
+
+fn read_requests<R: Decode<()>>(
+    conn_no: u64,
+    mut reader: Stream,
+    connections: &SharedConnections,
+    tx: &Sender<(u64, R)>,
+) -> Result<(), ChannelError> {
+    let mut buf = Vec::new();
+    loop {
+        let (kind, id) = read_frame(&mut reader, &mut buf)?;
+        if kind == FrameKind::Heartbeat {
+            continue;
+        }
+        let (decoded, _len): (R, usize) = bincode::decode_from_slice(&buf, config::standard())?;
+        if let Some(conn) = connections.lock().unwrap().get_mut(&conn_no) {
+            conn.bot_ids.insert(id);
+        }
+        if tx.send((id, decoded)).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Sends the events to the bots connected to the [`listen`]ing socket.
+pub struct SocketSender<T> {
+    connections: SharedConnections,
+    _t_marker: PhantomData<T>,
+}
+
+impl<T> SocketSender<T>
+where
+    T: Encode,
+{
+    pub fn send(&self, id: u64, data: &T) -> Result<(), ChannelError> {
+        let frame = encode_frame(id, Some(data))?;
+        let mut connections = self.connections.lock().unwrap();
+        connections.retain(|conn_no, conn| {
+            if id != TO_ALL && !conn.bot_ids.contains(&id) {
+                return true;
+            }
+            match conn.stream.write_all(&frame) {
+                Ok(()) => true,
+                Err(error) => {
+                    warn!(conn_no, ?error, "Couldn't send to the bot.");
+                    conn.stream.shutdown();
+                    false
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Receives the requests from the bots connected to the [`listen`]ing socket.
+pub struct SocketReceiver<T> {
+    rx: Receiver<(u64, T)>,
+}
+
+impl<T> SocketReceiver<T> {
+    /// Returns `None` if no request is received until the `timeout` is reached.
+    pub fn receive_timeout(&self, timeout: Duration) -> Result<Option<(u64, T)>, ChannelError> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(received) => Ok(Some(received)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(ChannelError::BuildError(
+                "the listener is closed".to_string(),
+            )),
+        }
+    }
+}
+
+/// The bot side connection to a connector, which is reconnected automatically when it is lost.
+struct SocketClient {
+    endpoint: Endpoint,
+    writer: Arc<Mutex<Option<Stream>>>,
+    /// The registration requests to be sent again after reconnection, so that the connector
+    /// resends the current state of the instruments.
+    registrations: Arc<Mutex<Vec<Vec<u8>>>>,
+    symbol_to_inst_no: HashMap<String, usize>,
+    closed: Arc<AtomicBool>,
+}
+
+impl SocketClient {
+    fn connect(
+        ch_no: usize,
+        endpoint: Endpoint,
+        tx: Sender<(usize, u64, LiveEvent)>,
+    ) -> Result<Self, ChannelError> {
+        let stream = endpoint.connect()?;
+        let reader = stream.try_clone()?;
+        let client = Self {
+            endpoint,
+            writer: Arc::new(Mutex::new(Some(stream))),
+            registrations: Default::default(),
+            symbol_to_inst_no: Default::default(),
+            closed: Default::default(),
+        };
+
+        let endpoint = client.endpoint.clone();
+        let writer = client.writer.clone();
+        let registrations = client.registrations.clone();
+        let closed = client.closed.clone();
+        thread::spawn(move || {
+            let mut reader = reader;
+            let mut buf = Vec::new();
+            loop {
+                if let Err(error) = read_events(ch_no, &mut reader, &mut buf, &tx) {
+                    if closed.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    warn!(
+                        ?endpoint,
+                        ?error,
+                        "The connection to the connector is lost."
+                    );
+                }
+                if let Some(stream) = writer.lock().unwrap().take() {
+                    stream.shutdown();
+                }
+                let ev = LiveEvent::Error(LiveError::with(
+                    ErrorKind::ConnectionInterrupted,
+                    Value::String(endpoint.to_string()),
+                ));
+                if tx.send((ch_no, TO_ALL, ev)).is_err() {
+                    return;
+                }
+
+                match reconnect(&endpoint, &registrations, &closed) {
+                    Some((stream, reader_)) => {
+                        info!(?endpoint, "The connection to the connector is restored.");
+                        *writer.lock().unwrap() = Some(stream);
+                        reader = reader_;
+                    }
+                    None => return,
+                }
+            }
+        });
+
+        let writer = client.writer.clone();
+        let closed = client.closed.clone();
+        thread::spawn(move || {
+            let heartbeat = encode_frame::<()>(TO_ALL, None).unwrap();
+            while !closed.load(Ordering::Relaxed) {
+                thread::sleep(HEARTBEAT_INTERVAL);
+                let mut writer = writer.lock().unwrap();
+                if let Some(stream) = writer.as_mut() {
+                    if stream.write_all(&heartbeat).is_err() {
+                        // The reader thread detects the disconnection and reconnects.
+                        stream.shutdown();
+                    }
+                }
+            }
+        });
+
+        Ok(client)
+    }
+
+    fn register(&mut self, inst_no: usize, symbol: &str) -> bool {
+        if self.symbol_to_inst_no.contains_key(symbol) {
+            return false;
+        }
+        self.symbol_to_inst_no.insert(symbol.to_string(), inst_no);
+        true
+    }
+
+    fn send(&self, id: u64, request: &LiveRequest) -> Result<(), ChannelError> {
+        let frame = encode_frame(id, Some(request))?;
+        if let LiveRequest::RegisterInstrument { .. } = request {
+            self.registrations.lock().unwrap().push(frame.clone());
+        }
+        let mut writer = self.writer.lock().unwrap();
+        let stream = writer
+            .as_mut()
+            .ok_or(ChannelError::BuildError("not connected".to_string()))?;
+        if let Err(error) = stream.write_all(&frame) {
+            stream.shutdown();
+            return Err(error.into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SocketClient {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+        if let Some(stream) = self.writer.lock().unwrap().take() {
+            stream.shutdown();
+        }
+    }
+}
+
+fn read_events(
+    ch_no: usize,
+    reader: &mut Stream,
+    buf: &mut Vec<u8>,
+    tx: &Sender<(usize, u64, LiveEvent)>,
+) -> Result<(), ChannelError> {
+    loop {
+        let (kind, id) = read_frame(reader, buf)?;
+        if kind == FrameKind::Heartbeat {
+            continue;
+        }
+        let (ev, _len): (LiveEvent, usize) = bincode::decode_from_slice(buf, config::standard())?;
+        if tx.send((ch_no, id, ev)).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Reconnects with exponential backoff and sends the registration requests again. Returns `None`
+/// if the channel is closed.
+fn reconnect(
+    endpoint: &Endpoint,
+    registrations: &Mutex<Vec<Vec<u8>>>,
+    closed: &AtomicBool,
+) -> Option<(Stream, Stream)> {
+    let mut backoff = Duration::from_millis(100);
+    loop {
+        if closed.load(Ordering::Relaxed) {
+            return None;
+        }
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+
+        let result = endpoint.connect().and_then(|mut stream| {
+            for frame in registrations.lock().unwrap().iter() {
+                stream.write_all(frame)?;
+            }
+            let reader = stream.try_clone()?;
+            Ok((stream, reader))
+        });
+        match result {
+            Ok(streams) => return Some(streams),
+            Err(error) => {
+                warn!(?endpoint, ?error, "Couldn't reconnect to the connector.");
+            }
+        }
+    }
+}
+
+/// A [`Channel`] that communicates with the connectors over TCP or Unix domain sockets, allowing
+/// the bot to run on a different machine from the connector. The connector name of each
+/// [`Instrument`] is the connector's [`Endpoint`], such as `tcp://10.0.0.1:5000` or
+/// `unix:///tmp/binancefutures.sock`, and the connector must run in the listener mode on that
+/// endpoint.
+///
+/// The messages are the same bincode-encoded [`LiveRequest`] and [`LiveEvent`] as the iceoryx
+/// channel, framed with a length prefix and the bot ID. When a connection is lost, a
+/// [`ErrorKind::ConnectionInterrupted`] error is delivered and the channel keeps reconnecting in
+/// the background; once reconnected, the instruments are registered again so that the connector
+/// resends their current state.
+pub struct SocketUnifiedChannel {
+    clients: Vec<SocketClient>,
+    /// Maps the instrument number to the index of its client.
+    inst_to_client: Vec<usize>,
+    rx: Receiver<(usize, u64, LiveEvent)>,
+}
+
+impl Channel for SocketUnifiedChannel {
+    fn build<MD, PA>(instruments: &[Instrument<MD, PA>]) -> Result<Self, BuildError>
+    where
+        Self: Sized,
+    {
+        let (tx, rx) = channel();
+        let mut clients: Vec<SocketClient> = Vec::new();
+        let mut client_no: HashMap<String, usize> = HashMap::new();
+        let mut inst_to_client = Vec::with_capacity(instruments.len());
+        for (inst_no, instrument) in instruments.iter().enumerate() {
+            let ch_no = match client_no.entry(instrument.connector_name.clone()) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    let endpoint = Endpoint::parse(&instrument.connector_name).ok_or(
+                        BuildError::InvalidArgument(
+                            "connector name must be `tcp://host:port` or `unix:///path`",
+                        ),
+                    )?;
+                    let client = SocketClient::connect(clients.len(), endpoint, tx.clone())
+                        .map_err(|error| BuildError::Error(anyhow::Error::from(error)))?;
+                    clients.push(client);
+                    *entry.insert(clients.len() - 1)
+                }
+            };
+            if !clients[ch_no].register(inst_no, &instrument.symbol) {
+                return Err(BuildError::Duplicate(
+                    instrument.connector_name.clone(),
+                    instrument.symbol.clone(),
+                ));
+            }
+            inst_to_client.push(ch_no);
+        }
+        if clients.is_empty() {
+            return Err(BuildError::BuilderIncomplete("instruments"));
+        }
+
+        Ok(Self {
+            clients,
+            inst_to_client,
+            rx,
+        })
+    }
+
+    fn recv_timeout(&mut self, id: u64, timeout: Duration) -> Result<(usize, LiveEvent), BotError> {
+        let instant = Instant::now();
+        loop {
+            let remaining = timeout
+                .checked_sub(instant.elapsed())
+                .ok_or(BotError::Timeout)?;
+            let (ch_no, dst_id, ev) = match self.rx.recv_timeout(remaining) {
+                Ok(received) => received,
+                Err(RecvTimeoutError::Timeout) => return Err(BotError::Timeout),
+                Err(RecvTimeoutError::Disconnected) => return Err(BotError::Interrupted),
+            };
+            if dst_id != TO_ALL && dst_id != id {
+                continue;
+            }
+            match &ev {
+                LiveEvent::BatchStart | LiveEvent::BatchEnd | LiveEvent::Error(_) => {
+                    return Ok((0, ev));
+                }
+                LiveEvent::Feed { symbol, .. }
+                | LiveEvent::Order { symbol, .. }
+                | LiveEvent::Position { symbol, .. } => {
+                    if let Some(inst_no) = self.clients[ch_no].symbol_to_inst_no.get(symbol) {
+                        return Ok((*inst_no, ev));
+                    }
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, id: u64, inst_no: usize, request: LiveRequest) -> Result<(), BotError> {
+        let ch_no = *self
+            .inst_to_client
+            .get(inst_no)
+            .ok_or(BotError::InstrumentNotFound)?;
+        self.clients[ch_no]
+            .send(id, &request)
+            .map_err(|err| BotError::Custom(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        depth::HashMapMarketDepth,
+        prelude::HkPriceAction,
+        types::{Event, LOCAL_BID_DEPTH_EVENT},
+    };
+
+    #[test]
+    fn parse_endpoint() {
+        assert_eq!(
+            Endpoint::parse("tcp://127.0.0.1:5000"),
+            Some(Endpoint::Tcp("127.0.0.1:5000".to_string()))
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            Endpoint::parse("unix:///tmp/connector.sock"),
+            Some(Endpoint::Unix(PathBuf::from("/tmp/connector.sock")))
+        );
+        assert_eq!(Endpoint::parse("binancefutures"), None);
+    }
+
+    #[test]
+    fn frame_roundtrip() {
+        let request = LiveRequest::RegisterInstrument {
+            symbol: "btcusdt".to_string(),
+            tick_size: 0.1,
+            lot_size: 0.001,
+        };
+        let frame = encode_frame(7, Some(&request)).unwrap();
+        let mut buf = Vec::new();
+        let (kind, id) = read_frame(&mut frame.as_slice(), &mut buf).unwrap();
+        assert_eq!(kind, FrameKind::Data);
+        assert_eq!(id, 7);
+        let (decoded, _): (LiveRequest, usize) =
+            bincode::decode_from_slice(&buf, config::standard()).unwrap();
+        match decoded {
+            LiveRequest::RegisterInstrument { symbol, .. } => assert_eq!(symbol, "btcusdt"),
+            _ => panic!("unexpected request"),
+        }
+
+        let heartbeat = encode_frame::<()>(TO_ALL, None).unwrap();
+        let (kind, _) = read_frame(&mut heartbeat.as_slice(), &mut buf).unwrap();
+        assert_eq!(kind, FrameKind::Heartbeat);
+        assert!(buf.is_empty());
+    }
+    fn feed(exch_ts: i64) -> LiveEvent {
+        LiveEvent::Feed {
+            symbol: "btcusdt".to_string(),
+            event: Event {
+                ev: LOCAL_BID_DEPTH_EVENT,
+                exch_ts,
+                local_ts: exch_ts,
+                px: 100.0,
+                qty: 1.0,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            },
+        }
+    }
+
+    fn recv_register(receiver: &SocketReceiver<LiveRequest>) -> u64 {
+        match receiver.receive_timeout(Duration::from_secs(5)).unwrap() {
+            Some((id, LiveRequest::RegisterInstrument { symbol, .. })) => {
+                assert_eq!(symbol, "btcusdt");
+                id
+            }
+            _ => panic!("the instrument is not registered"),
+        }
+    }
+
+    fn recv_feed(channel: &mut SocketUnifiedChannel, id: u64) -> i64 {
+        match channel.recv_timeout(id, Duration::from_secs(5)).unwrap() {
+            (0, LiveEvent::Feed { event, .. }) => event.exch_ts,
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    /// Connects a bot to the connector listening on the endpoint, and checks that the events are
+    /// routed by the bot ID and that the bot registers again after the connection is dropped.
+    fn reconnects_and_routes(endpoint: &str) {
+        let (sender, receiver) =
+            listen::<LiveEvent, LiveRequest>(&Endpoint::parse(endpoint).unwrap()).unwrap();
+        let mut channel = SocketUnifiedChannel::build(&[Instrument::new(
+            endpoint,
+            "btcusdt",
+            0.1,
+            1.0,
+            HashMapMarketDepth::new(0.1, 1.0),
+            0,
+            HkPriceAction::new(vec![], vec![]),
+        )])
+        .unwrap();
+
+        let request = LiveRequest::RegisterInstrument {
+            symbol: "btcusdt".to_string(),
+            tick_size: 0.1,
+            lot_size: 1.0,
+        };
+        channel.send(7, 0, request).unwrap();
+        assert_eq!(recv_register(&receiver), 7);
+
+        // The event for another bot isn't sent through this connection.
+        sender.send(8, &feed(1)).unwrap();
+        sender.send(7, &feed(2)).unwrap();
+        sender.send(TO_ALL, &feed(3)).unwrap();
+        assert_eq!(recv_feed(&mut channel, 7), 2);
+        assert_eq!(recv_feed(&mut channel, 7), 3);
+
+        for conn in sender.connections.lock().unwrap().values() {
+            conn.stream.shutdown();
+        }
+        match channel.recv_timeout(7, Duration::from_secs(5)).unwrap() {
+            (0, LiveEvent::Error(error)) => {
+                assert_eq!(error.kind, ErrorKind::ConnectionInterrupted)
+            }
+            _ => panic!("the interruption is not reported"),
+        }
+
+        // The registration is sent again through the new connection, which then routes the events
+        // for the bot.
+        assert_eq!(recv_register(&receiver), 7);
+        sender.send(7, &feed(4)).unwrap();
+        assert_eq!(recv_feed(&mut channel, 7), 4);
+    }
+
+    #[test]
+    fn reconnects_and_routes_over_tcp() {
+        // Finds a free port.
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        reconnects_and_routes(&format!("tcp://127.0.0.1:{port}"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reconnects_and_routes_over_unix() {
+        let path = std::env::temp_dir().join(format!("hftbacktest_{}.sock", std::process::id()));
+        reconnects_and_routes(&format!("unix://{}", path.display()));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use prometheus::{
+    Histogram,
+    HistogramOpts,
+    HistogramVec,
+    IntCounter,
+    IntCounterVec,
+    Opts,
+    Registry,
+    exponential_buckets,
+    labels,
+};
+
+use crate::types::ErrorKind;
+
+/// From 100 microseconds to about 3.3 seconds.
+fn latency_buckets() -> Vec<f64> {
+    exponential_buckets(0.0001, 2.0, 16).unwrap()
+}
+
+struct InstrumentMetrics {
+    feed_latency: Histogram,
+    order_ack_latency: Histogram,
+    order_requests: IntCounter,
+    rejected_orders: IntCounter,
+}
+
+/// Collects the metrics of a [`LiveBot`](crate::live::LiveBot). See
+/// [`LiveBotBuilder::metrics`](crate::live::LiveBotBuilder::metrics) for the list of metrics.
+pub(crate) struct BotMetrics {
+    instruments: Vec<InstrumentMetrics>,
+    errors: IntCounterVec,
+}
+
+impl BotMetrics {
+    /// `instruments` is the list of `(connector_name, symbol)` in the order of the instrument
+    /// number.
+    pub fn new(
+        registry: &Registry,
+        bot_id: u64,
+        instruments: &[(&str, &str)],
+    ) -> prometheus::Result<Self> {
+        let const_labels = labels! { "bot".to_string() => bot_id.to_string() };
+        let inst_labels = ["connector", "symbol"];
+
+        let feed_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "hftbacktest_bot_feed_latency_seconds",
+                "Feed latency between the exchange and the local.",
+            )
+            .const_labels(const_labels.clone())
+            .buckets(latency_buckets()),
+            &inst_labels,
+        )?;
+        let order_ack_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "hftbacktest_bot_order_ack_latency_seconds",
+                "Time from sending an order request until receiving its first response.",
+            )
+            .const_labels(const_labels.clone())
+            .buckets(latency_buckets()),
+            &inst_labels,
+        )?;
+        let order_requests = IntCounterVec::new(
+            Opts::new(
+                "hftbacktest_bot_order_requests_total",
+                "Number of order requests.",
+            )
+            .const_labels(const_labels.clone()),
+            &inst_labels,
+        )?;
+        let rejected_orders = IntCounterVec::new(
+            Opts::new(
+                "hftbacktest_bot_rejected_orders_total",
+                "Number of orders rejected or expired before being acknowledged.",
+            )
+            .const_labels(const_labels.clone()),
+            &inst_labels,
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new(
+                "hftbacktest_bot_errors_total",
+                "Number of errors from the connectors.",
+            )
+            .const_labels(const_labels),
+            &["kind"],
+        )?;
+        registry.register(Box::new(feed_latency.clone()))?;
+        registry.register(Box::new(order_ack_latency.clone()))?;
+        registry.register(Box::new(order_requests.clone()))?;
+        registry.register(Box::new(rejected_orders.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+
+        let instruments = instruments
+            .iter()
+            .map(|(connector, symbol)| {
+                let values = [*connector, *symbol];
+                InstrumentMetrics {
+                    feed_latency: feed_latency.with_label_values(&values),
+                    order_ack_latency: order_ack_latency.with_label_values(&values),
+                    order_requests: order_requests.with_label_values(&values),
+                    rejected_orders: rejected_orders.with_label_values(&values),
+                }
+            })
+            .collect();
+        Ok(Self {
+            instruments,
+            errors,
+        })
+    }
+
+    pub fn on_feed(&self, inst_no: usize, exch_ts: i64, local_ts: i64) {
+        if let Some(inst) = self.instruments.get(inst_no) {
+            inst.feed_latency
+                .observe((local_ts - exch_ts) as f64 / 1_000_000_000.0);
+        }
+    }
+
+    pub fn on_order_request(&self, inst_no: usize) {
+        if let Some(inst) = self.instruments.get(inst_no) {
+            inst.order_requests.inc();
+        }
+    }
+
+    /// Records the response to the order request sent at `req_ts`.
+    pub fn on_order_response(&self, inst_no: usize, req_ts: i64, resp_ts: i64, rejected: bool) {
+        if let Some(inst) = self.instruments.get(inst_no) {
+            inst.order_ack_latency
+                .observe((resp_ts - req_ts) as f64 / 1_000_000_000.0);
+            if rejected {
+                inst.rejected_orders.inc();
+            }
+        }
+    }
+
+    pub fn on_error(&self, kind: &ErrorKind) {
+        let kind = match kind {
+            ErrorKind::ConnectionInterrupted => "connection_interrupted".to_string(),
+            ErrorKind::CriticalConnectionError => "critical_connection_error".to_string(),
+            ErrorKind::OrderError => "order_error".to_string(),
+            ErrorKind::StateRepaired => "state_repaired".to_string(),
+            ErrorKind::DepthResync => "depth_resync".to_string(),
+            ErrorKind::Custom(code) => format!("custom_{code}"),
+        };
+        self.errors.with_label_values(&[kind.as_str()]).inc();
+    }
+}
@@ -1,16 +1,26 @@
 use std::collections::HashMap;
 
 pub use bot::{BotError, LiveBot, LiveBotBuilder};
+#[cfg(test)]
+pub(crate) use bot::tests::{SCRIPT, ScriptedChannel};
+#[cfg(feature = "backtest")]
+pub use capture::{PositionRecord, SessionCapture};
 pub use recorder::LoggingRecorder;
 
 use crate::{
     prelude::StateValues,
     types::{Event, Order, OrderId},
+    utils::UpdatedOrders,
 };
 
 mod bot;
+#[cfg(feature = "backtest")]
+mod capture;
 pub mod ipc;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod recorder;
+mod timer;
 
 /// Provides asset information for internal use.
 pub struct Instrument<MD,PA> {
@@ -21,6 +31,7 @@ pub struct Instrument<MD,PA> {
     depth: MD,
     last_trades: Vec<Event>,
     orders: HashMap<OrderId, Order>,
+    updated_orders: UpdatedOrders,
     last_feed_latency: Option<(i64, i64)>,
     last_order_latency: Option<(i64, i64, i64)>,
     state: StateValues,
@@ -53,10 +64,17 @@ impl<MD,PA> Instrument<MD,PA> {
             depth,
             last_trades: Vec::with_capacity(last_trades_capacity),
             orders: Default::default(),
+            updated_orders: Default::default(),
             last_feed_latency: None,
             last_order_latency: None,
             state: Default::default(),
             price_action,
         }
     }
+
+    fn clear_inactive_orders(&mut self) {
+        self.orders.retain(|_, order| order.active());
+        self.updated_orders
+            .retain(|order_id| self.orders.contains_key(order_id));
+    }
 }
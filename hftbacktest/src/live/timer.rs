@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+/// The time span of a slot, in nanoseconds.
+const TICK: i64 = 1_000_000;
+
+/// The number of slots, covering about half a second per revolution.
+const NUM_SLOTS: i64 = 512;
+
+/// A hashed timer wheel managing the user timers of a live bot.
+///
+/// Each timer is placed in the slot of its deadline's tick, and the slots are visited as the time
+/// advances. A timer whose deadline is more than a revolution away stays in its slot until the
+/// revolution in which it is due. Canceled and rescheduled timers are removed lazily.
+pub struct TimerWheel {
+    slots: Vec<Vec<(i64, u64)>>,
+    scheduled: HashMap<u64, i64>,
+    // The tick from which the slots haven't been fully visited.
+    cur_tick: i64,
+    expired: Vec<u64>,
+}
+
+impl TimerWheel {
+    /// Constructs a `TimerWheel` starting from the timestamp.
+    pub fn new(timestamp: i64) -> Self {
+        Self {
+            slots: (0..NUM_SLOTS).map(|_| Vec::new()).collect(),
+            scheduled: HashMap::new(),
+            cur_tick: timestamp / TICK,
+            expired: Vec::new(),
+        }
+    }
+
+    fn slot_no(tick: i64) -> usize {
+        tick.rem_euclid(NUM_SLOTS) as usize
+    }
+
+    /// Schedules the timer at the timestamp, replacing the one with the same ID, if any.
+    pub fn schedule(&mut self, timestamp: i64, timer_id: u64) {
+        // A timer in the past is placed in the current slot to expire at the next advance.
+        let tick = (timestamp / TICK).max(self.cur_tick);
+        if self.scheduled.insert(timer_id, timestamp) == Some(timestamp) {
+            return;
+        }
+        self.slots[Self::slot_no(tick)].push((timestamp, timer_id));
+    }
+
+    /// Cancels the timer. Returns `false` if there is no such timer scheduled.
+    pub fn cancel(&mut self, timer_id: u64) -> bool {
+        self.scheduled.remove(&timer_id).is_some()
+    }
+
+    /// Returns `true` if the entry is still scheduled, rather than canceled or rescheduled.
+    fn is_live(scheduled: &HashMap<u64, i64>, timestamp: i64, timer_id: u64) -> bool {
+        scheduled.get(&timer_id) == Some(&timestamp)
+    }
+
+    /// Expires the timers due by the timestamp. Returns `true` if any timer expires.
+    pub fn advance(&mut self, timestamp: i64) -> bool {
+        if self.scheduled.is_empty() {
+            self.cur_tick = self.cur_tick.max(timestamp / TICK);
+            return false;
+        }
+        let tick = timestamp / TICK;
+        // Visits every slot at most once, even if more than a revolution has passed.
+        let from = self.cur_tick.max(tick - NUM_SLOTS + 1);
+        let mut expired = Vec::new();
+        for tick in from..=tick {
+            let scheduled = &mut self.scheduled;
+            self.slots[Self::slot_no(tick)].retain(|&(deadline, timer_id)| {
+                if !Self::is_live(scheduled, deadline, timer_id) {
+                    false
+                } else if deadline <= timestamp {
+                    scheduled.remove(&timer_id);
+                    expired.push((deadline, timer_id));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        // The slot of the current tick can still hold timers due later within the tick.
+        self.cur_tick = self.cur_tick.max(tick);
+        expired.sort_unstable();
+        self.expired.extend(expired.iter().map(|(_, timer_id)| *timer_id));
+        !expired.is_empty()
+    }
+
+    /// Returns the earliest deadline of the scheduled timers, looking up to a revolution ahead. If
+    /// there is no timer due within the revolution, returns the end of the revolution so that the
+    /// wheel keeps turning.
+    pub fn next_deadline(&self) -> Option<i64> {
+        if self.scheduled.is_empty() {
+            return None;
+        }
+        for tick in self.cur_tick..(self.cur_tick + NUM_SLOTS) {
+            let deadline = self.slots[Self::slot_no(tick)]
+                .iter()
+                .filter(|(deadline, timer_id)| {
+                    // Timers of the later revolutions are skipped.
+                    *deadline < (tick + 1) * TICK
+                        && Self::is_live(&self.scheduled, *deadline, *timer_id)
+                })
+                .map(|(deadline, _)| *deadline)
+                .min();
+            if deadline.is_some() {
+                return deadline;
+            }
+        }
+        Some((self.cur_tick + NUM_SLOTS) * TICK)
+    }
+
+    /// Returns the IDs of the expired timers in the order of expiration.
+    pub fn expired(&self) -> &[u64] {
+        &self.expired
+    }
+
+    /// Clears the expired timers.
+    pub fn clear_expired(&mut self) {
+        self.expired.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::live::timer::{NUM_SLOTS, TICK, TimerWheel};
+
+    #[test]
+    fn expire_in_order() {
+        let start = 1_000 * TICK;
+        let mut wheel = TimerWheel::new(start);
+        wheel.schedule(start + 3 * TICK + 10, 1);
+        wheel.schedule(start + 2 * TICK, 2);
+        // More than a revolution ahead.
+        wheel.schedule(start + 2000 * TICK, 3);
+        wheel.schedule(start + 5 * TICK, 4);
+        assert!(wheel.cancel(4));
+        wheel.schedule(start + 4 * TICK, 1);
+
+        assert_eq!(wheel.next_deadline(), Some(start + 2 * TICK));
+        assert!(!wheel.advance(start + TICK));
+        assert!(wheel.advance(start + 4 * TICK));
+        assert_eq!(wheel.expired(), &[2, 1]);
+        wheel.clear_expired();
+
+        assert!(!wheel.advance(start + 1000 * TICK));
+        assert!(wheel.expired().is_empty());
+        // No timer is due within the revolution.
+        assert_eq!(
+            wheel.next_deadline(),
+            Some(start + (1000 + NUM_SLOTS) * TICK)
+        );
+        assert!(wheel.advance(start + 2000 * TICK));
+        assert_eq!(wheel.expired(), &[3]);
+        assert_eq!(wheel.next_deadline(), None);
+    }
+}
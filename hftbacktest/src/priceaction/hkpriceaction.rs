@@ -2,12 +2,20 @@ use std::{
     any::Any, collections::HashMap, fmt::{Debug, Formatter}, hash::Hash, ops::Div, time::{Duration, SystemTime, UNIX_EPOCH}
 };
 
-use crate::types::Side;
+use bincode::{Decode, Encode};
+
+use crate::{
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+    },
+    types::Side,
+};
 
 use super::{nanos_to_ymdhms, KLine, PriceAction, Swings,Imbalance};
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Decode, Encode)]
 pub struct TickFlows {
     pub buy_tick_qtys: HashMap<i64, f64>,
     pub sell_tick_qtys: HashMap<i64, f64>,
@@ -84,7 +92,7 @@ impl TickFlows {
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Decode, Encode)]
 pub struct HkPriceAction {
     // pub klines: HashMap<i64, Vec<KLine>>,
     pub kmaps: HashMap<i64, HashMap<i64, KLine>>,
@@ -515,4 +523,13 @@ impl PriceAction for HkPriceAction{
     fn last_acc_trades(&self) -> (i64, f64, i64, Side){
         (self.last_tick, self.last_tick_qty, self.last_tick_time, self.last_side)
     }
+
+    fn save_checkpoint(&self, writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        writer.write(self)
+    }
+
+    fn restore_checkpoint(&mut self, reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        *self = reader.read()?;
+        Ok(())
+    }
 }
\ No newline at end of file
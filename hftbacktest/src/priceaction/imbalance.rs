@@ -1,8 +1,10 @@
 use std::{collections::HashMap, ops::{Add, Sub}};
 
+use bincode::{Decode, Encode};
+
 use crate::types::Side;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Decode, Encode)]
 pub struct Imbalance {
     pub ticks: Vec<i64>,
     pub buy_qtys: HashMap<i64, f64>,
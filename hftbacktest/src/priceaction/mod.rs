@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use bincode::{Decode, Encode};
 use chrono::{Datelike, NaiveDateTime, TimeZone, Timelike, Utc};
 use chrono_tz::Asia::Shanghai;
 
@@ -7,12 +8,18 @@ use chrono_tz::Asia::Shanghai;
 
 pub use hkpriceaction::HkPriceAction;
 
-use crate::types::Side;
+use crate::{
+    backtest::{
+        BacktestError,
+        checkpoint::{CheckpointReader, CheckpointWriter},
+    },
+    types::Side,
+};
 pub mod hkpriceaction;
 mod imbalance;
 pub use imbalance::Imbalance;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Decode, Encode)]
 pub struct KLine {
     pub open_tick: i64,
     pub high_tick: i64,
@@ -106,7 +113,7 @@ impl KLine {
 // 其它K线，忽略，直到有明确的多或空
 // 找到第一根后，赋值cur_tick,cur_opentime,high_or_low
 // 每根K线结束，判断high_or_low方向是否有新高或新低，如果没有，judge_time+1，如果有，judge_time=0，更新cur_tick,cur_opentime,high_or_low
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Decode, Encode)]
 pub struct Swings {
     swing_hights: Vec<(i64, i64)>,//opentime,price_tick
     swing_lows: Vec<(i64, i64)>,//opentime,price_tick   
@@ -126,6 +133,16 @@ pub trait PriceAction {
     fn swings(&self, nums:usize) -> Vec<(i64, i64)>;
     fn kmaps(&self, intevrval:i64, nums:usize) -> (HashMap<i64, &KLine>, i64);
     fn last_acc_trades(&self) -> (i64, f64, i64, Side);
+
+    /// Writes the price action state into the backtest checkpoint.
+    fn save_checkpoint(&self, _writer: &mut CheckpointWriter) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the price action"))
+    }
+
+    /// Restores the price action state from the backtest checkpoint.
+    fn restore_checkpoint(&mut self, _reader: &mut CheckpointReader) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("the price action"))
+    }
 }
 
 #[derive(Debug, Clone)]
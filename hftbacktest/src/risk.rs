@@ -0,0 +1,1020 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+#[cfg(feature = "backtest")]
+use crate::backtest::assettype::AssetType;
+use crate::{
+    depth::MarketDepth,
+    prelude::PriceAction,
+    types::{
+        Bot,
+        Event,
+        OrdType,
+        Order,
+        OrderId,
+        OrderRequest,
+        Side,
+        StateValues,
+        Status,
+        TimeInForce,
+    },
+};
+
+const ONE_SECOND: i64 = 1_000_000_000;
+const ONE_DAY: i64 = 86_400_000_000_000;
+
+/// Rejection reasons raised by [`RiskManager`].
+#[derive(Error, Clone, PartialEq, Debug)]
+pub enum RiskError {
+    #[error("the position would be {position}, exceeding the limit {limit}")]
+    MaxPosition { position: f64, limit: f64 },
+    #[error("the notional would be {notional}, exceeding the limit {limit}")]
+    MaxNotional { notional: f64, limit: f64 },
+    #[error("{open_orders} orders are already open, reaching the limit {limit}")]
+    MaxOpenOrders { open_orders: usize, limit: usize },
+    #[error("{limit} orders have already been sent within the last second")]
+    MaxOrderRate { limit: usize },
+    #[error("the price {price} deviates from the mid-price {mid} by more than {band}")]
+    PriceBand { price: f64, mid: f64, band: f64 },
+    #[error("the mid-price is not available")]
+    NoReferencePrice,
+    #[error("the daily loss {loss} has reached the limit {limit}")]
+    DailyLossLimit { loss: f64, limit: f64 },
+    #[error("the order {order_id} is not found")]
+    OrderNotFound { order_id: OrderId },
+}
+
+/// Errors returned by [`RiskManager`].
+#[derive(Error, Debug)]
+pub enum RiskManagerError<E> {
+    /// The order is rejected by a pre-trade risk check and is not sent.
+    #[error("Rejected: {0}")]
+    Rejected(RiskError),
+    /// An error from the underlying bot.
+    #[error("{0}")]
+    Bot(E),
+}
+
+/// Computes the value amount from the price and the quantity.
+type Amount = dyn Fn(f64, f64) -> f64 + Send + Sync;
+
+/// Computes the equity from the mid-price and the state values.
+type Equity = dyn Fn(f64, &StateValues) -> f64 + Send + Sync;
+
+#[derive(Clone)]
+struct Valuation {
+    amount: Arc<Amount>,
+    equity: Arc<Equity>,
+}
+
+impl Debug for Valuation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Valuation")
+    }
+}
+
+/// Pre-trade risk limits of an asset. All limits are disabled by default.
+#[derive(Clone, Debug, Default)]
+pub struct RiskLimits {
+    max_position: Option<f64>,
+    max_notional: Option<f64>,
+    max_open_orders: Option<usize>,
+    max_order_rate: Option<usize>,
+    price_band: Option<f64>,
+    max_daily_loss: Option<f64>,
+    valuation: Option<Valuation>,
+}
+
+impl RiskLimits {
+    /// Constructs `RiskLimits` with all limits disabled.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum absolute position, assuming that all open orders on the same side and
+    /// the new order are filled.
+    pub fn max_position(self, max_position: f64) -> Self {
+        Self {
+            max_position: Some(max_position),
+            ..self
+        }
+    }
+
+    /// Sets the maximum absolute notional value of the position, assuming that all open orders on
+    /// the same side and the new order are filled, valued at the order price.
+    pub fn max_notional(self, max_notional: f64) -> Self {
+        Self {
+            max_notional: Some(max_notional),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of open orders.
+    pub fn max_open_orders(self, max_open_orders: usize) -> Self {
+        Self {
+            max_open_orders: Some(max_open_orders),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of order submissions and modifications per second.
+    pub fn max_order_rate(self, max_order_rate: usize) -> Self {
+        Self {
+            max_order_rate: Some(max_order_rate),
+            ..self
+        }
+    }
+
+    /// Sets the maximum deviation of a limit order's price from the mid-price, as a ratio of the
+    /// mid-price. For example, `0.01` allows prices within 1% of the mid-price.
+    pub fn price_band(self, price_band: f64) -> Self {
+        Self {
+            price_band: Some(price_band),
+            ..self
+        }
+    }
+
+    /// Sets the maximum loss within a UTC day. Once reached, all new orders are rejected until
+    /// the next day. The loss is measured from the equity at the first order of the day, where the
+    /// equity is computed at the mid-price from the fills in the order updates, rather than from
+    /// [`StateValues`], which a live bot doesn't provide. This way, the limit works the same in
+    /// backtesting and live trading. Fees are not included. Unless the asset type is set by
+    /// [`RiskLimits::asset_type`], the equity is computed as the one of a linear asset with a
+    /// contract size of 1.
+    pub fn max_daily_loss(self, max_daily_loss: f64) -> Self {
+        Self {
+            max_daily_loss: Some(max_daily_loss),
+            ..self
+        }
+    }
+
+    /// Sets the asset type by which the equity is computed for the daily loss limit. This should
+    /// be the same as the one the asset is backtested with.
+    #[cfg(feature = "backtest")]
+    pub fn asset_type<AT>(self, asset_type: AT) -> Self
+    where
+        AT: AssetType + Send + Sync + 'static,
+    {
+        let asset_type = Arc::new(asset_type);
+        let amount = asset_type.clone();
+        Self {
+            valuation: Some(Valuation {
+                amount: Arc::new(move |price, qty| amount.amount(price, qty)),
+                equity: Arc::new(move |mid, state_values| {
+                    asset_type.equity(
+                        mid,
+                        state_values.balance,
+                        state_values.position,
+                        state_values.fee,
+                    )
+                }),
+            }),
+            ..self
+        }
+    }
+
+    fn amount(&self, price: f64, qty: f64) -> f64 {
+        match &self.valuation {
+            Some(valuation) => (valuation.amount)(price, qty),
+            None => price * qty,
+        }
+    }
+
+    fn equity(&self, mid: f64, state_values: &StateValues) -> f64 {
+        match &self.valuation {
+            Some(valuation) => (valuation.equity)(mid, state_values),
+            None => state_values.balance + state_values.position * mid - state_values.fee,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RiskState {
+    order_timestamps: VecDeque<i64>,
+    day: i64,
+    day_start_equity: Option<f64>,
+    /// The loss at which the kill switch is triggered.
+    killed: Option<f64>,
+    /// The position and the balance accumulated from the fills, by which the daily loss is
+    /// measured.
+    fills: StateValues,
+    /// The leaves quantity of each order as of the last order update, by which fills are detected.
+    leaves_qty: HashMap<OrderId, f64>,
+}
+
+/// Wraps a [`Bot`] and enforces [`RiskLimits`] before order submissions and modifications are
+/// passed to the underlying bot. Since the checks rely only on the [`Bot`] interface, they
+/// behave identically in backtesting and live trading, so the limits can be tested offline.
+///
+/// Cancellations are never rejected.
+///
+/// ```ignore
+/// let mut hbt = RiskManager::new(
+///     hbt,
+///     RiskLimits::new()
+///         .max_position(10.0)
+///         .max_open_orders(20)
+///         .max_order_rate(10)
+///         .price_band(0.01),
+/// );
+/// ```
+pub struct RiskManager<B> {
+    bot: B,
+    limits: Vec<RiskLimits>,
+    states: Vec<RiskState>,
+}
+
+impl<B> RiskManager<B> {
+    /// Constructs a `RiskManager` that applies the given limits to all assets.
+    pub fn new<MD, PA>(bot: B, limits: RiskLimits) -> Self
+    where
+        B: Bot<MD, PA>,
+        MD: MarketDepth,
+        PA: PriceAction,
+    {
+        let num_assets = bot.num_assets();
+        let states = (0..num_assets)
+            .map(|asset_no| RiskState {
+                // The position held before is valued from the start, without its cost.
+                fills: StateValues {
+                    position: bot.position(asset_no),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect();
+        Self {
+            bot,
+            limits: vec![limits; num_assets],
+            states,
+        }
+    }
+
+    /// Overrides the limits of the given asset.
+    pub fn asset_limits(mut self, asset_no: usize, limits: RiskLimits) -> Self {
+        self.limits[asset_no] = limits;
+        self
+    }
+
+    /// Returns whether the daily loss limit of the given asset is reached.
+    pub fn is_killed(&self, asset_no: usize) -> bool {
+        self.states[asset_no].killed.is_some()
+    }
+
+    /// Releases the daily loss kill switch of the given asset. The loss is measured again from
+    /// the current equity.
+    pub fn reset_kill_switch(&mut self, asset_no: usize) {
+        let state = &mut self.states[asset_no];
+        state.killed = None;
+        state.day_start_equity = None;
+    }
+
+    /// Returns the underlying bot.
+    pub fn into_inner(self) -> B {
+        self.bot
+    }
+
+    /// Applies the fills in the updated orders of all assets with the daily loss limit. It must be
+    /// called before the updated orders are cleared, and after every call that processes order
+    /// responses.
+    fn apply_fills<MD, PA>(&mut self)
+    where
+        B: Bot<MD, PA>,
+        MD: MarketDepth,
+        PA: PriceAction,
+    {
+        for (asset_no, (limits, state)) in self.limits.iter().zip(&mut self.states).enumerate() {
+            if limits.max_daily_loss.is_none() {
+                continue;
+            }
+            let orders = self.bot.orders(asset_no);
+            for order_id in self.bot.updated_orders(asset_no) {
+                let Some(order) = orders.get(order_id) else {
+                    continue;
+                };
+                let prev_leaves_qty = state.leaves_qty.insert(*order_id, order.leaves_qty);
+                // Only the last fill is kept in the order if it is filled more than once before
+                // this is called, so the filled quantity is taken from the change in the leaves
+                // quantity instead.
+                let exec_qty = prev_leaves_qty.unwrap_or(order.qty) - order.leaves_qty;
+                let filled =
+                    order.status == Status::Filled || order.status == Status::PartiallyFilled;
+                if filled && order.exec_qty > 0.0 && exec_qty > 0.0 {
+                    let amount = limits.amount(order.exec_price(), exec_qty);
+                    let sign = *AsRef::<f64>::as_ref(&order.side);
+                    state.fills.position += exec_qty * sign;
+                    state.fills.balance -= amount * sign;
+                    state.fills.num_trades += 1;
+                    state.fills.trading_volume += exec_qty;
+                    state.fills.trading_value += amount;
+                }
+            }
+            state
+                .leaves_qty
+                .retain(|order_id, _| orders.contains_key(order_id));
+        }
+    }
+
+    fn check<MD, PA>(
+        &mut self,
+        asset_no: usize,
+        side: Side,
+        price: f64,
+        qty: f64,
+        order_type: OrdType,
+        replacing: Option<OrderId>,
+    ) -> Result<(), RiskError>
+    where
+        B: Bot<MD, PA>,
+        MD: MarketDepth,
+        PA: PriceAction,
+    {
+        if asset_no >= self.limits.len() {
+            // Lets the underlying bot report the invalid asset number.
+            return Ok(());
+        }
+        self.apply_fills::<MD, PA>();
+        let limits = &self.limits[asset_no];
+        let state = &mut self.states[asset_no];
+        let timestamp = self.bot.current_timestamp();
+        let depth = self.bot.depth(asset_no);
+        let mid = (depth.best_bid() + depth.best_ask()) / 2.0;
+
+        if let Some(limit) = limits.max_daily_loss {
+            let day = timestamp.div_euclid(ONE_DAY);
+            if state.day != day {
+                state.day = day;
+                state.day_start_equity = None;
+                state.killed = None;
+            }
+            if state.killed.is_none() && mid.is_finite() {
+                let equity = limits.equity(mid, &state.fills);
+                let loss = *state.day_start_equity.get_or_insert(equity) - equity;
+                if loss >= limit {
+                    state.killed = Some(loss);
+                }
+            }
+            if let Some(loss) = state.killed {
+                return Err(RiskError::DailyLossLimit { loss, limit });
+            }
+        }
+
+        if let Some(band) = limits.price_band {
+            if order_type == OrdType::Limit {
+                if !mid.is_finite() {
+                    return Err(RiskError::NoReferencePrice);
+                }
+                if (price - mid).abs() > band * mid {
+                    return Err(RiskError::PriceBand { price, mid, band });
+                }
+            }
+        }
+
+        let orders = self.bot.orders(asset_no);
+        if let Some(limit) = limits.max_open_orders {
+            if replacing.is_none() {
+                let open_orders = orders.values().filter(|order| order.active()).count();
+                if open_orders >= limit {
+                    return Err(RiskError::MaxOpenOrders { open_orders, limit });
+                }
+            }
+        }
+
+        if limits.max_position.is_some() || limits.max_notional.is_some() {
+            let open_qty: f64 = orders
+                .values()
+                .filter(|order| {
+                    order.side == side && order.active() && Some(order.order_id) != replacing
+                })
+                .map(|order| order.leaves_qty)
+                .sum();
+            let position = match side {
+                Side::Buy => self.bot.position(asset_no) + open_qty + qty,
+                _ => self.bot.position(asset_no) - open_qty - qty,
+            };
+            if let Some(limit) = limits.max_position {
+                if position.abs() > limit {
+                    return Err(RiskError::MaxPosition { position, limit });
+                }
+            }
+            if let Some(limit) = limits.max_notional {
+                let price = if order_type == OrdType::Market || price <= 0.0 {
+                    mid
+                } else {
+                    price
+                };
+                if !price.is_finite() {
+                    return Err(RiskError::NoReferencePrice);
+                }
+                let notional = position.abs() * price;
+                if notional > limit {
+                    return Err(RiskError::MaxNotional { notional, limit });
+                }
+            }
+        }
+
+        if let Some(limit) = limits.max_order_rate {
+            while state
+                .order_timestamps
+                .front()
+                .is_some_and(|ts| *ts <= timestamp - ONE_SECOND)
+            {
+                state.order_timestamps.pop_front();
+            }
+            if state.order_timestamps.len() >= limit {
+                return Err(RiskError::MaxOrderRate { limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts the order request sent at the timestamp towards the order rate limit, only if the
+    /// underlying bot accepts it.
+    fn record_order<T, E>(
+        &mut self,
+        asset_no: usize,
+        timestamp: i64,
+        result: Result<T, E>,
+    ) -> Result<T, RiskManagerError<E>> {
+        let value = result.map_err(RiskManagerError::Bot)?;
+        let rate_limited = self
+            .limits
+            .get(asset_no)
+            .is_some_and(|limits| limits.max_order_rate.is_some());
+        if rate_limited {
+            self.states[asset_no].order_timestamps.push_back(timestamp);
+        }
+        Ok(value)
+    }
+}
+
+impl<B, MD, PA> Bot<MD, PA> for RiskManager<B>
+where
+    B: Bot<MD, PA>,
+    MD: MarketDepth,
+    PA: PriceAction,
+{
+    type Error = RiskManagerError<B::Error>;
+
+    #[inline]
+    fn current_timestamp(&self) -> i64 {
+        self.bot.current_timestamp()
+    }
+
+    #[inline]
+    fn num_assets(&self) -> usize {
+        self.bot.num_assets()
+    }
+
+    #[inline]
+    fn position(&self, asset_no: usize) -> f64 {
+        self.bot.position(asset_no)
+    }
+
+    #[inline]
+    fn state_values(&self, asset_no: usize) -> &StateValues {
+        self.bot.state_values(asset_no)
+    }
+
+    #[inline]
+    fn depth(&self, asset_no: usize) -> &MD {
+        self.bot.depth(asset_no)
+    }
+
+    #[inline]
+    fn price_action(&self, asset_no: usize) -> &PA {
+        self.bot.price_action(asset_no)
+    }
+
+    #[inline]
+    fn last_trades(&self, asset_no: usize) -> &[Event] {
+        self.bot.last_trades(asset_no)
+    }
+
+    #[inline]
+    fn clear_last_trades(&mut self, asset_no: Option<usize>) {
+        self.bot.clear_last_trades(asset_no)
+    }
+
+    #[inline]
+    fn orders(&self, asset_no: usize) -> &HashMap<OrderId, Order> {
+        self.bot.orders(asset_no)
+    }
+
+    #[inline]
+    fn updated_orders(&self, asset_no: usize) -> &[OrderId] {
+        self.bot.updated_orders(asset_no)
+    }
+
+    #[inline]
+    fn clear_updated_orders(&mut self, asset_no: Option<usize>) {
+        self.apply_fills::<MD, PA>();
+        self.bot.clear_updated_orders(asset_no)
+    }
+
+    fn submit_buy_order(
+        &mut self,
+        asset_no: usize,
+        order_id: OrderId,
+        price: f64,
+        qty: f64,
+        time_in_force: TimeInForce,
+        order_type: OrdType,
+        wait: bool,
+    ) -> Result<bool, Self::Error> {
+        self.check(asset_no, Side::Buy, price, qty, order_type, None)
+            .map_err(RiskManagerError::Rejected)?;
+        let timestamp = self.bot.current_timestamp();
+        let result = self.bot.submit_buy_order(
+            asset_no,
+            order_id,
+            price,
+            qty,
+            time_in_force,
+            order_type,
+            wait,
+        );
+        self.record_order(asset_no, timestamp, result)
+    }
+
+    fn submit_sell_order(
+        &mut self,
+        asset_no: usize,
+        order_id: OrderId,
+        price: f64,
+        qty: f64,
+        time_in_force: TimeInForce,
+        order_type: OrdType,
+        wait: bool,
+    ) -> Result<bool, Self::Error> {
+        self.check(asset_no, Side::Sell, price, qty, order_type, None)
+            .map_err(RiskManagerError::Rejected)?;
+        let timestamp = self.bot.current_timestamp();
+        let result = self.bot.submit_sell_order(
+            asset_no,
+            order_id,
+            price,
+            qty,
+            time_in_force,
+            order_type,
+            wait,
+        );
+        self.record_order(asset_no, timestamp, result)
+    }
+
+    fn submit_order(
+        &mut self,
+        asset_no: usize,
+        order: OrderRequest,
+        wait: bool,
+    ) -> Result<bool, Self::Error> {
+        self.check(
+            asset_no,
+            order.side,
+            order.price,
+            order.qty,
+            order.order_type,
+            None,
+        )
+        .map_err(RiskManagerError::Rejected)?;
+        let timestamp = self.bot.current_timestamp();
+        let result = self.bot.submit_order(asset_no, order, wait);
+        self.record_order(asset_no, timestamp, result)
+    }
+
+    fn modify(
+        &mut self,
+        asset_no: usize,
+        order_id: OrderId,
+        price: f64,
+        qty: f64,
+        wait: bool,
+    ) -> Result<bool, Self::Error> {
+        if asset_no < self.limits.len() {
+            let Some(order) = self.bot.orders(asset_no).get(&order_id) else {
+                return Err(RiskManagerError::Rejected(RiskError::OrderNotFound {
+                    order_id,
+                }));
+            };
+            let side = order.side;
+            let order_type = order.order_type;
+            self.check(asset_no, side, price, qty, order_type, Some(order_id))
+                .map_err(RiskManagerError::Rejected)?;
+        }
+        let timestamp = self.bot.current_timestamp();
+        let result = self.bot.modify(asset_no, order_id, price, qty, wait);
+        self.record_order(asset_no, timestamp, result)
+    }
+
+    #[inline]
+    fn cancel(
+        &mut self,
+        asset_no: usize,
+        order_id: OrderId,
+        wait: bool,
+    ) -> Result<bool, Self::Error> {
+        self.bot
+            .cancel(asset_no, order_id, wait)
+            .map_err(RiskManagerError::Bot)
+    }
+
+    #[inline]
+    fn clear_inactive_orders(&mut self, asset_no: Option<usize>) {
+        self.apply_fills::<MD, PA>();
+        self.bot.clear_inactive_orders(asset_no)
+    }
+
+    fn wait_order_response(
+        &mut self,
+        asset_no: usize,
+        order_id: OrderId,
+        timeout: i64,
+    ) -> Result<bool, Self::Error> {
+        let result = self.bot.wait_order_response(asset_no, order_id, timeout);
+        self.apply_fills::<MD, PA>();
+        result.map_err(RiskManagerError::Bot)
+    }
+
+    fn wait_next_feed(
+        &mut self,
+        include_order_resp: bool,
+        timeout: i64,
+    ) -> Result<bool, Self::Error> {
+        let result = self.bot.wait_next_feed(include_order_resp, timeout);
+        self.apply_fills::<MD, PA>();
+        result.map_err(RiskManagerError::Bot)
+    }
+
+    #[inline]
+    fn schedule_at(&mut self, timestamp: i64, timer_id: u64) {
+        self.bot.schedule_at(timestamp, timer_id);
+    }
+
+    #[inline]
+    fn schedule_after(&mut self, duration: i64, timer_id: u64) {
+        self.bot.schedule_after(duration, timer_id);
+    }
+
+    #[inline]
+    fn cancel_timer(&mut self, timer_id: u64) -> bool {
+        self.bot.cancel_timer(timer_id)
+    }
+
+    #[inline]
+    fn expired_timers(&self) -> &[u64] {
+        self.bot.expired_timers()
+    }
+
+    #[inline]
+    fn clear_expired_timers(&mut self) {
+        self.bot.clear_expired_timers();
+    }
+
+    fn elapse(&mut self, duration: i64) -> Result<bool, Self::Error> {
+        let result = self.bot.elapse(duration);
+        self.apply_fills::<MD, PA>();
+        result.map_err(RiskManagerError::Bot)
+    }
+
+    fn elapse_bt(&mut self, duration: i64) -> Result<bool, Self::Error> {
+        let result = self.bot.elapse_bt(duration);
+        self.apply_fills::<MD, PA>();
+        result.map_err(RiskManagerError::Bot)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Self::Error> {
+        self.bot.close().map_err(RiskManagerError::Bot)
+    }
+
+    #[inline]
+    fn feed_latency(&self, asset_no: usize) -> Option<(i64, i64)> {
+        self.bot.feed_latency(asset_no)
+    }
+
+    #[inline]
+    fn order_latency(&self, asset_no: usize) -> Option<(i64, i64, i64)> {
+        self.bot.order_latency(asset_no)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::{
+            Backtest,
+            DataSource,
+            ExchangeKind::NoPartialFillExchange,
+            L2AssetBuilder,
+            assettype::LinearAsset,
+            data::Data,
+            models::{
+                CommonFees,
+                ConstantLatency,
+                PowerProbQueueFunc3,
+                ProbQueueModel,
+                TradingValueFeeModel,
+            },
+        },
+        depth::HashMapMarketDepth,
+        prelude::{Bot, Event, HkPriceAction, OrdType, TimeInForce},
+        risk::{RiskError, RiskLimits, RiskManager, RiskManagerError},
+        types::{EXCH_EVENT, LOCAL_ASK_DEPTH_EVENT, LOCAL_BID_DEPTH_EVENT},
+    };
+
+    type TestBacktest = Backtest<HashMapMarketDepth, HkPriceAction>;
+
+    fn event(ev: u64, ts: i64, px: f64, qty: f64) -> Event {
+        Event {
+            ev: ev | EXCH_EVENT,
+            exch_ts: ts,
+            local_ts: ts,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    /// Builds a backtest whose mid-price is 100.5, except that it falls to 99.5 between 2s and 3s.
+    fn backtest(contract_size: f64) -> TestBacktest {
+        let data = Data::from_data(&[
+            event(LOCAL_BID_DEPTH_EVENT, 100, 100.0, 10.0),
+            event(LOCAL_ASK_DEPTH_EVENT, 100, 101.0, 10.0),
+            event(LOCAL_BID_DEPTH_EVENT, 2_000_000_000, 100.0, 0.0),
+            event(LOCAL_BID_DEPTH_EVENT, 2_000_000_000, 99.0, 10.0),
+            event(LOCAL_ASK_DEPTH_EVENT, 2_000_000_000, 100.0, 10.0),
+            event(LOCAL_ASK_DEPTH_EVENT, 3_000_000_000, 100.0, 0.0),
+            event(LOCAL_BID_DEPTH_EVENT, 3_000_000_000, 100.0, 10.0),
+            event(LOCAL_BID_DEPTH_EVENT, 10_000_000_000, 99.0, 10.0),
+        ]);
+        let mut hbt: TestBacktest = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(10, 10))
+                    .asset_type(LinearAsset::new(contract_size))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(1.0, 1.0))
+                    .price_action(HkPriceAction::new(vec![], vec![]))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        hbt.elapse(1000).unwrap();
+        hbt
+    }
+
+    fn buy(
+        hbt: &mut RiskManager<TestBacktest>,
+        order_id: u64,
+        price: f64,
+        qty: f64,
+    ) -> Result<(), RiskError> {
+        match hbt.submit_buy_order(
+            0,
+            order_id,
+            price,
+            qty,
+            TimeInForce::GTC,
+            OrdType::Limit,
+            true,
+        ) {
+            Ok(_) => Ok(()),
+            Err(RiskManagerError::Rejected(error)) => Err(error),
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    #[test]
+    fn max_position() {
+        let mut hbt = RiskManager::new(backtest(1.0), RiskLimits::new().max_position(2.0));
+        assert_eq!(buy(&mut hbt, 1, 99.0, 1.5), Ok(()));
+        // The open order counts towards the position.
+        assert_eq!(
+            buy(&mut hbt, 2, 99.0, 1.0),
+            Err(RiskError::MaxPosition {
+                position: 2.5,
+                limit: 2.0
+            })
+        );
+        assert_eq!(buy(&mut hbt, 3, 99.0, 0.5), Ok(()));
+    }
+
+    #[test]
+    fn max_notional() {
+        let mut hbt = RiskManager::new(backtest(1.0), RiskLimits::new().max_notional(200.0));
+        assert_eq!(buy(&mut hbt, 1, 100.0, 1.0), Ok(()));
+        assert_eq!(
+            buy(&mut hbt, 2, 100.0, 1.5),
+            Err(RiskError::MaxNotional {
+                notional: 250.0,
+                limit: 200.0
+            })
+        );
+        assert_eq!(buy(&mut hbt, 3, 100.0, 1.0), Ok(()));
+    }
+
+    #[test]
+    fn max_open_orders() {
+        let mut hbt = RiskManager::new(backtest(1.0), RiskLimits::new().max_open_orders(2));
+        assert_eq!(buy(&mut hbt, 1, 99.0, 1.0), Ok(()));
+        assert_eq!(buy(&mut hbt, 2, 98.0, 1.0), Ok(()));
+        assert_eq!(
+            buy(&mut hbt, 3, 97.0, 1.0),
+            Err(RiskError::MaxOpenOrders {
+                open_orders: 2,
+                limit: 2
+            })
+        );
+        hbt.cancel(0, 1, true).unwrap();
+        assert_eq!(buy(&mut hbt, 3, 97.0, 1.0), Ok(()));
+    }
+
+    #[test]
+    fn max_order_rate() {
+        let mut hbt = RiskManager::new(backtest(1.0), RiskLimits::new().max_order_rate(2));
+        assert_eq!(buy(&mut hbt, 1, 99.0, 1.0), Ok(()));
+        // The request that the underlying bot fails to send doesn't count towards the rate.
+        assert!(matches!(
+            hbt.submit_buy_order(0, 1, 99.0, 1.0, TimeInForce::GTC, OrdType::Limit, true),
+            Err(RiskManagerError::Bot(_))
+        ));
+        assert_eq!(buy(&mut hbt, 2, 98.0, 1.0), Ok(()));
+        assert_eq!(
+            buy(&mut hbt, 3, 97.0, 1.0),
+            Err(RiskError::MaxOrderRate { limit: 2 })
+        );
+        hbt.elapse(1_000_000_000).unwrap();
+        assert_eq!(buy(&mut hbt, 3, 97.0, 1.0), Ok(()));
+    }
+
+    #[test]
+    fn price_band() {
+        let mut hbt = RiskManager::new(backtest(1.0), RiskLimits::new().price_band(0.01));
+        assert_eq!(buy(&mut hbt, 1, 100.0, 1.0), Ok(()));
+        assert_eq!(
+            buy(&mut hbt, 2, 99.0, 1.0),
+            Err(RiskError::PriceBand {
+                price: 99.0,
+                mid: 100.5,
+                band: 0.01
+            })
+        );
+        // Market orders aren't bound by the band.
+        assert!(
+            hbt.submit_buy_order(0, 2, 0.0, 1.0, TimeInForce::GTC, OrdType::Market, true)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn modify_unknown_order() {
+        let mut hbt = RiskManager::new(backtest(1.0), RiskLimits::new());
+        assert!(matches!(
+            hbt.modify(0, 1, 99.0, 1.0, true),
+            Err(RiskManagerError::Rejected(RiskError::OrderNotFound {
+                order_id: 1
+            }))
+        ));
+    }
+
+    /// The loss is 10 * (101 - 99.5) = 15 after buying 1 at 101 with a contract size of 10, while
+    /// it would be 1.5 if the asset type were ignored.
+    fn daily_loss_limits() -> RiskLimits {
+        RiskLimits::new()
+            .max_daily_loss(10.0)
+            .asset_type(LinearAsset::new(10.0))
+    }
+
+    #[test]
+    fn daily_loss_kill_switch() {
+        let mut hbt = RiskManager::new(backtest(10.0), daily_loss_limits());
+        assert_eq!(buy(&mut hbt, 1, 101.0, 1.0), Ok(()));
+        assert_eq!(hbt.position(0), 1.0);
+        hbt.elapse(2_000_000_000).unwrap();
+        assert_eq!(
+            buy(&mut hbt, 2, 99.0, 1.0),
+            Err(RiskError::DailyLossLimit {
+                loss: 15.0,
+                limit: 10.0
+            })
+        );
+        assert!(hbt.is_killed(0));
+
+        // The kill switch stays on even if the loss is recovered.
+        hbt.elapse(1_000_000).unwrap();
+        assert!(matches!(
+            buy(&mut hbt, 2, 100.0, 1.0),
+            Err(RiskError::DailyLossLimit { .. })
+        ));
+        hbt.reset_kill_switch(0);
+        assert_eq!(buy(&mut hbt, 2, 100.0, 1.0), Ok(()));
+    }
+
+    #[cfg(feature = "live")]
+    #[test]
+    fn daily_loss_kill_switch_live() {
+        use std::time::Duration;
+
+        use crate::{
+            live::{Instrument, LiveBot, LiveBotBuilder, SCRIPT, ScriptedChannel},
+            types::{LiveEvent, Order, Side, Status},
+        };
+
+        let feed = |ev, px, qty| {
+            let event = event(ev, 0, px, qty);
+            (
+                Duration::ZERO,
+                LiveEvent::Feed {
+                    symbol: "btcusdt".to_string(),
+                    event,
+                },
+            )
+        };
+        let live_bot: LiveBot<ScriptedChannel, HashMapMarketDepth, HkPriceAction> =
+            LiveBotBuilder::new()
+                .register(Instrument::new(
+                    "scripted",
+                    "btcusdt",
+                    1.0,
+                    1.0,
+                    HashMapMarketDepth::new(1.0, 1.0),
+                    0,
+                    HkPriceAction::new(vec![], vec![]),
+                ))
+                .heartbeat_interval(Duration::ZERO)
+                .build()
+                .unwrap();
+        SCRIPT.with_borrow_mut(|script| {
+            script.push_back(feed(LOCAL_BID_DEPTH_EVENT, 100.0, 10.0));
+            script.push_back(feed(LOCAL_ASK_DEPTH_EVENT, 101.0, 10.0));
+        });
+        let mut hbt = RiskManager::new(live_bot, daily_loss_limits());
+        hbt.elapse(10_000_000).unwrap();
+
+        // Drives the same fill and the same price move as in the backtest. A live bot provides
+        // only the position in `StateValues`.
+        assert!(
+            hbt.submit_buy_order(0, 1, 101.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)
+                .is_ok()
+        );
+        let mut order = Order::new(
+            1,
+            101,
+            1.0,
+            1.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        order.leaves_qty = 0.0;
+        order.exec_qty = 1.0;
+        order.exec_price_tick = 101;
+        order.exch_timestamp = 1;
+        order.status = Status::Filled;
+        SCRIPT.with_borrow_mut(|script| {
+            script.push_back((
+                Duration::ZERO,
+                LiveEvent::Order {
+                    symbol: "btcusdt".to_string(),
+                    order,
+                },
+            ));
+            script.push_back((
+                Duration::ZERO,
+                LiveEvent::Position {
+                    symbol: "btcusdt".to_string(),
+                    qty: 1.0,
+                    exch_ts: 1,
+                },
+            ));
+            script.push_back(feed(LOCAL_BID_DEPTH_EVENT, 100.0, 0.0));
+            script.push_back(feed(LOCAL_BID_DEPTH_EVENT, 99.0, 10.0));
+            script.push_back(feed(LOCAL_ASK_DEPTH_EVENT, 100.0, 10.0));
+        });
+        hbt.elapse(10_000_000).unwrap();
+        assert_eq!(hbt.position(0), 1.0);
+        assert_eq!(hbt.state_values(0).balance, 0.0);
+        assert_eq!(
+            hbt.submit_buy_order(0, 2, 99.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)
+                .map_err(|error| match error {
+                    RiskManagerError::Rejected(error) => error,
+                    error => panic!("{error}"),
+                }),
+            Err(RiskError::DailyLossLimit {
+                loss: 15.0,
+                limit: 10.0
+            })
+        );
+    }
+}
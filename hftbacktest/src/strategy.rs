@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+
+use crate::{
+    depth::MarketDepth,
+    prelude::PriceAction,
+    types::{Bot, OrderId},
+};
+
+/// The maximum duration to wait for the next feed, in nanoseconds. A pending timer set on the bot
+/// wakes the runner up earlier.
+const MAX_WAIT: i64 = 60_000_000_000;
+
+/// An event-driven strategy run by [`StrategyRunner`].
+///
+/// Each callback is invoked at the exact time the corresponding event is received, with the bot
+/// already updated by the event. Since the strategy is generic over the bot, the same strategy can
+/// be run on a backtester and a live bot without changes.
+///
+/// All callbacks do nothing by default.
+pub trait Strategy<MD, PA, I>
+where
+    MD: MarketDepth,
+    PA: PriceAction,
+    I: Bot<MD, PA>,
+{
+    /// Called when the market feed of the asset is received, after the market depth is updated.
+    /// This is also called when the received feed consists only of trades.
+    fn on_depth(&mut self, bot: &mut I, asset_no: usize) -> Result<(), I::Error> {
+        let _ = (bot, asset_no);
+        Ok(())
+    }
+
+    /// Called when market trades of the asset are received. The trades are available in
+    /// [`Bot::last_trades`] and are cleared after this callback, so the last trades capacity must
+    /// be set for this to be called.
+    fn on_trade(&mut self, bot: &mut I, asset_no: usize) -> Result<(), I::Error> {
+        let _ = (bot, asset_no);
+        Ok(())
+    }
+
+    /// Called when an order of the asset is updated by a response from the exchange, such as an
+    /// acceptance, a fill, a cancellation, or a rejection.
+    ///
+    /// Responses received while the strategy itself waits for them, using `wait` of the order
+    /// methods, are not reported. To react to every response, send orders without waiting.
+    fn on_order_update(
+        &mut self,
+        bot: &mut I,
+        asset_no: usize,
+        order_id: OrderId,
+    ) -> Result<(), I::Error> {
+        let _ = (bot, asset_no, order_id);
+        Ok(())
+    }
+
+    /// Called when a timer set by [`Bot::schedule_at`] or [`Bot::schedule_after`] expires, at the
+    /// expiration timestamp. To fire periodically, the timer can be rescheduled in this callback.
+    fn on_timer(&mut self, bot: &mut I, timer_id: u64) -> Result<(), I::Error> {
+        let _ = (bot, timer_id);
+        Ok(())
+    }
+
+    /// Called when a bar of the interval set by [`StrategyRunner::bar_intervals`] is closed,
+    /// with the open time of the closed bar. Since the bars are built from the trades by
+    /// [`PriceAction`], a bar is considered closed when the first trade of the next bar is
+    /// received. The closed bar can be retrieved by [`PriceAction::kmaps`].
+    fn on_bar_close(
+        &mut self,
+        bot: &mut I,
+        asset_no: usize,
+        interval: i64,
+        open_time: i64,
+    ) -> Result<(), I::Error> {
+        let _ = (bot, asset_no, interval, open_time);
+        Ok(())
+    }
+}
+
+/// The state of an asset last seen by the runner.
+#[derive(Default)]
+struct AssetState {
+    feed_latency: Option<(i64, i64)>,
+    bar_open_times: HashMap<i64, i64>,
+}
+
+/// Runs a [`Strategy`] on a [`Bot`], either a backtester or a live bot, dispatching the received
+/// events to the strategy's callbacks.
+///
+/// Underneath, it waits for the next feed, order response, or timer expiration using
+/// [`Bot::wait_next_feed`], so that the strategy reacts at the exact time of each event rather
+/// than at the next polling interval. The updated orders are tracked by [`Bot::updated_orders`],
+/// which the runner clears after each dispatch.
+pub struct StrategyRunner<S> {
+    strategy: S,
+    bar_intervals: Vec<i64>,
+}
+
+impl<S> StrategyRunner<S> {
+    /// Constructs a `StrategyRunner`.
+    pub fn new(strategy: S) -> Self {
+        Self {
+            strategy,
+            bar_intervals: Vec::new(),
+        }
+    }
+
+    /// Sets the bar intervals for [`Strategy::on_bar_close`], in nanoseconds. The intervals must
+    /// be the ones configured in the [`PriceAction`] of the assets.
+    pub fn bar_intervals(self, intervals: Vec<i64>) -> Self {
+        Self {
+            bar_intervals: intervals,
+            ..self
+        }
+    }
+
+    /// Returns the strategy.
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// Returns the strategy, consuming the runner.
+    pub fn into_inner(self) -> S {
+        self.strategy
+    }
+
+    /// Runs the strategy until the end of the data is reached in backtesting, or until the bot is
+    /// interrupted in live trading.
+    pub fn run<MD, PA, I>(&mut self, bot: &mut I) -> Result<(), I::Error>
+    where
+        MD: MarketDepth,
+        PA: PriceAction,
+        I: Bot<MD, PA>,
+        S: Strategy<MD, PA, I>,
+    {
+        let mut assets: Vec<AssetState> = (0..bot.num_assets())
+            .map(|_| AssetState::default())
+            .collect();
+
+        // Processes the events at the start time, which also initializes the backtester.
+        if !bot.elapse(0)? {
+            return Ok(());
+        }
+        let mut running = true;
+
+        loop {
+            self.dispatch(bot, &mut assets)?;
+            if !running {
+                return Ok(());
+            }
+            // Clears the orders updated while dispatching, including the ones the strategy has just
+            // sent, so that only the changes by the responses are reported.
+            bot.clear_updated_orders(None);
+
+            // The events processed until the end of the data is reached are still dispatched.
+            running = bot.wait_next_feed(true, MAX_WAIT)?;
+        }
+    }
+
+    fn dispatch<MD, PA, I>(
+        &mut self,
+        bot: &mut I,
+        assets: &mut [AssetState],
+    ) -> Result<(), I::Error>
+    where
+        MD: MarketDepth,
+        PA: PriceAction,
+        I: Bot<MD, PA>,
+        S: Strategy<MD, PA, I>,
+    {
+        // Takes the state before invoking any callback, as the callbacks can process more events
+        // while waiting for the order responses.
+        let mut received = Vec::new();
+        for (asset_no, asset) in assets.iter_mut().enumerate() {
+            let feed_latency = bot.feed_latency(asset_no);
+            let has_feed = feed_latency != asset.feed_latency;
+            asset.feed_latency = feed_latency;
+            let has_trades = !bot.last_trades(asset_no).is_empty();
+
+            let mut closed_bars = Vec::new();
+            if has_feed {
+                for &interval in &self.bar_intervals {
+                    let (_, open_time) = bot.price_action(asset_no).kmaps(interval, 1);
+                    if open_time == 0 {
+                        continue;
+                    }
+                    match asset.bar_open_times.insert(interval, open_time) {
+                        Some(prev_open_time) if open_time > prev_open_time => {
+                            closed_bars.push((interval, prev_open_time));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let updated_orders = bot.updated_orders(asset_no).to_vec();
+
+            received.push((has_feed, has_trades, closed_bars, updated_orders));
+        }
+        let expired_timers = bot.expired_timers().to_vec();
+        bot.clear_expired_timers();
+
+        for (asset_no, (has_feed, has_trades, closed_bars, updated_orders)) in
+            received.into_iter().enumerate()
+        {
+            if has_feed {
+                self.strategy.on_depth(bot, asset_no)?;
+            }
+            if has_trades {
+                self.strategy.on_trade(bot, asset_no)?;
+                bot.clear_last_trades(Some(asset_no));
+            }
+            for (interval, open_time) in closed_bars {
+                self.strategy
+                    .on_bar_close(bot, asset_no, interval, open_time)?;
+            }
+            for order_id in updated_orders {
+                self.strategy.on_order_update(bot, asset_no, order_id)?;
+            }
+        }
+        for timer_id in expired_timers {
+            self.strategy.on_timer(bot, timer_id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::{
+        backtest::{
+            Backtest,
+            BacktestError,
+            DataSource,
+            ExchangeKind::NoPartialFillExchange,
+            L2AssetBuilder,
+            assettype::LinearAsset,
+            data::Data,
+            models::{
+                CommonFees,
+                ConstantLatency,
+                PowerProbQueueFunc3,
+                ProbQueueModel,
+                TradingValueFeeModel,
+            },
+        },
+        depth::HashMapMarketDepth,
+        prelude::{Bot, Event, HkPriceAction, OrdType, OrderId, TimeInForce},
+        strategy::{Strategy, StrategyRunner},
+        types::{
+            EXCH_EVENT,
+            LOCAL_ASK_DEPTH_EVENT,
+            LOCAL_BID_DEPTH_EVENT,
+            LOCAL_BUY_TRADE_EVENT,
+            LOCAL_SELL_TRADE_EVENT,
+        },
+    };
+
+    type TestBacktest = Backtest<HashMapMarketDepth, HkPriceAction>;
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<(&'static str, i64)>,
+    }
+
+    impl Strategy<HashMapMarketDepth, HkPriceAction, TestBacktest> for Recorder {
+        fn on_depth(
+            &mut self,
+            bot: &mut TestBacktest,
+            asset_no: usize,
+        ) -> Result<(), BacktestError> {
+            self.calls.push(("depth", bot.current_timestamp()));
+            if bot.orders(asset_no).is_empty() {
+                bot.submit_buy_order(
+                    asset_no,
+                    1,
+                    99.0,
+                    1.0,
+                    TimeInForce::GTC,
+                    OrdType::Limit,
+                    false,
+                )?;
+            }
+            Ok(())
+        }
+
+        fn on_trade(
+            &mut self,
+            bot: &mut TestBacktest,
+            _asset_no: usize,
+        ) -> Result<(), BacktestError> {
+            self.calls.push(("trade", bot.current_timestamp()));
+            Ok(())
+        }
+
+        fn on_order_update(
+            &mut self,
+            bot: &mut TestBacktest,
+            _asset_no: usize,
+            _order_id: OrderId,
+        ) -> Result<(), BacktestError> {
+            self.calls.push(("order", bot.current_timestamp()));
+            Ok(())
+        }
+
+        fn on_timer(&mut self, bot: &mut TestBacktest, timer_id: u64) -> Result<(), BacktestError> {
+            self.calls.push(("timer", bot.current_timestamp()));
+            bot.schedule_after(1000, timer_id);
+            Ok(())
+        }
+
+        fn on_bar_close(
+            &mut self,
+            bot: &mut TestBacktest,
+            _asset_no: usize,
+            _interval: i64,
+            open_time: i64,
+        ) -> Result<(), BacktestError> {
+            self.calls.push(("bar", open_time));
+            assert_eq!(bot.current_timestamp(), 2500);
+            Ok(())
+        }
+    }
+
+    fn event(ev: u64, ts: i64, px: f64) -> Event {
+        Event {
+            ev: ev | EXCH_EVENT,
+            exch_ts: ts,
+            local_ts: ts,
+            px,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn dispatches_events_at_event_time() -> Result<(), Box<dyn Error>> {
+        let data = Data::from_data(&[
+            event(LOCAL_BID_DEPTH_EVENT, 100, 100.0),
+            event(LOCAL_ASK_DEPTH_EVENT, 100, 101.0),
+            event(LOCAL_BUY_TRADE_EVENT, 1500, 101.0),
+            event(LOCAL_SELL_TRADE_EVENT, 2500, 100.0),
+            event(LOCAL_BID_DEPTH_EVENT, 3700, 100.0),
+        ]);
+        let mut backtester: TestBacktest = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(1.0, 1.0))
+                    .price_action(HkPriceAction::new(vec![1000], vec![]))
+                    .last_trades_capacity(10)
+                    .build()?,
+            )
+            .build()?;
+
+        backtester.schedule_at(1000, 1);
+
+        let mut runner = StrategyRunner::new(Recorder::default()).bar_intervals(vec![1000]);
+        runner.run(&mut backtester)?;
+
+        assert_eq!(
+            &runner.strategy().calls[..9],
+            &[
+                ("depth", 100),
+                ("order", 200),
+                ("timer", 1000),
+                ("depth", 1500),
+                ("trade", 1500),
+                ("timer", 2000),
+                ("depth", 2500),
+                ("trade", 2500),
+                ("bar", 1000),
+            ]
+        );
+        assert_eq!(
+            &runner.strategy().calls[9..],
+            &[("timer", 3000), ("depth", 3700)]
+        );
+        Ok(())
+    }
+}
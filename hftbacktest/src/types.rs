@@ -82,8 +82,82 @@ impl Value {
 
 impl From<anyhow::Error> for Value {
     fn from(value: Error) -> Self {
-        // todo!: improve this to deliver detailed error information.
-        Value::String(value.to_string())
+        // Includes the chain of causes.
+        Value::String(format!("{value:#}"))
+    }
+}
+
+/// Normalized reason for an error reported by an exchange, common across the connectors, so that
+/// the bots can handle the errors without knowing the exchange-specific error codes.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Decode, Encode)]
+pub enum ErrorReason {
+    /// The request rate limit is exceeded.
+    RateLimited,
+    /// The margin or balance is insufficient for the order.
+    InsufficientMargin,
+    /// The post-only order is rejected since it would take liquidity.
+    PostOnlyWouldTake,
+    /// The order price is outside the range allowed by the exchange.
+    PriceOutOfBand,
+    /// The order doesn't exist on the exchange, or it has already been closed.
+    UnknownOrder,
+    /// The order ID, or the client order ID derived from it, is already in use.
+    DuplicateOrderId,
+    /// The API key or the signature is invalid, or the permission is denied.
+    AuthFailure,
+    /// Any other error, which can be identified by the exchange's error code and message.
+    Other,
+}
+
+/// Structured information of an error reported by an exchange.
+#[derive(Clone, Debug, Decode, Encode)]
+pub struct VenueError {
+    /// The normalized reason.
+    pub reason: ErrorReason,
+    /// The exchange's own error code, if any.
+    pub code: Option<i64>,
+    /// The exchange's error message.
+    pub msg: String,
+    /// The symbol of the order that caused the error, if any.
+    pub symbol: Option<String>,
+    /// The ID of the order that caused the error, if any.
+    pub order_id: Option<OrderId>,
+}
+
+impl VenueError {
+    /// Constructs an instance of `VenueError`.
+    pub fn new(reason: ErrorReason, msg: impl Into<String>) -> Self {
+        Self {
+            reason,
+            code: None,
+            msg: msg.into(),
+            symbol: None,
+            order_id: None,
+        }
+    }
+
+    /// Sets the exchange's error code.
+    pub fn code(self, code: i64) -> Self {
+        Self {
+            code: Some(code),
+            ..self
+        }
+    }
+
+    /// Sets the symbol of the order that caused the error.
+    pub fn symbol(self, symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: Some(symbol.into()),
+            ..self
+        }
+    }
+
+    /// Sets the ID of the order that caused the error.
+    pub fn order_id(self, order_id: OrderId) -> Self {
+        Self {
+            order_id: Some(order_id),
+            ..self
+        }
     }
 }
 
@@ -92,6 +166,8 @@ impl From<anyhow::Error> for Value {
 pub struct LiveError {
     pub kind: ErrorKind,
     pub value: Value,
+    /// The structured error reported by the exchange, if the error originates from it.
+    pub venue: Option<VenueError>,
 }
 
 impl LiveError {
@@ -100,18 +176,41 @@ impl LiveError {
         Self {
             kind,
             value: Value::Empty,
+            venue: None,
         }
     }
 
     /// Constructs an instance of `LiveError` with a value that contains detailed error information.
     pub fn with(kind: ErrorKind, value: Value) -> LiveError {
-        Self { kind, value }
+        Self {
+            kind,
+            value,
+            venue: None,
+        }
+    }
+
+    /// Attaches the structured error reported by the exchange.
+    pub fn with_venue(self, venue: VenueError) -> LiveError {
+        Self {
+            venue: Some(venue),
+            ..self
+        }
     }
 
     /// Returns a reference to the value that contains detailed error information.
     pub fn value(&self) -> &Value {
         &self.value
     }
+
+    /// Returns a reference to the structured error reported by the exchange, if any.
+    pub fn venue(&self) -> Option<&VenueError> {
+        self.venue.as_ref()
+    }
+
+    /// Returns the normalized reason for the error reported by the exchange, if any.
+    pub fn reason(&self) -> Option<ErrorReason> {
+        self.venue.as_ref().map(|venue| venue.reason)
+    }
 }
 
 /// Error type assigned to [`LiveError`].
@@ -120,6 +219,12 @@ pub enum ErrorKind {
     ConnectionInterrupted,
     CriticalConnectionError,
     OrderError,
+    /// The connector repaired the order states that diverged from the exchange, such as after a
+    /// reconnection of the private stream.
+    StateRepaired,
+    /// The connector detected a gap in the market depth updates and is resynchronizing the market
+    /// depth from a new snapshot. The connection itself is intact.
+    DepthResync,
     Custom(i64),
 }
 
@@ -712,6 +817,15 @@ pub enum LiveRequest {
         tick_size: f64,
         lot_size: f64,
     },
+    /// Indicates that the bot is alive. If the connector monitors the bots, it cancels the orders
+    /// of a bot whose heartbeats stop.
+    Heartbeat,
+    /// A request to cancel all open orders of the given symbol, or of all symbols traded through
+    /// the connector if `None`, and optionally to flatten the position.
+    CancelAll {
+        symbol: Option<String>,
+        flatten: bool,
+    },
 }
 
 /// Provides state values.
@@ -719,7 +833,7 @@ pub enum LiveRequest {
 /// **Note:** In a live bot, currently only `position` value is delivered correctly, and other
 /// values are invalid.
 #[repr(C)]
-#[derive(PartialEq, Clone, Debug, Default)]
+#[derive(PartialEq, Clone, Debug, Default, Decode, Encode)]
 pub struct StateValues {
     pub position: f64,
     /// Backtest only
@@ -751,6 +865,66 @@ pub enum BuildError {
     Error(#[from] anyhow::Error),
 }
 
+/// Indicates that the order record is a request sent by the bot.
+pub const ORDER_REQUEST: u64 = 1;
+
+/// Indicates that the order record is a response to an order request.
+pub const ORDER_RESPONSE: u64 = 2;
+
+/// An order request or response, recorded by
+/// [`BacktestRecorder`](crate::backtest::recorder::BacktestRecorder) in backtesting and by
+/// [`SessionCapture`](crate::live::SessionCapture) in live trading.
+#[repr(C)]
+#[derive(Clone, Debug, NpyDTyped)]
+pub struct OrderRecord {
+    /// [`ORDER_REQUEST`] or [`ORDER_RESPONSE`].
+    pub kind: u64,
+    /// The local timestamp at which the request is sent or the response is received.
+    pub local_ts: i64,
+    /// The exchange timestamp of the response. This is zero for requests.
+    pub exch_ts: i64,
+    pub order_id: u64,
+    /// `1` for buy and `-1` for sell.
+    pub side: i64,
+    pub price: f64,
+    pub qty: f64,
+    pub leaves_qty: f64,
+    pub exec_price: f64,
+    pub exec_qty: f64,
+    /// [`Status`] as an integer.
+    pub status: u64,
+    /// The requested [`Status`] as an integer.
+    pub req: u64,
+    /// [`TimeInForce`] as an integer.
+    pub time_in_force: u64,
+    /// [`OrdType`] as an integer.
+    pub order_type: u64,
+}
+
+unsafe impl POD for OrderRecord {}
+
+impl OrderRecord {
+    /// Constructs an `OrderRecord` from the given order.
+    pub fn new(kind: u64, local_ts: i64, order: &Order) -> Self {
+        Self {
+            kind,
+            local_ts,
+            exch_ts: order.exch_timestamp,
+            order_id: order.order_id,
+            side: order.side as i64,
+            price: order.price(),
+            qty: order.qty,
+            leaves_qty: order.leaves_qty,
+            exec_price: order.exec_price(),
+            exec_qty: order.exec_qty,
+            status: order.status as u64,
+            req: order.req as u64,
+            time_in_force: order.time_in_force as u64,
+            order_type: order.order_type as u64,
+        }
+    }
+}
+
 /// Used to submit an order in a live bot.
 #[derive(Decode, Encode)]
 pub struct OrderRequest {
@@ -808,6 +982,20 @@ where
     /// * `asset_no` - Asset number from which orders will be retrieved.
     fn orders(&self, asset_no: usize) -> &HashMap<OrderId, Order>;
 
+    /// Returns the IDs of the orders that have been updated by a request or a response since they
+    /// were last cleared, in the order of their first update. This allows the changes in the
+    /// orders to be tracked without scanning all of them, as
+    /// [`BacktestRecorder`](crate::backtest::recorder::BacktestRecorder) does.
+    ///
+    /// * `asset_no` - Asset number from which the updated orders will be retrieved.
+    fn updated_orders(&self, asset_no: usize) -> &[OrderId];
+
+    /// Clears the IDs of the updated orders.
+    ///
+    /// * `asset_no` - Asset number at which this command will be executed. If `None`, the updated
+    ///                orders in all assets will be cleared.
+    fn clear_updated_orders(&mut self, asset_no: Option<usize>);
+
     /// Places a buy order.
     ///
     /// * `asset_no` - Asset number at which this command will be executed.
@@ -915,6 +1103,31 @@ where
         timeout: i64,
     ) -> Result<bool, Self::Error>;
 
+    /// Schedules a timer with the given ID at the timestamp. When the timer expires, the ongoing
+    /// [`elapse`](Self::elapse) or [`wait_next_feed`](Self::wait_next_feed) returns at that exact
+    /// timestamp, and the timer ID is added to [`expired_timers`](Self::expired_timers).
+    /// Scheduling a timer with the ID of a pending timer reschedules it.
+    ///
+    /// In backtesting, timers don't extend the backtest beyond the end of the data.
+    fn schedule_at(&mut self, timestamp: i64, timer_id: u64);
+
+    /// Schedules a timer with the given ID after the duration from the current timestamp. See
+    /// [`schedule_at`](Self::schedule_at).
+    fn schedule_after(&mut self, duration: i64, timer_id: u64) {
+        let timestamp = self.current_timestamp().saturating_add(duration);
+        self.schedule_at(timestamp, timer_id);
+    }
+
+    /// Cancels the pending timer. Returns `false` if there is no such timer.
+    fn cancel_timer(&mut self, timer_id: u64) -> bool;
+
+    /// Returns the IDs of the timers that have expired since they were last cleared, in the order
+    /// of expiration.
+    fn expired_timers(&self) -> &[u64];
+
+    /// Clears the expired timers.
+    fn clear_expired_timers(&mut self);
+
     /// Elapses the specified duration.
     ///
     /// Args:
@@ -1,7 +1,45 @@
 mod aligned;
 
+use std::collections::HashSet;
+
 pub use aligned::{AlignedArray, CACHE_LINE_SIZE};
 
+use crate::types::OrderId;
+
+/// Tracks the IDs of the updated orders, in the order of their first update since cleared.
+#[derive(Default)]
+pub(crate) struct UpdatedOrders {
+    order_ids: Vec<OrderId>,
+    updated: HashSet<OrderId>,
+}
+
+impl UpdatedOrders {
+    pub fn insert(&mut self, order_id: OrderId) {
+        if self.updated.insert(order_id) {
+            self.order_ids.push(order_id);
+        }
+    }
+
+    pub fn as_slice(&self) -> &[OrderId] {
+        &self.order_ids
+    }
+
+    pub fn clear(&mut self) {
+        self.order_ids.clear();
+        self.updated.clear();
+    }
+
+    /// Retains only the order IDs for which the predicate returns `true`, so that the IDs of the
+    /// orders removed from the order map don't pile up if the updated orders are never cleared.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&OrderId) -> bool,
+    {
+        self.order_ids.retain(|order_id| f(order_id));
+        self.updated.retain(|order_id| f(order_id));
+    }
+}
+
 /// Gets price precision.
 ///
 /// * `tick_size` - This should not be a computed value.
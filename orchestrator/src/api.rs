@@ -0,0 +1,181 @@
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    Json,
+    Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{
+        IntoResponse,
+        Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use serde::Deserialize;
+use serde_json::json;
+use tokio_stream::{Stream, StreamExt, wrappers::BroadcastStream};
+
+use crate::{
+    config::ProcessConfig,
+    orchestrator::{Orchestrator, OrchestratorError},
+    process::ProcessKind,
+};
+
+impl IntoResponse for OrchestratorError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            OrchestratorError::NotFound(_) => StatusCode::NOT_FOUND,
+            OrchestratorError::AlreadyExists(_)
+            | OrchestratorError::Running(_)
+            | OrchestratorError::NotRunning(_) => StatusCode::CONFLICT,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+/// The path segment that selects the kind of process.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    Connectors,
+    Bots,
+}
+
+impl From<Kind> for ProcessKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Connectors => ProcessKind::Connector,
+            Kind::Bots => ProcessKind::Bot,
+        }
+    }
+}
+
+type AppState = Arc<Orchestrator>;
+
+/// Builds the HTTP API.
+///
+/// * `GET /{connectors|bots}` - Lists the registered processes.
+/// * `POST /{connectors|bots}` - Registers a process with the [`ProcessConfig`] in the body.
+/// * `GET /{connectors|bots}/{name}` - Returns the process's status.
+/// * `DELETE /{connectors|bots}/{name}` - Unregisters the process, which must be stopped.
+/// * `POST /{connectors|bots}/{name}/{start|stop|restart}` - Controls the process.
+/// * `GET /{connectors|bots}/{name}/logs` - Returns the recent lines of stdout and stderr.
+/// * `GET /bots/{name}/state` - Returns the state values and open orders of the bot's instruments.
+/// * `GET /positions` - Returns the positions across the bots, grouped by connector.
+/// * `GET /errors` - Returns the recent errors reported by the connectors.
+/// * `GET /events[?bot={name}]` - Streams the updates as server-sent events, optionally only
+///   those concerning the bot.
+pub fn router(orchestrator: AppState) -> Router {
+    Router::new()
+        .route("/positions", get(positions))
+        .route("/errors", get(errors))
+        .route("/events", get(events))
+        .route("/bots/{name}/state", get(bot_state))
+        .route("/{kind}", get(list).post(register))
+        .route("/{kind}/{name}", get(info).delete(unregister))
+        .route("/{kind}/{name}/logs", get(logs))
+        .route("/{kind}/{name}/start", post(start))
+        .route("/{kind}/{name}/stop", post(stop))
+        .route("/{kind}/{name}/restart", post(restart))
+        .with_state(orchestrator)
+}
+
+async fn list(State(orch): State<AppState>, Path(kind): Path<Kind>) -> Response {
+    Json(orch.list(kind.into())).into_response()
+}
+
+async fn register(
+    State(orch): State<AppState>,
+    Path(kind): Path<Kind>,
+    Json(config): Json<ProcessConfig>,
+) -> Result<StatusCode, OrchestratorError> {
+    orch.register(kind.into(), config)?;
+    Ok(StatusCode::CREATED)
+}
+
+async fn info(
+    State(orch): State<AppState>,
+    Path((kind, name)): Path<(Kind, String)>,
+) -> Result<Response, OrchestratorError> {
+    Ok(Json(orch.info(kind.into(), &name)?).into_response())
+}
+
+async fn unregister(
+    State(orch): State<AppState>,
+    Path((kind, name)): Path<(Kind, String)>,
+) -> Result<StatusCode, OrchestratorError> {
+    orch.unregister(kind.into(), &name)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn logs(
+    State(orch): State<AppState>,
+    Path((kind, name)): Path<(Kind, String)>,
+) -> Result<Response, OrchestratorError> {
+    Ok(Json(orch.logs(kind.into(), &name)?).into_response())
+}
+
+async fn start(
+    State(orch): State<AppState>,
+    Path((kind, name)): Path<(Kind, String)>,
+) -> Result<StatusCode, OrchestratorError> {
+    orch.start(kind.into(), &name)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn stop(
+    State(orch): State<AppState>,
+    Path((kind, name)): Path<(Kind, String)>,
+) -> Result<StatusCode, OrchestratorError> {
+    orch.stop(kind.into(), &name).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn restart(
+    State(orch): State<AppState>,
+    Path((kind, name)): Path<(Kind, String)>,
+) -> Result<StatusCode, OrchestratorError> {
+    orch.restart(kind.into(), &name).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn bot_state(
+    State(orch): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Response, OrchestratorError> {
+    Ok(Json(orch.bot_state(&name)?).into_response())
+}
+
+async fn positions(State(orch): State<AppState>) -> Response {
+    Json(orch.positions()).into_response()
+}
+
+async fn errors(State(orch): State<AppState>) -> Response {
+    Json(orch.errors()).into_response()
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    bot: Option<String>,
+}
+
+async fn events(
+    State(orch): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, OrchestratorError> {
+    let bot = query.bot.map(|name| orch.bot(&name)).transpose()?;
+    // Lagged receivers skip the missed updates.
+    let stream = BroadcastStream::new(orch.subscribe()).filter_map(move |update| {
+        let update = update.ok()?;
+        if bot.as_ref().is_some_and(|bot| !update.concerns(bot)) {
+            return None;
+        }
+        Event::default()
+            .event(update.name())
+            .json_data(&update)
+            .ok()
+            .map(Ok)
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The orchestrator's configuration, which lists the connectors and bots registered at startup.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub connectors: Vec<ProcessConfig>,
+    #[serde(default)]
+    pub bots: Vec<ProcessConfig>,
+}
+
+/// Describes how to launch a connector or bot process.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProcessConfig {
+    /// Unique name of the process.
+    pub name: String,
+    /// Path to the executable.
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// Starts the process as soon as it is registered.
+    #[serde(default)]
+    pub autostart: bool,
+    /// The instruments traded by the bot. The orchestrator observes them through the connectors
+    /// to track the bot's state values and orders. This is ignored for connectors.
+    #[serde(default)]
+    pub instruments: Vec<InstrumentConfig>,
+}
+
+/// Determines whether the process is restarted when it exits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+/// An instrument traded by a bot.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstrumentConfig {
+    /// Connector name, as set in the bot. This is either the name of the connector for the shared
+    /// memory or a socket endpoint, such as `tcp://127.0.0.1:5000`.
+    pub connector: String,
+    pub symbol: String,
+    pub tick_size: f64,
+    pub lot_size: f64,
+}
@@ -0,0 +1,90 @@
+use std::{fs::read_to_string, future::IntoFuture, net::SocketAddr};
+
+use clap::Parser;
+use tokio::{net::TcpListener, signal};
+use tracing::{error, info};
+
+use crate::{config::Config, orchestrator::Orchestrator, process::ProcessKind};
+
+mod api;
+mod config;
+mod observer;
+mod orchestrator;
+mod process;
+mod state;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Configuration file path listing the connectors and bots to register at startup.
+    config: Option<String>,
+
+    /// Address on which the HTTP API listens.
+    #[arg(long, default_value = "127.0.0.1:7000")]
+    bind: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    tracing_subscriber::fmt::init();
+
+    let config: Config = match &args.config {
+        Some(path) => {
+            let config = read_to_string(path)
+                .map_err(|error| {
+                    error!(
+                        ?error,
+                        config = path,
+                        "An error occurred while reading the configuration file."
+                    );
+                })
+                .unwrap();
+            toml::from_str(&config)
+                .map_err(|error| {
+                    error!(?error, "Couldn't parse the configuration file.");
+                })
+                .unwrap()
+        }
+        None => Config::default(),
+    };
+
+    let orchestrator = Orchestrator::new();
+    let processes = config
+        .connectors
+        .into_iter()
+        .map(|config| (ProcessKind::Connector, config))
+        .chain(
+            config
+                .bots
+                .into_iter()
+                .map(|config| (ProcessKind::Bot, config)),
+        );
+    for (kind, config) in processes {
+        let name = config.name.clone();
+        if let Err(error) = orchestrator.register(kind, config) {
+            error!(?error, %name, "Couldn't register the process.");
+        }
+    }
+
+    let listener = TcpListener::bind(args.bind)
+        .await
+        .map_err(|error| {
+            error!(?error, bind = %args.bind, "Couldn't bind the API server.");
+        })
+        .unwrap();
+    info!(bind = %args.bind, "The API server is listening.");
+    // The graceful shutdown isn't used, as it waits for the event streams that never end.
+    tokio::select! {
+        result = axum::serve(listener, api::router(orchestrator.clone())).into_future() => {
+            if let Err(error) = result {
+                error!(?error, "The API server has stopped.");
+            }
+        }
+        _ = signal::ctrl_c() => {}
+    }
+
+    info!("Stopping all processes.");
+    orchestrator.stop_all().await;
+}
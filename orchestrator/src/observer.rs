@@ -0,0 +1,150 @@
+use std::{
+    sync::{
+        Arc,
+        mpsc::{Receiver, Sender, TryRecvError, channel},
+    },
+    thread,
+    time::Duration,
+};
+
+use hftbacktest::{
+    live::{
+        BotError,
+        Instrument,
+        ipc::{
+            Channel,
+            iceoryx::IceoryxUnifiedChannel,
+            socket::{Endpoint, SocketUnifiedChannel},
+        },
+    },
+    prelude::*,
+};
+use tracing::{error, info, warn};
+
+use crate::{config::InstrumentConfig, orchestrator::Orchestrator};
+
+const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Observes a connector through the same IPC [`Channel`] that the bots use, as if it were a bot
+/// that never sends orders. It registers the instruments traded by the bots, so that the
+/// connector sends their current orders and positions, and then receives the order, position,
+/// and error events the connector publishes to all bots.
+pub struct Observer {
+    tx: Sender<Vec<InstrumentConfig>>,
+}
+
+impl Observer {
+    /// Spawns a thread that observes the connector. The connector name is interpreted in the same
+    /// way as the bots do; if it is a socket endpoint, the socket channel is used, otherwise the
+    /// shared memory channel.
+    pub fn spawn(connector: String, orchestrator: Arc<Orchestrator>) -> Self {
+        let (tx, rx) = channel();
+        thread::spawn(move || run(connector, orchestrator, rx));
+        Self { tx }
+    }
+
+    /// Adds the instruments to observe.
+    pub fn watch(&self, instruments: Vec<InstrumentConfig>) {
+        let _ = self.tx.send(instruments);
+    }
+}
+
+fn build_channel(
+    connector: &str,
+    instruments: &[InstrumentConfig],
+) -> Result<Box<dyn Channel>, BuildError> {
+    let instruments: Vec<Instrument<(), ()>> = instruments
+        .iter()
+        .map(|inst| {
+            Instrument::new(
+                &inst.connector,
+                &inst.symbol,
+                inst.tick_size,
+                inst.lot_size,
+                (),
+                0,
+                (),
+            )
+        })
+        .collect();
+    if Endpoint::parse(connector).is_some() {
+        Ok(Box::new(SocketUnifiedChannel::build(&instruments)?))
+    } else {
+        Ok(Box::new(IceoryxUnifiedChannel::build(&instruments)?))
+    }
+}
+
+fn run(connector: String, orchestrator: Arc<Orchestrator>, rx: Receiver<Vec<InstrumentConfig>>) {
+    let id = rand::random::<u64>();
+    let mut instruments: Vec<InstrumentConfig> = Vec::new();
+    let mut channel: Option<Box<dyn Channel>> = None;
+    let mut in_batch = false;
+    loop {
+        // The channel is rebuilt when new instruments are added since the instruments are fixed
+        // when the channel is built.
+        let mut updated = false;
+        loop {
+            match rx.try_recv() {
+                Ok(added) => {
+                    for inst in added {
+                        if !instruments.iter().any(|i| i.symbol == inst.symbol) {
+                            instruments.push(inst);
+                            updated = true;
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        if updated || (channel.is_none() && !instruments.is_empty()) {
+            channel = None;
+            match build_channel(&connector, &instruments) {
+                Ok(mut ch) => {
+                    for (inst_no, inst) in instruments.iter().enumerate() {
+                        let req = LiveRequest::RegisterInstrument {
+                            symbol: inst.symbol.clone(),
+                            tick_size: inst.tick_size,
+                            lot_size: inst.lot_size,
+                        };
+                        if let Err(error) = ch.send(id, inst_no, req) {
+                            error!(?error, %connector, "Couldn't register the instrument.");
+                        }
+                    }
+                    info!(
+                        %connector,
+                        num_instruments = instruments.len(),
+                        "Observing the connector."
+                    );
+                    channel = Some(ch);
+                }
+                Err(error) => {
+                    error!(?error, %connector, "Couldn't connect to the connector.");
+                    thread::sleep(RETRY_INTERVAL);
+                    continue;
+                }
+            }
+        }
+
+        let Some(ch) = channel.as_mut() else {
+            thread::sleep(RECV_TIMEOUT);
+            continue;
+        };
+        match ch.recv_timeout(id, RECV_TIMEOUT) {
+            Ok((_, ev)) => {
+                match ev {
+                    LiveEvent::BatchStart => in_batch = true,
+                    LiveEvent::BatchEnd => in_batch = false,
+                    _ => {}
+                }
+                orchestrator.apply(&connector, &ev, in_batch);
+            }
+            Err(BotError::Timeout) => {}
+            Err(error) => {
+                warn!(?error, %connector, "Couldn't receive from the connector.");
+                thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::{Arc, Mutex},
+};
+
+use hftbacktest::prelude::LiveEvent;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::{broadcast, oneshot};
+
+use crate::{
+    config::{InstrumentConfig, ProcessConfig},
+    observer::Observer,
+    process::{ManagedProcess, ProcessInfo, ProcessKind, ProcessStatus, supervise},
+    state::{ErrorInfo, InstrumentState, TradingState, Update},
+};
+
+const UPDATE_CAPACITY: usize = 10_000;
+
+#[derive(Error, Debug)]
+pub enum OrchestratorError {
+    #[error("`{0}` is not found")]
+    NotFound(String),
+    #[error("`{0}` already exists")]
+    AlreadyExists(String),
+    #[error("`{0}` is running")]
+    Running(String),
+    #[error("`{0}` is not running")]
+    NotRunning(String),
+}
+
+/// The aggregated position of a symbol on a connector, along with the bots trading it.
+#[derive(Serialize)]
+pub struct Position {
+    pub qty: f64,
+    pub bots: Vec<String>,
+}
+
+/// Manages the connector and bot processes and the state observed through the connectors.
+pub struct Orchestrator {
+    processes: Mutex<HashMap<String, ManagedProcess>>,
+    state: Mutex<TradingState>,
+    observers: Mutex<HashMap<String, Observer>>,
+    updates: broadcast::Sender<Update>,
+}
+
+impl Orchestrator {
+    pub fn new() -> Arc<Self> {
+        let (updates, _) = broadcast::channel(UPDATE_CAPACITY);
+        Arc::new(Self {
+            processes: Default::default(),
+            state: Default::default(),
+            observers: Default::default(),
+            updates,
+        })
+    }
+
+    /// Registers the process, and starts it if `autostart` is set.
+    pub fn register(
+        self: &Arc<Self>,
+        kind: ProcessKind,
+        config: ProcessConfig,
+    ) -> Result<(), OrchestratorError> {
+        let name = config.name.clone();
+        let autostart = config.autostart;
+        if kind == ProcessKind::Bot {
+            self.watch(&config.instruments);
+        }
+        match self.processes.lock().unwrap().entry(name.clone()) {
+            Entry::Occupied(_) => return Err(OrchestratorError::AlreadyExists(name)),
+            Entry::Vacant(entry) => {
+                entry.insert(ManagedProcess::new(kind, config));
+            }
+        }
+        if autostart {
+            self.start(kind, &name)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the process, which must not be running.
+    pub fn unregister(&self, kind: ProcessKind, name: &str) -> Result<(), OrchestratorError> {
+        let mut processes = self.processes.lock().unwrap();
+        let process = get_mut(&mut processes, kind, name)?;
+        if process.is_running() {
+            return Err(OrchestratorError::Running(name.to_string()));
+        }
+        processes.remove(name);
+        Ok(())
+    }
+
+    pub fn start(self: &Arc<Self>, kind: ProcessKind, name: &str) -> Result<(), OrchestratorError> {
+        let mut processes = self.processes.lock().unwrap();
+        let process = get_mut(&mut processes, kind, name)?;
+        if process.is_running() {
+            return Err(OrchestratorError::Running(name.to_string()));
+        }
+        let (stop_tx, stop_rx) = oneshot::channel();
+        process.stop = Some(stop_tx);
+        process.task = Some(tokio::spawn(supervise(
+            self.clone(),
+            process.config.clone(),
+            stop_rx,
+        )));
+        Ok(())
+    }
+
+    /// Stops the process and waits until it exits.
+    pub async fn stop(&self, kind: ProcessKind, name: &str) -> Result<(), OrchestratorError> {
+        let (stop, task) = {
+            let mut processes = self.processes.lock().unwrap();
+            let process = get_mut(&mut processes, kind, name)?;
+            if !process.is_running() {
+                return Err(OrchestratorError::NotRunning(name.to_string()));
+            }
+            (process.stop.take(), process.task.take())
+        };
+        if let Some(stop) = stop {
+            let _ = stop.send(());
+        }
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+        Ok(())
+    }
+
+    pub async fn restart(
+        self: &Arc<Self>,
+        kind: ProcessKind,
+        name: &str,
+    ) -> Result<(), OrchestratorError> {
+        match self.stop(kind, name).await {
+            Ok(()) | Err(OrchestratorError::NotRunning(_)) => {}
+            Err(error) => return Err(error),
+        }
+        self.start(kind, name)
+    }
+
+    /// Stops all running processes, the bots first.
+    pub async fn stop_all(&self) {
+        for kind in [ProcessKind::Bot, ProcessKind::Connector] {
+            for info in self.list(kind) {
+                let _ = self.stop(kind, &info.config.name).await;
+            }
+        }
+    }
+
+    pub fn list(&self, kind: ProcessKind) -> Vec<ProcessInfo> {
+        self.processes
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|process| process.kind == kind)
+            .map(ProcessInfo::from)
+            .collect()
+    }
+
+    pub fn info(&self, kind: ProcessKind, name: &str) -> Result<ProcessInfo, OrchestratorError> {
+        let mut processes = self.processes.lock().unwrap();
+        Ok(ProcessInfo::from(&*get_mut(&mut processes, kind, name)?))
+    }
+
+    /// Returns the most recent lines of the process's stdout and stderr.
+    pub fn logs(&self, kind: ProcessKind, name: &str) -> Result<Vec<String>, OrchestratorError> {
+        let mut processes = self.processes.lock().unwrap();
+        Ok(get_mut(&mut processes, kind, name)?
+            .logs
+            .iter()
+            .cloned()
+            .collect())
+    }
+
+    /// Returns the bot's configuration.
+    pub fn bot(&self, name: &str) -> Result<ProcessConfig, OrchestratorError> {
+        let mut processes = self.processes.lock().unwrap();
+        Ok(get_mut(&mut processes, ProcessKind::Bot, name)?
+            .config
+            .clone())
+    }
+
+    /// Returns the state values and open orders of the bot's instruments.
+    pub fn bot_state(&self, name: &str) -> Result<Vec<InstrumentState>, OrchestratorError> {
+        let config = self.bot(name)?;
+        let state = self.state.lock().unwrap();
+        Ok(config
+            .instruments
+            .iter()
+            .map(|inst| state.instrument(&inst.connector, &inst.symbol))
+            .collect())
+    }
+
+    /// Returns the positions across the bots, grouped by connector.
+    pub fn positions(&self) -> HashMap<String, HashMap<String, Position>> {
+        let mut positions: HashMap<String, HashMap<String, Position>> = self
+            .state
+            .lock()
+            .unwrap()
+            .positions()
+            .into_iter()
+            .map(|(connector, symbols)| {
+                let symbols = symbols
+                    .into_iter()
+                    .map(|(symbol, qty)| {
+                        (
+                            symbol,
+                            Position {
+                                qty,
+                                bots: Vec::new(),
+                            },
+                        )
+                    })
+                    .collect();
+                (connector, symbols)
+            })
+            .collect();
+        for process in self.processes.lock().unwrap().values() {
+            if process.kind != ProcessKind::Bot {
+                continue;
+            }
+            for inst in process.config.instruments.iter() {
+                if let Some(position) = positions
+                    .get_mut(&inst.connector)
+                    .and_then(|symbols| symbols.get_mut(&inst.symbol))
+                {
+                    position.bots.push(process.config.name.clone());
+                }
+            }
+        }
+        positions
+    }
+
+    pub fn errors(&self) -> Vec<ErrorInfo> {
+        self.state.lock().unwrap().errors()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Update> {
+        self.updates.subscribe()
+    }
+
+    /// Starts observing the instruments through their connectors.
+    fn watch(self: &Arc<Self>, instruments: &[InstrumentConfig]) {
+        let mut by_connector: HashMap<&str, Vec<InstrumentConfig>> = HashMap::new();
+        for inst in instruments {
+            by_connector
+                .entry(&inst.connector)
+                .or_default()
+                .push(inst.clone());
+        }
+        let mut observers = self.observers.lock().unwrap();
+        for (connector, instruments) in by_connector {
+            observers
+                .entry(connector.to_string())
+                .or_insert_with(|| Observer::spawn(connector.to_string(), self.clone()))
+                .watch(instruments);
+        }
+    }
+
+    pub(crate) fn apply(&self, connector: &str, ev: &LiveEvent, in_batch: bool) {
+        let updates = self.state.lock().unwrap().apply(connector, ev, in_batch);
+        for update in updates {
+            self.publish(update);
+        }
+    }
+
+    pub(crate) fn set_status(&self, name: &str, status: ProcessStatus) {
+        if let Some(process) = self.processes.lock().unwrap().get_mut(name) {
+            process.status = status.clone();
+        }
+        self.publish(Update::Process {
+            name: name.to_string(),
+            status,
+        });
+    }
+
+    pub(crate) fn count_restart(&self, name: &str) {
+        if let Some(process) = self.processes.lock().unwrap().get_mut(name) {
+            process.restarts += 1;
+        }
+    }
+
+    pub(crate) fn push_log(&self, name: &str, line: String) {
+        if let Some(process) = self.processes.lock().unwrap().get_mut(name) {
+            process.push_log(line.clone());
+        }
+        self.publish(Update::Log {
+            name: name.to_string(),
+            line,
+        });
+    }
+
+    fn publish(&self, update: Update) {
+        // Fails only when there is no subscriber.
+        let _ = self.updates.send(update);
+    }
+}
+
+fn get_mut<'a>(
+    processes: &'a mut HashMap<String, ManagedProcess>,
+    kind: ProcessKind,
+    name: &str,
+) -> Result<&'a mut ManagedProcess, OrchestratorError> {
+    processes
+        .get_mut(name)
+        .filter(|process| process.kind == kind)
+        .ok_or_else(|| OrchestratorError::NotFound(name.to_string()))
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::VecDeque,
+    process::{ExitStatus, Stdio},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::oneshot,
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::{ProcessConfig, RestartPolicy},
+    orchestrator::Orchestrator,
+};
+
+const MAX_LOG_LINES: usize = 1000;
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessKind {
+    Connector,
+    Bot,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProcessStatus {
+    Stopped,
+    Running { pid: u32, started_at: i64 },
+    Exited { code: Option<i32>, exited_at: i64 },
+}
+
+/// A registered connector or bot process.
+pub struct ManagedProcess {
+    pub kind: ProcessKind,
+    pub config: ProcessConfig,
+    pub status: ProcessStatus,
+    pub restarts: u64,
+    pub logs: VecDeque<String>,
+    /// Requests the supervisor task to stop the process.
+    pub stop: Option<oneshot::Sender<()>>,
+    pub task: Option<JoinHandle<()>>,
+}
+
+impl ManagedProcess {
+    pub fn new(kind: ProcessKind, config: ProcessConfig) -> Self {
+        Self {
+            kind,
+            config,
+            status: ProcessStatus::Stopped,
+            restarts: 0,
+            logs: VecDeque::new(),
+            stop: None,
+            task: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    pub fn push_log(&mut self, line: String) {
+        if self.logs.len() == MAX_LOG_LINES {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(line);
+    }
+}
+
+/// Information on a registered process returned by the API.
+#[derive(Serialize)]
+pub struct ProcessInfo {
+    pub kind: ProcessKind,
+    #[serde(flatten)]
+    pub config: ProcessConfig,
+    #[serde(flatten)]
+    pub status: ProcessStatus,
+    pub restarts: u64,
+}
+
+impl From<&ManagedProcess> for ProcessInfo {
+    fn from(process: &ManagedProcess) -> Self {
+        Self {
+            kind: process.kind,
+            config: process.config.clone(),
+            status: process.status.clone(),
+            restarts: process.restarts,
+        }
+    }
+}
+
+/// Runs the process and restarts it according to its restart policy until it is requested to
+/// stop.
+pub async fn supervise(
+    orchestrator: Arc<Orchestrator>,
+    config: ProcessConfig,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let name = config.name.clone();
+    loop {
+        let mut command = Command::new(&config.path);
+        command
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        // Runs the process in its own process group so that its children, such as those of a
+        // shell script, are also terminated.
+        #[cfg(unix)]
+        command.process_group(0);
+        if let Some(working_dir) = &config.working_dir {
+            command.current_dir(working_dir);
+        }
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(error) => {
+                error!(?error, %name, "Couldn't start the process.");
+                orchestrator.push_log(&name, format!("Couldn't start the process: {error}"));
+                orchestrator.set_status(
+                    &name,
+                    ProcessStatus::Exited {
+                        code: None,
+                        exited_at: Utc::now().timestamp_nanos_opt().unwrap(),
+                    },
+                );
+                return;
+            }
+        };
+
+        let pid = child.id().unwrap_or_default();
+        info!(%name, %pid, "The process has started.");
+        orchestrator.set_status(
+            &name,
+            ProcessStatus::Running {
+                pid,
+                started_at: Utc::now().timestamp_nanos_opt().unwrap(),
+            },
+        );
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_logs(orchestrator.clone(), name.clone(), stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_logs(orchestrator.clone(), name.clone(), stderr));
+        }
+
+        let exit_status = tokio::select! {
+            exit_status = child.wait() => exit_status,
+            _ = &mut stop_rx => {
+                terminate(&mut child).await;
+                info!(%name, "The process has stopped.");
+                orchestrator.set_status(&name, ProcessStatus::Stopped);
+                return;
+            }
+        };
+
+        let code = exit_status.as_ref().ok().and_then(ExitStatus::code);
+        warn!(%name, ?code, "The process has exited.");
+        orchestrator.set_status(
+            &name,
+            ProcessStatus::Exited {
+                code,
+                exited_at: Utc::now().timestamp_nanos_opt().unwrap(),
+            },
+        );
+
+        let success = exit_status.is_ok_and(|exit_status| exit_status.success());
+        let restart = match config.restart {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !success,
+            RestartPolicy::Always => true,
+        };
+        if !restart {
+            return;
+        }
+        tokio::select! {
+            _ = sleep(RESTART_DELAY) => {}
+            _ = &mut stop_rx => {
+                orchestrator.set_status(&name, ProcessStatus::Stopped);
+                return;
+            }
+        }
+        orchestrator.count_restart(&name);
+    }
+}
+
+/// Asks the process to terminate and kills it if it doesn't exit in time.
+async fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGTERM);
+        }
+        if timeout(STOP_TIMEOUT, child.wait()).await.is_ok() {
+            return;
+        }
+    }
+    if let Err(error) = child.kill().await {
+        error!(?error, "Couldn't kill the process.");
+    }
+}
+
+async fn forward_logs<R: AsyncRead + Unpin>(orchestrator: Arc<Orchestrator>, name: String, out: R) {
+    let mut lines = BufReader::new(out).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        orchestrator.push_log(&name, line);
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::Utc;
+use hftbacktest::prelude::*;
+use serde::Serialize;
+
+use crate::{config::ProcessConfig, process::ProcessStatus};
+
+const MAX_ERRORS: usize = 100;
+
+#[derive(Serialize)]
+#[serde(remote = "StateValues")]
+struct StateValuesDef {
+    position: f64,
+    balance: f64,
+    fee: f64,
+    num_trades: i64,
+    trading_volume: f64,
+    trading_value: f64,
+}
+
+/// The state values of an instrument, as observed through the connector. As in the live bot,
+/// `balance` and `fee` are not available.
+#[derive(Clone, Debug, Serialize)]
+pub struct InstrumentState {
+    pub connector: String,
+    pub symbol: String,
+    #[serde(with = "StateValuesDef")]
+    pub state: StateValues,
+    pub orders: Vec<OrderInfo>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrderInfo {
+    pub order_id: OrderId,
+    pub side: String,
+    pub price: f64,
+    pub qty: f64,
+    pub leaves_qty: f64,
+    pub exec_price: f64,
+    pub exec_qty: f64,
+    pub status: String,
+    pub order_type: String,
+    pub time_in_force: String,
+    pub exch_timestamp: i64,
+    pub local_timestamp: i64,
+}
+
+impl From<&Order> for OrderInfo {
+    fn from(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id,
+            side: format!("{:?}", order.side),
+            price: order.price(),
+            qty: order.qty,
+            leaves_qty: order.leaves_qty,
+            exec_price: order.exec_price(),
+            exec_qty: order.exec_qty,
+            status: format!("{:?}", order.status),
+            order_type: format!("{:?}", order.order_type),
+            time_in_force: format!("{:?}", order.time_in_force),
+            exch_timestamp: order.exch_timestamp,
+            local_timestamp: order.local_timestamp,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ErrorInfo {
+    pub connector: String,
+    pub timestamp: i64,
+    pub kind: String,
+    pub value: String,
+    /// The normalized reason, if the error is reported by the exchange.
+    pub reason: Option<String>,
+}
+
+/// The updates streamed to the clients.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Update {
+    Process {
+        name: String,
+        status: ProcessStatus,
+    },
+    Log {
+        name: String,
+        line: String,
+    },
+    State {
+        connector: String,
+        symbol: String,
+        #[serde(with = "StateValuesDef")]
+        state: StateValues,
+    },
+    Order {
+        connector: String,
+        symbol: String,
+        order: OrderInfo,
+    },
+    Error(ErrorInfo),
+}
+
+impl Update {
+    /// Returns the name of the update, used as the event name of the stream.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Update::Process { .. } => "process",
+            Update::Log { .. } => "log",
+            Update::State { .. } => "state",
+            Update::Order { .. } => "order",
+            Update::Error(_) => "error",
+        }
+    }
+
+    /// Returns `true` if the update concerns the bot, either as its process or through the
+    /// instruments it trades.
+    pub fn concerns(&self, bot: &ProcessConfig) -> bool {
+        match self {
+            Update::Process { name, .. } | Update::Log { name, .. } => *name == bot.name,
+            Update::State {
+                connector, symbol, ..
+            }
+            | Update::Order {
+                connector, symbol, ..
+            } => bot
+                .instruments
+                .iter()
+                .any(|inst| inst.connector == *connector && inst.symbol == *symbol),
+            Update::Error(error) => bot
+                .instruments
+                .iter()
+                .any(|inst| inst.connector == error.connector),
+        }
+    }
+}
+
+#[derive(Default)]
+struct SymbolState {
+    state: StateValues,
+    position_ts: i64,
+    orders: HashMap<OrderId, OrderInfo>,
+}
+
+/// Aggregates the positions, orders, and errors observed through the connectors.
+///
+/// Since the orders are published to all bots connected to a connector, they cannot be attributed
+/// to a specific bot; the state of an instrument is shared by all bots trading it.
+#[derive(Default)]
+pub struct TradingState {
+    symbols: HashMap<(String, String), SymbolState>,
+    errors: VecDeque<ErrorInfo>,
+}
+
+impl TradingState {
+    /// Applies the live event received from the connector and returns the resulting updates.
+    /// Fills are not counted while `in_batch` is set, since the orders sent in a batch are the
+    /// current state rather than new executions.
+    pub fn apply(&mut self, connector: &str, ev: &LiveEvent, in_batch: bool) -> Vec<Update> {
+        match ev {
+            LiveEvent::Order { symbol, order } => {
+                let entry = self
+                    .symbols
+                    .entry((connector.to_string(), symbol.clone()))
+                    .or_default();
+                let info = OrderInfo::from(order);
+                if order.active() {
+                    entry.orders.insert(order.order_id, info.clone());
+                } else {
+                    entry.orders.remove(&order.order_id);
+                }
+                let mut updates = vec![Update::Order {
+                    connector: connector.to_string(),
+                    symbol: symbol.clone(),
+                    order: info,
+                }];
+                if !in_batch
+                    && order.exec_qty > 0.0
+                    && matches!(order.status, Status::Filled | Status::PartiallyFilled)
+                {
+                    entry.state.num_trades += 1;
+                    entry.state.trading_volume += order.exec_qty;
+                    entry.state.trading_value += order.exec_qty * order.exec_price();
+                    updates.push(Update::State {
+                        connector: connector.to_string(),
+                        symbol: symbol.clone(),
+                        state: entry.state.clone(),
+                    });
+                }
+                updates
+            }
+            LiveEvent::Position {
+                symbol,
+                qty,
+                exch_ts,
+            } => {
+                let entry = self
+                    .symbols
+                    .entry((connector.to_string(), symbol.clone()))
+                    .or_default();
+                if *exch_ts < entry.position_ts {
+                    return Vec::new();
+                }
+                entry.position_ts = *exch_ts;
+                entry.state.position = *qty;
+                vec![Update::State {
+                    connector: connector.to_string(),
+                    symbol: symbol.clone(),
+                    state: entry.state.clone(),
+                }]
+            }
+            LiveEvent::Error(error) => {
+                let info = ErrorInfo {
+                    connector: connector.to_string(),
+                    timestamp: Utc::now().timestamp_nanos_opt().unwrap(),
+                    kind: format!("{:?}", error.kind),
+                    value: format!("{:?}", error.value),
+                    reason: error.reason().map(|reason| format!("{reason:?}")),
+                };
+                if self.errors.len() == MAX_ERRORS {
+                    self.errors.pop_front();
+                }
+                self.errors.push_back(info.clone());
+                vec![Update::Error(info)]
+            }
+            LiveEvent::Feed { .. } | LiveEvent::BatchStart | LiveEvent::BatchEnd => Vec::new(),
+        }
+    }
+
+    /// Returns the state of the instrument.
+    pub fn instrument(&self, connector: &str, symbol: &str) -> InstrumentState {
+        let entry = self
+            .symbols
+            .get(&(connector.to_string(), symbol.to_string()));
+        InstrumentState {
+            connector: connector.to_string(),
+            symbol: symbol.to_string(),
+            state: entry.map(|entry| entry.state.clone()).unwrap_or_default(),
+            orders: entry
+                .map(|entry| entry.orders.values().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the positions of all observed instruments, grouped by connector.
+    pub fn positions(&self) -> HashMap<String, HashMap<String, f64>> {
+        let mut positions: HashMap<String, HashMap<String, f64>> = HashMap::new();
+        for ((connector, symbol), entry) in self.symbols.iter() {
+            positions
+                .entry(connector.clone())
+                .or_default()
+                .insert(symbol.clone(), entry.state.position);
+        }
+        positions
+    }
+
+    /// Returns the most recent errors.
+    pub fn errors(&self) -> Vec<ErrorInfo> {
+        self.errors.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_id: OrderId, status: Status, exec_qty: f64) -> LiveEvent {
+        let mut order = Order::new(
+            order_id,
+            1000,
+            0.1,
+            1.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        order.status = status;
+        order.exec_qty = exec_qty;
+        order.exec_price_tick = 1000;
+        order.leaves_qty = 1.0 - exec_qty;
+        LiveEvent::Order {
+            symbol: "btcusdt".to_string(),
+            order,
+        }
+    }
+
+    #[test]
+    fn apply_live_events() {
+        let mut state = TradingState::default();
+
+        // The orders in a batch are the current state, so fills are not counted.
+        state.apply("bf", &order(1, Status::PartiallyFilled, 0.5), true);
+        state.apply("bf", &order(2, Status::New, 0.0), false);
+        let updates = state.apply("bf", &order(2, Status::Filled, 1.0), false);
+        assert_eq!(updates.len(), 2);
+
+        state.apply(
+            "bf",
+            &LiveEvent::Position {
+                symbol: "btcusdt".to_string(),
+                qty: 1.5,
+                exch_ts: 2,
+            },
+            false,
+        );
+        // Outdated position.
+        let updates = state.apply(
+            "bf",
+            &LiveEvent::Position {
+                symbol: "btcusdt".to_string(),
+                qty: 0.5,
+                exch_ts: 1,
+            },
+            false,
+        );
+        assert!(updates.is_empty());
+
+        let inst = state.instrument("bf", "btcusdt");
+        assert_eq!(inst.state.position, 1.5);
+        assert_eq!(inst.state.num_trades, 1);
+        assert_eq!(inst.state.trading_volume, 1.0);
+        assert!((inst.state.trading_value - 100.0).abs() < 1e-9);
+        assert_eq!(inst.orders.len(), 1);
+        assert_eq!(inst.orders[0].order_id, 1);
+
+        assert_eq!(state.positions()["bf"]["btcusdt"], 1.5);
+    }
+}
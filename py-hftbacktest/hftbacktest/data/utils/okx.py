@@ -0,0 +1,160 @@
+import gzip
+import json
+from typing import Optional, Literal
+
+import numpy as np
+from numpy.typing import NDArray
+
+from ..validation import correct_event_order, correct_local_timestamp, validate_event_order
+from ...types import (
+    DEPTH_EVENT,
+    DEPTH_CLEAR_EVENT,
+    DEPTH_SNAPSHOT_EVENT,
+    TRADE_EVENT,
+    BUY_EVENT,
+    SELL_EVENT,
+    event_dtype
+)
+
+
+def convert(
+        input_filename: str,
+        output_filename: Optional[str] = None,
+        opt: Literal['', 't'] = '',
+        base_latency: float = 0,
+        buffer_size: int = 100_000_000
+) -> NDArray:
+    r"""
+    Converts raw OKX feed stream file collected by the collector into a format compatible with HftBacktest.
+    If you encounter an ``IndexError`` due to an out-of-bounds, try increasing the ``buffer_size``.
+
+    The ``books`` and ``books-l2-tbt`` channels are processed as a snapshot followed by incremental updates, while
+    the ``books5`` channel is processed as a series of snapshots, since it pushes the full depth each time.
+
+    **File Format:**
+
+    .. code-block::
+
+        local_timestamp raw_stream
+        1729296000012345678 {"arg":{"channel":"books","instId":"BTC-USDT-SWAP"},"action":"snapshot","data":[{"asks":[["67950.1","12.5","0","3"],["67950.2","0.3","0","1"]],"bids":[["67950","7.1","0","5"],["67949.9","1.2","0","2"]],"ts":"1729295999998","checksum":-1361372473,"seqId":21530371839,"prevSeqId":-1}]}
+        1729296000034567890 {"arg":{"channel":"books","instId":"BTC-USDT-SWAP"},"action":"update","data":[{"asks":[["67950.1","10.5","0","2"]],"bids":[],"ts":"1729296000021","checksum":-1735207373,"seqId":21530371852,"prevSeqId":21530371839}]}
+        1729296000045678901 {"arg":{"channel":"trades","instId":"BTC-USDT-SWAP"},"data":[{"instId":"BTC-USDT-SWAP","tradeId":"1234567890","px":"67950.1","sz":"2","side":"buy","ts":"1729296000030","count":"1"}]}
+        1729296000046789012 {"arg":{"channel":"bbo-tbt","instId":"BTC-USDT-SWAP"},"data":[{"asks":[["67950.1","8.5","0","2"]],"bids":[["67950","7.1","0","5"]],"ts":"1729296000031","seqId":21530371860}]}
+
+    Args:
+        input_filename: Input filename with path.
+        output_filename: If provided, the converted data will be saved to the specified filename in ``npz`` format.
+        opt: Additional processing options:
+
+             - ``t``: Processes ``bbo-tbt`` channel with the following custom event IDs.
+
+                - best bid: ``103``
+                - best ask: ``104``
+
+        base_latency: The value to be added to the feed latency.
+                      See :func:`.correct_local_timestamp`.
+        buffer_size: Sets a preallocated row size for the buffer.
+
+    Returns:
+        Converted data compatible with HftBacktest.
+    """
+    timestamp_slice = 19
+    timestamp_mul = 1000000
+
+    tmp = np.empty(buffer_size, event_dtype)
+    row_num = 0
+    with gzip.open(input_filename, 'r') as f:
+        while True:
+            line = f.readline()
+            if not line:
+                break
+            local_timestamp = int(line[:timestamp_slice])
+            message = json.loads(line[timestamp_slice + 1:])
+            arg = message.get('arg')
+            data = message.get('data')
+            if arg is None or data is None:
+                continue
+            channel = arg['channel']
+            if channel == 'trades':
+                for trade in data:
+                    exch_timestamp = int(trade['ts']) * timestamp_mul
+                    tmp[row_num] = (
+                        TRADE_EVENT | (SELL_EVENT if trade['side'] == 'sell' else BUY_EVENT),  # trade initiator's side
+                        exch_timestamp,
+                        local_timestamp,
+                        float(trade['px']),
+                        float(trade['sz']),
+                        0,
+                        0,
+                        0
+                    )
+                    row_num += 1
+            elif channel.startswith('books'):
+                # `books5` pushes the full 5 levels every time, without an action.
+                is_snapshot = channel == 'books5' or message.get('action') == 'snapshot'
+                for book in data:
+                    exch_timestamp = int(book['ts']) * timestamp_mul
+                    for side, levels in ((BUY_EVENT, book['bids']), (SELL_EVENT, book['asks'])):
+                        if is_snapshot:
+                            if len(levels) == 0:
+                                continue
+                            # clears the existing market depth upto the prices in the snapshot.
+                            tmp[row_num] = (
+                                DEPTH_CLEAR_EVENT | side,
+                                exch_timestamp,
+                                local_timestamp,
+                                float(levels[-1][0]),
+                                0,
+                                0,
+                                0,
+                                0
+                            )
+                            row_num += 1
+                        for level in levels:
+                            tmp[row_num] = (
+                                (DEPTH_SNAPSHOT_EVENT if is_snapshot else DEPTH_EVENT) | side,
+                                exch_timestamp,
+                                local_timestamp,
+                                float(level[0]),
+                                float(level[1]),
+                                0,
+                                0,
+                                0
+                            )
+                            row_num += 1
+            elif channel == 'bbo-tbt' and 't' in opt:
+                for bbo in data:
+                    exch_timestamp = int(bbo['ts']) * timestamp_mul
+                    for ev, levels in ((103, bbo['bids']), (104, bbo['asks'])):
+                        if len(levels) == 0:
+                            continue
+                        tmp[row_num] = (
+                            ev,
+                            exch_timestamp,
+                            local_timestamp,
+                            float(levels[0][0]),
+                            float(levels[0][1]),
+                            0,
+                            0,
+                            0
+                        )
+                        row_num += 1
+    tmp = tmp[:row_num]
+
+    print('Correcting the latency')
+    tmp = correct_local_timestamp(tmp, base_latency)
+
+    print('Correcting the event order')
+    data = correct_event_order(
+        tmp,
+        np.argsort(tmp['exch_ts'], kind='mergesort'),
+        np.argsort(tmp['local_ts'], kind='mergesort')
+    )
+
+    validate_event_order(data)
+
+    if output_filename is not None:
+        print('Saving to %s' % output_filename)
+        np.savez_compressed(output_filename, data=data)
+
+    return data
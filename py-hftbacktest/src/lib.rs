@@ -3,14 +3,17 @@ use std::{ffi::c_void, mem::size_of, ptr::slice_from_raw_parts_mut};
 pub use backtest::*;
 pub use depth::*;
 #[cfg(feature = "live")]
-use hftbacktest::live::{Instrument, LiveBotBuilder};
+use hftbacktest::{
+    live::{Instrument, LiveBotBuilder},
+    prelude::HkPriceAction,
+};
 use hftbacktest::{
     backtest::{
         Asset,
         Backtest,
         DataSource,
         assettype::{InverseAsset, LinearAsset},
-        data::{Data, DataPtr, FeedLatencyAdjustment, Reader, read_npz_file},
+        data::{Data, DataPtr, FeedLatencyAdjustment, Reader, read_data_source},
         models::{
             CommonFees,
             ConstantLatency,
@@ -41,6 +44,7 @@ use hftbacktest::{
         state::State,
     },
     prelude::{ApplySnapshot, Event, HashMapMarketDepth, ROIVectorMarketDepth},
+    types::BuildError,
 };
 use hftbacktest_derive::build_asset;
 pub use order::*;
@@ -518,7 +522,8 @@ pub fn build_hashmap_backtest(assets: Vec<PyRefMut<BacktestAsset>>) -> PyResult<
                 TradingQtyFeeModel { fees },
                 FlatPerTradeFeeModel { fees },
             ]
-        );
+        )
+        .map_err(|error| PyErr::new::<PyValueError, _>(error.to_string()))?;
         local.push(asst.local);
         exch.push(asst.exch);
         readers.push(asst.reader);
@@ -575,7 +580,8 @@ pub fn build_roivec_backtest(assets: Vec<PyRefMut<BacktestAsset>>) -> PyResult<u
                 TradingQtyFeeModel { fees },
                 FlatPerTradeFeeModel { fees },
             ]
-        );
+        )
+        .map_err(|error| PyErr::new::<PyValueError, _>(error.to_string()))?;
         local.push(asst.local);
         exch.push(asst.exch);
         readers.push(asst.reader);
@@ -616,7 +622,10 @@ impl LiveInstrument {
         }
     }
 
-    /// Sets a connector name.
+    /// Sets a connector name. If it's a socket endpoint, such as ``tcp://10.0.0.1:5000`` or
+    /// ``unix:///tmp/bf.sock``, the bot connects to the connector running in the listener mode on
+    /// that endpoint, without iceoryx. Otherwise, it's the name of the connector to communicate
+    /// with over iceoryx. All instruments of a bot must use the same kind of connector name.
     pub fn connector(mut slf: PyRefMut<Self>, name: String) -> PyRefMut<Self> {
         slf.connector_name = name;
         slf
@@ -680,13 +689,14 @@ pub fn build_hashmap_livebot(instruments: Vec<PyRefMut<LiveInstrument>>) -> PyRe
             instrument.lot_size,
             HashMapMarketDepth::new(instrument.tick_size, instrument.lot_size),
             instrument.last_trades_cap,
+            HkPriceAction::new(vec![], vec![]),
         ));
     }
     let hbt: HashMapMarketDepthLiveBot = builder
         .error_handler(|_error| Ok(()))
         .order_recv_hook(|_prev, _new| Ok(()))
         .build()
-        .unwrap();
+        .map_err(|error| PyErr::new::<PyValueError, _>(error.to_string()))?;
 
     Ok(Box::into_raw(Box::new(hbt)) as *mut c_void as usize)
 }
@@ -708,13 +718,14 @@ pub fn build_roivec_livebot(instruments: Vec<PyRefMut<LiveInstrument>>) -> PyRes
                 instrument.roi_ub,
             ),
             instrument.last_trades_cap,
+            HkPriceAction::new(vec![], vec![]),
         ));
     }
     let hbt: ROIVectorMarketDepthLiveBot = builder
         .error_handler(|_error| Ok(()))
         .order_recv_hook(|_prev, _new| Ok(()))
         .build()
-        .unwrap();
+        .map_err(|error| PyErr::new::<PyValueError, _>(error.to_string()))?;
 
     Ok(Box::into_raw(Box::new(hbt)) as *mut c_void as usize)
 }
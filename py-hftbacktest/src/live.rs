@@ -4,13 +4,13 @@ use std::{collections::HashMap, mem};
 
 use hftbacktest::{
     depth::{HashMapMarketDepth, ROIVectorMarketDepth},
-    live::{BotError, LiveBot, ipc::iceoryx::IceoryxUnifiedChannel},
-    prelude::{Bot, Event, Order, StateValues},
+    live::{BotError, LiveBot, ipc::UnifiedChannel},
+    prelude::{Bot, Event, HkPriceAction, Order, StateValues},
     types::{OrdType, TimeInForce},
 };
 
-pub type HashMapMarketDepthLiveBot = LiveBot<IceoryxUnifiedChannel, HashMapMarketDepth>;
-pub type ROIVectorMarketDepthLiveBot = LiveBot<IceoryxUnifiedChannel, ROIVectorMarketDepth>;
+pub type HashMapMarketDepthLiveBot = LiveBot<UnifiedChannel, HashMapMarketDepth, HkPriceAction>;
+pub type ROIVectorMarketDepthLiveBot = LiveBot<UnifiedChannel, ROIVectorMarketDepth, HkPriceAction>;
 
 #[unsafe(no_mangle)]
 pub extern "C" fn hashmaplive_current_timestamp(hbt_ptr: *const HashMapMarketDepthLiveBot) -> i64 {
@@ -0,0 +1,98 @@
+import os
+import socket
+import struct
+import tempfile
+import unittest
+
+import hftbacktest
+from hftbacktest import LiveInstrument
+
+# `len: u32`, `kind: u8`, and `id: u64` in little-endian.
+FRAME_HEADER = struct.Struct('<IBQ')
+
+
+def read_frame(conn):
+    header = b''
+    while len(header) < FRAME_HEADER.size:
+        chunk = conn.recv(FRAME_HEADER.size - len(header))
+        if not chunk:
+            raise ConnectionError('closed')
+        header += chunk
+    length, kind, _ = FRAME_HEADER.unpack(header)
+    payload = b''
+    while len(payload) < length:
+        chunk = conn.recv(length - len(payload))
+        if not chunk:
+            raise ConnectionError('closed')
+        payload += chunk
+    return kind, payload
+
+
+def instrument(connector, symbol):
+    return (
+        LiveInstrument()
+            .connector(connector)
+            .symbol(symbol)
+            .tick_size(0.1)
+            .lot_size(0.001)
+    )
+
+
+@unittest.skipUnless(hftbacktest.LIVE_FEATURE, 'requires the live feature')
+class TestLiveBotSocketChannel(unittest.TestCase):
+    def assert_registers(self, listener, endpoint):
+        hbt = hftbacktest.HashMapMarketDepthLiveBot([instrument(endpoint, 'btcusdt')])
+        try:
+            conn, _ = listener.accept()
+            with conn:
+                conn.settimeout(5)
+                # The bot registers the instrument once connected. Heartbeats have no payload.
+                while True:
+                    kind, payload = read_frame(conn)
+                    if kind == 0:
+                        break
+                self.assertIn(b'btcusdt', payload)
+        finally:
+            self.assertEqual(hbt.close(), 0)
+
+    def test_build_tcp(self):
+        with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as listener:
+            listener.bind(('127.0.0.1', 0))
+            listener.listen(1)
+            listener.settimeout(5)
+            port = listener.getsockname()[1]
+            self.assert_registers(listener, f'tcp://127.0.0.1:{port}')
+
+    @unittest.skipUnless(hasattr(socket, 'AF_UNIX'), 'requires Unix domain sockets')
+    def test_build_unix(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, 'connector.sock')
+            with socket.socket(socket.AF_UNIX, socket.SOCK_STREAM) as listener:
+                listener.bind(path)
+                listener.listen(1)
+                listener.settimeout(5)
+                self.assert_registers(listener, f'unix://{path}')
+
+    def test_build_fails_without_listener(self):
+        with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as s:
+            s.bind(('127.0.0.1', 0))
+            port = s.getsockname()[1]
+        with self.assertRaises(ValueError):
+            hftbacktest.ROIVectorMarketDepthLiveBot([
+                instrument(f'tcp://127.0.0.1:{port}', 'btcusdt'),
+            ])
+
+    def test_build_rejects_mixed_connectors(self):
+        with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as listener:
+            listener.bind(('127.0.0.1', 0))
+            listener.listen(1)
+            port = listener.getsockname()[1]
+            with self.assertRaises(ValueError):
+                hftbacktest.HashMapMarketDepthLiveBot([
+                    instrument(f'tcp://127.0.0.1:{port}', 'btcusdt'),
+                    instrument('binancefutures', 'ethusdt'),
+                ])
+
+
+if __name__ == '__main__':
+    unittest.main()